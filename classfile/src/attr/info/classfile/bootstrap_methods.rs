@@ -0,0 +1,157 @@
+use constant::{ConstantPool, ConstantPoolEntry, ConstantMethodHandleInfo, StrPool};
+use error::*;
+use std::slice::Iter;
+
+#[derive(Debug)]
+pub struct BootstrapMethodsAttrInfo {
+    methods: Vec<BootstrapMethod>,
+}
+
+impl BootstrapMethodsAttrInfo {
+    pub fn methods<'a>(&'a self) -> Iter<'a, BootstrapMethod> {
+        self.methods.iter()
+    }
+
+    /// Get the bootstrap method at `index`, as referenced by `CONSTANT_InvokeDynamic` entries.
+    pub fn get(&self, index: usize) -> Option<&BootstrapMethod> {
+        self.methods.get(index)
+    }
+}
+
+impl_read! {
+    BootstrapMethodsAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
+        let methods_count = try!(reader.read_u16::<BigEndian>()) as usize;
+        let mut methods = Vec::with_capacity(methods_count);
+        for _ in 0..methods_count {
+            let method = try!(BootstrapMethod::read(reader));
+            methods.push(method);
+        }
+
+        Ok(BootstrapMethodsAttrInfo {
+            methods: methods,
+        })
+    }
+}
+
+impl_write! {
+    BootstrapMethodsAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.methods.len() as u16));
+        for method in self.methods() {
+            try!(method.write(writer));
+        }
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    BootstrapMethodsAttrInfo(self, printer, constant_pool: &ConstantPool) {
+        for (index, method) in self.methods().enumerate() {
+            try!(printer.write_indent());
+            try!(writeln!(printer, "Bootstrap method #{}:", index));
+
+            try!(method.print(&mut printer.sub_indent(1), constant_pool));
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BootstrapMethod {
+    method_ref: usize,
+    arguments: Vec<usize>,
+}
+
+impl BootstrapMethod {
+    pub fn method_ref<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a ConstantMethodHandleInfo> {
+        constant_pool.get(self.method_ref).and_then(|entry| match *entry {
+            ConstantPoolEntry::MethodHandle(ref info) => Some(info),
+            _ => None,
+        })
+    }
+
+    pub fn method_ref_index(&self) -> usize {
+        self.method_ref
+    }
+
+    pub fn arguments<'a, 'b>(&'a self, constant_pool: &'b ConstantPool) -> Arguments<'a, 'b> {
+        Arguments::new(self, constant_pool)
+    }
+
+    pub fn argument_indexes(&self) -> &[usize] {
+        &self.arguments
+    }
+}
+
+impl_read! {
+    BootstrapMethod(reader) -> Result<Self> = {
+        let method_ref = try!(reader.read_u16::<BigEndian>()) as usize;
+
+        let arguments_count = try!(reader.read_u16::<BigEndian>()) as usize;
+        let mut arguments = Vec::with_capacity(arguments_count);
+        for _ in 0..arguments_count {
+            let argument = try!(reader.read_u16::<BigEndian>()) as usize;
+            arguments.push(argument);
+        }
+
+        Ok(BootstrapMethod {
+            method_ref: method_ref,
+            arguments: arguments,
+        })
+    }
+}
+
+impl_write! {
+    BootstrapMethod(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.method_ref as u16));
+
+        try!(writer.write_u16::<BigEndian>(self.arguments.len() as u16));
+        for &argument in self.arguments.iter() {
+            try!(writer.write_u16::<BigEndian>(argument as u16));
+        }
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    BootstrapMethod(self, printer, constant_pool: &ConstantPool) {
+        let method_ref = try!(self.method_ref(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid method ref index")));
+
+        try!(printer.write_indent());
+        try!(method_ref.print(&mut printer.by_ref(), constant_pool));
+        try!(writeln!(printer, ""));
+
+        try!(printer.write_indent());
+        try!(writeln!(printer, "Arguments:"));
+        for argument in self.arguments(constant_pool) {
+            let argument = try!(argument.ok_or_else(|| ::utils::print::invalid("Invalid argument index")));
+
+            let mut printer = printer.sub_indent(1);
+            try!(printer.write_indent());
+            try!(argument.print(&mut printer.by_ref(), constant_pool));
+            try!(writeln!(printer, ""));
+        }
+    }
+}
+
+pub struct Arguments<'a, 'b> {
+    iter: Iter<'a, usize>,
+    constant_pool: &'b ConstantPool,
+}
+
+impl<'a, 'b> Arguments<'a, 'b> {
+    fn new(method: &'a BootstrapMethod, constant_pool: &'b ConstantPool) -> Self {
+        Arguments {
+            iter: method.arguments.iter(),
+            constant_pool: constant_pool,
+        }
+    }
+}
+
+impl<'a, 'b> Iterator for Arguments<'a, 'b> {
+    type Item = Option<&'b ConstantPoolEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|&index| self.constant_pool.get(index))
+    }
+}
@@ -1,4 +1,4 @@
-use constant::{ConstantPool, ConstantClassInfo};
+use constant::{ConstantPool, ConstantClassInfo, StrPool};
 use error::*;
 
 #[derive(Debug)]
@@ -7,7 +7,7 @@ pub struct InnerClassesAttrInfo {
 }
 
 impl_read! {
-    InnerClassesAttrInfo(reader, _constant_pool: &ConstantPool) -> Result<Self> = {
+    InnerClassesAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
         let classes_count = try!(reader.read_u16::<BigEndian>()) as usize;
         let mut classes = Vec::with_capacity(classes_count);
         for _ in 0..classes_count {
@@ -21,6 +21,17 @@ impl_read! {
     }
 }
 
+impl_write! {
+    InnerClassesAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.classes.len() as u16));
+        for class in self.classes.iter() {
+            try!(class.write(writer));
+        }
+
+        Ok(())
+    }
+}
+
 impl_print! {
     InnerClassesAttrInfo(self, printer, constant_pool: &ConstantPool) {
         for class in self.classes.iter() {
@@ -82,9 +93,20 @@ impl_read! {
     }
 }
 
+impl_write! {
+    Class(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.inner_class_info_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.outer_class_info_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.inner_name_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.inner_class_access_flags.bits()));
+
+        Ok(())
+    }
+}
+
 impl_print! {
     Class(self, printer, constant_pool: &ConstantPool) {
-        let inner_class_info = self.inner_class_info(constant_pool).expect("Invalid index");
+        let inner_class_info = try!(self.inner_class_info(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid index")));
 
         try!(printer.write_indent());
         try!(write!(printer, "Inner class: "));
@@ -1,8 +1,18 @@
-use constant::{ConstantPool, ConstantPoolEntry, ConstantClassInfo, ConstantNameAndTypeInfo};
+use constant::{ConstantPool, ConstantPoolEntry, ConstantClassInfo, ConstantNameAndTypeInfo, StrPool};
 use error::Result;
+pub use self::bootstrap_methods::BootstrapMethodsAttrInfo;
 pub use self::inner_classes::InnerClassesAttrInfo;
+pub use self::module::{ModuleAttrInfo, ModulePackagesAttrInfo, ModuleMainClassAttrInfo};
+pub use self::nest::{NestHostAttrInfo, NestMembersAttrInfo};
+pub use self::permitted_subclasses::PermittedSubclassesAttrInfo;
+pub use self::record::RecordAttrInfo;
 
+pub mod bootstrap_methods;
 pub mod inner_classes;
+pub mod module;
+pub mod nest;
+pub mod permitted_subclasses;
+pub mod record;
 
 #[derive(Debug)]
 pub struct SourceFileAttrInfo {
@@ -10,13 +20,17 @@ pub struct SourceFileAttrInfo {
 }
 
 impl SourceFileAttrInfo {
+    pub fn sourcefile_index(&self) -> usize {
+        self.sourcefile_index
+    }
+
     pub fn sourcefile<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a str> {
         constant_pool.get_str(self.sourcefile_index)
     }
 }
 
 impl_read! {
-    SourceFileAttrInfo(reader, _constant_pool: &ConstantPool) -> Result<Self> = {
+    SourceFileAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
         let sourcefile_index = try!(reader.read_u16::<BigEndian>()) as usize;
 
         Ok(SourceFileAttrInfo {
@@ -25,9 +39,17 @@ impl_read! {
     }
 }
 
+impl_write! {
+    SourceFileAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.sourcefile_index as u16));
+
+        Ok(())
+    }
+}
+
 impl_print! {
     SourceFileAttrInfo(self, printer, constant_pool: &ConstantPool) {
-        let sourcefile = self.sourcefile(constant_pool).expect("Invalid index");
+        let sourcefile = try!(self.sourcefile(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid index")));
 
         try!(printer.write_indent());
         try!(writeln!(printer, "Source file: {}", sourcefile));
@@ -58,7 +80,7 @@ impl EnclosingMethodAttrInfo {
 }
 
 impl_read! {
-    EnclosingMethodAttrInfo(reader, _constant_pool: &ConstantPool) -> Result<Self> = {
+    EnclosingMethodAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
         let class_index = try!(reader.read_u16::<BigEndian>()) as usize;
         let method_index = try!(reader.read_u16::<BigEndian>()) as usize;
 
@@ -69,9 +91,18 @@ impl_read! {
     }
 }
 
+impl_write! {
+    EnclosingMethodAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.class_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.method_index as u16));
+
+        Ok(())
+    }
+}
+
 impl_print! {
     EnclosingMethodAttrInfo(self, printer, constant_pool: &ConstantPool) {
-        let class = self.class(constant_pool).expect("Invalid class index");
+        let class = try!(self.class(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid class index")));
 
         try!(printer.write_indent());
         try!(class.print(printer, constant_pool));
@@ -91,7 +122,7 @@ pub struct SourceDebugExtensionAttrInfo {
 }
 
 impl_read! {
-    SourceDebugExtensionAttrInfo(reader, _constant_pool: &ConstantPool) -> Result<Self> = {
+    SourceDebugExtensionAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
         let mut data = String::new();
         try!(reader.read_to_string(&mut data));
 
@@ -101,9 +132,43 @@ impl_read! {
     }
 }
 
+impl_write! {
+    SourceDebugExtensionAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_all(self.data.as_bytes()));
+
+        Ok(())
+    }
+}
+
 impl_print! {
     SourceDebugExtensionAttrInfo(self, printer, _constant_pool: &ConstantPool) {
         try!(printer.write_indent());
         try!(writeln!(printer, "{}", self.data));
     }
 }
+
+index_iter!(Classes, ConstantClassInfo, get_class_info);
+
+fn read_indexes<R: ::std::io::Read>(reader: &mut R) -> Result<Vec<usize>> {
+    use byteorder::{ReadBytesExt, BigEndian};
+
+    let count = try!(reader.read_u16::<BigEndian>()) as usize;
+    let mut indexes = Vec::with_capacity(count);
+    for _ in 0..count {
+        let index = try!(reader.read_u16::<BigEndian>()) as usize;
+        indexes.push(index);
+    }
+
+    Ok(indexes)
+}
+
+fn write_indexes<W: ::std::io::Write>(writer: &mut W, indexes: &[usize]) -> Result<()> {
+    use byteorder::{WriteBytesExt, BigEndian};
+
+    try!(writer.write_u16::<BigEndian>(indexes.len() as u16));
+    for &index in indexes {
+        try!(writer.write_u16::<BigEndian>(index as u16));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,516 @@
+use constant::{ConstantPool, ConstantClassInfo, ConstantModuleInfo, ConstantPackageInfo, StrPool};
+use error::*;
+use self::flags::{ModuleFlags, RequiresFlags, ExportsFlags};
+use super::{Classes, read_indexes, write_indexes};
+
+#[derive(Debug)]
+pub struct ModuleAttrInfo {
+    name_index: usize,
+    pub flags: ModuleFlags,
+    version_index: usize,
+    pub requires: Vec<Requires>,
+    pub exports: Vec<Exports>,
+    pub opens: Vec<Exports>,
+    uses: Vec<usize>,
+    pub provides: Vec<Provides>,
+}
+
+impl ModuleAttrInfo {
+    pub fn name<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a ConstantModuleInfo> {
+        constant_pool.get_module_info(self.name_index)
+    }
+
+    pub fn version<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a str> {
+        if self.version_index != 0 {
+            constant_pool.get_str(self.version_index)
+        } else {
+            None
+        }
+    }
+
+    pub fn name_index(&self) -> usize {
+        self.name_index
+    }
+
+    pub fn version_index(&self) -> usize {
+        self.version_index
+    }
+
+    /// Service interfaces consumed by the module.
+    pub fn uses<'a, 'b>(&'a self, constant_pool: &'b ConstantPool) -> Classes<'a, 'b> {
+        Classes::new(&self.uses, constant_pool)
+    }
+
+    pub fn uses_indexes(&self) -> &[usize] {
+        &self.uses
+    }
+}
+
+impl_read! {
+    ModuleAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
+        let name_index = try!(reader.read_u16::<BigEndian>()) as usize;
+        let flags = try!(reader.read_u16::<BigEndian>());
+        let flags = match ModuleFlags::from_bits(flags) {
+            Some(flags) => flags,
+            None => bail!(ErrorKind::BadAccessFlags(flags)),
+        };
+        let version_index = try!(reader.read_u16::<BigEndian>()) as usize;
+
+        let requires_count = try!(reader.read_u16::<BigEndian>()) as usize;
+        let mut requires = Vec::with_capacity(requires_count);
+        for _ in 0..requires_count {
+            let entry = try!(Requires::read(reader));
+            requires.push(entry);
+        }
+
+        let exports_count = try!(reader.read_u16::<BigEndian>()) as usize;
+        let mut exports = Vec::with_capacity(exports_count);
+        for _ in 0..exports_count {
+            let entry = try!(Exports::read(reader));
+            exports.push(entry);
+        }
+
+        let opens_count = try!(reader.read_u16::<BigEndian>()) as usize;
+        let mut opens = Vec::with_capacity(opens_count);
+        for _ in 0..opens_count {
+            let entry = try!(Exports::read(reader));
+            opens.push(entry);
+        }
+
+        let uses = try!(read_indexes(reader));
+
+        let provides_count = try!(reader.read_u16::<BigEndian>()) as usize;
+        let mut provides = Vec::with_capacity(provides_count);
+        for _ in 0..provides_count {
+            let entry = try!(Provides::read(reader));
+            provides.push(entry);
+        }
+
+        Ok(ModuleAttrInfo {
+            name_index: name_index,
+            flags: flags,
+            version_index: version_index,
+            requires: requires,
+            exports: exports,
+            opens: opens,
+            uses: uses,
+            provides: provides,
+        })
+    }
+}
+
+impl_write! {
+    ModuleAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.name_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.flags.bits()));
+        try!(writer.write_u16::<BigEndian>(self.version_index as u16));
+
+        try!(writer.write_u16::<BigEndian>(self.requires.len() as u16));
+        for entry in self.requires.iter() {
+            try!(entry.write(writer));
+        }
+
+        try!(writer.write_u16::<BigEndian>(self.exports.len() as u16));
+        for entry in self.exports.iter() {
+            try!(entry.write(writer));
+        }
+
+        try!(writer.write_u16::<BigEndian>(self.opens.len() as u16));
+        for entry in self.opens.iter() {
+            try!(entry.write(writer));
+        }
+
+        try!(write_indexes(writer, &self.uses));
+
+        try!(writer.write_u16::<BigEndian>(self.provides.len() as u16));
+        for entry in self.provides.iter() {
+            try!(entry.write(writer));
+        }
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    ModuleAttrInfo(self, printer, constant_pool: &ConstantPool) {
+        let name = try!(self.name(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid module index")));
+
+        try!(printer.write_indent());
+        try!(name.print(printer, constant_pool));
+        if let Some(version) = self.version(constant_pool) {
+            try!(write!(printer, " @ {}", version));
+        }
+        try!(writeln!(printer, ""));
+
+        try!(printer.write_indent());
+        try!(writeln!(printer, "Flags: {:?}", self.flags));
+
+        for entry in self.requires.iter() {
+            try!(printer.write_indent());
+            try!(writeln!(printer, "Requires:"));
+            try!(entry.print(&mut printer.sub_indent(1), constant_pool));
+        }
+
+        for entry in self.exports.iter() {
+            try!(printer.write_indent());
+            try!(writeln!(printer, "Exports:"));
+            try!(entry.print(&mut printer.sub_indent(1), constant_pool));
+        }
+
+        for entry in self.opens.iter() {
+            try!(printer.write_indent());
+            try!(writeln!(printer, "Opens:"));
+            try!(entry.print(&mut printer.sub_indent(1), constant_pool));
+        }
+
+        for class in self.uses(constant_pool).flatten() {
+            try!(printer.write_indent());
+            try!(write!(printer, "Uses: "));
+            try!(class.print(printer, constant_pool));
+            try!(writeln!(printer, ""));
+        }
+
+        for entry in self.provides.iter() {
+            try!(printer.write_indent());
+            try!(writeln!(printer, "Provides:"));
+            try!(entry.print(&mut printer.sub_indent(1), constant_pool));
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Requires {
+    module_index: usize,
+    pub flags: RequiresFlags,
+    version_index: usize,
+}
+
+impl Requires {
+    pub fn module<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a ConstantModuleInfo> {
+        constant_pool.get_module_info(self.module_index)
+    }
+
+    /// Version of the required module when this module was compiled, if recorded.
+    pub fn version<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a str> {
+        if self.version_index != 0 {
+            constant_pool.get_str(self.version_index)
+        } else {
+            None
+        }
+    }
+
+    pub fn module_index(&self) -> usize {
+        self.module_index
+    }
+
+    pub fn version_index(&self) -> usize {
+        self.version_index
+    }
+}
+
+impl_read! {
+    Requires(reader) -> Result<Self> = {
+        let module_index = try!(reader.read_u16::<BigEndian>()) as usize;
+        let flags = try!(reader.read_u16::<BigEndian>());
+        let flags = match RequiresFlags::from_bits(flags) {
+            Some(flags) => flags,
+            None => bail!(ErrorKind::BadAccessFlags(flags)),
+        };
+        let version_index = try!(reader.read_u16::<BigEndian>()) as usize;
+
+        Ok(Requires {
+            module_index: module_index,
+            flags: flags,
+            version_index: version_index,
+        })
+    }
+}
+
+impl_write! {
+    Requires(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.module_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.flags.bits()));
+        try!(writer.write_u16::<BigEndian>(self.version_index as u16));
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    Requires(self, printer, constant_pool: &ConstantPool) {
+        let module = try!(self.module(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid module index")));
+
+        try!(printer.write_indent());
+        try!(module.print(printer, constant_pool));
+        if let Some(version) = self.version(constant_pool) {
+            try!(write!(printer, " @ {}", version));
+        }
+        try!(writeln!(printer, ""));
+
+        try!(printer.write_indent());
+        try!(writeln!(printer, "Flags: {:?}", self.flags));
+    }
+}
+
+/// An entry of either the `exports` or the `opens` table, which share the same layout.
+#[derive(Debug)]
+pub struct Exports {
+    package_index: usize,
+    pub flags: ExportsFlags,
+    to: Vec<usize>,
+}
+
+impl Exports {
+    pub fn package<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a ConstantPackageInfo> {
+        constant_pool.get_package_info(self.package_index)
+    }
+
+    /// Modules the package is restricted to; empty when it is unqualified.
+    pub fn to<'a, 'b>(&'a self, constant_pool: &'b ConstantPool) -> Modules<'a, 'b> {
+        Modules::new(&self.to, constant_pool)
+    }
+
+    pub fn package_index(&self) -> usize {
+        self.package_index
+    }
+
+    pub fn to_indexes(&self) -> &[usize] {
+        &self.to
+    }
+}
+
+impl_read! {
+    Exports(reader) -> Result<Self> = {
+        let package_index = try!(reader.read_u16::<BigEndian>()) as usize;
+        let flags = try!(reader.read_u16::<BigEndian>());
+        let flags = match ExportsFlags::from_bits(flags) {
+            Some(flags) => flags,
+            None => bail!(ErrorKind::BadAccessFlags(flags)),
+        };
+        let to = try!(read_indexes(reader));
+
+        Ok(Exports {
+            package_index: package_index,
+            flags: flags,
+            to: to,
+        })
+    }
+}
+
+impl_write! {
+    Exports(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.package_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.flags.bits()));
+        try!(write_indexes(writer, &self.to));
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    Exports(self, printer, constant_pool: &ConstantPool) {
+        let package = try!(self.package(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid package index")));
+
+        try!(printer.write_indent());
+        try!(package.print(printer, constant_pool));
+        try!(writeln!(printer, ""));
+
+        try!(printer.write_indent());
+        try!(writeln!(printer, "Flags: {:?}", self.flags));
+
+        for module in self.to(constant_pool).flatten() {
+            try!(printer.write_indent());
+            try!(write!(printer, "To: "));
+            try!(module.print(printer, constant_pool));
+            try!(writeln!(printer, ""));
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Provides {
+    class_index: usize,
+    with: Vec<usize>,
+}
+
+impl Provides {
+    /// The service interface.
+    pub fn class<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a ConstantClassInfo> {
+        constant_pool.get_class_info(self.class_index)
+    }
+
+    /// The implementations provided for the service.
+    pub fn with<'a, 'b>(&'a self, constant_pool: &'b ConstantPool) -> Classes<'a, 'b> {
+        Classes::new(&self.with, constant_pool)
+    }
+
+    pub fn class_index(&self) -> usize {
+        self.class_index
+    }
+
+    pub fn with_indexes(&self) -> &[usize] {
+        &self.with
+    }
+}
+
+impl_read! {
+    Provides(reader) -> Result<Self> = {
+        let class_index = try!(reader.read_u16::<BigEndian>()) as usize;
+        let with = try!(read_indexes(reader));
+
+        Ok(Provides {
+            class_index: class_index,
+            with: with,
+        })
+    }
+}
+
+impl_write! {
+    Provides(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.class_index as u16));
+        try!(write_indexes(writer, &self.with));
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    Provides(self, printer, constant_pool: &ConstantPool) {
+        let class = try!(self.class(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid class index")));
+
+        try!(printer.write_indent());
+        try!(class.print(printer, constant_pool));
+        try!(writeln!(printer, ""));
+
+        for class in self.with(constant_pool).flatten() {
+            try!(printer.write_indent());
+            try!(write!(printer, "With: "));
+            try!(class.print(printer, constant_pool));
+            try!(writeln!(printer, ""));
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ModulePackagesAttrInfo {
+    packages: Vec<usize>,
+}
+
+impl ModulePackagesAttrInfo {
+    pub fn packages<'a, 'b>(&'a self, constant_pool: &'b ConstantPool) -> Packages<'a, 'b> {
+        Packages::new(&self.packages, constant_pool)
+    }
+
+    pub fn package_indexes(&self) -> &[usize] {
+        &self.packages
+    }
+}
+
+impl_read! {
+    ModulePackagesAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
+        let packages = try!(read_indexes(reader));
+
+        Ok(ModulePackagesAttrInfo {
+            packages: packages,
+        })
+    }
+}
+
+impl_write! {
+    ModulePackagesAttrInfo(self, writer) -> Result<()> = {
+        try!(write_indexes(writer, &self.packages));
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    ModulePackagesAttrInfo(self, printer, constant_pool: &ConstantPool) {
+        for package in self.packages(constant_pool).flatten() {
+            try!(printer.write_indent());
+            try!(package.print(printer, constant_pool));
+            try!(writeln!(printer, ""));
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ModuleMainClassAttrInfo {
+    main_class_index: usize,
+}
+
+impl ModuleMainClassAttrInfo {
+    pub fn main_class<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a ConstantClassInfo> {
+        constant_pool.get_class_info(self.main_class_index)
+    }
+
+    pub fn main_class_index(&self) -> usize {
+        self.main_class_index
+    }
+}
+
+impl_read! {
+    ModuleMainClassAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
+        let main_class_index = try!(reader.read_u16::<BigEndian>()) as usize;
+
+        Ok(ModuleMainClassAttrInfo {
+            main_class_index: main_class_index,
+        })
+    }
+}
+
+impl_write! {
+    ModuleMainClassAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.main_class_index as u16));
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    ModuleMainClassAttrInfo(self, printer, constant_pool: &ConstantPool) {
+        let main_class = try!(self.main_class(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid class index")));
+
+        try!(printer.write_indent());
+        try!(main_class.print(printer, constant_pool));
+        try!(writeln!(printer, ""));
+    }
+}
+
+index_iter!(Modules, ConstantModuleInfo, get_module_info);
+index_iter!(Packages, ConstantPackageInfo, get_package_info);
+
+pub mod flags {
+    bitflags! {
+        pub struct ModuleFlags: u16 {
+            #[doc = "Declared open; all its packages are opened for deep reflection."]
+            const ACC_OPEN = 0x0020;
+            #[doc = "Declared synthetic; not present in the source code."]
+            const ACC_SYNTHETIC = 0x1000;
+            #[doc = "Implicitly declared."]
+            const ACC_MANDATED = 0x8000;
+        }
+    }
+
+    bitflags! {
+        pub struct RequiresFlags: u16 {
+            #[doc = "Modules depending on this module also implicitly depend on the required one."]
+            const ACC_TRANSITIVE = 0x0020;
+            #[doc = "Required at compile time only."]
+            const ACC_STATIC_PHASE = 0x0040;
+            #[doc = "Declared synthetic; not present in the source code."]
+            const ACC_SYNTHETIC = 0x1000;
+            #[doc = "Implicitly declared."]
+            const ACC_MANDATED = 0x8000;
+        }
+    }
+
+    bitflags! {
+        pub struct ExportsFlags: u16 {
+            #[doc = "Declared synthetic; not present in the source code."]
+            const ACC_SYNTHETIC = 0x1000;
+            #[doc = "Implicitly declared."]
+            const ACC_MANDATED = 0x8000;
+        }
+    }
+}
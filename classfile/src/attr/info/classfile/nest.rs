@@ -0,0 +1,89 @@
+use constant::{ConstantPool, ConstantClassInfo, StrPool};
+use error::Result;
+use super::{Classes, read_indexes, write_indexes};
+
+#[derive(Debug)]
+pub struct NestHostAttrInfo {
+    host_class_index: usize,
+}
+
+impl NestHostAttrInfo {
+    pub fn host_class<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a ConstantClassInfo> {
+        constant_pool.get_class_info(self.host_class_index)
+    }
+
+    pub fn host_class_index(&self) -> usize {
+        self.host_class_index
+    }
+}
+
+impl_read! {
+    NestHostAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
+        let host_class_index = try!(reader.read_u16::<BigEndian>()) as usize;
+
+        Ok(NestHostAttrInfo {
+            host_class_index: host_class_index,
+        })
+    }
+}
+
+impl_write! {
+    NestHostAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.host_class_index as u16));
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    NestHostAttrInfo(self, printer, constant_pool: &ConstantPool) {
+        let host_class = try!(self.host_class(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid class index")));
+
+        try!(printer.write_indent());
+        try!(host_class.print(printer, constant_pool));
+        try!(writeln!(printer, ""));
+    }
+}
+
+#[derive(Debug)]
+pub struct NestMembersAttrInfo {
+    classes: Vec<usize>,
+}
+
+impl NestMembersAttrInfo {
+    pub fn classes<'a, 'b>(&'a self, constant_pool: &'b ConstantPool) -> Classes<'a, 'b> {
+        Classes::new(&self.classes, constant_pool)
+    }
+
+    pub fn class_indexes(&self) -> &[usize] {
+        &self.classes
+    }
+}
+
+impl_read! {
+    NestMembersAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
+        let classes = try!(read_indexes(reader));
+
+        Ok(NestMembersAttrInfo {
+            classes: classes,
+        })
+    }
+}
+
+impl_write! {
+    NestMembersAttrInfo(self, writer) -> Result<()> = {
+        try!(write_indexes(writer, &self.classes));
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    NestMembersAttrInfo(self, printer, constant_pool: &ConstantPool) {
+        for class in self.classes(constant_pool).flatten() {
+            try!(printer.write_indent());
+            try!(class.print(printer, constant_pool));
+            try!(writeln!(printer, ""));
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use constant::{ConstantPool, StrPool};
+use error::Result;
+use super::{Classes, read_indexes, write_indexes};
+
+/// Direct subclasses (or implementations) allowed for a sealed class or interface.
+#[derive(Debug)]
+pub struct PermittedSubclassesAttrInfo {
+    classes: Vec<usize>,
+}
+
+impl PermittedSubclassesAttrInfo {
+    pub fn classes<'a, 'b>(&'a self, constant_pool: &'b ConstantPool) -> Classes<'a, 'b> {
+        Classes::new(&self.classes, constant_pool)
+    }
+
+    pub fn class_indexes(&self) -> &[usize] {
+        &self.classes
+    }
+}
+
+impl_read! {
+    PermittedSubclassesAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
+        let classes = try!(read_indexes(reader));
+
+        Ok(PermittedSubclassesAttrInfo {
+            classes: classes,
+        })
+    }
+}
+
+impl_write! {
+    PermittedSubclassesAttrInfo(self, writer) -> Result<()> = {
+        try!(write_indexes(writer, &self.classes));
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    PermittedSubclassesAttrInfo(self, printer, constant_pool: &ConstantPool) {
+        for class in self.classes(constant_pool).flatten() {
+            try!(printer.write_indent());
+            try!(class.print(printer, constant_pool));
+            try!(writeln!(printer, ""));
+        }
+    }
+}
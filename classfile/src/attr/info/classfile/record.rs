@@ -0,0 +1,116 @@
+use attr::{self, Attr};
+use constant::{ConstantPool, StrPool};
+use error::{Diagnostics, Result};
+
+#[derive(Debug)]
+pub struct RecordAttrInfo {
+    pub components: Vec<RecordComponent>,
+}
+
+impl_read! {
+    RecordAttrInfo(reader, constant_pool: &dyn StrPool, diagnostics: &mut Diagnostics) -> Result<Self> = {
+        let components_count = try!(reader.read_u16::<BigEndian>()) as usize;
+        let mut components = Vec::with_capacity(components_count);
+        for i in 0..components_count {
+            let component = try!(diagnostics.within(|| format!("components[{}]", i), |diagnostics| {
+                RecordComponent::read(reader, constant_pool, diagnostics)
+            }));
+            components.push(component);
+        }
+
+        Ok(RecordAttrInfo {
+            components: components,
+        })
+    }
+}
+
+impl_write! {
+    RecordAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.components.len() as u16));
+        for component in self.components.iter() {
+            try!(component.write(writer));
+        }
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    RecordAttrInfo(self, printer, constant_pool: &ConstantPool) {
+        for component in self.components.iter() {
+            try!(component.print(printer, constant_pool));
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RecordComponent {
+    name_index: usize,
+    desc_index: usize,
+    /// Attributes of the component, e.g. `Signature` or annotations.
+    pub attrs: Vec<Attr>,
+}
+
+impl RecordComponent {
+    pub fn name<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a str> {
+        constant_pool.get_str(self.name_index)
+    }
+
+    pub fn desc<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a str> {
+        constant_pool.get_str(self.desc_index)
+    }
+
+    pub fn name_index(&self) -> usize {
+        self.name_index
+    }
+
+    pub fn desc_index(&self) -> usize {
+        self.desc_index
+    }
+}
+
+impl_read! {
+    RecordComponent(reader, constant_pool: &dyn StrPool, diagnostics: &mut Diagnostics) -> Result<Self> = {
+        let name_index = try!(reader.read_u16::<BigEndian>()) as usize;
+        let desc_index = try!(reader.read_u16::<BigEndian>()) as usize;
+
+        let attrs = try!(attr::read_attrs(reader, constant_pool, diagnostics));
+
+        Ok(RecordComponent {
+            name_index: name_index,
+            desc_index: desc_index,
+            attrs: attrs,
+        })
+    }
+}
+
+impl_write! {
+    RecordComponent(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.name_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.desc_index as u16));
+        try!(attr::write_attrs(writer, &self.attrs));
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    RecordComponent(self, printer, constant_pool: &ConstantPool) {
+        let name = try!(self.name(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid name index")));
+        let desc = try!(self.desc(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid descriptor index")));
+
+        try!(printer.write_indent());
+        try!(writeln!(printer, "Component `{}` [{}]:", name, desc));
+
+        if !self.attrs.is_empty() {
+            let mut printer = printer.sub_indent(1);
+
+            try!(printer.write_indent());
+            try!(writeln!(printer, "Attrs:"));
+
+            for attr in self.attrs.iter() {
+                try!(attr.print(&mut printer.sub_indent(1).by_ref(), constant_pool));
+            }
+        }
+    }
+}
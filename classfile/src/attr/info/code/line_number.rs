@@ -1,4 +1,4 @@
-use constant::ConstantPool;
+use constant::StrPool;
 use error::Result;
 use std::slice::Iter;
 
@@ -14,7 +14,7 @@ impl LineNumberTableAttrInfo {
 }
 
 impl_read! {
-    LineNumberTableAttrInfo(reader, _constant_pool: &ConstantPool) -> Result<Self> = {
+    LineNumberTableAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
         // Read entries
         let entries_count = try!(reader.read_u16::<BigEndian>()) as usize;
         let mut entries = Vec::with_capacity(entries_count);
@@ -29,6 +29,17 @@ impl_read! {
     }
 }
 
+impl_write! {
+    LineNumberTableAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.entries.len() as u16));
+        for entry in self.entries() {
+            try!(entry.write(writer));
+        }
+
+        Ok(())
+    }
+}
+
 impl_print! {
     LineNumberTableAttrInfo(self, printer) {
         for entry in self.entries() {
@@ -57,6 +68,15 @@ impl_read! {
     }
 }
 
+impl_write! {
+    LineNumber(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.start_pc as u16));
+        try!(writer.write_u16::<BigEndian>(self.line_number as u16));
+
+        Ok(())
+    }
+}
+
 impl_print! {
     LineNumber(self, printer) {
         try!(write!(printer, "{:#x} = Line {}", self.start_pc, self.line_number));
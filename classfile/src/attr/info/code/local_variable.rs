@@ -1,4 +1,4 @@
-use constant::ConstantPool;
+use constant::{ConstantPool, StrPool};
 use error::Result;
 use std::slice::Iter;
 
@@ -14,7 +14,7 @@ impl LocalVariableTableAttrInfo {
 }
 
 impl_read! {
-    LocalVariableTableAttrInfo(reader, _constant_pool: &ConstantPool) -> Result<Self> = {
+    LocalVariableTableAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
         let entries_count = try!(reader.read_u16::<BigEndian>()) as usize;
         let mut entries = Vec::with_capacity(entries_count);
         for _ in 0..entries_count {
@@ -28,6 +28,17 @@ impl_read! {
     }
 }
 
+impl_write! {
+    LocalVariableTableAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.entries.len() as u16));
+        for entry in self.entries() {
+            try!(entry.write(writer));
+        }
+
+        Ok(())
+    }
+}
+
 impl_print! {
     LocalVariableTableAttrInfo(self, printer, constant_pool: &ConstantPool) {
         for entry in self.entries() {
@@ -53,6 +64,11 @@ impl LocalVariable {
     pub fn desc<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a str> {
         constant_pool.get_str(self.desc_index)
     }
+
+    /// Find the generic signature of this variable, i.e. the entry for the same slot and pc range.
+    pub fn ty<'a>(&self, types: &'a LocalVariableTypeTableAttrInfo) -> Option<&'a LocalVariableType> {
+        types.get(self.index, self.start_pc, self.length)
+    }
 }
 
 impl_read! {
@@ -73,10 +89,22 @@ impl_read! {
     }
 }
 
+impl_write! {
+    LocalVariable(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.start_pc as u16));
+        try!(writer.write_u16::<BigEndian>(self.length as u16));
+        try!(writer.write_u16::<BigEndian>(self.name_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.desc_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.index as u16));
+
+        Ok(())
+    }
+}
+
 impl_print! {
     LocalVariable(self, printer, constant_pool: &ConstantPool) {
-        let name = self.name(constant_pool).expect("Invalid name index");
-        let desc = self.desc(constant_pool).expect("Invalid desc index");
+        let name = try!(self.name(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid name index")));
+        let desc = try!(self.desc(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid desc index")));
 
         let start = self.start_pc;
         let end = start + self.length;
@@ -92,3 +120,125 @@ impl_print! {
         }
     }
 }
+
+/// Generic signatures of the local variables whose type uses type variables or parameterized
+/// types. Each entry complements the `LocalVariableTable` entry with the same slot and pc range.
+#[derive(Debug)]
+pub struct LocalVariableTypeTableAttrInfo {
+    entries: Vec<LocalVariableType>,
+}
+
+impl LocalVariableTypeTableAttrInfo {
+    pub fn entries<'a>(&'a self) -> Iter<'a, LocalVariableType> {
+        self.entries.iter()
+    }
+
+    pub fn get(&self, index: usize, start_pc: usize, length: usize) -> Option<&LocalVariableType> {
+        self.entries.iter().find(|entry| {
+            entry.index == index && entry.start_pc == start_pc && entry.length == length
+        })
+    }
+}
+
+impl_read! {
+    LocalVariableTypeTableAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
+        let entries_count = try!(reader.read_u16::<BigEndian>()) as usize;
+        let mut entries = Vec::with_capacity(entries_count);
+        for _ in 0..entries_count {
+            let entry = try!(LocalVariableType::read(reader));
+            entries.push(entry);
+        }
+
+        Ok(LocalVariableTypeTableAttrInfo {
+            entries: entries,
+        })
+    }
+}
+
+impl_write! {
+    LocalVariableTypeTableAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.entries.len() as u16));
+        for entry in self.entries() {
+            try!(entry.write(writer));
+        }
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    LocalVariableTypeTableAttrInfo(self, printer, constant_pool: &ConstantPool) {
+        for entry in self.entries() {
+            try!(entry.print(printer, constant_pool));
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LocalVariableType {
+    pub start_pc: usize,
+    pub length: usize,
+    name_index: usize,
+    signature_index: usize,
+    pub index: usize,
+}
+
+impl LocalVariableType {
+    pub fn name<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a str> {
+        constant_pool.get_str(self.name_index)
+    }
+
+    pub fn signature<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a str> {
+        constant_pool.get_str(self.signature_index)
+    }
+}
+
+impl_read! {
+    LocalVariableType(reader) -> Result<Self> = {
+        let start_pc = try!(reader.read_u16::<BigEndian>()) as usize;
+        let length = try!(reader.read_u16::<BigEndian>()) as usize;
+        let name_index = try!(reader.read_u16::<BigEndian>()) as usize;
+        let signature_index = try!(reader.read_u16::<BigEndian>()) as usize;
+        let index = try!(reader.read_u16::<BigEndian>()) as usize;
+
+        Ok(LocalVariableType {
+            start_pc: start_pc,
+            length: length,
+            name_index: name_index,
+            signature_index: signature_index,
+            index: index,
+        })
+    }
+}
+
+impl_write! {
+    LocalVariableType(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.start_pc as u16));
+        try!(writer.write_u16::<BigEndian>(self.length as u16));
+        try!(writer.write_u16::<BigEndian>(self.name_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.signature_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.index as u16));
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    LocalVariableType(self, printer, constant_pool: &ConstantPool) {
+        let name = try!(self.name(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid name index")));
+        let signature = try!(self.signature(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid signature index")));
+
+        let start = self.start_pc;
+        let end = start + self.length;
+
+        try!(printer.write_indent());
+        try!(writeln!(printer, "Local variable `{}` [{}] @ {:#x}:", name, signature, self.index));
+
+        {
+            let mut printer = printer.sub_indent(1);
+
+            try!(printer.write_indent());
+            try!(writeln!(printer, "Location: [{:#x}:{:#x}]", start, end));
+        }
+    }
+}
@@ -1,5 +1,5 @@
 pub use self::line_number::LineNumberTableAttrInfo;
-pub use self::local_variable::LocalVariableTableAttrInfo;
+pub use self::local_variable::{LocalVariableTableAttrInfo, LocalVariableTypeTableAttrInfo};
 pub use self::stack_map::StackMapTableAttrInfo;
 
 pub mod line_number;
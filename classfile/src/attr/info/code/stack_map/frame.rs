@@ -1,4 +1,8 @@
-use error::Result;
+use constant::ConstantPool;
+use error::*;
+use std::io;
+use super::verification_type::VerificationTypeInfo;
+use utils::print::Printer;
 
 #[derive(Debug)]
 pub struct StackMapFrame {
@@ -7,6 +11,10 @@ pub struct StackMapFrame {
 }
 
 impl StackMapFrame {
+    pub fn tag(&self) -> u8 {
+        self.tag
+    }
+
     pub fn offset_delta(&self) -> usize {
         match self.info {
             StackMapFrameInfo::SameFrame                                     => self.tag as usize,
@@ -20,10 +28,14 @@ impl StackMapFrame {
     }
 
     pub fn offset(&self, frames: &[StackMapFrame]) -> usize {
-        match frames.split_last() {
-            Some((ref last, frames)) => last.offset(frames) + self.offset_delta() + 1,
-            None                     => self.offset_delta(),
+        let mut offset = None;
+        for frame in frames.iter().chain(Some(self)) {
+            offset = Some(match offset {
+                Some(offset) => offset + frame.offset_delta() + 1,
+                None => frame.offset_delta(),
+            });
         }
+        offset.unwrap_or(0)
     }
 }
 
@@ -39,12 +51,21 @@ impl_read! {
     }
 }
 
+impl_write! {
+    StackMapFrame(self, writer) -> Result<()> = {
+        try!(writer.write_u8(self.tag));
+        try!(self.info.write(writer));
+
+        Ok(())
+    }
+}
+
 impl_print! {
-    StackMapFrame(self, printer, frames: &[StackMapFrame]) {
+    StackMapFrame(self, printer, frames: &[StackMapFrame], constant_pool: &ConstantPool) {
         try!(printer.write_indent());
         try!(writeln!(printer, "{} ({:#x}):", self.info.name(), self.offset(frames)));
 
-        try!(self.info.print(&mut printer.sub_indent(1), self, frames));
+        try!(self.info.print(&mut printer.sub_indent(1), constant_pool));
     }
 }
 
@@ -60,7 +81,7 @@ pub enum StackMapFrameInfo {
 }
 
 impl StackMapFrameInfo {
-    fn name(&self) -> &'static str {
+    pub fn name(&self) -> &'static str {
         match *self {
             StackMapFrameInfo::SameFrame                                => "SameFrame",
             StackMapFrameInfo::SameLocalsOneStackItemFrame(..)          => "SameLocalsOneStackItemFrame",
@@ -75,82 +96,169 @@ impl StackMapFrameInfo {
 
 impl_read! {
     StackMapFrameInfo(reader, tag: u8) -> Result<Self> = {
-        use error::Error;
-
         let info = match tag {
-            0...63      => StackMapFrameInfo::SameFrame,
-            64...127    => StackMapFrameInfo::SameLocalsOneStackItemFrame(
+            0..=63      => StackMapFrameInfo::SameFrame,
+            64..=127    => StackMapFrameInfo::SameLocalsOneStackItemFrame(
                     try!(SameLocalsOneStackItemFrameInfo::read(reader, tag))
                 ),
-            128...246   => panic!("Reserved tag"),
+            128..=246   => bail!(ErrorKind::ReservedFrameType(tag)),
             247         => StackMapFrameInfo::SameLocalsOneStackItemFrameExtended(
                     try!(SameLocalsOneStackItemFrameExtendedInfo::read(reader, tag))
                 ),
-            248...250   => StackMapFrameInfo::ChopFrame(try!(ChopFrameInfo::read(reader, tag))),
+            248..=250   => StackMapFrameInfo::ChopFrame(try!(ChopFrameInfo::read(reader, tag))),
             251         => StackMapFrameInfo::SameFrameExtended(
                     try!(SameFrameExtendedInfo::read(reader, tag))
                 ),
-            252...254   => StackMapFrameInfo::AppendFrame(try!(AppendFrameInfo::read(reader, tag))),
+            252..=254   => StackMapFrameInfo::AppendFrame(try!(AppendFrameInfo::read(reader, tag))),
             255         => StackMapFrameInfo::FullFrame(try!(FullFrameInfo::read(reader, tag))),
-            _           => unreachable!(),
         };
 
         Ok(info)
     }
 }
 
+impl_write! {
+    StackMapFrameInfo(self, writer) -> Result<()> = {
+        match *self {
+            StackMapFrameInfo::SameFrame => {}
+            StackMapFrameInfo::SameLocalsOneStackItemFrame(ref info) => try!(info.write(writer)),
+            StackMapFrameInfo::SameLocalsOneStackItemFrameExtended(ref info) => try!(info.write(writer)),
+            StackMapFrameInfo::ChopFrame(ref info) => try!(info.write(writer)),
+            StackMapFrameInfo::SameFrameExtended(ref info) => try!(info.write(writer)),
+            StackMapFrameInfo::AppendFrame(ref info) => try!(info.write(writer)),
+            StackMapFrameInfo::FullFrame(ref info) => try!(info.write(writer)),
+        }
+
+        Ok(())
+    }
+}
+
 impl_print! {
-    StackMapFrameInfo(self, printer, _frame: &StackMapFrame, _frames: &[StackMapFrame]) {
-        try!(printer.write_indent());
-        try!(writeln!(printer, "[ data ]"));
+    StackMapFrameInfo(self, printer, constant_pool: &ConstantPool) {
+        match *self {
+            StackMapFrameInfo::SameFrame | StackMapFrameInfo::SameFrameExtended(..) => {}
+            StackMapFrameInfo::SameLocalsOneStackItemFrame(ref info) => {
+                try!(print_types(printer, "Stack", Some(&info.stack_item), constant_pool));
+            }
+            StackMapFrameInfo::SameLocalsOneStackItemFrameExtended(ref info) => {
+                try!(print_types(printer, "Stack", Some(&info.stack_item), constant_pool));
+            }
+            StackMapFrameInfo::ChopFrame(ref info) => {
+                try!(printer.write_indent());
+                try!(writeln!(printer, "Chopped locals: {}", info.chopped()));
+            }
+            StackMapFrameInfo::AppendFrame(ref info) => {
+                try!(print_types(printer, "Locals", info.locals.iter(), constant_pool));
+            }
+            StackMapFrameInfo::FullFrame(ref info) => {
+                try!(print_types(printer, "Locals", info.locals.iter(), constant_pool));
+                try!(print_types(printer, "Stack", info.stack.iter(), constant_pool));
+            }
+        }
     }
 }
 
+fn print_types<'a, W, I>(printer: &mut Printer<W>, name: &str, types: I,
+                         constant_pool: &ConstantPool) -> io::Result<()>
+    where W: io::Write, I: IntoIterator<Item = &'a VerificationTypeInfo>
+{
+    use std::io::Write;
+
+    try!(printer.write_indent());
+    try!(write!(printer, "{}: [", name));
+    for (i, ty) in types.into_iter().enumerate() {
+        if i > 0 {
+            try!(write!(printer, ", "));
+        }
+        try!(ty.print(printer, constant_pool));
+    }
+    try!(writeln!(printer, "]"));
+
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct SameLocalsOneStackItemFrameInfo {
-    type_info: (), // TODO: Create VerificationTypeInfo type
+    pub stack_item: VerificationTypeInfo,
 }
 
 impl_read! {
-    SameLocalsOneStackItemFrameInfo(_reader, _tag: u8) -> Result<Self> = {
+    SameLocalsOneStackItemFrameInfo(reader, _tag: u8) -> Result<Self> = {
+        let stack_item = try!(VerificationTypeInfo::read(reader));
+
         Ok(SameLocalsOneStackItemFrameInfo {
-            type_info: (),
+            stack_item: stack_item,
         })
     }
 }
 
+impl_write! {
+    SameLocalsOneStackItemFrameInfo(self, writer) -> Result<()> = {
+        try!(self.stack_item.write(writer));
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct SameLocalsOneStackItemFrameExtendedInfo {
     offset_delta: u16,
-    type_info: (), // TODO: Create VerificationTypeInfo type
+    pub stack_item: VerificationTypeInfo,
 }
 
 impl_read! {
     SameLocalsOneStackItemFrameExtendedInfo(reader, _tag: u8) -> Result<Self> = {
         let offset_delta = try!(reader.read_u16::<BigEndian>());
+        let stack_item = try!(VerificationTypeInfo::read(reader));
 
         Ok(SameLocalsOneStackItemFrameExtendedInfo {
             offset_delta: offset_delta,
-            type_info: (),
+            stack_item: stack_item,
         })
     }
 }
 
+impl_write! {
+    SameLocalsOneStackItemFrameExtendedInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.offset_delta));
+        try!(self.stack_item.write(writer));
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct ChopFrameInfo {
     offset_delta: u16,
+    chopped: u8,
+}
+
+impl ChopFrameInfo {
+    /// Number of trailing locals removed from the previous frame.
+    pub fn chopped(&self) -> usize {
+        self.chopped as usize
+    }
 }
 
 impl_read! {
-    ChopFrameInfo(reader, _tag: u8) -> Result<Self> = {
+    ChopFrameInfo(reader, tag: u8) -> Result<Self> = {
         let offset_delta = try!(reader.read_u16::<BigEndian>());
 
         Ok(ChopFrameInfo {
             offset_delta: offset_delta,
+            chopped: 251 - tag,
         })
     }
 }
 
+impl_write! {
+    ChopFrameInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.offset_delta));
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct SameFrameExtendedInfo {
     offset_delta: u16,
@@ -166,38 +274,96 @@ impl_read! {
     }
 }
 
+impl_write! {
+    SameFrameExtendedInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.offset_delta));
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct AppendFrameInfo {
     offset_delta: u16,
-    locals: Vec<()>,
+    pub locals: Vec<VerificationTypeInfo>,
 }
 
 impl_read! {
-    AppendFrameInfo(reader, _tag: u8) -> Result<Self> = {
+    AppendFrameInfo(reader, tag: u8) -> Result<Self> = {
         let offset_delta = try!(reader.read_u16::<BigEndian>());
 
+        let locals_count = (tag - 251) as usize;
+        let mut locals = Vec::with_capacity(locals_count);
+        for _ in 0..locals_count {
+            let local = try!(VerificationTypeInfo::read(reader));
+            locals.push(local);
+        }
+
         Ok(AppendFrameInfo {
             offset_delta: offset_delta,
-            locals: Vec::new(),
+            locals: locals,
         })
     }
 }
 
+impl_write! {
+    AppendFrameInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.offset_delta));
+        for local in self.locals.iter() {
+            try!(local.write(writer));
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct FullFrameInfo {
     offset_delta: u16,
-    locals: Vec<()>,
-    stack: Vec<()>,
+    pub locals: Vec<VerificationTypeInfo>,
+    pub stack: Vec<VerificationTypeInfo>,
 }
 
 impl_read! {
     FullFrameInfo(reader, _tag: u8) -> Result<Self> = {
         let offset_delta = try!(reader.read_u16::<BigEndian>());
 
+        let locals_count = try!(reader.read_u16::<BigEndian>()) as usize;
+        let mut locals = Vec::with_capacity(locals_count);
+        for _ in 0..locals_count {
+            let local = try!(VerificationTypeInfo::read(reader));
+            locals.push(local);
+        }
+
+        let stack_count = try!(reader.read_u16::<BigEndian>()) as usize;
+        let mut stack = Vec::with_capacity(stack_count);
+        for _ in 0..stack_count {
+            let item = try!(VerificationTypeInfo::read(reader));
+            stack.push(item);
+        }
+
         Ok(FullFrameInfo {
             offset_delta: offset_delta,
-            locals: Vec::new(),
-            stack: Vec::new(),
+            locals: locals,
+            stack: stack,
         })
     }
 }
+
+impl_write! {
+    FullFrameInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.offset_delta));
+
+        try!(writer.write_u16::<BigEndian>(self.locals.len() as u16));
+        for local in self.locals.iter() {
+            try!(local.write(writer));
+        }
+
+        try!(writer.write_u16::<BigEndian>(self.stack.len() as u16));
+        for item in self.stack.iter() {
+            try!(item.write(writer));
+        }
+
+        Ok(())
+    }
+}
@@ -1,20 +1,76 @@
-use constant::ConstantPool;
-use error::Result;
-use self::frame::StackMapFrame;
+use constant::{ConstantPool, StrPool};
+use error::*;
+use self::frame::{StackMapFrame, StackMapFrameInfo};
+use self::verification_type::VerificationTypeInfo;
 
 pub mod frame;
+pub mod verification_type;
 
 #[derive(Debug)]
 pub struct StackMapTableAttrInfo {
     entries: Vec<StackMapFrame>,
 }
 
+impl StackMapTableAttrInfo {
+    pub fn entries(&self) -> &[StackMapFrame] {
+        &self.entries
+    }
+
+    /// Expand the delta-encoded entries into absolute frames, one per entry.
+    ///
+    /// `initial_locals` is the implicit frame at offset 0, as derived from the method descriptor.
+    /// Locals keep the compact form used by the attribute: `long` and `double` count as one entry.
+    pub fn frames(&self, initial_locals: Vec<VerificationTypeInfo>) -> Result<Vec<Frame>> {
+        let mut frames: Vec<Frame> = Vec::with_capacity(self.entries.len());
+        let mut locals = initial_locals;
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            let offset = match frames.last() {
+                Some(prev) => prev.offset + entry.offset_delta() + 1,
+                None => entry.offset_delta(),
+            };
+
+            let stack = match entry.info {
+                StackMapFrameInfo::SameFrame | StackMapFrameInfo::SameFrameExtended(..) => Vec::new(),
+                StackMapFrameInfo::SameLocalsOneStackItemFrame(ref info) => vec![info.stack_item.clone()],
+                StackMapFrameInfo::SameLocalsOneStackItemFrameExtended(ref info) => {
+                    vec![info.stack_item.clone()]
+                }
+                StackMapFrameInfo::ChopFrame(ref info) => {
+                    if info.chopped() > locals.len() {
+                        bail!(ErrorKind::BadStackMapFrame(i));
+                    }
+                    let len = locals.len() - info.chopped();
+                    locals.truncate(len);
+                    Vec::new()
+                }
+                StackMapFrameInfo::AppendFrame(ref info) => {
+                    locals.extend(info.locals.iter().cloned());
+                    Vec::new()
+                }
+                StackMapFrameInfo::FullFrame(ref info) => {
+                    locals = info.locals.clone();
+                    info.stack.clone()
+                }
+            };
+
+            frames.push(Frame {
+                offset: offset,
+                locals: locals.clone(),
+                stack: stack,
+            });
+        }
+
+        Ok(frames)
+    }
+}
+
 impl_read! {
-    StackMapTableAttrInfo(reader, _constant_pool: &ConstantPool) -> Result<Self> = {
+    StackMapTableAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
         let entries_count = try!(reader.read_u16::<BigEndian>()) as usize;
         let mut entries = Vec::with_capacity(entries_count);
-        for _ in 0..entries_count {
-            let entry = try!(StackMapFrame::read(reader));
+        for i in 0..entries_count {
+            let entry = try!(StackMapFrame::read(reader).within(|| format!("frame[{}]", i)));
             entries.push(entry);
         }
 
@@ -24,10 +80,71 @@ impl_read! {
     }
 }
 
+impl_write! {
+    StackMapTableAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.entries.len() as u16));
+        for entry in self.entries.iter() {
+            try!(entry.write(writer));
+        }
+
+        Ok(())
+    }
+}
+
 impl_print! {
-    StackMapTableAttrInfo(self, printer, _constant_pool: &ConstantPool) {
+    StackMapTableAttrInfo(self, printer, constant_pool: &ConstantPool) {
         for (i, entry) in self.entries.iter().enumerate() {
-            try!(entry.print(printer, &self.entries[0..i]));
+            try!(entry.print(printer, &self.entries[0..i], constant_pool));
         }
     }
 }
+
+/// A fully expanded stack map frame, located at an absolute bytecode offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub offset: usize,
+    pub locals: Vec<VerificationTypeInfo>,
+    pub stack: Vec<VerificationTypeInfo>,
+}
+
+#[cfg(test)]
+mod tests {
+    use constant::ConstantPool;
+    use std::io::Cursor;
+    use super::*;
+
+    #[test]
+    fn frames_round_trip() {
+        let bytes: &[u8] = &[
+            0, 7,                               // entries
+            3,                                  // same_frame
+            65, 7, 0, 2,                        // same_locals_1_stack_item_frame, Object #2
+            247, 0, 10, 8, 0, 4,                // ..._extended, Uninitialized(4)
+            249, 0, 3,                          // chop_frame, 2 locals
+            251, 1, 0,                          // same_frame_extended
+            253, 0, 5, 4, 6,                    // append_frame, long, uninitialized_this
+            255, 0, 7, 0, 2, 1, 3, 0, 1, 5,     // full_frame, [int, double], [null]
+        ];
+        let constant_pool = ConstantPool::read(&mut Cursor::new(&[0, 1])).unwrap();
+        let info = StackMapTableAttrInfo::read(&mut Cursor::new(bytes), &constant_pool).unwrap();
+
+        let mut written = Vec::new();
+        info.write(&mut written).unwrap();
+        assert_eq!(written, bytes);
+
+        let frames = info.frames(vec![VerificationTypeInfo::Integer; 3]).unwrap();
+        let offsets: Vec<usize> = frames.iter().map(|frame| frame.offset).collect();
+        assert_eq!(offsets, [3, 5, 16, 20, 277, 283, 291]);
+        assert_eq!(frames[5].locals, [VerificationTypeInfo::Integer, VerificationTypeInfo::Long,
+                                      VerificationTypeInfo::UninitializedThis]);
+        assert_eq!(frames[6].stack, [VerificationTypeInfo::Null]);
+    }
+
+    #[test]
+    fn reserved_frame_type() {
+        let constant_pool = ConstantPool::read(&mut Cursor::new(&[0, 1])).unwrap();
+        let err = StackMapTableAttrInfo::read(&mut Cursor::new(&[0, 1, 200]), &constant_pool).unwrap_err();
+        assert_eq!(err.path(), Some("frame[0]"));
+        assert!(err.iter().any(|cause| cause.to_string() == "Reserved stack map frame type: 200"));
+    }
+}
@@ -0,0 +1,127 @@
+use constant::{ConstantPool, ConstantClassInfo};
+use error::*;
+use std::fmt;
+
+mod tag {
+    pub const ITEM_TOP: u8                  = 0;
+    pub const ITEM_INTEGER: u8              = 1;
+    pub const ITEM_FLOAT: u8                = 2;
+    pub const ITEM_DOUBLE: u8               = 3;
+    pub const ITEM_LONG: u8                 = 4;
+    pub const ITEM_NULL: u8                 = 5;
+    pub const ITEM_UNINITIALIZED_THIS: u8   = 6;
+    pub const ITEM_OBJECT: u8               = 7;
+    pub const ITEM_UNINITIALIZED: u8        = 8;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationTypeInfo {
+    Top,
+    Integer,
+    Float,
+    Long,
+    Double,
+    Null,
+    UninitializedThis,
+    /// Instance of the class at the given constant pool index.
+    Object(usize),
+    /// Object created by the `new` instruction at the given offset, not yet initialized.
+    Uninitialized(usize),
+}
+
+impl VerificationTypeInfo {
+    pub fn class<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a ConstantClassInfo> {
+        match *self {
+            VerificationTypeInfo::Object(index) => constant_pool.get_class_info(index),
+            _ => None,
+        }
+    }
+
+    /// Whether a value of this type takes two local variable slots.
+    pub fn is_category2(&self) -> bool {
+        matches!(*self, VerificationTypeInfo::Long | VerificationTypeInfo::Double)
+    }
+
+    pub fn tag(&self) -> u8 {
+        match *self {
+            VerificationTypeInfo::Top               => tag::ITEM_TOP,
+            VerificationTypeInfo::Integer           => tag::ITEM_INTEGER,
+            VerificationTypeInfo::Float             => tag::ITEM_FLOAT,
+            VerificationTypeInfo::Long              => tag::ITEM_LONG,
+            VerificationTypeInfo::Double            => tag::ITEM_DOUBLE,
+            VerificationTypeInfo::Null              => tag::ITEM_NULL,
+            VerificationTypeInfo::UninitializedThis => tag::ITEM_UNINITIALIZED_THIS,
+            VerificationTypeInfo::Object(..)        => tag::ITEM_OBJECT,
+            VerificationTypeInfo::Uninitialized(..) => tag::ITEM_UNINITIALIZED,
+        }
+    }
+}
+
+impl_read! {
+    VerificationTypeInfo(reader) -> Result<Self> = {
+        let tag = try!(reader.read_u8());
+
+        let info = match tag {
+            tag::ITEM_TOP                   => VerificationTypeInfo::Top,
+            tag::ITEM_INTEGER               => VerificationTypeInfo::Integer,
+            tag::ITEM_FLOAT                 => VerificationTypeInfo::Float,
+            tag::ITEM_DOUBLE                => VerificationTypeInfo::Double,
+            tag::ITEM_LONG                  => VerificationTypeInfo::Long,
+            tag::ITEM_NULL                  => VerificationTypeInfo::Null,
+            tag::ITEM_UNINITIALIZED_THIS    => VerificationTypeInfo::UninitializedThis,
+            tag::ITEM_OBJECT                => {
+                let index = try!(reader.read_u16::<BigEndian>()) as usize;
+                VerificationTypeInfo::Object(index)
+            }
+            tag::ITEM_UNINITIALIZED         => {
+                let offset = try!(reader.read_u16::<BigEndian>()) as usize;
+                VerificationTypeInfo::Uninitialized(offset)
+            }
+            _ => bail!(ErrorKind::BadTagValue(tag)),
+        };
+
+        Ok(info)
+    }
+}
+
+impl_write! {
+    VerificationTypeInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u8(self.tag()));
+
+        match *self {
+            VerificationTypeInfo::Object(index) => try!(writer.write_u16::<BigEndian>(index as u16)),
+            VerificationTypeInfo::Uninitialized(offset) => try!(writer.write_u16::<BigEndian>(offset as u16)),
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    VerificationTypeInfo(self, printer, constant_pool: &ConstantPool) {
+        match *self {
+            VerificationTypeInfo::Object(..) => {
+                let class = try!(self.class(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid class index")));
+                try!(class.print(printer, constant_pool));
+            }
+            _ => try!(write!(printer, "{}", self)),
+        }
+    }
+}
+
+impl fmt::Display for VerificationTypeInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VerificationTypeInfo::Top                       => write!(f, "top"),
+            VerificationTypeInfo::Integer                   => write!(f, "int"),
+            VerificationTypeInfo::Float                     => write!(f, "float"),
+            VerificationTypeInfo::Long                      => write!(f, "long"),
+            VerificationTypeInfo::Double                    => write!(f, "double"),
+            VerificationTypeInfo::Null                      => write!(f, "null"),
+            VerificationTypeInfo::UninitializedThis         => write!(f, "uninitialized_this"),
+            VerificationTypeInfo::Object(index)             => write!(f, "class #{}", index),
+            VerificationTypeInfo::Uninitialized(offset)     => write!(f, "uninitialized {}", offset),
+        }
+    }
+}
@@ -1,7 +1,7 @@
-use constant::{ConstantPool, ConstantPoolEntry};
+use constant::{ConstantPool, ConstantPoolEntry, StrPool};
 use error::Result;
-use byteorder::{ReadBytesExt, BigEndian};
-use std::io::Read;
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
+use std::io::{Read, Write};
 
 #[derive(Debug)]
 pub struct ConstantValueAttrInfo {
@@ -9,7 +9,7 @@ pub struct ConstantValueAttrInfo {
 }
 
 impl ConstantValueAttrInfo {
-    pub fn read<R: Read>(reader: &mut R, _pool: &ConstantPool) -> Result<ConstantValueAttrInfo> {
+    pub fn read<R: Read>(reader: &mut R, _pool: &dyn StrPool) -> Result<ConstantValueAttrInfo> {
         let value_index = try!(reader.read_u16::<BigEndian>()) as usize;
 
         Ok(ConstantValueAttrInfo {
@@ -17,6 +17,12 @@ impl ConstantValueAttrInfo {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u16::<BigEndian>(self.value_index as u16));
+
+        Ok(())
+    }
+
     pub fn value<'a>(&self, pool: &'a ConstantPool) -> Option<&'a ConstantPoolEntry> {
         pool.get(self.value_index)
     }
@@ -24,7 +30,7 @@ impl ConstantValueAttrInfo {
 
 impl_print! {
     ConstantValueAttrInfo(self, printer, constant_pool: &ConstantPool) {
-        let value = self.value(constant_pool).expect("Invalid value index");
+        let value = try!(self.value(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid value index")));
 
         try!(printer.write_indent());
         try!(value.print(printer, constant_pool));
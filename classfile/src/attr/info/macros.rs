@@ -5,9 +5,13 @@ macro_rules! empty_attr_info {
         pub struct $name;
 
         impl $name {
-            pub fn read<R: ::std::io::Read>(_reader: &mut R, _constant_pool: &$crate::constant::ConstantPool) -> $crate::error::Result<$name> {
+            pub fn read<R: ::std::io::Read>(_reader: &mut R, _constant_pool: &dyn $crate::constant::StrPool) -> $crate::error::Result<$name> {
                 Ok($name)
             }
+
+            pub fn write<W: ::std::io::Write>(&self, _writer: &mut W) -> $crate::error::Result<()> {
+                Ok(())
+            }
         }
 
         impl_print! {
@@ -15,3 +19,30 @@ macro_rules! empty_attr_info {
         }
     }
 }
+
+/// Iterator resolving a table of constant pool indexes, e.g. the classes of a `NestMembers` attribute.
+macro_rules! index_iter {
+    ($name:ident, $item:ty, $get:ident) => {
+        pub struct $name<'a, 'b> {
+            iter: ::std::slice::Iter<'a, usize>,
+            constant_pool: &'b $crate::constant::ConstantPool,
+        }
+
+        impl<'a, 'b> $name<'a, 'b> {
+            fn new(indexes: &'a [usize], constant_pool: &'b $crate::constant::ConstantPool) -> Self {
+                $name {
+                    iter: indexes.iter(),
+                    constant_pool: constant_pool,
+                }
+            }
+        }
+
+        impl<'a, 'b> Iterator for $name<'a, 'b> {
+            type Item = Option<&'b $item>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.iter.next().map(|&index| self.constant_pool.$get(index))
+            }
+        }
+    };
+}
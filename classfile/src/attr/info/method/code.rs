@@ -1,6 +1,9 @@
-use attr::Attr;
-use constant::{ConstantPool, ConstantClassInfo};
-use error::Result;
+use attr::{self, Attr};
+use attr::info::AttrInfo;
+use attr::info::code::local_variable::{LocalVariable, LocalVariableType};
+use bytecode::Instructions;
+use constant::{ConstantPool, ConstantClassInfo, StrPool};
+use error::{Diagnostics, Result};
 
 #[derive(Debug)]
 pub struct CodeAttrInfo {
@@ -11,8 +14,35 @@ pub struct CodeAttrInfo {
     pub attrs: Vec<Attr>,
 }
 
+impl CodeAttrInfo {
+    pub fn instructions<'a>(&'a self) -> Instructions<'a> {
+        Instructions::new(&self.code)
+    }
+
+    /// All the entries of the `LocalVariableTable` attributes, each joined with the matching
+    /// `LocalVariableTypeTable` entry if there is one.
+    pub fn local_variables(&self) -> Vec<(&LocalVariable, Option<&LocalVariableType>)> {
+        let mut variables = Vec::new();
+
+        for attr in self.attrs.iter() {
+            if let AttrInfo::LocalVariableTable(ref table) = attr.info {
+                for variable in table.entries() {
+                    let ty = self.attrs.iter().filter_map(|attr| match attr.info {
+                        AttrInfo::LocalVariableTypeTable(ref types) => variable.ty(types),
+                        _ => None,
+                    }).next();
+
+                    variables.push((variable, ty));
+                }
+            }
+        }
+
+        variables
+    }
+}
+
 impl_read! {
-    CodeAttrInfo(reader, constant_pool: &ConstantPool) -> Result<Self> = {
+    CodeAttrInfo(reader, constant_pool: &dyn StrPool, diagnostics: &mut Diagnostics) -> Result<Self> = {
         use utils::io::ReadExt;
 
         // Read indexes
@@ -32,12 +62,7 @@ impl_read! {
         }
 
         // Read attributes
-        let attrs_count = try!(reader.read_u16::<BigEndian>()) as usize;
-        let mut attrs = Vec::with_capacity(attrs_count);
-        for _ in 0..attrs_count {
-            let attr = try!(Attr::read(reader, constant_pool));
-            attrs.push(attr);
-        }
+        let attrs = try!(attr::read_attrs(reader, constant_pool, diagnostics));
 
         Ok(CodeAttrInfo {
             max_stack: max_stack,
@@ -49,6 +74,31 @@ impl_read! {
     }
 }
 
+impl_write! {
+    CodeAttrInfo(self, writer) -> Result<()> = {
+        use attr::write_attrs;
+
+        // Write indexes
+        try!(writer.write_u16::<BigEndian>(self.max_stack as u16));
+        try!(writer.write_u16::<BigEndian>(self.max_locals as u16));
+
+        // Write code
+        try!(writer.write_u32::<BigEndian>(self.code.len() as u32));
+        try!(writer.write_all(&self.code));
+
+        // Write exception table
+        try!(writer.write_u16::<BigEndian>(self.exception_handlers.len() as u16));
+        for exception_handler in self.exception_handlers.iter() {
+            try!(exception_handler.write(writer));
+        }
+
+        // Write attributes
+        try!(write_attrs(writer, &self.attrs));
+
+        Ok(())
+    }
+}
+
 impl_print! {
     CodeAttrInfo(self, printer, constant_pool: &ConstantPool) {
         try!(printer.write_indent());
@@ -59,6 +109,15 @@ impl_print! {
 
         try!(printer.write_indent());
         try!(writeln!(printer, "Code: [ {} bytes ]", self.code.len()));
+        for insn in self.instructions() {
+            let mut printer = printer.sub_indent(1);
+
+            try!(printer.write_indent());
+            match insn {
+                Ok((pc, insn)) => try!(writeln!(printer, "{:#06x}: {}", pc, insn)),
+                Err(e) => try!(writeln!(printer, "<{}>", e)),
+            }
+        }
 
         try!(printer.write_indent());
         try!(writeln!(printer, "Exception handlers:"));
@@ -83,6 +142,11 @@ pub struct ExceptionHandler {
 }
 
 impl ExceptionHandler {
+    /// Constant pool index of the caught class, 0 for `finally` handlers.
+    pub fn catch_type_index(&self) -> usize {
+        self.catch_type
+    }
+
     pub fn catch_type<'a>(&self, pool: &'a ConstantPool) -> Option<&'a ConstantClassInfo> {
         if self.catch_type != 0 {
             pool.get_class_info(self.catch_type)
@@ -108,6 +172,17 @@ impl_read! {
     }
 }
 
+impl_write! {
+    ExceptionHandler(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.start_pc as u16));
+        try!(writer.write_u16::<BigEndian>(self.end_pc as u16));
+        try!(writer.write_u16::<BigEndian>(self.handler_pc as u16));
+        try!(writer.write_u16::<BigEndian>(self.catch_type as u16));
+
+        Ok(())
+    }
+}
+
 impl_print! {
     ExceptionHandler(self, printer, constant_pool: &ConstantPool) {
         try!(printer.write_indent());
@@ -1,4 +1,4 @@
-use constant::{ConstantPool, ConstantClassInfo};
+use constant::{ConstantPool, ConstantClassInfo, StrPool};
 use error::Result;
 
 #[derive(Debug)]
@@ -7,13 +7,18 @@ pub struct ExceptionsAttrInfo {
 }
 
 impl ExceptionsAttrInfo {
+    /// Constant pool indexes of the exception classes.
+    pub fn indexes(&self) -> &[usize] {
+        &self.table
+    }
+
     pub fn table<'a, 'b>(&'a self, pool: &'b ConstantPool) -> ExceptionsTable<'a, 'b> {
         ExceptionsTable::new(self, pool)
     }
 }
 
 impl_read! {
-    ExceptionsAttrInfo(reader, _constant_pool: &ConstantPool) -> Result<Self> = {
+    ExceptionsAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
         let table_size = try!(reader.read_u16::<BigEndian>()) as usize;
         let mut table = Vec::with_capacity(table_size);
         for _ in 0..table_size {
@@ -27,6 +32,17 @@ impl_read! {
     }
 }
 
+impl_write! {
+    ExceptionsAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.table.len() as u16));
+        for &index in self.table.iter() {
+            try!(writer.write_u16::<BigEndian>(index as u16));
+        }
+
+        Ok(())
+    }
+}
+
 impl_print! {
     ExceptionsAttrInfo(self, printer, constant_pool: &ConstantPool) {
         for class in self.table(constant_pool).filter_map(|class| class) {
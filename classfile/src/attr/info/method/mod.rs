@@ -1,5 +1,7 @@
 pub use self::code::CodeAttrInfo;
 pub use self::exception::ExceptionsAttrInfo;
+pub use self::parameters::MethodParametersAttrInfo;
 
 pub mod code;
 pub mod exception;
+pub mod parameters;
@@ -0,0 +1,124 @@
+use constant::{ConstantPool, StrPool};
+use error::*;
+use std::slice::Iter;
+
+#[derive(Debug)]
+pub struct MethodParametersAttrInfo {
+    parameters: Vec<MethodParameter>,
+}
+
+impl MethodParametersAttrInfo {
+    pub fn parameters<'a>(&'a self) -> Iter<'a, MethodParameter> {
+        self.parameters.iter()
+    }
+}
+
+impl_read! {
+    MethodParametersAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
+        let parameters_count = try!(reader.read_u8()) as usize;
+        let mut parameters = Vec::with_capacity(parameters_count);
+        for _ in 0..parameters_count {
+            let parameter = try!(MethodParameter::read(reader));
+            parameters.push(parameter);
+        }
+
+        Ok(MethodParametersAttrInfo {
+            parameters: parameters,
+        })
+    }
+}
+
+impl_write! {
+    MethodParametersAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u8(self.parameters.len() as u8));
+        for parameter in self.parameters() {
+            try!(parameter.write(writer));
+        }
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    MethodParametersAttrInfo(self, printer, constant_pool: &ConstantPool) {
+        for parameter in self.parameters() {
+            try!(parameter.print(printer, constant_pool));
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MethodParameter {
+    name_index: usize,
+    pub access_flags: flags::AccessFlags,
+}
+
+impl MethodParameter {
+    /// Name of the parameter, or `None` for a formal parameter with no name.
+    pub fn name<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a str> {
+        if self.name_index != 0 {
+            constant_pool.get_str(self.name_index)
+        } else {
+            None
+        }
+    }
+
+    pub fn name_index(&self) -> usize {
+        self.name_index
+    }
+}
+
+impl_read! {
+    MethodParameter(reader) -> Result<Self> = {
+        let name_index = try!(reader.read_u16::<BigEndian>()) as usize;
+        let access_flags = try!(reader.read_u16::<BigEndian>());
+        let access_flags = match flags::AccessFlags::from_bits(access_flags) {
+            Some(flags) => flags,
+            None => bail!(ErrorKind::BadAccessFlags(access_flags)),
+        };
+
+        Ok(MethodParameter {
+            name_index: name_index,
+            access_flags: access_flags,
+        })
+    }
+}
+
+impl_write! {
+    MethodParameter(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.name_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.access_flags.bits()));
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    MethodParameter(self, printer, constant_pool: &ConstantPool) {
+        try!(printer.write_indent());
+        match self.name(constant_pool) {
+            Some(name) => try!(writeln!(printer, "Parameter `{}`:", name)),
+            None => try!(writeln!(printer, "Parameter:")),
+        }
+
+        {
+            let mut printer = printer.sub_indent(1);
+
+            try!(printer.write_indent());
+            try!(writeln!(printer, "Access flags: {:?}", self.access_flags));
+        }
+    }
+}
+
+pub mod flags {
+    bitflags! {
+        pub struct AccessFlags: u16 {
+            #[doc = "Declared final."]
+            const ACC_FINAL = 0x0010;
+            #[doc = "Not explicitly or implicitly declared in source code."]
+            const ACC_SYNTHETIC = 0x1000;
+            #[doc = "Implicitly declared in source code, e.g. the outer instance of an inner class constructor."]
+            const ACC_MANDATED = 0x8000;
+        }
+    }
+}
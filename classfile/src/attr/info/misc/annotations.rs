@@ -1,42 +1,167 @@
-use constant::{ConstantPool, ConstantPoolEntry};
+use constant::{ConstantPool, ConstantPoolEntry, StrPool};
 use error::*;
+use std::io::{Read, Write};
 use std::slice::Iter;
 
-#[derive(Debug)]
-pub struct RuntimeVisibleAnnotationsAttrInfo {
-    annotations: Vec<Annotation>,
+macro_rules! annotations_attr_info {
+    ($name:ident) => {
+        #[derive(Debug)]
+        pub struct $name {
+            annotations: Vec<Annotation>,
+        }
+
+        impl $name {
+            pub fn annotations<'a>(&'a self) -> Iter<'a, Annotation> {
+                self.annotations.iter()
+            }
+        }
+
+        impl_read! {
+            $name(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
+                let annotations = try!(read_annotations(reader));
+
+                Ok($name {
+                    annotations: annotations,
+                })
+            }
+        }
+
+        impl_write! {
+            $name(self, writer) -> Result<()> = {
+                try!(write_annotations(writer, &self.annotations));
+
+                Ok(())
+            }
+        }
+
+        impl_print! {
+            $name(self, printer, constant_pool: &ConstantPool) {
+                for annotation in self.annotations() {
+                    try!(printer.write_indent());
+                    try!(annotation.print(printer, constant_pool));
+                }
+            }
+        }
+    };
 }
 
-impl RuntimeVisibleAnnotationsAttrInfo {
-    pub fn annotations<'a>(&'a self) -> Iter<'a, Annotation> {
-        self.annotations.iter()
-    }
+macro_rules! parameter_annotations_attr_info {
+    ($name:ident) => {
+        #[derive(Debug)]
+        pub struct $name {
+            parameters: Vec<Vec<Annotation>>,
+        }
+
+        impl $name {
+            /// Annotations of each formal parameter, in declaration order.
+            pub fn parameters<'a>(&'a self) -> Iter<'a, Vec<Annotation>> {
+                self.parameters.iter()
+            }
+        }
+
+        impl_read! {
+            $name(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
+                let parameters_count = try!(reader.read_u8()) as usize;
+                let mut parameters = Vec::with_capacity(parameters_count);
+                for i in 0..parameters_count {
+                    let annotations = try!(read_annotations(reader).within(|| format!("parameters[{}]", i)));
+                    parameters.push(annotations);
+                }
+
+                Ok($name {
+                    parameters: parameters,
+                })
+            }
+        }
+
+        impl_write! {
+            $name(self, writer) -> Result<()> = {
+                try!(writer.write_u8(self.parameters.len() as u8));
+                for annotations in self.parameters() {
+                    try!(write_annotations(writer, annotations));
+                }
+
+                Ok(())
+            }
+        }
+
+        impl_print! {
+            $name(self, printer, constant_pool: &ConstantPool) {
+                for (index, annotations) in self.parameters().enumerate() {
+                    try!(printer.write_indent());
+                    try!(writeln!(printer, "Parameter #{}:", index));
+
+                    let mut printer = printer.sub_indent(1);
+                    for annotation in annotations.iter() {
+                        try!(printer.write_indent());
+                        try!(annotation.print(&mut printer, constant_pool));
+                    }
+                }
+            }
+        }
+    };
+}
+
+annotations_attr_info!(RuntimeVisibleAnnotationsAttrInfo);
+annotations_attr_info!(RuntimeInvisibleAnnotationsAttrInfo);
+parameter_annotations_attr_info!(RuntimeVisibleParameterAnnotationsAttrInfo);
+parameter_annotations_attr_info!(RuntimeInvisibleParameterAnnotationsAttrInfo);
+
+/// Default value of an annotation interface element, found on its method.
+#[derive(Debug)]
+pub struct AnnotationDefaultAttrInfo {
+    pub default_value: ElementValue,
 }
 
 impl_read! {
-    RuntimeVisibleAnnotationsAttrInfo(reader, _constant_pool: &ConstantPool) -> Result<Self> = {
-        let annotations_count = try!(reader.read_u16::<BigEndian>()) as usize;
-        let mut annotations = Vec::with_capacity(annotations_count);
-        for _ in 0..annotations_count {
-            let annotation = try!(Annotation::read(reader));
-            annotations.push(annotation);
-        }
+    AnnotationDefaultAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
+        let default_value = try!(ElementValue::read(reader));
 
-        Ok(RuntimeVisibleAnnotationsAttrInfo {
-            annotations: annotations,
+        Ok(AnnotationDefaultAttrInfo {
+            default_value: default_value,
         })
     }
 }
 
+impl_write! {
+    AnnotationDefaultAttrInfo(self, writer) -> Result<()> = {
+        try!(self.default_value.write(writer));
+
+        Ok(())
+    }
+}
+
 impl_print! {
-    RuntimeVisibleAnnotationsAttrInfo(self, printer, constant_pool: &ConstantPool) {
-        for annotation in self.annotations() {
-            try!(printer.write_indent());
-            try!(annotation.print(printer, constant_pool));
-        }
+    AnnotationDefaultAttrInfo(self, printer, constant_pool: &ConstantPool) {
+        try!(printer.write_indent());
+        try!(self.default_value.print(printer, constant_pool));
     }
 }
 
+fn read_annotations<R: Read>(reader: &mut R) -> Result<Vec<Annotation>> {
+    use byteorder::{ReadBytesExt, BigEndian};
+
+    let annotations_count = try!(reader.read_u16::<BigEndian>()) as usize;
+    let mut annotations = Vec::with_capacity(annotations_count);
+    for i in 0..annotations_count {
+        let annotation = try!(Annotation::read(reader).within(|| format!("annotations[{}]", i)));
+        annotations.push(annotation);
+    }
+
+    Ok(annotations)
+}
+
+fn write_annotations<W: Write>(writer: &mut W, annotations: &[Annotation]) -> Result<()> {
+    use byteorder::{WriteBytesExt, BigEndian};
+
+    try!(writer.write_u16::<BigEndian>(annotations.len() as u16));
+    for annotation in annotations {
+        try!(annotation.write(writer));
+    }
+
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct Annotation {
     type_index: usize,
@@ -72,9 +197,23 @@ impl_read! {
     }
 }
 
+impl_write! {
+    Annotation(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.type_index as u16));
+
+        // Write element values
+        try!(writer.write_u16::<BigEndian>(self.element_values.len() as u16));
+        for element_value in self.element_values() {
+            try!(element_value.write(writer));
+        }
+
+        Ok(())
+    }
+}
+
 impl_print! {
     Annotation(self, printer, constant_pool: &ConstantPool) {
-        let ty = self.ty(constant_pool).expect("Invalid type index");
+        let ty = try!(self.ty(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid type index")));
 
         try!(writeln!(printer, "Annotation [{}]:", ty));
 
@@ -108,9 +247,18 @@ impl_read! {
     }
 }
 
+impl_write! {
+    NamedElementValue(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.name_index as u16));
+        try!(self.value.write(writer));
+
+        Ok(())
+    }
+}
+
 impl_print! {
     NamedElementValue(self, printer, constant_pool: &ConstantPool) {
-        let name = self.name(constant_pool).expect("Invalid name index");
+        let name = try!(self.name(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid name index")));
 
         try!(printer.write_indent());
         try!(write!(printer, "{}: ", name));
@@ -133,7 +281,7 @@ impl_read! {
 
         let value = match tag {
             b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' | b's'
-                => ElementValue::ConstValue(try!(ConstValueInfo::read(reader))),
+                => ElementValue::ConstValue(try!(ConstValueInfo::read(reader, tag))),
             b'e'
                 => ElementValue::EnumConstValue(try!(EnumConstValueInfo::read(reader))),
             b'c'
@@ -158,6 +306,38 @@ impl_read! {
     }
 }
 
+impl_write! {
+    ElementValue(self, writer) -> Result<()> = {
+        match *self {
+            ElementValue::ConstValue(ref info) => {
+                try!(writer.write_u8(info.tag));
+                try!(info.write(writer));
+            }
+            ElementValue::EnumConstValue(ref info) => {
+                try!(writer.write_u8(b'e'));
+                try!(info.write(writer));
+            }
+            ElementValue::Class(ref info) => {
+                try!(writer.write_u8(b'c'));
+                try!(info.write(writer));
+            }
+            ElementValue::Annotation(ref annotation) => {
+                try!(writer.write_u8(b'@'));
+                try!(annotation.write(writer));
+            }
+            ElementValue::Array(ref values) => {
+                try!(writer.write_u8(b'['));
+                try!(writer.write_u16::<BigEndian>(values.len() as u16));
+                for value in values.iter() {
+                    try!(value.write(writer));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl_print! {
     ElementValue(self, printer, constant_pool: &ConstantPool) {
         match *self {
@@ -180,28 +360,43 @@ impl_print! {
 
 #[derive(Debug)]
 pub struct ConstValueInfo {
+    tag: u8,
     index: usize,
 }
 
 impl ConstValueInfo {
+    /// Type of the constant, as a descriptor character (`s` for `String`).
+    pub fn tag(&self) -> u8 {
+        self.tag
+    }
+
     pub fn value<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a ConstantPoolEntry> {
         constant_pool.get(self.index)
     }
 }
 
 impl_read! {
-    ConstValueInfo(reader) -> Result<ConstValueInfo> = {
+    ConstValueInfo(reader, tag: u8) -> Result<ConstValueInfo> = {
         let index = try!(reader.read_u16::<BigEndian>()) as usize;
 
         Ok(ConstValueInfo {
+            tag: tag,
             index: index,
         })
     }
 }
 
+impl_write! {
+    ConstValueInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.index as u16));
+
+        Ok(())
+    }
+}
+
 impl_print! {
     ConstValueInfo(self, printer, constant_pool: &ConstantPool) {
-        let value = self.value(constant_pool).expect("Invalid value index");
+        let value = try!(self.value(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid value index")));
 
         try!(write!(printer, "Constant value: "));
         try!(value.print(printer, constant_pool));
@@ -237,10 +432,19 @@ impl_read! {
     }
 }
 
+impl_write! {
+    EnumConstValueInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.type_name_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.const_name_index as u16));
+
+        Ok(())
+    }
+}
+
 impl_print! {
     EnumConstValueInfo(self, printer, constant_pool: &ConstantPool) {
-        let type_name = self.type_name(constant_pool).expect("Invalid name index");
-        let const_name = self.const_name(constant_pool).expect("Invalid name index");
+        let type_name = try!(self.type_name(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid name index")));
+        let const_name = try!(self.const_name(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid name index")));
 
         try!(writeln!(printer, "Enum constant value: {} [{}]", const_name, type_name));
     }
@@ -267,9 +471,17 @@ impl_read! {
     }
 }
 
+impl_write! {
+    ClassInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.index as u16));
+
+        Ok(())
+    }
+}
+
 impl_print! {
     ClassInfo(self, printer, constant_pool: &ConstantPool) {
-        let class = self.class(constant_pool).expect("Invalid class index");
+        let class = try!(self.class(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid class index")));
 
         try!(writeln!(printer, "Class: {}", class));
     }
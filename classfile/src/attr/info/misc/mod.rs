@@ -1,8 +1,12 @@
 pub mod annotations;
 pub mod signature;
+pub mod type_annotations;
 
-pub use self::annotations::RuntimeVisibleAnnotationsAttrInfo;
+pub use self::annotations::{RuntimeVisibleAnnotationsAttrInfo, RuntimeInvisibleAnnotationsAttrInfo};
+pub use self::annotations::{RuntimeVisibleParameterAnnotationsAttrInfo, RuntimeInvisibleParameterAnnotationsAttrInfo};
+pub use self::annotations::AnnotationDefaultAttrInfo;
 pub use self::signature::SignatureAttrInfo;
+pub use self::type_annotations::{RuntimeVisibleTypeAnnotationsAttrInfo, RuntimeInvisibleTypeAnnotationsAttrInfo};
 
 empty_attr_info!(SyntheticAttrInfo);
 empty_attr_info!(DeprecatedAttrInfo);
@@ -1,5 +1,6 @@
-use constant::ConstantPool;
+use constant::{ConstantPool, StrPool};
 use error::Result;
+use signature::{ClassSignature, MethodSignature, ReferenceType};
 
 #[derive(Debug)]
 pub struct SignatureAttrInfo {
@@ -7,13 +8,32 @@ pub struct SignatureAttrInfo {
 }
 
 impl SignatureAttrInfo {
+    pub fn signature_index(&self) -> usize {
+        self.index
+    }
+
     pub fn value<'a>(&self, constant_pool: &'a ConstantPool) -> Option<&'a str> {
         constant_pool.get_str(self.index)
     }
+
+    /// Parse the value as the signature of a class. A missing value is reported as an empty one.
+    pub fn class_signature(&self, constant_pool: &ConstantPool) -> Result<ClassSignature> {
+        ClassSignature::parse(self.value(constant_pool).unwrap_or(""))
+    }
+
+    /// Parse the value as the signature of a method.
+    pub fn method_signature(&self, constant_pool: &ConstantPool) -> Result<MethodSignature> {
+        MethodSignature::parse(self.value(constant_pool).unwrap_or(""))
+    }
+
+    /// Parse the value as the signature of a field, record component or local variable.
+    pub fn field_signature(&self, constant_pool: &ConstantPool) -> Result<ReferenceType> {
+        ReferenceType::parse(self.value(constant_pool).unwrap_or(""))
+    }
 }
 
 impl_read! {
-    SignatureAttrInfo(reader, _constant_pool: &ConstantPool) -> Result<SignatureAttrInfo> = {
+    SignatureAttrInfo(reader, _constant_pool: &dyn StrPool) -> Result<SignatureAttrInfo> = {
         let index = try!(reader.read_u16::<BigEndian>()) as usize;
 
         Ok(SignatureAttrInfo {
@@ -22,9 +42,17 @@ impl_read! {
     }
 }
 
+impl_write! {
+    SignatureAttrInfo(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.index as u16));
+
+        Ok(())
+    }
+}
+
 impl_print! {
     SignatureAttrInfo(self, printer, constant_pool: &ConstantPool) {
-        let value = self.value(constant_pool).expect("Invalid signature index");
+        let value = try!(self.value(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid signature index")));
 
         try!(printer.write_indent());
         try!(writeln!(printer, "{:?}", value));
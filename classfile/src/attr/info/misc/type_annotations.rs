@@ -0,0 +1,394 @@
+use constant::{ConstantPool, StrPool};
+use error::*;
+use std::slice::Iter;
+use super::annotations::Annotation;
+
+mod target_type {
+    pub const CLASS_TYPE_PARAMETER: u8              = 0x00;
+    pub const METHOD_TYPE_PARAMETER: u8             = 0x01;
+    pub const CLASS_EXTENDS: u8                     = 0x10;
+    pub const CLASS_TYPE_PARAMETER_BOUND: u8        = 0x11;
+    pub const METHOD_TYPE_PARAMETER_BOUND: u8       = 0x12;
+    pub const FIELD: u8                             = 0x13;
+    pub const METHOD_RETURN: u8                     = 0x14;
+    pub const METHOD_RECEIVER: u8                   = 0x15;
+    pub const METHOD_FORMAL_PARAMETER: u8           = 0x16;
+    pub const THROWS: u8                            = 0x17;
+    pub const LOCAL_VARIABLE: u8                    = 0x40;
+    pub const RESOURCE_VARIABLE: u8                 = 0x41;
+    pub const EXCEPTION_PARAMETER: u8               = 0x42;
+    pub const INSTANCEOF: u8                        = 0x43;
+    pub const NEW: u8                               = 0x44;
+    pub const CONSTRUCTOR_REFERENCE: u8             = 0x45;
+    pub const METHOD_REFERENCE: u8                  = 0x46;
+    pub const CAST: u8                              = 0x47;
+    pub const CONSTRUCTOR_INVOCATION_TYPE_ARGUMENT: u8 = 0x48;
+    pub const METHOD_INVOCATION_TYPE_ARGUMENT: u8   = 0x49;
+    pub const CONSTRUCTOR_REFERENCE_TYPE_ARGUMENT: u8 = 0x4a;
+    pub const METHOD_REFERENCE_TYPE_ARGUMENT: u8    = 0x4b;
+}
+
+macro_rules! type_annotations_attr_info {
+    ($name:ident) => {
+        #[derive(Debug)]
+        pub struct $name {
+            annotations: Vec<TypeAnnotation>,
+        }
+
+        impl $name {
+            pub fn annotations<'a>(&'a self) -> Iter<'a, TypeAnnotation> {
+                self.annotations.iter()
+            }
+        }
+
+        impl_read! {
+            $name(reader, _constant_pool: &dyn StrPool) -> Result<Self> = {
+                let annotations_count = try!(reader.read_u16::<BigEndian>()) as usize;
+                let mut annotations = Vec::with_capacity(annotations_count);
+                for i in 0..annotations_count {
+                    let annotation = try!(TypeAnnotation::read(reader).within(|| format!("annotations[{}]", i)));
+                    annotations.push(annotation);
+                }
+
+                Ok($name {
+                    annotations: annotations,
+                })
+            }
+        }
+
+        impl_write! {
+            $name(self, writer) -> Result<()> = {
+                try!(writer.write_u16::<BigEndian>(self.annotations.len() as u16));
+                for annotation in self.annotations() {
+                    try!(annotation.write(writer));
+                }
+
+                Ok(())
+            }
+        }
+
+        impl_print! {
+            $name(self, printer, constant_pool: &ConstantPool) {
+                for annotation in self.annotations() {
+                    try!(annotation.print(printer, constant_pool));
+                }
+            }
+        }
+    };
+}
+
+type_annotations_attr_info!(RuntimeVisibleTypeAnnotationsAttrInfo);
+type_annotations_attr_info!(RuntimeInvisibleTypeAnnotationsAttrInfo);
+
+#[derive(Debug)]
+pub struct TypeAnnotation {
+    target_type: u8,
+    pub target_info: TargetInfo,
+    pub target_path: TypePath,
+    pub annotation: Annotation,
+}
+
+impl TypeAnnotation {
+    /// Kind of the annotated type, e.g. `0x13` for a field type (JVMS table 4.7.20-A/B).
+    pub fn target_type(&self) -> u8 {
+        self.target_type
+    }
+}
+
+impl_read! {
+    TypeAnnotation(reader) -> Result<Self> = {
+        let target_type = try!(reader.read_u8());
+        let target_info = try!(TargetInfo::read(reader, target_type));
+        let target_path = try!(TypePath::read(reader));
+        let annotation = try!(Annotation::read(reader));
+
+        Ok(TypeAnnotation {
+            target_type: target_type,
+            target_info: target_info,
+            target_path: target_path,
+            annotation: annotation,
+        })
+    }
+}
+
+impl_write! {
+    TypeAnnotation(self, writer) -> Result<()> = {
+        try!(writer.write_u8(self.target_type));
+        try!(self.target_info.write(writer));
+        try!(self.target_path.write(writer));
+        try!(self.annotation.write(writer));
+
+        Ok(())
+    }
+}
+
+impl_print! {
+    TypeAnnotation(self, printer, constant_pool: &ConstantPool) {
+        try!(printer.write_indent());
+        try!(writeln!(printer, "Target [{:#04x}]: {:?}", self.target_type, self.target_info));
+
+        if !self.target_path.entries.is_empty() {
+            try!(printer.write_indent());
+            try!(writeln!(printer, "Path: {:?}", self.target_path.entries));
+        }
+
+        try!(printer.write_indent());
+        try!(self.annotation.print(printer, constant_pool));
+    }
+}
+
+/// Which type in a declaration or expression is annotated; the variant is given by the target type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetInfo {
+    TypeParameter {
+        type_parameter_index: u8,
+    },
+    /// `supertype_index` is `65535` for the superclass, or an index into `interfaces`.
+    Supertype {
+        supertype_index: u16,
+    },
+    TypeParameterBound {
+        type_parameter_index: u8,
+        bound_index: u8,
+    },
+    Empty,
+    FormalParameter {
+        formal_parameter_index: u8,
+    },
+    Throws {
+        throws_type_index: u16,
+    },
+    LocalVar(Vec<LocalVarTarget>),
+    Catch {
+        exception_table_index: u16,
+    },
+    Offset {
+        offset: u16,
+    },
+    TypeArgument {
+        offset: u16,
+        type_argument_index: u8,
+    },
+}
+
+impl_read! {
+    TargetInfo(reader, target_type: u8) -> Result<Self> = {
+        use self::target_type::*;
+
+        let info = match target_type {
+            CLASS_TYPE_PARAMETER | METHOD_TYPE_PARAMETER => TargetInfo::TypeParameter {
+                type_parameter_index: try!(reader.read_u8()),
+            },
+            CLASS_EXTENDS => TargetInfo::Supertype {
+                supertype_index: try!(reader.read_u16::<BigEndian>()),
+            },
+            CLASS_TYPE_PARAMETER_BOUND | METHOD_TYPE_PARAMETER_BOUND => TargetInfo::TypeParameterBound {
+                type_parameter_index: try!(reader.read_u8()),
+                bound_index: try!(reader.read_u8()),
+            },
+            FIELD | METHOD_RETURN | METHOD_RECEIVER => TargetInfo::Empty,
+            METHOD_FORMAL_PARAMETER => TargetInfo::FormalParameter {
+                formal_parameter_index: try!(reader.read_u8()),
+            },
+            THROWS => TargetInfo::Throws {
+                throws_type_index: try!(reader.read_u16::<BigEndian>()),
+            },
+            LOCAL_VARIABLE | RESOURCE_VARIABLE => {
+                let table_length = try!(reader.read_u16::<BigEndian>()) as usize;
+                let mut table = Vec::with_capacity(table_length);
+                for _ in 0..table_length {
+                    let entry = try!(LocalVarTarget::read(reader));
+                    table.push(entry);
+                }
+
+                TargetInfo::LocalVar(table)
+            }
+            EXCEPTION_PARAMETER => TargetInfo::Catch {
+                exception_table_index: try!(reader.read_u16::<BigEndian>()),
+            },
+            INSTANCEOF | NEW | CONSTRUCTOR_REFERENCE | METHOD_REFERENCE => TargetInfo::Offset {
+                offset: try!(reader.read_u16::<BigEndian>()),
+            },
+            CAST | CONSTRUCTOR_INVOCATION_TYPE_ARGUMENT | METHOD_INVOCATION_TYPE_ARGUMENT |
+            CONSTRUCTOR_REFERENCE_TYPE_ARGUMENT | METHOD_REFERENCE_TYPE_ARGUMENT => TargetInfo::TypeArgument {
+                offset: try!(reader.read_u16::<BigEndian>()),
+                type_argument_index: try!(reader.read_u8()),
+            },
+            _ => bail!(ErrorKind::BadTagValue(target_type)),
+        };
+
+        Ok(info)
+    }
+}
+
+impl_write! {
+    TargetInfo(self, writer) -> Result<()> = {
+        match *self {
+            TargetInfo::TypeParameter { type_parameter_index } => try!(writer.write_u8(type_parameter_index)),
+            TargetInfo::Supertype { supertype_index } => try!(writer.write_u16::<BigEndian>(supertype_index)),
+            TargetInfo::TypeParameterBound { type_parameter_index, bound_index } => {
+                try!(writer.write_u8(type_parameter_index));
+                try!(writer.write_u8(bound_index));
+            }
+            TargetInfo::Empty => {}
+            TargetInfo::FormalParameter { formal_parameter_index } => try!(writer.write_u8(formal_parameter_index)),
+            TargetInfo::Throws { throws_type_index } => try!(writer.write_u16::<BigEndian>(throws_type_index)),
+            TargetInfo::LocalVar(ref table) => {
+                try!(writer.write_u16::<BigEndian>(table.len() as u16));
+                for entry in table.iter() {
+                    try!(entry.write(writer));
+                }
+            }
+            TargetInfo::Catch { exception_table_index } => {
+                try!(writer.write_u16::<BigEndian>(exception_table_index));
+            }
+            TargetInfo::Offset { offset } => try!(writer.write_u16::<BigEndian>(offset)),
+            TargetInfo::TypeArgument { offset, type_argument_index } => {
+                try!(writer.write_u16::<BigEndian>(offset));
+                try!(writer.write_u8(type_argument_index));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Live range of an annotated local variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalVarTarget {
+    pub start_pc: u16,
+    pub length: u16,
+    pub index: u16,
+}
+
+impl_read! {
+    LocalVarTarget(reader) -> Result<Self> = {
+        let start_pc = try!(reader.read_u16::<BigEndian>());
+        let length = try!(reader.read_u16::<BigEndian>());
+        let index = try!(reader.read_u16::<BigEndian>());
+
+        Ok(LocalVarTarget {
+            start_pc: start_pc,
+            length: length,
+            index: index,
+        })
+    }
+}
+
+impl_write! {
+    LocalVarTarget(self, writer) -> Result<()> = {
+        try!(writer.write_u16::<BigEndian>(self.start_pc));
+        try!(writer.write_u16::<BigEndian>(self.length));
+        try!(writer.write_u16::<BigEndian>(self.index));
+
+        Ok(())
+    }
+}
+
+/// Location of the annotated type within a compound type, e.g. `String` in `List<@A String>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypePath {
+    pub entries: Vec<TypePathEntry>,
+}
+
+impl_read! {
+    TypePath(reader) -> Result<Self> = {
+        let path_length = try!(reader.read_u8()) as usize;
+        let mut entries = Vec::with_capacity(path_length);
+        for _ in 0..path_length {
+            let entry = try!(TypePathEntry::read(reader));
+            entries.push(entry);
+        }
+
+        Ok(TypePath {
+            entries: entries,
+        })
+    }
+}
+
+impl_write! {
+    TypePath(self, writer) -> Result<()> = {
+        try!(writer.write_u8(self.entries.len() as u8));
+        for entry in self.entries.iter() {
+            try!(entry.write(writer));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypePathEntry {
+    pub kind: TypePathKind,
+    /// Index of the type argument for `TypePathKind::TypeArgument`, and 0 otherwise in a well-formed
+    /// class file. Kept as read for every kind, so that writing the entry back doesn't alter it.
+    pub type_argument_index: u8,
+}
+
+impl_read! {
+    TypePathEntry(reader) -> Result<Self> = {
+        let kind = try!(reader.read_u8());
+        let type_argument_index = try!(reader.read_u8());
+
+        let kind = match kind {
+            0 => TypePathKind::ArrayElement,
+            1 => TypePathKind::Nested,
+            2 => TypePathKind::WildcardBound,
+            3 => TypePathKind::TypeArgument,
+            _ => bail!(ErrorKind::BadTagValue(kind)),
+        };
+
+        Ok(TypePathEntry {
+            kind: kind,
+            type_argument_index: type_argument_index,
+        })
+    }
+}
+
+impl_write! {
+    TypePathEntry(self, writer) -> Result<()> = {
+        let kind = match self.kind {
+            TypePathKind::ArrayElement => 0,
+            TypePathKind::Nested => 1,
+            TypePathKind::WildcardBound => 2,
+            TypePathKind::TypeArgument => 3,
+        };
+
+        try!(writer.write_u8(kind));
+        try!(writer.write_u8(self.type_argument_index));
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypePathKind {
+    /// Deeper in an array type.
+    ArrayElement,
+    /// Deeper in a nested type.
+    Nested,
+    /// On the bound of a wildcard type argument.
+    WildcardBound,
+    /// On a type argument of a parameterized type.
+    TypeArgument,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::*;
+
+    #[test]
+    fn type_path_round_trip() {
+        // A nonzero type_argument_index on a non-TypeArgument kind is kept as is
+        let bytes: &[u8] = &[3, 0, 0, 3, 2, 1, 3];
+        let path = TypePath::read(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(path.entries[0], TypePathEntry { kind: TypePathKind::ArrayElement, type_argument_index: 0 });
+        assert_eq!(path.entries[1], TypePathEntry { kind: TypePathKind::TypeArgument, type_argument_index: 2 });
+        assert_eq!(path.entries[2], TypePathEntry { kind: TypePathKind::Nested, type_argument_index: 3 });
+
+        let mut written = Vec::new();
+        path.write(&mut written).unwrap();
+        assert_eq!(written, bytes);
+    }
+}
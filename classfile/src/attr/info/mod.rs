@@ -5,8 +5,9 @@ pub mod field;
 pub mod method;
 pub mod misc;
 
-use error::Result;
-use constant::ConstantPool;
+use error::{Diagnostics, Result};
+use constant::{ConstantPool, StrPool};
+use std::io::Read;
 
 #[derive(Debug)]
 pub enum AttrInfo {
@@ -15,7 +16,14 @@ pub enum AttrInfo {
     InnerClasses(classfile::InnerClassesAttrInfo),
     EnclosingMethod(classfile::EnclosingMethodAttrInfo),
     SourceDebugExtension(classfile::SourceDebugExtensionAttrInfo),
-    // BootstrapMethods
+    BootstrapMethods(classfile::BootstrapMethodsAttrInfo),
+    Module(classfile::ModuleAttrInfo),
+    ModulePackages(classfile::ModulePackagesAttrInfo),
+    ModuleMainClass(classfile::ModuleMainClassAttrInfo),
+    NestHost(classfile::NestHostAttrInfo),
+    NestMembers(classfile::NestMembersAttrInfo),
+    PermittedSubclasses(classfile::PermittedSubclassesAttrInfo),
+    Record(classfile::RecordAttrInfo),
 
     // Field
     ConstantValue(field::ConstantValueAttrInfo),
@@ -23,15 +31,15 @@ pub enum AttrInfo {
     // Method
     Code(method::CodeAttrInfo),
     Exceptions(method::ExceptionsAttrInfo),
-    // RuntimeVisibleParameterAnnotations
-    // RuntimeInvisibleParameterAnnotations
-    // AnnotationDefault
-    // MethodParameters
+    RuntimeVisibleParameterAnnotations(misc::RuntimeVisibleParameterAnnotationsAttrInfo),
+    RuntimeInvisibleParameterAnnotations(misc::RuntimeInvisibleParameterAnnotationsAttrInfo),
+    AnnotationDefault(misc::AnnotationDefaultAttrInfo),
+    MethodParameters(method::MethodParametersAttrInfo),
 
     // Code
     LineNumberTable(code::LineNumberTableAttrInfo),
     LocalVariableTable(code::LocalVariableTableAttrInfo),
-    // LocalVariableTypeTable
+    LocalVariableTypeTable(code::LocalVariableTypeTableAttrInfo),
     StackMapTable(code::StackMapTableAttrInfo),
 
     // Misc
@@ -39,9 +47,9 @@ pub enum AttrInfo {
     Deprecated(misc::DeprecatedAttrInfo),
     Signature(misc::SignatureAttrInfo),
     RuntimeVisibleAnnotations(misc::RuntimeVisibleAnnotationsAttrInfo),
-    // RuntimeInvisibleAnnotations
-    // RuntimeVisibleTypeAnnotations
-    // RuntimeInvisibleTypeAnnotations
+    RuntimeInvisibleAnnotations(misc::RuntimeInvisibleAnnotationsAttrInfo),
+    RuntimeVisibleTypeAnnotations(misc::RuntimeVisibleTypeAnnotationsAttrInfo),
+    RuntimeInvisibleTypeAnnotations(misc::RuntimeInvisibleTypeAnnotationsAttrInfo),
 
     // Unknown
     Unknown(Vec<u8>),
@@ -52,8 +60,8 @@ macro_rules! read_by_name {
         $($name:ident => $read:path),*
     ) => {
         match $name_expr {
-            $(stringify!($name) => return $read(&mut $reader, $pool).map(AttrInfo::$name),)*
-            _ => {}
+            $(stringify!($name) => Some($read(&mut $reader, $pool).map(AttrInfo::$name)),)*
+            _ => None,
         }
     };
     ($name_expr:expr, $reader:expr, $pool:expr =>
@@ -66,45 +74,148 @@ macro_rules! read_by_name {
 }
 
 impl_read! {
-    AttrInfo(reader, name: &str, pool: &ConstantPool) -> Result<AttrInfo> = {
+    AttrInfo(reader, name: &str, pool: &dyn StrPool, diagnostics: &mut Diagnostics) -> Result<AttrInfo> = {
+        use std::io::Cursor;
         use utils::io::ReadExt;
 
         let size = try!(reader.read_u32::<BigEndian>()) as usize;
+
+        if !diagnostics.is_lenient() {
+            // Parse straight from the class file rather than from a copy of the data, so that
+            // the offset of errors is known.
+            let mut reader = (reader as &mut dyn Read).take(size as u64);
+            return match read_known(&mut reader, name, pool, diagnostics) {
+                Some(info) => {
+                    let info = try!(info);
+
+                    // Skip trailing bytes
+                    let rest = reader.limit() as usize;
+                    try!(reader.read_vec(rest));
+
+                    Ok(info)
+                }
+                None => Ok(AttrInfo::Unknown(try!(reader.read_vec(size)))),
+            };
+        }
+
+        // Keep the data around, to fall back to an unknown attribute
         let data = try!(reader.read_vec(size));
+        match read_known(&mut Cursor::new(&data[..]), name, pool, diagnostics) {
+            Some(Ok(info)) => Ok(info),
+            Some(Err(err)) => {
+                diagnostics.report(err);
+                Ok(AttrInfo::Unknown(data))
+            }
+            None => Ok(AttrInfo::Unknown(data)),
+        }
+    }
+}
+
+/// Decode the data of the attribute `name`, or return `None` if the attribute isn't known.
+fn read_known<R: Read>(reader: &mut R, name: &str, pool: &dyn StrPool, diagnostics: &mut Diagnostics)
+    -> Option<Result<AttrInfo>>
+{
+    // Attributes containing attributes
+    match name {
+        "Code" => return Some(method::CodeAttrInfo::read(reader, pool, diagnostics).map(AttrInfo::Code)),
+        "Record" => return Some(classfile::RecordAttrInfo::read(reader, pool, diagnostics).map(AttrInfo::Record)),
+        _ => {}
+    }
+
+    read_by_name!(name, *reader, pool =>
+        // Class file
+        InnerClasses => classfile::InnerClassesAttrInfo::read,
+        SourceFile => classfile::SourceFileAttrInfo::read,
+        EnclosingMethod => classfile::EnclosingMethodAttrInfo::read,
+        SourceDebugExtension => classfile::SourceDebugExtensionAttrInfo::read,
+        BootstrapMethods => classfile::BootstrapMethodsAttrInfo::read,
+        Module => classfile::ModuleAttrInfo::read,
+        ModulePackages => classfile::ModulePackagesAttrInfo::read,
+        ModuleMainClass => classfile::ModuleMainClassAttrInfo::read,
+        NestHost => classfile::NestHostAttrInfo::read,
+        NestMembers => classfile::NestMembersAttrInfo::read,
+        PermittedSubclasses => classfile::PermittedSubclassesAttrInfo::read,
 
-        {
-            use std::io::Cursor;
+        // Field
+        ConstantValue => field::ConstantValueAttrInfo::read,
 
-            let mut reader: Cursor<&[u8]> = Cursor::new(&data);
+        // Method
+        Exceptions => method::ExceptionsAttrInfo::read,
+        RuntimeVisibleParameterAnnotations => misc::RuntimeVisibleParameterAnnotationsAttrInfo::read,
+        RuntimeInvisibleParameterAnnotations => misc::RuntimeInvisibleParameterAnnotationsAttrInfo::read,
+        AnnotationDefault => misc::AnnotationDefaultAttrInfo::read,
+        MethodParameters => method::MethodParametersAttrInfo::read,
 
-            read_by_name!(name, reader, pool =>
-                // Class file
-                InnerClasses => classfile::InnerClassesAttrInfo::read,
-                SourceFile => classfile::SourceFileAttrInfo::read,
-                EnclosingMethod => classfile::EnclosingMethodAttrInfo::read,
-                SourceDebugExtension => classfile::SourceDebugExtensionAttrInfo::read,
+        // Code
+        StackMapTable => code::StackMapTableAttrInfo::read,
+        LineNumberTable => code::LineNumberTableAttrInfo::read,
+        LocalVariableTable => code::LocalVariableTableAttrInfo::read,
+        LocalVariableTypeTable => code::LocalVariableTypeTableAttrInfo::read,
 
-                // Field
-                ConstantValue => field::ConstantValueAttrInfo::read,
+        // Misc
+        Syncthetic => misc::SyntheticAttrInfo::read,
+        Deprecated => misc::DeprecatedAttrInfo::read,
+        RuntimeVisibleAnnotations => misc::RuntimeVisibleAnnotationsAttrInfo::read,
+        RuntimeInvisibleAnnotations => misc::RuntimeInvisibleAnnotationsAttrInfo::read,
+        RuntimeVisibleTypeAnnotations => misc::RuntimeVisibleTypeAnnotationsAttrInfo::read,
+        RuntimeInvisibleTypeAnnotations => misc::RuntimeInvisibleTypeAnnotationsAttrInfo::read,
+        Signature => misc::SignatureAttrInfo::read,
+    )
+}
+
+impl_write! {
+    AttrInfo(self, writer) -> Result<()> = {
+        let mut data = Vec::new();
+
+        match *self {
+            // Class file
+            AttrInfo::SourceFile(ref info) => try!(info.write(&mut data)),
+            AttrInfo::InnerClasses(ref info) => try!(info.write(&mut data)),
+            AttrInfo::EnclosingMethod(ref info) => try!(info.write(&mut data)),
+            AttrInfo::SourceDebugExtension(ref info) => try!(info.write(&mut data)),
+            AttrInfo::BootstrapMethods(ref info) => try!(info.write(&mut data)),
+            AttrInfo::Module(ref info) => try!(info.write(&mut data)),
+            AttrInfo::ModulePackages(ref info) => try!(info.write(&mut data)),
+            AttrInfo::ModuleMainClass(ref info) => try!(info.write(&mut data)),
+            AttrInfo::NestHost(ref info) => try!(info.write(&mut data)),
+            AttrInfo::NestMembers(ref info) => try!(info.write(&mut data)),
+            AttrInfo::PermittedSubclasses(ref info) => try!(info.write(&mut data)),
+            AttrInfo::Record(ref info) => try!(info.write(&mut data)),
+
+            // Field
+            AttrInfo::ConstantValue(ref info) => try!(info.write(&mut data)),
 
-                // Method
-                Code => method::CodeAttrInfo::read,
-                Exceptions => method::ExceptionsAttrInfo::read,
+            // Method
+            AttrInfo::Code(ref info) => try!(info.write(&mut data)),
+            AttrInfo::Exceptions(ref info) => try!(info.write(&mut data)),
+            AttrInfo::RuntimeVisibleParameterAnnotations(ref info) => try!(info.write(&mut data)),
+            AttrInfo::RuntimeInvisibleParameterAnnotations(ref info) => try!(info.write(&mut data)),
+            AttrInfo::AnnotationDefault(ref info) => try!(info.write(&mut data)),
+            AttrInfo::MethodParameters(ref info) => try!(info.write(&mut data)),
+
+            // Code
+            AttrInfo::StackMapTable(ref info) => try!(info.write(&mut data)),
+            AttrInfo::LineNumberTable(ref info) => try!(info.write(&mut data)),
+            AttrInfo::LocalVariableTable(ref info) => try!(info.write(&mut data)),
+            AttrInfo::LocalVariableTypeTable(ref info) => try!(info.write(&mut data)),
 
-                // Code
-                StackMapTable => code::StackMapTableAttrInfo::read,
-                LineNumberTable => code::LineNumberTableAttrInfo::read,
-                LocalVariableTable => code::LocalVariableTableAttrInfo::read,
+            // Misc
+            AttrInfo::Syncthetic(ref info) => try!(info.write(&mut data)),
+            AttrInfo::Deprecated(ref info) => try!(info.write(&mut data)),
+            AttrInfo::RuntimeVisibleAnnotations(ref info) => try!(info.write(&mut data)),
+            AttrInfo::RuntimeInvisibleAnnotations(ref info) => try!(info.write(&mut data)),
+            AttrInfo::RuntimeVisibleTypeAnnotations(ref info) => try!(info.write(&mut data)),
+            AttrInfo::RuntimeInvisibleTypeAnnotations(ref info) => try!(info.write(&mut data)),
+            AttrInfo::Signature(ref info) => try!(info.write(&mut data)),
 
-                // Misc
-                Syncthetic => misc::SyntheticAttrInfo::read,
-                Deprecated => misc::DeprecatedAttrInfo::read,
-                RuntimeVisibleAnnotations => misc::RuntimeVisibleAnnotationsAttrInfo::read,
-                Signature => misc::SignatureAttrInfo::read,
-            );
+            // Unknown
+            AttrInfo::Unknown(ref raw) => data.extend_from_slice(raw),
         }
 
-        Ok(AttrInfo::Unknown(data))
+        try!(writer.write_u32::<BigEndian>(data.len() as u32));
+        try!(writer.write_all(&data));
+
+        Ok(())
     }
 }
 
@@ -116,6 +227,14 @@ impl_print! {
             AttrInfo::InnerClasses(ref info) => try!(info.print(printer, constant_pool)),
             AttrInfo::EnclosingMethod(ref info) => try!(info.print(printer, constant_pool)),
             AttrInfo::SourceDebugExtension(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::BootstrapMethods(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::Module(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::ModulePackages(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::ModuleMainClass(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::NestHost(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::NestMembers(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::PermittedSubclasses(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::Record(ref info) => try!(info.print(printer, constant_pool)),
 
             // Field
             AttrInfo::ConstantValue(ref info) => try!(info.print(printer, constant_pool)),
@@ -123,16 +242,24 @@ impl_print! {
             // Method
             AttrInfo::Code(ref info) => try!(info.print(printer, constant_pool)),
             AttrInfo::Exceptions(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::RuntimeVisibleParameterAnnotations(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::RuntimeInvisibleParameterAnnotations(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::AnnotationDefault(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::MethodParameters(ref info) => try!(info.print(printer, constant_pool)),
 
             // Code
             AttrInfo::StackMapTable(ref info) => try!(info.print(printer, constant_pool)),
             AttrInfo::LineNumberTable(ref info) => try!(info.print(printer)),
             AttrInfo::LocalVariableTable(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::LocalVariableTypeTable(ref info) => try!(info.print(printer, constant_pool)),
 
             // Misc
             AttrInfo::Syncthetic(ref info) => try!(info.print(printer)),
             AttrInfo::Deprecated(ref info) => try!(info.print(printer)),
             AttrInfo::RuntimeVisibleAnnotations(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::RuntimeInvisibleAnnotations(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::RuntimeVisibleTypeAnnotations(ref info) => try!(info.print(printer, constant_pool)),
+            AttrInfo::RuntimeInvisibleTypeAnnotations(ref info) => try!(info.print(printer, constant_pool)),
             AttrInfo::Signature(ref info) => try!(info.print(printer, constant_pool)),
 
             // Unknown
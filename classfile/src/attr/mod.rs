@@ -2,7 +2,8 @@ pub mod info;
 
 use error::*;
 use self::info::AttrInfo;
-use super::constant::ConstantPool;
+use std::io::{Read, Write};
+use super::constant::{ConstantPool, StrPool};
 
 #[derive(Debug)]
 pub struct Attr {
@@ -17,16 +18,18 @@ impl Attr {
 }
 
 impl_read! {
-    Attr(reader, pool: &ConstantPool) -> Result<Attr> = {
+    Attr(reader, pool: &dyn StrPool, diagnostics: &mut Diagnostics) -> Result<Attr> = {
         // Read name index
         let name_index = try!(reader.read_u16::<BigEndian>()) as usize;
-        let name = match pool.get_str(name_index) {
+        let name = match pool.get_utf8(name_index) {
             Some(name) => name,
             None => bail!(ErrorKind::BadAttrName(name_index)),
         };
 
         // Read attr info
-        let info = try!(AttrInfo::read(reader, name, pool));
+        let info = try!(diagnostics.within(|| format!("attrs[{}]", name), |diagnostics| {
+            AttrInfo::read(reader, &name, pool, diagnostics)
+        }));
 
         Ok(Attr {
             name_index: name_index,
@@ -35,9 +38,47 @@ impl_read! {
     }
 }
 
+impl_write! {
+    Attr(self, writer) -> Result<()> = {
+        // Write name index
+        try!(writer.write_u16::<BigEndian>(self.name_index as u16));
+
+        // Write attr info
+        try!(self.info.write(writer));
+
+        Ok(())
+    }
+}
+
+/// Read an attribute table, preceded by its `u16` size.
+pub fn read_attrs<R: Read>(reader: &mut R, pool: &dyn StrPool, diagnostics: &mut Diagnostics) -> Result<Vec<Attr>> {
+    use byteorder::{ReadBytesExt, BigEndian};
+
+    let attrs_count = try!(reader.read_u16::<BigEndian>()) as usize;
+    let mut attrs = Vec::with_capacity(attrs_count);
+    for _ in 0..attrs_count {
+        let attr = try!(Attr::read(reader, pool, diagnostics));
+        attrs.push(attr);
+    }
+
+    Ok(attrs)
+}
+
+/// Write an attribute table, preceded by its `u16` size.
+pub fn write_attrs<W: Write>(writer: &mut W, attrs: &[Attr]) -> Result<()> {
+    use byteorder::{WriteBytesExt, BigEndian};
+
+    try!(writer.write_u16::<BigEndian>(attrs.len() as u16));
+    for attr in attrs {
+        try!(attr.write(writer));
+    }
+
+    Ok(())
+}
+
 impl_print! {
     Attr(self, printer, constant_pool: &ConstantPool) {
-        let name = self.name(constant_pool).expect("Invalid name index");
+        let name = self.name(constant_pool).unwrap_or("<invalid>");
 
         try!(printer.write_indent());
         try!(writeln!(printer, "Attr `{}`:", name));
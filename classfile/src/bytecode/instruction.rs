@@ -0,0 +1,382 @@
+use std::fmt;
+
+macro_rules! instructions {
+    (
+        simple { $($simple:ident = $simple_opcode:expr, $simple_mnemonic:expr;)* }
+        operands { $($name:ident($($ty:ty),+) = $opcode:expr, $mnemonic:expr;)* }
+    ) => {
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Instruction {
+            $($simple,)*
+            $($name($($ty),+),)*
+        }
+
+        impl Instruction {
+            /// Decode an instruction that has no operand.
+            pub(super) fn from_simple_opcode(opcode: u8) -> Option<Instruction> {
+                match opcode {
+                    $($simple_opcode => Some(Instruction::$simple),)*
+                    _ => None,
+                }
+            }
+
+            pub fn opcode(&self) -> u8 {
+                match *self {
+                    $(Instruction::$simple => $simple_opcode,)*
+                    $(Instruction::$name(..) => $opcode,)*
+                }
+            }
+
+            pub fn mnemonic(&self) -> &'static str {
+                match *self {
+                    $(Instruction::$simple => $simple_mnemonic,)*
+                    $(Instruction::$name(..) => $mnemonic,)*
+                }
+            }
+        }
+    };
+}
+
+instructions! {
+    simple {
+        Nop = 0x00, "nop";
+        AconstNull = 0x01, "aconst_null";
+        IconstM1 = 0x02, "iconst_m1";
+        Iconst0 = 0x03, "iconst_0";
+        Iconst1 = 0x04, "iconst_1";
+        Iconst2 = 0x05, "iconst_2";
+        Iconst3 = 0x06, "iconst_3";
+        Iconst4 = 0x07, "iconst_4";
+        Iconst5 = 0x08, "iconst_5";
+        Lconst0 = 0x09, "lconst_0";
+        Lconst1 = 0x0a, "lconst_1";
+        Fconst0 = 0x0b, "fconst_0";
+        Fconst1 = 0x0c, "fconst_1";
+        Fconst2 = 0x0d, "fconst_2";
+        Dconst0 = 0x0e, "dconst_0";
+        Dconst1 = 0x0f, "dconst_1";
+        Iload0 = 0x1a, "iload_0";
+        Iload1 = 0x1b, "iload_1";
+        Iload2 = 0x1c, "iload_2";
+        Iload3 = 0x1d, "iload_3";
+        Lload0 = 0x1e, "lload_0";
+        Lload1 = 0x1f, "lload_1";
+        Lload2 = 0x20, "lload_2";
+        Lload3 = 0x21, "lload_3";
+        Fload0 = 0x22, "fload_0";
+        Fload1 = 0x23, "fload_1";
+        Fload2 = 0x24, "fload_2";
+        Fload3 = 0x25, "fload_3";
+        Dload0 = 0x26, "dload_0";
+        Dload1 = 0x27, "dload_1";
+        Dload2 = 0x28, "dload_2";
+        Dload3 = 0x29, "dload_3";
+        Aload0 = 0x2a, "aload_0";
+        Aload1 = 0x2b, "aload_1";
+        Aload2 = 0x2c, "aload_2";
+        Aload3 = 0x2d, "aload_3";
+        Iaload = 0x2e, "iaload";
+        Laload = 0x2f, "laload";
+        Faload = 0x30, "faload";
+        Daload = 0x31, "daload";
+        Aaload = 0x32, "aaload";
+        Baload = 0x33, "baload";
+        Caload = 0x34, "caload";
+        Saload = 0x35, "saload";
+        Istore0 = 0x3b, "istore_0";
+        Istore1 = 0x3c, "istore_1";
+        Istore2 = 0x3d, "istore_2";
+        Istore3 = 0x3e, "istore_3";
+        Lstore0 = 0x3f, "lstore_0";
+        Lstore1 = 0x40, "lstore_1";
+        Lstore2 = 0x41, "lstore_2";
+        Lstore3 = 0x42, "lstore_3";
+        Fstore0 = 0x43, "fstore_0";
+        Fstore1 = 0x44, "fstore_1";
+        Fstore2 = 0x45, "fstore_2";
+        Fstore3 = 0x46, "fstore_3";
+        Dstore0 = 0x47, "dstore_0";
+        Dstore1 = 0x48, "dstore_1";
+        Dstore2 = 0x49, "dstore_2";
+        Dstore3 = 0x4a, "dstore_3";
+        Astore0 = 0x4b, "astore_0";
+        Astore1 = 0x4c, "astore_1";
+        Astore2 = 0x4d, "astore_2";
+        Astore3 = 0x4e, "astore_3";
+        Iastore = 0x4f, "iastore";
+        Lastore = 0x50, "lastore";
+        Fastore = 0x51, "fastore";
+        Dastore = 0x52, "dastore";
+        Aastore = 0x53, "aastore";
+        Bastore = 0x54, "bastore";
+        Castore = 0x55, "castore";
+        Sastore = 0x56, "sastore";
+        Pop = 0x57, "pop";
+        Pop2 = 0x58, "pop2";
+        Dup = 0x59, "dup";
+        DupX1 = 0x5a, "dup_x1";
+        DupX2 = 0x5b, "dup_x2";
+        Dup2 = 0x5c, "dup2";
+        Dup2X1 = 0x5d, "dup2_x1";
+        Dup2X2 = 0x5e, "dup2_x2";
+        Swap = 0x5f, "swap";
+        Iadd = 0x60, "iadd";
+        Ladd = 0x61, "ladd";
+        Fadd = 0x62, "fadd";
+        Dadd = 0x63, "dadd";
+        Isub = 0x64, "isub";
+        Lsub = 0x65, "lsub";
+        Fsub = 0x66, "fsub";
+        Dsub = 0x67, "dsub";
+        Imul = 0x68, "imul";
+        Lmul = 0x69, "lmul";
+        Fmul = 0x6a, "fmul";
+        Dmul = 0x6b, "dmul";
+        Idiv = 0x6c, "idiv";
+        Ldiv = 0x6d, "ldiv";
+        Fdiv = 0x6e, "fdiv";
+        Ddiv = 0x6f, "ddiv";
+        Irem = 0x70, "irem";
+        Lrem = 0x71, "lrem";
+        Frem = 0x72, "frem";
+        Drem = 0x73, "drem";
+        Ineg = 0x74, "ineg";
+        Lneg = 0x75, "lneg";
+        Fneg = 0x76, "fneg";
+        Dneg = 0x77, "dneg";
+        Ishl = 0x78, "ishl";
+        Lshl = 0x79, "lshl";
+        Ishr = 0x7a, "ishr";
+        Lshr = 0x7b, "lshr";
+        Iushr = 0x7c, "iushr";
+        Lushr = 0x7d, "lushr";
+        Iand = 0x7e, "iand";
+        Land = 0x7f, "land";
+        Ior = 0x80, "ior";
+        Lor = 0x81, "lor";
+        Ixor = 0x82, "ixor";
+        Lxor = 0x83, "lxor";
+        I2l = 0x85, "i2l";
+        I2f = 0x86, "i2f";
+        I2d = 0x87, "i2d";
+        L2i = 0x88, "l2i";
+        L2f = 0x89, "l2f";
+        L2d = 0x8a, "l2d";
+        F2i = 0x8b, "f2i";
+        F2l = 0x8c, "f2l";
+        F2d = 0x8d, "f2d";
+        D2i = 0x8e, "d2i";
+        D2l = 0x8f, "d2l";
+        D2f = 0x90, "d2f";
+        I2b = 0x91, "i2b";
+        I2c = 0x92, "i2c";
+        I2s = 0x93, "i2s";
+        Lcmp = 0x94, "lcmp";
+        Fcmpl = 0x95, "fcmpl";
+        Fcmpg = 0x96, "fcmpg";
+        Dcmpl = 0x97, "dcmpl";
+        Dcmpg = 0x98, "dcmpg";
+        Ireturn = 0xac, "ireturn";
+        Lreturn = 0xad, "lreturn";
+        Freturn = 0xae, "freturn";
+        Dreturn = 0xaf, "dreturn";
+        Areturn = 0xb0, "areturn";
+        Return = 0xb1, "return";
+        Arraylength = 0xbe, "arraylength";
+        Athrow = 0xbf, "athrow";
+        Monitorenter = 0xc2, "monitorenter";
+        Monitorexit = 0xc3, "monitorexit";
+    }
+
+    operands {
+        Bipush(i8) = 0x10, "bipush";
+        Sipush(i16) = 0x11, "sipush";
+        Ldc(usize) = 0x12, "ldc";
+        LdcW(usize) = 0x13, "ldc_w";
+        Ldc2W(usize) = 0x14, "ldc2_w";
+        Iload(u16) = 0x15, "iload";
+        Lload(u16) = 0x16, "lload";
+        Fload(u16) = 0x17, "fload";
+        Dload(u16) = 0x18, "dload";
+        Aload(u16) = 0x19, "aload";
+        Istore(u16) = 0x36, "istore";
+        Lstore(u16) = 0x37, "lstore";
+        Fstore(u16) = 0x38, "fstore";
+        Dstore(u16) = 0x39, "dstore";
+        Astore(u16) = 0x3a, "astore";
+        Iinc(u16, i16) = 0x84, "iinc";
+        Ifeq(i32) = 0x99, "ifeq";
+        Ifne(i32) = 0x9a, "ifne";
+        Iflt(i32) = 0x9b, "iflt";
+        Ifge(i32) = 0x9c, "ifge";
+        Ifgt(i32) = 0x9d, "ifgt";
+        Ifle(i32) = 0x9e, "ifle";
+        IfIcmpeq(i32) = 0x9f, "if_icmpeq";
+        IfIcmpne(i32) = 0xa0, "if_icmpne";
+        IfIcmplt(i32) = 0xa1, "if_icmplt";
+        IfIcmpge(i32) = 0xa2, "if_icmpge";
+        IfIcmpgt(i32) = 0xa3, "if_icmpgt";
+        IfIcmple(i32) = 0xa4, "if_icmple";
+        IfAcmpeq(i32) = 0xa5, "if_acmpeq";
+        IfAcmpne(i32) = 0xa6, "if_acmpne";
+        Goto(i32) = 0xa7, "goto";
+        Jsr(i32) = 0xa8, "jsr";
+        Ret(u16) = 0xa9, "ret";
+        Tableswitch(TableSwitch) = 0xaa, "tableswitch";
+        Lookupswitch(LookupSwitch) = 0xab, "lookupswitch";
+        Getstatic(usize) = 0xb2, "getstatic";
+        Putstatic(usize) = 0xb3, "putstatic";
+        Getfield(usize) = 0xb4, "getfield";
+        Putfield(usize) = 0xb5, "putfield";
+        Invokevirtual(usize) = 0xb6, "invokevirtual";
+        Invokespecial(usize) = 0xb7, "invokespecial";
+        Invokestatic(usize) = 0xb8, "invokestatic";
+        Invokeinterface(usize, u8) = 0xb9, "invokeinterface";
+        Invokedynamic(usize) = 0xba, "invokedynamic";
+        New(usize) = 0xbb, "new";
+        Newarray(ArrayType) = 0xbc, "newarray";
+        Anewarray(usize) = 0xbd, "anewarray";
+        Checkcast(usize) = 0xc0, "checkcast";
+        Instanceof(usize) = 0xc1, "instanceof";
+        Multianewarray(usize, u8) = 0xc5, "multianewarray";
+        Ifnull(i32) = 0xc6, "ifnull";
+        Ifnonnull(i32) = 0xc7, "ifnonnull";
+        GotoW(i32) = 0xc8, "goto_w";
+        JsrW(i32) = 0xc9, "jsr_w";
+    }
+}
+
+impl Instruction {
+    /// Constant pool index referenced by this instruction, if any.
+    pub fn constant_index(&self) -> Option<usize> {
+        match *self {
+            Instruction::Ldc(index) | Instruction::LdcW(index) | Instruction::Ldc2W(index) |
+            Instruction::Getstatic(index) | Instruction::Putstatic(index) |
+            Instruction::Getfield(index) | Instruction::Putfield(index) |
+            Instruction::Invokevirtual(index) | Instruction::Invokespecial(index) |
+            Instruction::Invokestatic(index) | Instruction::Invokeinterface(index, _) |
+            Instruction::Invokedynamic(index) | Instruction::New(index) |
+            Instruction::Anewarray(index) | Instruction::Checkcast(index) |
+            Instruction::Instanceof(index) | Instruction::Multianewarray(index, _) => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Relative offset of a conditional or unconditional single-target branch.
+    pub fn branch_offset(&self) -> Option<i32> {
+        match *self {
+            Instruction::Ifeq(offset) | Instruction::Ifne(offset) | Instruction::Iflt(offset) |
+            Instruction::Ifge(offset) | Instruction::Ifgt(offset) | Instruction::Ifle(offset) |
+            Instruction::IfIcmpeq(offset) | Instruction::IfIcmpne(offset) |
+            Instruction::IfIcmplt(offset) | Instruction::IfIcmpge(offset) |
+            Instruction::IfIcmpgt(offset) | Instruction::IfIcmple(offset) |
+            Instruction::IfAcmpeq(offset) | Instruction::IfAcmpne(offset) |
+            Instruction::Goto(offset) | Instruction::Jsr(offset) |
+            Instruction::Ifnull(offset) | Instruction::Ifnonnull(offset) |
+            Instruction::GotoW(offset) | Instruction::JsrW(offset) => Some(offset),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "{}", self.mnemonic()));
+
+        match *self {
+            Instruction::Bipush(value) => write!(f, " {}", value),
+            Instruction::Sipush(value) => write!(f, " {}", value),
+            Instruction::Iload(index) | Instruction::Lload(index) | Instruction::Fload(index) |
+            Instruction::Dload(index) | Instruction::Aload(index) | Instruction::Istore(index) |
+            Instruction::Lstore(index) | Instruction::Fstore(index) | Instruction::Dstore(index) |
+            Instruction::Astore(index) | Instruction::Ret(index) => write!(f, " {}", index),
+            Instruction::Iinc(index, value) => write!(f, " {}, {}", index, value),
+            Instruction::Invokeinterface(index, count) => write!(f, " #{}, {}", index, count),
+            Instruction::Multianewarray(index, dimensions) => write!(f, " #{}, {}", index, dimensions),
+            Instruction::Newarray(ty) => write!(f, " {}", ty.name()),
+            Instruction::Tableswitch(ref info) => {
+                write!(f, " {{ {}..{}, default: {:+} }}", info.low, info.high, info.default)
+            }
+            Instruction::Lookupswitch(ref info) => {
+                write!(f, " {{ {} pairs, default: {:+} }}", info.pairs.len(), info.default)
+            }
+            _ => {
+                if let Some(index) = self.constant_index() {
+                    write!(f, " #{}", index)
+                } else if let Some(offset) = self.branch_offset() {
+                    write!(f, " {:+}", offset)
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSwitch {
+    pub default: i32,
+    pub low: i32,
+    pub high: i32,
+    pub offsets: Vec<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LookupSwitch {
+    pub default: i32,
+    pub pairs: Vec<(i32, i32)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayType {
+    Boolean,
+    Char,
+    Float,
+    Double,
+    Byte,
+    Short,
+    Int,
+    Long,
+}
+
+impl ArrayType {
+    pub fn from_atype(atype: u8) -> Option<ArrayType> {
+        match atype {
+            4 => Some(ArrayType::Boolean),
+            5 => Some(ArrayType::Char),
+            6 => Some(ArrayType::Float),
+            7 => Some(ArrayType::Double),
+            8 => Some(ArrayType::Byte),
+            9 => Some(ArrayType::Short),
+            10 => Some(ArrayType::Int),
+            11 => Some(ArrayType::Long),
+            _ => None,
+        }
+    }
+
+    pub fn atype(&self) -> u8 {
+        match *self {
+            ArrayType::Boolean => 4,
+            ArrayType::Char => 5,
+            ArrayType::Float => 6,
+            ArrayType::Double => 7,
+            ArrayType::Byte => 8,
+            ArrayType::Short => 9,
+            ArrayType::Int => 10,
+            ArrayType::Long => 11,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ArrayType::Boolean => "boolean",
+            ArrayType::Char => "char",
+            ArrayType::Float => "float",
+            ArrayType::Double => "double",
+            ArrayType::Byte => "byte",
+            ArrayType::Short => "short",
+            ArrayType::Int => "int",
+            ArrayType::Long => "long",
+        }
+    }
+}
@@ -0,0 +1,294 @@
+pub use self::instruction::{Instruction, TableSwitch, LookupSwitch, ArrayType};
+
+pub mod instruction;
+
+use byteorder::{ByteOrder, BigEndian};
+use error::*;
+
+const WIDE: u8 = 0xc4;
+
+/// Iterator over the instructions of a method body, yielding `(pc, Instruction)` pairs.
+///
+/// Decoding stops at the first malformed instruction, which is reported as an error.
+pub struct Instructions<'a> {
+    reader: CodeReader<'a>,
+    done: bool,
+}
+
+impl<'a> Instructions<'a> {
+    pub fn new(code: &'a [u8]) -> Instructions<'a> {
+        Instructions {
+            reader: CodeReader::new(code),
+            done: false,
+        }
+    }
+}
+
+impl<'a> Iterator for Instructions<'a> {
+    type Item = Result<(usize, Instruction)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.reader.is_empty() {
+            return None;
+        }
+
+        let pc = self.reader.pc;
+        match self.reader.read_instruction() {
+            Ok(insn) => Some(Ok((pc, insn))),
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Decode the single instruction starting at `pc`, returning it with the pc of the next one.
+pub fn decode(code: &[u8], pc: usize) -> Result<(Instruction, usize)> {
+    let mut reader = CodeReader::new(code);
+    reader.pc = pc;
+
+    let insn = try!(reader.read_instruction());
+    Ok((insn, reader.pc))
+}
+
+struct CodeReader<'a> {
+    code: &'a [u8],
+    pc: usize,
+    start: usize,
+}
+
+impl<'a> CodeReader<'a> {
+    fn new(code: &'a [u8]) -> CodeReader<'a> {
+        CodeReader {
+            code: code,
+            pc: 0,
+            start: 0,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pc >= self.code.len()
+    }
+
+    fn take(&mut self, size: usize) -> Result<&'a [u8]> {
+        if self.code.len() < size || self.pc > self.code.len() - size {
+            bail!(ErrorKind::TruncatedCode(self.start));
+        }
+
+        let data = &self.code[self.pc..self.pc + size];
+        self.pc += size;
+        Ok(data)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        self.take(1).map(|data| data[0])
+    }
+
+    fn read_i8(&mut self) -> Result<i8> {
+        self.read_u8().map(|value| value as i8)
+    }
+
+    fn read_u16(&mut self) -> Result<u16> {
+        self.take(2).map(BigEndian::read_u16)
+    }
+
+    fn read_i16(&mut self) -> Result<i16> {
+        self.take(2).map(BigEndian::read_i16)
+    }
+
+    fn read_i32(&mut self) -> Result<i32> {
+        self.take(4).map(BigEndian::read_i32)
+    }
+
+    fn read_index(&mut self) -> Result<usize> {
+        self.read_u16().map(|index| index as usize)
+    }
+
+    fn read_branch(&mut self) -> Result<i32> {
+        self.read_i16().map(|offset| offset as i32)
+    }
+
+    fn read_instruction(&mut self) -> Result<Instruction> {
+        self.start = self.pc;
+
+        let opcode = try!(self.read_u8());
+        if let Some(insn) = Instruction::from_simple_opcode(opcode) {
+            return Ok(insn);
+        }
+
+        let insn = match opcode {
+            0x10 => Instruction::Bipush(try!(self.read_i8())),
+            0x11 => Instruction::Sipush(try!(self.read_i16())),
+            0x12 => Instruction::Ldc(try!(self.read_u8()) as usize),
+            0x13 => Instruction::LdcW(try!(self.read_index())),
+            0x14 => Instruction::Ldc2W(try!(self.read_index())),
+            0x15 => Instruction::Iload(try!(self.read_u8()) as u16),
+            0x16 => Instruction::Lload(try!(self.read_u8()) as u16),
+            0x17 => Instruction::Fload(try!(self.read_u8()) as u16),
+            0x18 => Instruction::Dload(try!(self.read_u8()) as u16),
+            0x19 => Instruction::Aload(try!(self.read_u8()) as u16),
+            0x36 => Instruction::Istore(try!(self.read_u8()) as u16),
+            0x37 => Instruction::Lstore(try!(self.read_u8()) as u16),
+            0x38 => Instruction::Fstore(try!(self.read_u8()) as u16),
+            0x39 => Instruction::Dstore(try!(self.read_u8()) as u16),
+            0x3a => Instruction::Astore(try!(self.read_u8()) as u16),
+            0x84 => {
+                let index = try!(self.read_u8()) as u16;
+                let value = try!(self.read_i8()) as i16;
+                Instruction::Iinc(index, value)
+            }
+            0x99 => Instruction::Ifeq(try!(self.read_branch())),
+            0x9a => Instruction::Ifne(try!(self.read_branch())),
+            0x9b => Instruction::Iflt(try!(self.read_branch())),
+            0x9c => Instruction::Ifge(try!(self.read_branch())),
+            0x9d => Instruction::Ifgt(try!(self.read_branch())),
+            0x9e => Instruction::Ifle(try!(self.read_branch())),
+            0x9f => Instruction::IfIcmpeq(try!(self.read_branch())),
+            0xa0 => Instruction::IfIcmpne(try!(self.read_branch())),
+            0xa1 => Instruction::IfIcmplt(try!(self.read_branch())),
+            0xa2 => Instruction::IfIcmpge(try!(self.read_branch())),
+            0xa3 => Instruction::IfIcmpgt(try!(self.read_branch())),
+            0xa4 => Instruction::IfIcmple(try!(self.read_branch())),
+            0xa5 => Instruction::IfAcmpeq(try!(self.read_branch())),
+            0xa6 => Instruction::IfAcmpne(try!(self.read_branch())),
+            0xa7 => Instruction::Goto(try!(self.read_branch())),
+            0xa8 => Instruction::Jsr(try!(self.read_branch())),
+            0xa9 => Instruction::Ret(try!(self.read_u8()) as u16),
+            0xaa => Instruction::Tableswitch(try!(self.read_tableswitch())),
+            0xab => Instruction::Lookupswitch(try!(self.read_lookupswitch())),
+            0xb2 => Instruction::Getstatic(try!(self.read_index())),
+            0xb3 => Instruction::Putstatic(try!(self.read_index())),
+            0xb4 => Instruction::Getfield(try!(self.read_index())),
+            0xb5 => Instruction::Putfield(try!(self.read_index())),
+            0xb6 => Instruction::Invokevirtual(try!(self.read_index())),
+            0xb7 => Instruction::Invokespecial(try!(self.read_index())),
+            0xb8 => Instruction::Invokestatic(try!(self.read_index())),
+            0xb9 => {
+                let index = try!(self.read_index());
+                let count = try!(self.read_u8());
+                // Always zero, kept for historical reasons.
+                try!(self.read_u8());
+                Instruction::Invokeinterface(index, count)
+            }
+            0xba => {
+                let index = try!(self.read_index());
+                // Always zero.
+                try!(self.read_u16());
+                Instruction::Invokedynamic(index)
+            }
+            0xbb => Instruction::New(try!(self.read_index())),
+            0xbc => {
+                let atype = try!(self.read_u8());
+                match ArrayType::from_atype(atype) {
+                    Some(ty) => Instruction::Newarray(ty),
+                    None => bail!(ErrorKind::BadOperand(self.start)),
+                }
+            }
+            0xbd => Instruction::Anewarray(try!(self.read_index())),
+            0xc0 => Instruction::Checkcast(try!(self.read_index())),
+            0xc1 => Instruction::Instanceof(try!(self.read_index())),
+            WIDE => try!(self.read_wide()),
+            0xc5 => {
+                let index = try!(self.read_index());
+                let dimensions = try!(self.read_u8());
+                Instruction::Multianewarray(index, dimensions)
+            }
+            0xc6 => Instruction::Ifnull(try!(self.read_branch())),
+            0xc7 => Instruction::Ifnonnull(try!(self.read_branch())),
+            0xc8 => Instruction::GotoW(try!(self.read_i32())),
+            0xc9 => Instruction::JsrW(try!(self.read_i32())),
+            _ => bail!(ErrorKind::BadOpcode(opcode, self.start)),
+        };
+
+        Ok(insn)
+    }
+
+    fn read_wide(&mut self) -> Result<Instruction> {
+        let opcode = try!(self.read_u8());
+
+        let insn = match opcode {
+            0x15 => Instruction::Iload(try!(self.read_u16())),
+            0x16 => Instruction::Lload(try!(self.read_u16())),
+            0x17 => Instruction::Fload(try!(self.read_u16())),
+            0x18 => Instruction::Dload(try!(self.read_u16())),
+            0x19 => Instruction::Aload(try!(self.read_u16())),
+            0x36 => Instruction::Istore(try!(self.read_u16())),
+            0x37 => Instruction::Lstore(try!(self.read_u16())),
+            0x38 => Instruction::Fstore(try!(self.read_u16())),
+            0x39 => Instruction::Dstore(try!(self.read_u16())),
+            0x3a => Instruction::Astore(try!(self.read_u16())),
+            0xa9 => Instruction::Ret(try!(self.read_u16())),
+            0x84 => {
+                let index = try!(self.read_u16());
+                let value = try!(self.read_i16());
+                Instruction::Iinc(index, value)
+            }
+            _ => bail!(ErrorKind::BadOpcode(opcode, self.start)),
+        };
+
+        Ok(insn)
+    }
+
+    /// Skip the 0-3 padding bytes that align switch operands on a 4-byte boundary.
+    fn skip_padding(&mut self) -> Result<()> {
+        let padding = (4 - self.pc % 4) % 4;
+        try!(self.take(padding));
+        Ok(())
+    }
+
+    fn read_tableswitch(&mut self) -> Result<TableSwitch> {
+        try!(self.skip_padding());
+
+        let default = try!(self.read_i32());
+        let low = try!(self.read_i32());
+        let high = try!(self.read_i32());
+        if low > high {
+            bail!(ErrorKind::BadOperand(self.start));
+        }
+
+        let count = (high as i64 - low as i64 + 1) as usize;
+        if count > (self.code.len() - self.pc) / 4 {
+            bail!(ErrorKind::TruncatedCode(self.start));
+        }
+
+        let mut offsets = Vec::with_capacity(count);
+        for _ in 0..count {
+            offsets.push(try!(self.read_i32()));
+        }
+
+        Ok(TableSwitch {
+            default: default,
+            low: low,
+            high: high,
+            offsets: offsets,
+        })
+    }
+
+    fn read_lookupswitch(&mut self) -> Result<LookupSwitch> {
+        try!(self.skip_padding());
+
+        let default = try!(self.read_i32());
+        let npairs = try!(self.read_i32());
+        if npairs < 0 {
+            bail!(ErrorKind::BadOperand(self.start));
+        }
+
+        let count = npairs as usize;
+        if count > (self.code.len() - self.pc) / 8 {
+            bail!(ErrorKind::TruncatedCode(self.start));
+        }
+
+        let mut pairs = Vec::with_capacity(count);
+        for _ in 0..count {
+            let key = try!(self.read_i32());
+            let offset = try!(self.read_i32());
+            pairs.push((key, offset));
+        }
+
+        Ok(LookupSwitch {
+            default: default,
+            pairs: pairs,
+        })
+    }
+}
@@ -8,9 +8,17 @@ error_chain! {
     }
 
     errors {
+        BadEntry(index: usize) {
+            description("Bad constant pool entry")
+            display("Bad constant pool entry #{}", index)
+        }
         BadTagValue(value: u8) {
             description("Bad tag value")
             display("Bad tag value: {:x}", value)
         }
+        BadUtf8(offset: usize) {
+            description("Malformed modified UTF-8 string")
+            display("Malformed modified UTF-8 sequence at byte {}", offset)
+        }
     }
 }
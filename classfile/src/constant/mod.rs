@@ -1,11 +1,16 @@
 pub mod error;
+pub mod mutf8;
 
-use byteorder::{ReadBytesExt, BigEndian};
+use attr::info::classfile::BootstrapMethodsAttrInfo;
+use attr::info::classfile::bootstrap_methods::BootstrapMethod;
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
+use descriptor::{FieldType, MethodDescriptor};
 use self::error::*;
-use std::io::Read;
+use std::borrow::Cow;
+use std::io::{Read, Write};
 
 // #[allow(dead_code)]
-mod tag {
+pub mod tag {
     pub const CONSTANT_UTF8: u8               = 1;
     pub const CONSTANT_INTEGER: u8            = 3;
     pub const CONSTANT_FLOAT: u8              = 4;
@@ -19,7 +24,17 @@ mod tag {
     pub const CONSTANT_NAMEANDTYPE: u8        = 12;
     pub const CONSTANT_METHODHANDLE: u8       = 15;
     pub const CONSTANT_METHODTYPE: u8         = 16;
+    pub const CONSTANT_DYNAMIC: u8            = 17;
     pub const CONSTANT_INVOKEDYNAMIC: u8      = 18;
+    pub const CONSTANT_MODULE: u8             = 19;
+    pub const CONSTANT_PACKAGE: u8            = 20;
+}
+
+macro_rules! write_entry {
+    ($writer:expr, $tag:expr, $info:expr) => {{
+        try!($writer.write_u8($tag));
+        try!($info.write($writer));
+    }};
 }
 
 #[derive(Debug)]
@@ -30,11 +45,11 @@ pub struct ConstantPool {
 impl ConstantPool {
     pub fn read<R: Read>(reader: &mut R) -> Result<ConstantPool> {
         let entries_count = try!(reader.read_u16::<BigEndian>()) as usize;
-        let mut entries = Vec::with_capacity(entries_count - 1);
+        let mut entries = Vec::with_capacity(entries_count.saturating_sub(1));
 
         let mut index = 1;
         while index < entries_count {
-            let constant_pool_entry = try!(ConstantPoolEntry::read(reader));
+            let constant_pool_entry = try!(ConstantPoolEntry::read(reader).chain_err(|| ErrorKind::BadEntry(index)));
 
             let long_entry = match constant_pool_entry {
                 ConstantPoolEntry::Long(_) | ConstantPoolEntry::Double(_) => true,
@@ -55,9 +70,36 @@ impl ConstantPool {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u16::<BigEndian>(self.entries.len() as u16 + 1));
+
+        for entry in self.entries.iter() {
+            if let Some(ref entry) = *entry {
+                try!(entry.write(writer));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Number of slots in the pool, including the unusable ones following long and double entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries<'a>(&'a self) -> Entries<'a> {
+        Entries::new(self)
+    }
+
     pub fn get(&self, index: usize) -> Option<&ConstantPoolEntry> {
-        // Indexes starts at 1 in Java classfiles...
-        self.entries.get(index - 1).and_then(|entry| entry.as_ref())
+        // Indexes starts at 1 in Java classfiles, 0 means "no entry" (e.g. `super_class` of `module-info`).
+        index.checked_sub(1)
+            .and_then(|index| self.entries.get(index))
+            .and_then(|entry| entry.as_ref())
     }
 
     pub fn get_str(&self, index: usize) -> Option<&str> {
@@ -73,6 +115,33 @@ impl ConstantPool {
             _ => None,
         })
     }
+
+    pub fn get_module_info(&self, index: usize) -> Option<&ConstantModuleInfo> {
+        self.get(index).and_then(|entry| match *entry {
+            ConstantPoolEntry::Module(ref info) => Some(info),
+            _ => None,
+        })
+    }
+
+    pub fn get_package_info(&self, index: usize) -> Option<&ConstantPackageInfo> {
+        self.get(index).and_then(|entry| match *entry {
+            ConstantPoolEntry::Package(ref info) => Some(info),
+            _ => None,
+        })
+    }
+}
+
+/// Lookup of the strings of a constant pool, which is all reading attributes needs. Implemented by
+/// both the decoded `ConstantPool` and the lazy `view::ConstantPoolRef`.
+pub trait StrPool {
+    /// Value of the `Utf8` entry at `index`.
+    fn get_utf8(&self, index: usize) -> Option<Cow<'_, str>>;
+}
+
+impl StrPool for ConstantPool {
+    fn get_utf8(&self, index: usize) -> Option<Cow<'_, str>> {
+        self.get_str(index).map(Cow::Borrowed)
+    }
 }
 
 impl_print! {
@@ -87,6 +156,32 @@ impl_print! {
     }
 }
 
+/// Iterator over the `(index, entry)` pairs of a constant pool.
+pub struct Entries<'a> {
+    iter: ::std::iter::Enumerate<::std::slice::Iter<'a, Option<ConstantPoolEntry>>>,
+}
+
+impl<'a> Entries<'a> {
+    fn new(pool: &'a ConstantPool) -> Entries<'a> {
+        Entries {
+            iter: pool.entries.iter().enumerate(),
+        }
+    }
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = (usize, &'a ConstantPoolEntry);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, entry) in &mut self.iter {
+            if let Some(ref entry) = *entry {
+                return Some((index + 1, entry));
+            }
+        }
+        None
+    }
+}
+
 #[derive(Debug)]
 pub enum ConstantPoolEntry {
     Class(ConstantClassInfo),
@@ -102,7 +197,10 @@ pub enum ConstantPoolEntry {
     Utf8(ConstantUtf8Info),
     MethodHandle(ConstantMethodHandleInfo),
     MethodType(ConstantMethodTypeInfo),
+    Dynamic(ConstantDynamicInfo),
     InvokedDynamic(ConstantInvokedDynamicInfo),
+    Module(ConstantModuleInfo),
+    Package(ConstantPackageInfo),
 }
 
 impl ConstantPoolEntry {
@@ -123,10 +221,37 @@ impl ConstantPoolEntry {
             tag::CONSTANT_UTF8 => ConstantUtf8Info::read(reader).map(ConstantPoolEntry::Utf8),
             tag::CONSTANT_METHODHANDLE => ConstantMethodHandleInfo::read(reader).map(ConstantPoolEntry::MethodHandle),
             tag::CONSTANT_METHODTYPE => ConstantMethodTypeInfo::read(reader).map(ConstantPoolEntry::MethodType),
+            tag::CONSTANT_DYNAMIC => ConstantDynamicInfo::read(reader).map(ConstantPoolEntry::Dynamic),
             tag::CONSTANT_INVOKEDYNAMIC => ConstantInvokedDynamicInfo::read(reader).map(ConstantPoolEntry::InvokedDynamic),
+            tag::CONSTANT_MODULE => ConstantModuleInfo::read(reader).map(ConstantPoolEntry::Module),
+            tag::CONSTANT_PACKAGE => ConstantPackageInfo::read(reader).map(ConstantPoolEntry::Package),
             _ => Err(ErrorKind::BadTagValue(tag).into()),
         }
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match *self {
+            ConstantPoolEntry::Class(ref info) => write_entry!(writer, tag::CONSTANT_CLASS, info),
+            ConstantPoolEntry::FieldRef(ref info) => write_entry!(writer, tag::CONSTANT_FIELDREF, info),
+            ConstantPoolEntry::MethodRef(ref info) => write_entry!(writer, tag::CONSTANT_METHODREF, info),
+            ConstantPoolEntry::InterfaceMethodRef(ref info) => write_entry!(writer, tag::CONSTANT_INTERFACEMETHODREF, info),
+            ConstantPoolEntry::String(ref info) => write_entry!(writer, tag::CONSTANT_STRING, info),
+            ConstantPoolEntry::Integer(ref info) => write_entry!(writer, tag::CONSTANT_INTEGER, info),
+            ConstantPoolEntry::Float(ref info) => write_entry!(writer, tag::CONSTANT_FLOAT, info),
+            ConstantPoolEntry::Long(ref info) => write_entry!(writer, tag::CONSTANT_LONG, info),
+            ConstantPoolEntry::Double(ref info) => write_entry!(writer, tag::CONSTANT_DOUBLE, info),
+            ConstantPoolEntry::NameAndType(ref info) => write_entry!(writer, tag::CONSTANT_NAMEANDTYPE, info),
+            ConstantPoolEntry::Utf8(ref info) => write_entry!(writer, tag::CONSTANT_UTF8, info),
+            ConstantPoolEntry::MethodHandle(ref info) => write_entry!(writer, tag::CONSTANT_METHODHANDLE, info),
+            ConstantPoolEntry::MethodType(ref info) => write_entry!(writer, tag::CONSTANT_METHODTYPE, info),
+            ConstantPoolEntry::Dynamic(ref info) => write_entry!(writer, tag::CONSTANT_DYNAMIC, info),
+            ConstantPoolEntry::InvokedDynamic(ref info) => write_entry!(writer, tag::CONSTANT_INVOKEDYNAMIC, info),
+            ConstantPoolEntry::Module(ref info) => write_entry!(writer, tag::CONSTANT_MODULE, info),
+            ConstantPoolEntry::Package(ref info) => write_entry!(writer, tag::CONSTANT_PACKAGE, info),
+        }
+
+        Ok(())
+    }
 }
 
 impl_print! {
@@ -145,7 +270,10 @@ impl_print! {
             ConstantPoolEntry::Utf8(ref info) => try!(info.print(printer, constant_pool)),
             ConstantPoolEntry::MethodHandle(ref info) => try!(info.print(printer, constant_pool)),
             ConstantPoolEntry::MethodType(ref info) => try!(info.print(printer, constant_pool)),
+            ConstantPoolEntry::Dynamic(ref info) => try!(info.print(printer, constant_pool)),
             ConstantPoolEntry::InvokedDynamic(ref info) => try!(info.print(printer, constant_pool)),
+            ConstantPoolEntry::Module(ref info) => try!(info.print(printer, constant_pool)),
+            ConstantPoolEntry::Package(ref info) => try!(info.print(printer, constant_pool)),
         }
     }
 }
@@ -165,6 +293,16 @@ impl ConstantClassInfo {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u16::<BigEndian>(self.name_index as u16));
+
+        Ok(())
+    }
+
+    pub fn name_index(&self) -> usize {
+        self.name_index
+    }
+
     pub fn name<'a>(&self, pool: &'a ConstantPool) -> Option<&'a str> {
         pool.get_str(self.name_index)
     }
@@ -172,7 +310,7 @@ impl ConstantClassInfo {
 
 impl_print! {
     ConstantClassInfo(self, printer, constant_pool: &ConstantPool) {
-        let name = self.name(constant_pool).expect("Invalid name index.");
+        let name = try!(self.name(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid name index")));
 
         try!(write!(printer, "Class `{}`", name));
     }
@@ -196,6 +334,21 @@ impl ConstantFieldRefInfo {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u16::<BigEndian>(self.class_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.name_and_type_index as u16));
+
+        Ok(())
+    }
+
+    pub fn class_index(&self) -> usize {
+        self.class_index
+    }
+
+    pub fn name_and_type_index(&self) -> usize {
+        self.name_and_type_index
+    }
+
     pub fn class<'a>(&self, pool: &'a ConstantPool) -> Option<&'a ConstantClassInfo> {
         pool.get_class_info(self.class_index)
     }
@@ -212,8 +365,8 @@ impl_print! {
     ConstantFieldRefInfo(self, printer, constant_pool: &ConstantPool) {
         try!(writeln!(printer, "FieldRef:"));
 
-        let class = self.class(constant_pool).expect("Invalid class index.");
-        let name_and_type = self.name_and_type(constant_pool).expect("Invalid Name And Type index.");
+        let class = try!(self.class(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid class index")));
+        let name_and_type = try!(self.name_and_type(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid Name And Type index")));
 
         {
             let mut printer = printer.sub_indent(1);
@@ -246,6 +399,21 @@ impl ConstantMethodRefInfo {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u16::<BigEndian>(self.class_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.name_and_type_index as u16));
+
+        Ok(())
+    }
+
+    pub fn class_index(&self) -> usize {
+        self.class_index
+    }
+
+    pub fn name_and_type_index(&self) -> usize {
+        self.name_and_type_index
+    }
+
     pub fn class<'a>(&self, pool: &'a ConstantPool) -> Option<&'a ConstantClassInfo> {
         pool.get_class_info(self.class_index)
     }
@@ -260,8 +428,8 @@ impl ConstantMethodRefInfo {
 
 impl_print! {
     ConstantMethodRefInfo(self, printer, constant_pool: &ConstantPool) {
-        let class = self.class(constant_pool).expect("Invalid class index");
-        let name_and_type = self.name_and_type(constant_pool).expect("Invalid name and type index.");
+        let class = try!(self.class(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid class index")));
+        let name_and_type = try!(self.name_and_type(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid name and type index")));
 
         try!(writeln!(printer, "MethodRef:"));
 
@@ -296,6 +464,21 @@ impl ConstantInterfaceMethodRefInfo {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u16::<BigEndian>(self.class_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.name_and_type_index as u16));
+
+        Ok(())
+    }
+
+    pub fn class_index(&self) -> usize {
+        self.class_index
+    }
+
+    pub fn name_and_type_index(&self) -> usize {
+        self.name_and_type_index
+    }
+
     pub fn class<'a>(&self, pool: &'a ConstantPool) -> Option<&'a ConstantClassInfo> {
         pool.get_class_info(self.class_index)
     }
@@ -310,8 +493,8 @@ impl ConstantInterfaceMethodRefInfo {
 
 impl_print! {
     ConstantInterfaceMethodRefInfo(self, printer, constant_pool: &ConstantPool) {
-        let class = self.class(constant_pool).expect("Invalid class index");
-        let name_and_type = self.name_and_type(constant_pool).expect("Invalid name and type index.");
+        let class = try!(self.class(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid class index")));
+        let name_and_type = try!(self.name_and_type(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid name and type index")));
 
         try!(writeln!(printer, "InterfaceMethodRef:"));
 
@@ -342,6 +525,16 @@ impl ConstantStringInfo {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u16::<BigEndian>(self.string_index as u16));
+
+        Ok(())
+    }
+
+    pub fn string_index(&self) -> usize {
+        self.string_index
+    }
+
     pub fn value<'a>(&self, pool: &'a ConstantPool) -> Option<&'a str> {
         pool.get_str(self.string_index)
     }
@@ -349,7 +542,7 @@ impl ConstantStringInfo {
 
 impl_print! {
     ConstantStringInfo(self, printer, constant_pool: &ConstantPool) {
-        let value = self.value(constant_pool).expect("Invalid string index.");
+        let value = try!(self.value(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid string index")));
 
         try!(write!(printer, "String \"{}\"", value));
     }
@@ -369,6 +562,12 @@ impl ConstantIntegerInfo {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_i32::<BigEndian>(self.value));
+
+        Ok(())
+    }
+
     pub fn value(&self) -> i32 {
         self.value
     }
@@ -394,6 +593,12 @@ impl ConstantFloatInfo {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_f32::<BigEndian>(self.value));
+
+        Ok(())
+    }
+
     pub fn value(&self) -> f32 {
         self.value
     }
@@ -419,6 +624,12 @@ impl ConstantLongInfo {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_i64::<BigEndian>(self.value));
+
+        Ok(())
+    }
+
     pub fn value(&self) -> i64 {
         self.value
     }
@@ -444,6 +655,12 @@ impl ConstantDoubleInfo {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_f64::<BigEndian>(self.value));
+
+        Ok(())
+    }
+
     pub fn value(&self) -> f64 {
         self.value
     }
@@ -473,6 +690,21 @@ impl ConstantNameAndTypeInfo {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u16::<BigEndian>(self.name_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.desc_index as u16));
+
+        Ok(())
+    }
+
+    pub fn name_index(&self) -> usize {
+        self.name_index
+    }
+
+    pub fn desc_index(&self) -> usize {
+        self.desc_index
+    }
+
     pub fn name<'a>(&self, pool: &'a ConstantPool) -> Option<&'a str> {
         pool.get_str(self.name_index)
     }
@@ -480,12 +712,22 @@ impl ConstantNameAndTypeInfo {
     pub fn desc<'a>(&self, pool: &'a ConstantPool) -> Option<&'a str> {
         pool.get_str(self.desc_index)
     }
+
+    /// Parsed descriptor, as the type of a field. A missing descriptor is reported as an empty one.
+    pub fn field_type(&self, pool: &ConstantPool) -> ::error::Result<FieldType> {
+        FieldType::parse(self.desc(pool).unwrap_or(""))
+    }
+
+    /// Parsed descriptor, as that of a method. A missing descriptor is reported as an empty one.
+    pub fn method_descriptor(&self, pool: &ConstantPool) -> ::error::Result<MethodDescriptor> {
+        MethodDescriptor::parse(self.desc(pool).unwrap_or(""))
+    }
 }
 
 impl_print! {
     ConstantNameAndTypeInfo(self, printer, constant_pool: &ConstantPool) {
-        let name = self.name(constant_pool).expect("Invalid name index.");
-        let desc = self.desc(constant_pool).expect("Invalid descriptor index.");
+        let name = try!(self.name(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid name index")));
+        let desc = try!(self.desc(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid descriptor index")));
 
         try!(write!(printer, "NameAndType `{}` [{}]", name, desc));
     }
@@ -493,6 +735,7 @@ impl_print! {
 
 #[derive(Debug)]
 pub struct ConstantUtf8Info {
+    data: Vec<u8>,
     value: String,
 }
 
@@ -502,16 +745,34 @@ impl ConstantUtf8Info {
 
         let length = try!(reader.read_u16::<BigEndian>()) as usize;
         let data = try!(reader.read_vec(length));
-        let value = String::from_utf8_lossy(&data).into_owned();
+        let value = try!(mutf8::decode(&data));
 
         Ok(ConstantUtf8Info {
+            data: data,
             value: value,
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u16::<BigEndian>(self.data.len() as u16));
+        try!(writer.write_all(&self.data));
+
+        Ok(())
+    }
+
+    /// Raw bytes of the entry, as stored in the class file.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
     pub fn value(&self) -> &str {
         &self.value
     }
+
+    /// The exact Java string, as UTF-16 code units, including any unpaired surrogates.
+    pub fn utf16(&self) -> Result<Vec<u16>> {
+        mutf8::decode_utf16(&self.data)
+    }
 }
 
 impl_print! {
@@ -537,10 +798,21 @@ impl ConstantMethodHandleInfo {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u8(self.ref_kind));
+        try!(writer.write_u16::<BigEndian>(self.ref_index as u16));
+
+        Ok(())
+    }
+
     pub fn ref_kind(&self) -> u8 {
         self.ref_kind
     }
 
+    pub fn ref_index(&self) -> usize {
+        self.ref_index
+    }
+
     pub fn ref_entry<'a>(&self, pool: &'a ConstantPool) -> Option<&'a ConstantPoolEntry> {
         pool.get(self.ref_index)
     }
@@ -548,7 +820,7 @@ impl ConstantMethodHandleInfo {
 
 impl_print! {
     ConstantMethodHandleInfo(self, printer, constant_pool: &ConstantPool) {
-        let ref_entry = self.ref_entry(constant_pool).expect("Invalid ref index.");
+        let ref_entry = try!(self.ref_entry(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid ref index")));
 
         try!(writeln!(printer, "MethodRef [{}]", self.ref_kind));
 
@@ -575,6 +847,16 @@ impl ConstantMethodTypeInfo {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u16::<BigEndian>(self.desc_index as u16));
+
+        Ok(())
+    }
+
+    pub fn desc_index(&self) -> usize {
+        self.desc_index
+    }
+
     pub fn desc<'a>(&self, pool: &'a ConstantPool) -> Option<&'a str> {
         pool.get_str(self.desc_index)
     }
@@ -582,12 +864,75 @@ impl ConstantMethodTypeInfo {
 
 impl_print! {
     ConstantMethodTypeInfo(self, printer, constant_pool: &ConstantPool) {
-        let desc = self.desc(constant_pool).expect("Invalid desc index.");
+        let desc = try!(self.desc(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid desc index")));
 
         try!(write!(printer, "MethodType `{}`", desc));
     }
 }
 
+#[derive(Debug)]
+pub struct ConstantDynamicInfo {
+    bootstrap_method_attr_index: usize,
+    name_and_type_index: usize,
+}
+
+impl ConstantDynamicInfo {
+    pub fn read<R: Read>(reader: &mut R) -> Result<ConstantDynamicInfo> {
+        let bootstrap_method_attr_index = try!(reader.read_u16::<BigEndian>());
+        let name_and_type_index = try!(reader.read_u16::<BigEndian>());
+
+        Ok(ConstantDynamicInfo {
+            bootstrap_method_attr_index: bootstrap_method_attr_index as usize,
+            name_and_type_index: name_and_type_index as usize,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u16::<BigEndian>(self.bootstrap_method_attr_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.name_and_type_index as u16));
+
+        Ok(())
+    }
+
+    pub fn bootstrap_method_attr_index(&self) -> usize {
+        self.bootstrap_method_attr_index
+    }
+
+    pub fn name_and_type_index(&self) -> usize {
+        self.name_and_type_index
+    }
+
+    /// Resolve the bootstrap method through the class's `BootstrapMethods` attribute.
+    pub fn bootstrap_method<'a>(&self, bootstrap_methods: &'a BootstrapMethodsAttrInfo) -> Option<&'a BootstrapMethod> {
+        bootstrap_methods.get(self.bootstrap_method_attr_index)
+    }
+
+    pub fn name_and_type<'a>(&self, pool: &'a ConstantPool) -> Option<&'a ConstantNameAndTypeInfo> {
+        pool.get(self.name_and_type_index).and_then(|entry| match *entry {
+            ConstantPoolEntry::NameAndType(ref info) => Some(info),
+            _ => None,
+        })
+    }
+}
+
+impl_print! {
+    ConstantDynamicInfo(self, printer, constant_pool: &ConstantPool) {
+        let name_and_type = try!(self.name_and_type(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid index")));
+
+        try!(writeln!(printer, "Dynamic:"));
+
+        {
+            let mut printer = printer.sub_indent(1);
+
+            try!(printer.write_indent());
+            try!(writeln!(printer, "Bootstrap method #{}", self.bootstrap_method_attr_index));
+
+            try!(printer.write_indent());
+            try!(name_and_type.print(&mut printer, constant_pool));
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ConstantInvokedDynamicInfo {
     bootstrap_method_attr_index: usize,
@@ -605,8 +950,26 @@ impl ConstantInvokedDynamicInfo {
         })
     }
 
-    pub fn bootstrap_method_attr<'a>(&self, pool: &'a ConstantPool) -> Option<&'a ConstantPoolEntry> {
-        pool.get(self.bootstrap_method_attr_index)
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u16::<BigEndian>(self.bootstrap_method_attr_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.name_and_type_index as u16));
+
+        Ok(())
+    }
+
+    pub fn bootstrap_method_attr_index(&self) -> usize {
+        self.bootstrap_method_attr_index
+    }
+
+    pub fn name_and_type_index(&self) -> usize {
+        self.name_and_type_index
+    }
+
+    /// Resolve the bootstrap method through the class's `BootstrapMethods` attribute.
+    ///
+    /// Note that the index refers to that attribute's table, not to the constant pool.
+    pub fn bootstrap_method<'a>(&self, bootstrap_methods: &'a BootstrapMethodsAttrInfo) -> Option<&'a BootstrapMethod> {
+        bootstrap_methods.get(self.bootstrap_method_attr_index)
     }
 
     pub fn name_and_type<'a>(&self, pool: &'a ConstantPool) -> Option<&'a ConstantNameAndTypeInfo> {
@@ -619,8 +982,7 @@ impl ConstantInvokedDynamicInfo {
 
 impl_print! {
     ConstantInvokedDynamicInfo(self, printer, constant_pool: &ConstantPool) {
-        let bootstrap_method_attr = self.bootstrap_method_attr(constant_pool).expect("Invalid index.");
-        let name_and_type = self.name_and_type(constant_pool).expect("Invalid index.");
+        let name_and_type = try!(self.name_and_type(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid index")));
 
         try!(writeln!(printer, "InvokedDynamic:"));
 
@@ -628,11 +990,86 @@ impl_print! {
             let mut printer = printer.sub_indent(1);
 
             try!(printer.write_indent());
-            try!(bootstrap_method_attr.print(&mut printer.by_ref(), constant_pool));
-            try!(writeln!(printer, ""));
+            try!(writeln!(printer, "Bootstrap method #{}", self.bootstrap_method_attr_index));
 
             try!(printer.write_indent());
             try!(name_and_type.print(&mut printer, constant_pool));
         }
     }
 }
+
+#[derive(Debug)]
+pub struct ConstantModuleInfo {
+    name_index: usize,
+}
+
+impl ConstantModuleInfo {
+    pub fn read<R: Read>(reader: &mut R) -> Result<ConstantModuleInfo> {
+        let name_index = try!(reader.read_u16::<BigEndian>());
+
+        Ok(ConstantModuleInfo {
+            name_index: name_index as usize,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u16::<BigEndian>(self.name_index as u16));
+
+        Ok(())
+    }
+
+    pub fn name_index(&self) -> usize {
+        self.name_index
+    }
+
+    /// Module name, e.g. `java.base`.
+    pub fn name<'a>(&self, pool: &'a ConstantPool) -> Option<&'a str> {
+        pool.get_str(self.name_index)
+    }
+}
+
+impl_print! {
+    ConstantModuleInfo(self, printer, constant_pool: &ConstantPool) {
+        let name = try!(self.name(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid name index")));
+
+        try!(write!(printer, "Module `{}`", name));
+    }
+}
+
+#[derive(Debug)]
+pub struct ConstantPackageInfo {
+    name_index: usize,
+}
+
+impl ConstantPackageInfo {
+    pub fn read<R: Read>(reader: &mut R) -> Result<ConstantPackageInfo> {
+        let name_index = try!(reader.read_u16::<BigEndian>());
+
+        Ok(ConstantPackageInfo {
+            name_index: name_index as usize,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        try!(writer.write_u16::<BigEndian>(self.name_index as u16));
+
+        Ok(())
+    }
+
+    pub fn name_index(&self) -> usize {
+        self.name_index
+    }
+
+    /// Package name in internal form, e.g. `java/lang`.
+    pub fn name<'a>(&self, pool: &'a ConstantPool) -> Option<&'a str> {
+        pool.get_str(self.name_index)
+    }
+}
+
+impl_print! {
+    ConstantPackageInfo(self, printer, constant_pool: &ConstantPool) {
+        let name = try!(self.name(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid name index")));
+
+        try!(write!(printer, "Package `{}`", name));
+    }
+}
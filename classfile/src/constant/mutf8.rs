@@ -0,0 +1,131 @@
+//! Codec for the "modified UTF-8" encoding used by `CONSTANT_Utf8_info` entries (JVMS §4.4.7).
+//!
+//! It differs from standard UTF-8 in two ways: the null character is encoded on two bytes
+//! (`C0 80`), and supplementary characters are encoded as a pair of 3-byte surrogates.
+
+use super::error::*;
+use std::char;
+
+/// Decode `data` into the UTF-16 code units of the equivalent Java string.
+///
+/// This is lossless: unpaired surrogates, which Java strings may contain, are kept as-is.
+pub fn decode_utf16(data: &[u8]) -> Result<Vec<u16>> {
+    let mut units = Vec::with_capacity(data.len());
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let a = data[pos] as u16;
+        let (unit, len) = match a {
+            0x01..=0x7f => (a, 1),
+            0xc0..=0xdf => {
+                let b = try!(continuation(data, pos, 1));
+                ((a & 0x1f) << 6 | b, 2)
+            }
+            0xe0..=0xef => {
+                let b = try!(continuation(data, pos, 1));
+                let c = try!(continuation(data, pos, 2));
+                ((a & 0x0f) << 12 | b << 6 | c, 3)
+            }
+            _ => bail!(ErrorKind::BadUtf8(pos)),
+        };
+
+        units.push(unit);
+        pos += len;
+    }
+
+    Ok(units)
+}
+
+/// Decode `data` into a Rust string, joining surrogate pairs into supplementary characters.
+///
+/// Unpaired surrogates have no representation in a `String` and are replaced with U+FFFD;
+/// use `decode_utf16` when they matter.
+pub fn decode(data: &[u8]) -> Result<String> {
+    let units = try!(decode_utf16(data));
+    let value = char::decode_utf16(units.iter().cloned())
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect();
+
+    Ok(value)
+}
+
+/// Encode UTF-16 code units, as produced by `decode_utf16`.
+pub fn encode_utf16(units: &[u16]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(units.len());
+
+    for &unit in units {
+        match unit {
+            0x0001..=0x007f => data.push(unit as u8),
+            0x0000 | 0x0080..=0x07ff => {
+                data.push(0xc0 | (unit >> 6) as u8);
+                data.push(0x80 | (unit & 0x3f) as u8);
+            }
+            _ => {
+                data.push(0xe0 | (unit >> 12) as u8);
+                data.push(0x80 | ((unit >> 6) & 0x3f) as u8);
+                data.push(0x80 | (unit & 0x3f) as u8);
+            }
+        }
+    }
+
+    data
+}
+
+pub fn encode(value: &str) -> Vec<u8> {
+    let units: Vec<u16> = value.encode_utf16().collect();
+    encode_utf16(&units)
+}
+
+fn continuation(data: &[u8], start: usize, index: usize) -> Result<u16> {
+    match data.get(start + index) {
+        Some(&byte) if byte & 0xc0 == 0x80 => Ok((byte & 0x3f) as u16),
+        _ => bail!(ErrorKind::BadUtf8(start)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_character() {
+        assert_eq!(encode("a\0b"), [0x61, 0xc0, 0x80, 0x62]);
+        assert_eq!(decode(&[0x61, 0xc0, 0x80, 0x62]).unwrap(), "a\0b");
+    }
+
+    #[test]
+    fn supplementary_characters() {
+        let data = [0x61, 0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80, 0x62];
+        assert_eq!(encode("a\u{1f600}b"), data);
+        assert_eq!(decode_utf16(&data).unwrap(), [0x61, 0xd83d, 0xde00, 0x62]);
+        assert_eq!(decode(&data).unwrap(), "a\u{1f600}b");
+    }
+
+    #[test]
+    fn unpaired_surrogates() {
+        let units = [0xd800, 0x61];
+        let data = encode_utf16(&units);
+        assert_eq!(data, [0xed, 0xa0, 0x80, 0x61]);
+        assert_eq!(decode_utf16(&data).unwrap(), units);
+        assert_eq!(decode(&data).unwrap(), "\u{fffd}a");
+    }
+
+    #[test]
+    fn malformed_sequences() {
+        let error = |data: &[u8]| match decode(data).unwrap_err().kind() {
+            &ErrorKind::BadUtf8(pos) => Some(pos),
+            _ => None,
+        };
+        // Null byte
+        assert_eq!(error(&[0x61, 0x00]), Some(1));
+        // Stray continuation byte
+        assert_eq!(error(&[0x80]), Some(0));
+        // Truncated sequence
+        assert_eq!(error(&[0x61, 0xc3]), Some(1));
+        assert_eq!(error(&[0xe2, 0x82]), Some(0));
+        // Bad continuation byte
+        assert_eq!(error(&[0xe2, 0x41, 0xac]), Some(0));
+        // 4-byte sequence of standard UTF-8
+        assert_eq!(error(&[0xf0, 0x9f, 0x98, 0x80]), Some(0));
+    }
+}
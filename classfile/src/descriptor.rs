@@ -0,0 +1,326 @@
+//! Field and method descriptors (JVMS §4.3).
+
+use error::*;
+use std::fmt;
+
+/// Maximum number of array dimensions allowed in a descriptor.
+pub const MAX_DIMENSIONS: usize = 255;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BaseType {
+    Byte,
+    Char,
+    Double,
+    Float,
+    Int,
+    Long,
+    Short,
+    Boolean,
+}
+
+impl BaseType {
+    pub fn from_char(c: char) -> Option<BaseType> {
+        match c {
+            'B' => Some(BaseType::Byte),
+            'C' => Some(BaseType::Char),
+            'D' => Some(BaseType::Double),
+            'F' => Some(BaseType::Float),
+            'I' => Some(BaseType::Int),
+            'J' => Some(BaseType::Long),
+            'S' => Some(BaseType::Short),
+            'Z' => Some(BaseType::Boolean),
+            _ => None,
+        }
+    }
+
+    pub fn as_char(&self) -> char {
+        match *self {
+            BaseType::Byte => 'B',
+            BaseType::Char => 'C',
+            BaseType::Double => 'D',
+            BaseType::Float => 'F',
+            BaseType::Int => 'I',
+            BaseType::Long => 'J',
+            BaseType::Short => 'S',
+            BaseType::Boolean => 'Z',
+        }
+    }
+
+    /// Name of the type in Java source code, e.g. `int`.
+    pub fn java_name(&self) -> &'static str {
+        match *self {
+            BaseType::Byte => "byte",
+            BaseType::Char => "char",
+            BaseType::Double => "double",
+            BaseType::Float => "float",
+            BaseType::Int => "int",
+            BaseType::Long => "long",
+            BaseType::Short => "short",
+            BaseType::Boolean => "boolean",
+        }
+    }
+
+    /// Number of local variable or operand stack slots taken by a value of this type.
+    pub fn slots(&self) -> usize {
+        match *self {
+            BaseType::Long | BaseType::Double => 2,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    Base(BaseType),
+    /// Class or interface, by its internal binary name (e.g. `java/lang/String`).
+    Object(String),
+    /// Array of `dimensions` dimensions. The element type is never itself an array.
+    Array(usize, Box<FieldType>),
+}
+
+impl FieldType {
+    /// Parse a complete field descriptor, e.g. `[Ljava/lang/String;`.
+    pub fn parse(desc: &str) -> Result<FieldType> {
+        match FieldType::parse_prefix(desc) {
+            Some((ty, "")) => Ok(ty),
+            _ => bail!(ErrorKind::BadDescriptor(desc.to_owned())),
+        }
+    }
+
+    /// Parse the field type at the start of `desc`, and return it along with the rest of the string.
+    fn parse_prefix(desc: &str) -> Option<(FieldType, &str)> {
+        let dimensions = desc.bytes().take_while(|&b| b == b'[').count();
+        if dimensions > MAX_DIMENSIONS {
+            return None;
+        }
+
+        let rest = &desc[dimensions..];
+        let (element, rest) = match rest.chars().next() {
+            Some('L') => {
+                let end = rest.find(';')?;
+                let name = &rest[1..end];
+                if !is_binary_name(name) {
+                    return None;
+                }
+                (FieldType::Object(name.to_owned()), &rest[end + 1..])
+            }
+            Some(c) => (FieldType::Base(BaseType::from_char(c)?), &rest[1..]),
+            None => return None,
+        };
+
+        if dimensions > 0 {
+            Some((FieldType::Array(dimensions, Box::new(element)), rest))
+        } else {
+            Some((element, rest))
+        }
+    }
+
+    /// Number of local variable or operand stack slots taken by a value of this type.
+    pub fn slots(&self) -> usize {
+        match *self {
+            FieldType::Base(ref base) => base.slots(),
+            _ => 1,
+        }
+    }
+
+    /// Number of array dimensions, 0 if this isn't an array type.
+    pub fn dimensions(&self) -> usize {
+        match *self {
+            FieldType::Array(dimensions, _) => dimensions,
+            _ => 0,
+        }
+    }
+
+    /// Type of the elements of an array, i.e. the type with one less dimension.
+    pub fn component(&self) -> Option<FieldType> {
+        match *self {
+            FieldType::Array(1, ref element) => Some((**element).clone()),
+            FieldType::Array(dimensions, ref element) => Some(FieldType::Array(dimensions - 1, element.clone())),
+            _ => None,
+        }
+    }
+
+    /// Whether values of this type are references, i.e. objects or arrays.
+    pub fn is_reference(&self) -> bool {
+        !matches!(*self, FieldType::Base(..))
+    }
+
+    /// Render the type in Java source syntax, e.g. `java.lang.String[]`.
+    pub fn to_java(&self) -> String {
+        match *self {
+            FieldType::Base(ref base) => base.java_name().to_owned(),
+            FieldType::Object(ref name) => name.replace('/', "."),
+            FieldType::Array(dimensions, ref element) => element.to_java() + &"[]".repeat(dimensions),
+        }
+    }
+}
+
+impl fmt::Display for FieldType {
+    /// Render the type as a descriptor.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FieldType::Base(ref base) => write!(f, "{}", base.as_char()),
+            FieldType::Object(ref name) => write!(f, "L{};", name),
+            FieldType::Array(dimensions, ref element) => write!(f, "{}{}", "[".repeat(dimensions), element),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReturnType {
+    Void,
+    Type(FieldType),
+}
+
+impl ReturnType {
+    /// Number of operand stack slots taken by the returned value.
+    pub fn slots(&self) -> usize {
+        match *self {
+            ReturnType::Void => 0,
+            ReturnType::Type(ref ty) => ty.slots(),
+        }
+    }
+
+    pub fn to_java(&self) -> String {
+        match *self {
+            ReturnType::Void => "void".to_owned(),
+            ReturnType::Type(ref ty) => ty.to_java(),
+        }
+    }
+}
+
+impl fmt::Display for ReturnType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReturnType::Void => write!(f, "V"),
+            ReturnType::Type(ref ty) => write!(f, "{}", ty),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MethodDescriptor {
+    pub params: Vec<FieldType>,
+    pub ret: ReturnType,
+}
+
+impl MethodDescriptor {
+    /// Parse a method descriptor, e.g. `(ILjava/lang/String;)[J`.
+    pub fn parse(desc: &str) -> Result<MethodDescriptor> {
+        match MethodDescriptor::parse_inner(desc) {
+            Some(method) => Ok(method),
+            None => bail!(ErrorKind::BadDescriptor(desc.to_owned())),
+        }
+    }
+
+    fn parse_inner(desc: &str) -> Option<MethodDescriptor> {
+        if !desc.starts_with('(') {
+            return None;
+        }
+
+        let mut rest = &desc[1..];
+        let mut params = Vec::new();
+        while !rest.starts_with(')') {
+            let (ty, next) = FieldType::parse_prefix(rest)?;
+            params.push(ty);
+            rest = next;
+        }
+
+        let ret = match &rest[1..] {
+            "V" => ReturnType::Void,
+            ret => ReturnType::Type(FieldType::parse_prefix(ret).and_then(|(ty, rest)| {
+                if rest.is_empty() { Some(ty) } else { None }
+            })?),
+        };
+
+        Some(MethodDescriptor {
+            params: params,
+            ret: ret,
+        })
+    }
+
+    /// Number of local variable slots taken by the parameters, not counting `this`.
+    pub fn arg_slots(&self) -> usize {
+        self.params.iter().map(FieldType::slots).sum()
+    }
+
+    /// Render the parameter types in Java source syntax.
+    pub fn java_params(&self) -> Vec<String> {
+        self.params.iter().map(FieldType::to_java).collect()
+    }
+
+    /// Render a declaration of a method named `name` in Java source syntax,
+    /// e.g. `int foo(java.lang.String, long[])`.
+    pub fn to_java(&self, name: &str) -> String {
+        format!("{} {}({})", self.ret.to_java(), name, self.java_params().join(", "))
+    }
+}
+
+impl fmt::Display for MethodDescriptor {
+    /// Render the method type as a descriptor.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "("));
+        for param in self.params.iter() {
+            try!(write!(f, "{}", param));
+        }
+        write!(f, "){}", self.ret)
+    }
+}
+
+/// Whether `name` is a valid binary name in internal form, i.e. non-empty identifiers
+/// separated by `/` (JVMS §4.2.1).
+pub fn is_binary_name(name: &str) -> bool {
+    name.split('/').all(|part| {
+        !part.is_empty() && !part.contains(&['.', ';', '['][..])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_dimensional_arrays() {
+        let ty = FieldType::parse("[[[Ljava/lang/String;").unwrap();
+        assert_eq!(ty, FieldType::Array(3, Box::new(FieldType::Object("java/lang/String".to_owned()))));
+        assert_eq!(ty.dimensions(), 3);
+        assert_eq!(ty.component(), Some(FieldType::parse("[[Ljava/lang/String;").unwrap()));
+        assert_eq!(FieldType::parse("[I").unwrap().component(), Some(FieldType::Base(BaseType::Int)));
+
+        assert!(FieldType::parse(&format!("{}I", "[".repeat(MAX_DIMENSIONS))).is_ok());
+        assert!(FieldType::parse(&format!("{}I", "[".repeat(MAX_DIMENSIONS + 1))).is_err());
+    }
+
+    #[test]
+    fn argument_slots() {
+        let desc = MethodDescriptor::parse("(IJLjava/lang/Object;D[J)J").unwrap();
+        assert_eq!(desc.params.len(), 5);
+        assert_eq!(desc.arg_slots(), 7);
+        assert_eq!(desc.ret.slots(), 2);
+        assert_eq!(MethodDescriptor::parse("()V").unwrap().arg_slots(), 0);
+        assert_eq!(MethodDescriptor::parse("()V").unwrap().ret.slots(), 0);
+    }
+
+    #[test]
+    fn rendering() {
+        let ty = FieldType::parse("[[J").unwrap();
+        assert_eq!(ty.to_string(), "[[J");
+        assert_eq!(ty.to_java(), "long[][]");
+
+        let desc = MethodDescriptor::parse("(Ljava/lang/String;[IZ)[Ljava/util/List;").unwrap();
+        assert_eq!(desc.to_string(), "(Ljava/lang/String;[IZ)[Ljava/util/List;");
+        assert_eq!(desc.to_java("foo"), "java.util.List[] foo(java.lang.String, int[], boolean)");
+        assert_eq!(MethodDescriptor::parse("()V").unwrap().to_java("run"), "void run()");
+    }
+
+    #[test]
+    fn malformed_descriptors() {
+        for desc in ["", "[", "Lfoo", "L;", "La//b;", "V", "II", "Q"].iter() {
+            assert!(FieldType::parse(desc).is_err(), "{}", desc);
+        }
+        for desc in ["(I", "()", "I)V", "(V)V", "(Lfoo)V", "()II", "(I)Lfoo"].iter() {
+            assert!(MethodDescriptor::parse(desc).is_err(), "{}", desc);
+        }
+        assert_eq!(FieldType::parse("Lfoo").unwrap_err().to_string(), "Bad descriptor: `Lfoo`");
+    }
+}
@@ -17,14 +17,230 @@ error_chain! {
         }
         BadAttrName(value: usize) {
             description("Bad attribute name")
+            display("Bad attribute name index: #{}", value)
+        }
+        BadConstantIndex(index: usize, expected: &'static str) {
+            description("Bad constant pool index")
+            display("Bad constant pool index #{}: expected {}", index, expected)
+        }
+        BadDescriptor(desc: String) {
+            description("Bad descriptor")
+            display("Bad descriptor: `{}`", desc)
         }
         BadMagicValue(value: u32) {
             description("Bad magic value")
             display("Bad magic value: {:#x}", value)
         }
+        BadName(name: String) {
+            description("Bad name")
+            display("Bad name: `{}`", name)
+        }
+        BadOpcode(opcode: u8, pc: usize) {
+            description("Bad opcode")
+            display("Bad opcode: {:#x} at pc {:#x}", opcode, pc)
+        }
+        BadOperand(pc: usize) {
+            description("Bad instruction operand")
+            display("Bad operand for instruction at pc {:#x}", pc)
+        }
+        BadSignature(sig: String) {
+            description("Bad signature")
+            display("Bad signature: `{}`", sig)
+        }
+        BadStackMapFrame(index: usize) {
+            description("Bad stack map frame")
+            display("Bad stack map frame at entry {}", index)
+        }
         BadTagValue(value: u8) {
             description("Bad tag value")
             display("Bad tag value: {:#x} `{}`", value, *value as char)
         }
+        DuplicateMember(name: String, desc: String) {
+            description("Duplicate member")
+            display("Duplicate member `{}` with descriptor `{}`", name, desc)
+        }
+        FormatViolation(reason: &'static str) {
+            description("Format violation")
+            display("{}", reason)
+        }
+        IllegalAccessFlags(flags: u16, reason: &'static str) {
+            description("Illegal access flags")
+            display("Illegal access flags {:#x}: {}", flags, reason)
+        }
+        Malformed(path: String, offset: Option<usize>) {
+            description("Malformed class file")
+            display("Malformed class file at {}{}", if path.is_empty() { "top level" } else { path },
+                    offset.map(|offset| format!(", byte {:#x}", offset)).unwrap_or_default())
+        }
+        MissingAttr(name: &'static str) {
+            description("Missing attribute")
+            display("Missing `{}` attribute", name)
+        }
+        ReservedFrameType(frame_type: u8) {
+            description("Reserved stack map frame type")
+            display("Reserved stack map frame type: {}", frame_type)
+        }
+        TruncatedCode(pc: usize) {
+            description("Unexpected end of code")
+            display("Unexpected end of code in instruction at pc {:#x}", pc)
+        }
+        UnexpectedAttr(name: String) {
+            description("Unexpected attribute")
+            display("Unexpected `{}` attribute", name)
+        }
+    }
+}
+
+impl Error {
+    /// Structural path of the part of the class file where the error occurred,
+    /// e.g. `methods[3].attrs[Code].attrs[StackMapTable].frame[7]`.
+    pub fn path(&self) -> Option<&str> {
+        match self.0 {
+            ErrorKind::Malformed(ref path, _) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Offset in the class file of the byte at which reading stopped.
+    pub fn offset(&self) -> Option<usize> {
+        match self.0 {
+            ErrorKind::Malformed(_, offset) => offset,
+            _ => None,
+        }
+    }
+
+    /// Prefix the path of the error with `segment`, wrapping it into a `Malformed` error first
+    /// if needed.
+    pub fn within(self, segment: String) -> Error {
+        match self {
+            Error(ErrorKind::Malformed(path, offset), state) => {
+                let path = if path.is_empty() { segment } else { segment + "." + &path };
+                Error(ErrorKind::Malformed(path, offset), state)
+            }
+            err => Error::with_chain(err, ErrorKind::Malformed(segment, None)),
+        }
+    }
+
+    fn at_offset(self, offset: usize) -> Error {
+        match self {
+            Error(ErrorKind::Malformed(path, None), state) => Error(ErrorKind::Malformed(path, Some(offset)), state),
+            err @ Error(ErrorKind::Malformed(..), _) => err,
+            err => Error::with_chain(err, ErrorKind::Malformed(String::new(), Some(offset))),
+        }
+    }
+}
+
+/// Locate errors in the structure of a class file.
+pub trait Locate<T> {
+    /// Record that the error occurred while reading the part of the class file named `segment`.
+    fn within<F: FnOnce() -> String>(self, segment: F) -> Result<T>;
+
+    /// Record the offset in the class file at which reading stopped. Only the first offset is kept.
+    fn at_offset(self, offset: usize) -> Result<T>;
+}
+
+impl<T, E: Into<Error>> Locate<T> for ::std::result::Result<T, E> {
+    fn within<F: FnOnce() -> String>(self, segment: F) -> Result<T> {
+        self.map_err(|err| err.into().within(segment()))
+    }
+
+    fn at_offset(self, offset: usize) -> Result<T> {
+        self.map_err(|err| err.into().at_offset(offset))
+    }
+}
+
+/// How to deal with malformed parts of a class file, and the errors recovered from so far.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    lenient: bool,
+    errors: Vec<Error>,
+}
+
+impl Diagnostics {
+    /// Fail on the first error.
+    pub fn strict() -> Diagnostics {
+        Diagnostics::default()
+    }
+
+    /// Keep attributes that fail to decode as `AttrInfo::Unknown`, and keep unknown access flag
+    /// bits, reporting the errors instead of failing.
+    pub fn lenient() -> Diagnostics {
+        Diagnostics {
+            lenient: true,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    /// Errors recovered from, located by their path like the ones returned by `Classfile::read`, but
+    /// without an offset: lenient reading decodes attributes from a copy of their data.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    pub fn into_errors(self) -> Vec<Error> {
+        self.errors
+    }
+
+    /// Record an error recovered from.
+    pub fn report<E: Into<Error>>(&mut self, err: E) {
+        self.errors.push(err.into());
+    }
+
+    /// Convert the `bits` of access flags read with `from_bits`, failing on unknown bits. When
+    /// lenient, unknown bits are reported instead and kept with `from_bits_unchecked`, as they're
+    /// reserved for future use (JVMS §4.1).
+    pub fn access_flags<F>(&mut self, bits: u16, from_bits: fn(u16) -> Option<F>,
+                           from_bits_unchecked: unsafe fn(u16) -> F) -> Result<F> {
+        match from_bits(bits) {
+            Some(flags) => Ok(flags),
+            None if self.lenient => {
+                self.report(Error::from(ErrorKind::BadAccessFlags(bits)).within("access_flags".to_owned()));
+                Ok(unsafe { from_bits_unchecked(bits) })
+            }
+            None => Err(ErrorKind::BadAccessFlags(bits)).within(|| "access_flags".to_owned()),
+        }
+    }
+
+    /// Run `read`, locating both the error it returns and the ones it reports within `segment`.
+    pub fn within<T, S, F>(&mut self, segment: S, read: F) -> Result<T>
+        where S: FnOnce() -> String, F: FnOnce(&mut Diagnostics) -> Result<T>
+    {
+        let mark = self.errors.len();
+        let result = read(self);
+        if mark == self.errors.len() {
+            return result.within(segment);
+        }
+
+        let segment = segment();
+        let errors = self.errors.split_off(mark);
+        self.errors.extend(errors.into_iter().map(|err| err.within(segment.clone())));
+        result.within(|| segment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use method::flags::AccessFlags;
+    use super::*;
+
+    #[test]
+    fn unknown_access_flags() {
+        let read = |diagnostics: &mut Diagnostics, bits| {
+            diagnostics.access_flags(bits, AccessFlags::from_bits, AccessFlags::from_bits_unchecked)
+        };
+
+        let mut strict = Diagnostics::strict();
+        assert_eq!(read(&mut strict, 0x0009).unwrap(), AccessFlags::ACC_PUBLIC | AccessFlags::ACC_STATIC);
+        assert_eq!(read(&mut strict, 0x8001).unwrap_err().path(), Some("access_flags"));
+
+        let mut lenient = Diagnostics::lenient();
+        assert_eq!(read(&mut lenient, 0x8001).unwrap().bits(), 0x8001);
+        assert_eq!(lenient.errors().len(), 1);
+        assert_eq!(lenient.errors()[0].path(), Some("access_flags"));
+        assert_eq!(lenient.errors()[0].offset(), None);
     }
 }
@@ -0,0 +1,266 @@
+use attr::info::AttrInfo;
+use attr::info::code::{LineNumberTableAttrInfo, LocalVariableTableAttrInfo, LocalVariableTypeTableAttrInfo};
+use attr::info::code::StackMapTableAttrInfo;
+use attr::info::code::stack_map::frame::StackMapFrameInfo;
+use attr::info::code::stack_map::verification_type::VerificationTypeInfo;
+use attr::info::method::CodeAttrInfo;
+use bytecode::Instruction;
+use constant::ConstantPool;
+use std::io::{self, Write};
+use super::constant::describe;
+use super::print_attr;
+use utils::print::Printer;
+
+pub fn print_code<W: Write>(printer: &mut Printer<W>,
+                            code: &CodeAttrInfo,
+                            pool: &ConstantPool,
+                            this_class: &str,
+                            args_size: usize) -> io::Result<()> {
+    try!(printer.write_indent());
+    try!(writeln!(printer, "Code:"));
+
+    let mut printer = printer.sub_indent(1);
+
+    try!(printer.write_indent());
+    try!(writeln!(printer, "stack={}, locals={}, args_size={}", code.max_stack, code.max_locals, args_size));
+
+    for insn in code.instructions() {
+        match insn {
+            Ok((pc, insn)) => try!(print_instruction(&mut printer, pc, &insn, pool, this_class)),
+            Err(e) => {
+                try!(printer.write_indent());
+                try!(writeln!(printer, "<{}>", e));
+            }
+        }
+    }
+
+    if !code.exception_handlers.is_empty() {
+        try!(printer.write_indent());
+        try!(writeln!(printer, "Exception table:"));
+
+        try!(printer.write_indent());
+        try!(writeln!(printer, "   from    to  target type"));
+        for handler in code.exception_handlers.iter() {
+            let catch_type = match handler.catch_type(pool).and_then(|class| class.name(pool)) {
+                Some(name) => format!("Class {}", name),
+                None => "any".to_owned(),
+            };
+
+            try!(printer.write_indent());
+            try!(writeln!(printer, "  {:>5} {:>5} {:>5}   {}",
+                          handler.start_pc, handler.end_pc, handler.handler_pc, catch_type));
+        }
+    }
+
+    for attr in code.attrs.iter() {
+        match attr.info {
+            AttrInfo::LineNumberTable(ref info) => try!(print_line_numbers(&mut printer, info)),
+            AttrInfo::LocalVariableTable(ref info) => try!(print_local_variables(&mut printer, info, pool)),
+            AttrInfo::LocalVariableTypeTable(ref info) => try!(print_local_variable_types(&mut printer, info, pool)),
+            AttrInfo::StackMapTable(ref info) => try!(print_stack_map(&mut printer, info, pool)),
+            _ => try!(print_attr(&mut printer, attr, pool)),
+        }
+    }
+
+    Ok(())
+}
+
+fn print_instruction<W: Write>(printer: &mut Printer<W>,
+                               pc: usize,
+                               insn: &Instruction,
+                               pool: &ConstantPool,
+                               this_class: &str) -> io::Result<()> {
+    let target = |offset: i32| pc as i64 + offset as i64;
+
+    try!(printer.write_indent());
+    try!(write!(printer, "{:>4}: ", pc));
+
+    let operands = match *insn {
+        Instruction::Tableswitch(ref info) => {
+            try!(writeln!(printer, "{:<13} {{ // {} to {}", insn.mnemonic(), info.low, info.high));
+            for (i, &offset) in info.offsets.iter().enumerate() {
+                try!(printer.write_indent());
+                try!(writeln!(printer, "{:>24}: {}", info.low as i64 + i as i64, target(offset)));
+            }
+            return print_switch_end(printer, target(info.default));
+        }
+        Instruction::Lookupswitch(ref info) => {
+            try!(writeln!(printer, "{:<13} {{ // {}", insn.mnemonic(), info.pairs.len()));
+            for &(key, offset) in info.pairs.iter() {
+                try!(printer.write_indent());
+                try!(writeln!(printer, "{:>24}: {}", key, target(offset)));
+            }
+            return print_switch_end(printer, target(info.default));
+        }
+        Instruction::Bipush(value) => format!("{}", value),
+        Instruction::Sipush(value) => format!("{}", value),
+        Instruction::Iload(index) | Instruction::Lload(index) | Instruction::Fload(index) |
+        Instruction::Dload(index) | Instruction::Aload(index) | Instruction::Istore(index) |
+        Instruction::Lstore(index) | Instruction::Fstore(index) | Instruction::Dstore(index) |
+        Instruction::Astore(index) | Instruction::Ret(index) => format!("{}", index),
+        Instruction::Iinc(index, value) => format!("{}, {}", index, value),
+        Instruction::Newarray(ty) => ty.name().to_owned(),
+        Instruction::Invokeinterface(index, count) => format!("#{},  {}", index, count),
+        Instruction::Invokedynamic(index) => format!("#{},  0", index),
+        Instruction::Multianewarray(index, dimensions) => format!("#{},  {}", index, dimensions),
+        _ => {
+            if let Some(index) = insn.constant_index() {
+                format!("#{}", index)
+            } else if let Some(offset) = insn.branch_offset() {
+                format!("{}", target(offset))
+            } else {
+                String::new()
+            }
+        }
+    };
+
+    match insn.constant_index() {
+        Some(index) => {
+            let comment = describe(pool, index, this_class);
+            try!(writeln!(printer, "{:<13} {:<18} // {}", insn.mnemonic(), operands, comment));
+        }
+        None if operands.is_empty() => try!(writeln!(printer, "{}", insn.mnemonic())),
+        None => try!(writeln!(printer, "{:<13} {}", insn.mnemonic(), operands)),
+    }
+
+    Ok(())
+}
+
+fn print_switch_end<W: Write>(printer: &mut Printer<W>, default: i64) -> io::Result<()> {
+    try!(printer.write_indent());
+    try!(writeln!(printer, "{:>24}: {}", "default", default));
+
+    try!(printer.write_indent());
+    try!(writeln!(printer, "      }}"));
+
+    Ok(())
+}
+
+fn print_line_numbers<W: Write>(printer: &mut Printer<W>, info: &LineNumberTableAttrInfo) -> io::Result<()> {
+    try!(printer.write_indent());
+    try!(writeln!(printer, "LineNumberTable:"));
+
+    let mut printer = printer.sub_indent(1);
+    for entry in info.entries() {
+        try!(printer.write_indent());
+        try!(writeln!(printer, "line {}: {}", entry.line_number, entry.start_pc));
+    }
+
+    Ok(())
+}
+
+fn print_local_variables<W: Write>(printer: &mut Printer<W>,
+                                   info: &LocalVariableTableAttrInfo,
+                                   pool: &ConstantPool) -> io::Result<()> {
+    try!(printer.write_indent());
+    try!(writeln!(printer, "LocalVariableTable:"));
+
+    let mut printer = printer.sub_indent(1);
+
+    try!(printer.write_indent());
+    try!(writeln!(printer, "Start  Length  Slot  Name   Signature"));
+    for entry in info.entries() {
+        let name = entry.name(pool).unwrap_or("?");
+        let desc = entry.desc(pool).unwrap_or("?");
+
+        try!(printer.write_indent());
+        try!(writeln!(printer, "{:>5}  {:>6}  {:>4}  {:>4}   {}", entry.start_pc, entry.length, entry.index, name, desc));
+    }
+
+    Ok(())
+}
+
+fn print_local_variable_types<W: Write>(printer: &mut Printer<W>,
+                                       info: &LocalVariableTypeTableAttrInfo,
+                                       pool: &ConstantPool) -> io::Result<()> {
+    try!(printer.write_indent());
+    try!(writeln!(printer, "LocalVariableTypeTable:"));
+
+    let mut printer = printer.sub_indent(1);
+
+    try!(printer.write_indent());
+    try!(writeln!(printer, "Start  Length  Slot  Name   Signature"));
+    for entry in info.entries() {
+        let name = entry.name(pool).unwrap_or("?");
+        let signature = entry.signature(pool).unwrap_or("?");
+
+        try!(printer.write_indent());
+        try!(writeln!(printer, "{:>5}  {:>6}  {:>4}  {:>4}   {}", entry.start_pc, entry.length, entry.index, name, signature));
+    }
+
+    Ok(())
+}
+
+fn print_stack_map<W: Write>(printer: &mut Printer<W>,
+                             info: &StackMapTableAttrInfo,
+                             pool: &ConstantPool) -> io::Result<()> {
+    try!(printer.write_indent());
+    try!(writeln!(printer, "StackMapTable: number_of_entries = {}", info.entries().len()));
+
+    let mut printer = printer.sub_indent(1);
+    for frame in info.entries() {
+        let kind = match frame.info {
+            StackMapFrameInfo::SameFrame => "same",
+            StackMapFrameInfo::SameLocalsOneStackItemFrame(..) => "same_locals_1_stack_item",
+            StackMapFrameInfo::SameLocalsOneStackItemFrameExtended(..) => "same_locals_1_stack_item_frame_extended",
+            StackMapFrameInfo::ChopFrame(..) => "chop",
+            StackMapFrameInfo::SameFrameExtended(..) => "same_frame_extended",
+            StackMapFrameInfo::AppendFrame(..) => "append",
+            StackMapFrameInfo::FullFrame(..) => "full_frame",
+        };
+
+        try!(printer.write_indent());
+        try!(writeln!(printer, "frame_type = {} /* {} */", frame.tag(), kind));
+
+        let mut printer = printer.sub_indent(1);
+        match frame.info {
+            StackMapFrameInfo::SameFrame => {}
+            StackMapFrameInfo::SameLocalsOneStackItemFrame(ref info) => {
+                try!(print_types(&mut printer, "stack", Some(&info.stack_item), pool));
+            }
+            _ => {
+                try!(printer.write_indent());
+                try!(writeln!(printer, "offset_delta = {}", frame.offset_delta()));
+            }
+        }
+        match frame.info {
+            StackMapFrameInfo::SameLocalsOneStackItemFrameExtended(ref info) => {
+                try!(print_types(&mut printer, "stack", Some(&info.stack_item), pool));
+            }
+            StackMapFrameInfo::AppendFrame(ref info) => {
+                try!(print_types(&mut printer, "locals", info.locals.iter(), pool));
+            }
+            StackMapFrameInfo::FullFrame(ref info) => {
+                try!(print_types(&mut printer, "locals", info.locals.iter(), pool));
+                try!(print_types(&mut printer, "stack", info.stack.iter(), pool));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn print_types<'a, W, I>(printer: &mut Printer<W>, name: &str, types: I, pool: &ConstantPool) -> io::Result<()>
+    where W: Write, I: IntoIterator<Item = &'a VerificationTypeInfo>
+{
+    let types: Vec<_> = types.into_iter().map(|ty| match *ty {
+        VerificationTypeInfo::Object(..) => {
+            match ty.class(pool).and_then(|class| class.name(pool)) {
+                Some(name) if name.starts_with('[') => format!("class \"{}\"", name),
+                Some(name) => format!("class {}", name),
+                None => ty.to_string(),
+            }
+        }
+        _ => ty.to_string(),
+    }).collect();
+
+    try!(printer.write_indent());
+    if types.is_empty() {
+        try!(writeln!(printer, "{} = []", name));
+    } else {
+        try!(writeln!(printer, "{} = [ {} ]", name, types.join(", ")));
+    }
+
+    Ok(())
+}
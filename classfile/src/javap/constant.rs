@@ -0,0 +1,210 @@
+use constant::*;
+use std::io::{self, Write};
+use utils::print::Printer;
+
+pub fn print_pool<W: Write>(printer: &mut Printer<W>, pool: &ConstantPool) -> io::Result<()> {
+    try!(printer.write_indent());
+    try!(writeln!(printer, "Constant pool:"));
+
+    for (index, entry) in pool.entries() {
+        let (kind, value) = entry_info(entry);
+        let index = format!("#{}", index);
+
+        try!(write!(printer, "{:>6} = {:<18} ", index, kind));
+        match comment(entry, pool) {
+            Some(comment) => try!(writeln!(printer, "{:<14} // {}", value, comment)),
+            None => try!(writeln!(printer, "{}", value)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Kind name and raw value of an entry, as shown in the constant pool listing.
+fn entry_info(entry: &ConstantPoolEntry) -> (&'static str, String) {
+    match *entry {
+        ConstantPoolEntry::Class(ref info) => ("Class", format!("#{}", info.name_index())),
+        ConstantPoolEntry::FieldRef(ref info) =>
+            ("Fieldref", format!("#{}.#{}", info.class_index(), info.name_and_type_index())),
+        ConstantPoolEntry::MethodRef(ref info) =>
+            ("Methodref", format!("#{}.#{}", info.class_index(), info.name_and_type_index())),
+        ConstantPoolEntry::InterfaceMethodRef(ref info) =>
+            ("InterfaceMethodref", format!("#{}.#{}", info.class_index(), info.name_and_type_index())),
+        ConstantPoolEntry::String(ref info) => ("String", format!("#{}", info.string_index())),
+        ConstantPoolEntry::Integer(ref info) => ("Integer", format!("{}", info.value())),
+        ConstantPoolEntry::Float(ref info) => ("Float", format!("{}f", info.value())),
+        ConstantPoolEntry::Long(ref info) => ("Long", format!("{}l", info.value())),
+        ConstantPoolEntry::Double(ref info) => ("Double", format!("{}d", info.value())),
+        ConstantPoolEntry::NameAndType(ref info) =>
+            ("NameAndType", format!("#{}:#{}", info.name_index(), info.desc_index())),
+        ConstantPoolEntry::Utf8(ref info) => ("Utf8", escape(info.value())),
+        ConstantPoolEntry::MethodHandle(ref info) =>
+            ("MethodHandle", format!("{}:#{}", info.ref_kind(), info.ref_index())),
+        ConstantPoolEntry::MethodType(ref info) => ("MethodType", format!("#{}", info.desc_index())),
+        ConstantPoolEntry::Dynamic(ref info) =>
+            ("Dynamic", format!("#{}:#{}", info.bootstrap_method_attr_index(), info.name_and_type_index())),
+        ConstantPoolEntry::InvokedDynamic(ref info) =>
+            ("InvokeDynamic", format!("#{}:#{}", info.bootstrap_method_attr_index(), info.name_and_type_index())),
+        ConstantPoolEntry::Module(ref info) => ("Module", format!("#{}", info.name_index())),
+        ConstantPoolEntry::Package(ref info) => ("Package", format!("#{}", info.name_index())),
+    }
+}
+
+/// Resolved form of an entry, used in `// ...` comments. Returns `None` for entries
+/// whose raw value is already readable.
+fn comment(entry: &ConstantPoolEntry, pool: &ConstantPool) -> Option<String> {
+    match *entry {
+        ConstantPoolEntry::Integer(..) | ConstantPoolEntry::Float(..) |
+        ConstantPoolEntry::Long(..) | ConstantPoolEntry::Double(..) |
+        ConstantPoolEntry::Utf8(..) => None,
+        ConstantPoolEntry::String(ref info) => Some(escape(info.value(pool).unwrap_or("?"))),
+        ConstantPoolEntry::Class(ref info) => Some(quote(info.name(pool).unwrap_or("?"))),
+        ConstantPoolEntry::MethodType(ref info) => Some(info.desc(pool).unwrap_or("?").to_owned()),
+        _ => Some(describe_value(entry, pool, "")),
+    }
+}
+
+/// Describe the constant at `index` the way javap does next to instructions,
+/// e.g. `Method java/lang/Object."<init>":()V`.
+pub fn describe(pool: &ConstantPool, index: usize, this_class: &str) -> String {
+    match pool.get(index) {
+        Some(entry) => describe_entry(entry, pool, this_class),
+        None => format!("<invalid index #{}>", index),
+    }
+}
+
+pub fn describe_entry(entry: &ConstantPoolEntry, pool: &ConstantPool, this_class: &str) -> String {
+    let kind = match *entry {
+        ConstantPoolEntry::Class(..) => "class",
+        ConstantPoolEntry::FieldRef(..) => "Field",
+        ConstantPoolEntry::MethodRef(..) => "Method",
+        ConstantPoolEntry::InterfaceMethodRef(..) => "InterfaceMethod",
+        ConstantPoolEntry::String(..) => "String",
+        ConstantPoolEntry::Integer(..) => "int",
+        ConstantPoolEntry::Float(..) => "float",
+        ConstantPoolEntry::Long(..) => "long",
+        ConstantPoolEntry::Double(..) => "double",
+        ConstantPoolEntry::NameAndType(..) => "NameAndType",
+        ConstantPoolEntry::Utf8(..) => "Utf8",
+        ConstantPoolEntry::MethodHandle(..) => "MethodHandle",
+        ConstantPoolEntry::MethodType(..) => "MethodType",
+        ConstantPoolEntry::Dynamic(..) => "Dynamic",
+        ConstantPoolEntry::InvokedDynamic(..) => "InvokeDynamic",
+        ConstantPoolEntry::Module(..) => "Module",
+        ConstantPoolEntry::Package(..) => "Package",
+    };
+
+    format!("{} {}", kind, describe_value(entry, pool, this_class))
+}
+
+pub fn describe_value(entry: &ConstantPoolEntry, pool: &ConstantPool, this_class: &str) -> String {
+    match *entry {
+        ConstantPoolEntry::Class(ref info) => quote(info.name(pool).unwrap_or("?")),
+        ConstantPoolEntry::FieldRef(ref info) =>
+            member_ref(info.class(pool), info.name_and_type(pool), pool, this_class),
+        ConstantPoolEntry::MethodRef(ref info) =>
+            member_ref(info.class(pool), info.name_and_type(pool), pool, this_class),
+        ConstantPoolEntry::InterfaceMethodRef(ref info) =>
+            member_ref(info.class(pool), info.name_and_type(pool), pool, this_class),
+        ConstantPoolEntry::String(ref info) => escape(info.value(pool).unwrap_or("?")),
+        ConstantPoolEntry::Integer(ref info) => format!("{}", info.value()),
+        ConstantPoolEntry::Float(ref info) => format!("{}f", info.value()),
+        ConstantPoolEntry::Long(ref info) => format!("{}l", info.value()),
+        ConstantPoolEntry::Double(ref info) => format!("{}d", info.value()),
+        ConstantPoolEntry::NameAndType(ref info) => name_and_type(Some(info), pool),
+        ConstantPoolEntry::Utf8(ref info) => escape(info.value()),
+        ConstantPoolEntry::MethodHandle(ref info) => {
+            let target = match info.ref_entry(pool) {
+                Some(entry) => describe_value(entry, pool, ""),
+                None => "?".to_owned(),
+            };
+            format!("{} {}", ref_kind_name(info.ref_kind()), target)
+        }
+        ConstantPoolEntry::MethodType(ref info) => info.desc(pool).unwrap_or("?").to_owned(),
+        ConstantPoolEntry::Dynamic(ref info) => {
+            format!("#{}:{}", info.bootstrap_method_attr_index(), name_and_type(info.name_and_type(pool), pool))
+        }
+        ConstantPoolEntry::InvokedDynamic(ref info) => {
+            format!("#{}:{}", info.bootstrap_method_attr_index(), name_and_type(info.name_and_type(pool), pool))
+        }
+        ConstantPoolEntry::Module(ref info) => quote(info.name(pool).unwrap_or("?")),
+        ConstantPoolEntry::Package(ref info) => quote(info.name(pool).unwrap_or("?")),
+    }
+}
+
+fn member_ref(class: Option<&ConstantClassInfo>,
+              nat: Option<&ConstantNameAndTypeInfo>,
+              pool: &ConstantPool,
+              this_class: &str) -> String {
+    let class = class.and_then(|class| class.name(pool)).unwrap_or("?");
+    let nat = name_and_type(nat, pool);
+
+    if class == this_class {
+        nat
+    } else {
+        format!("{}.{}", quote(class), nat)
+    }
+}
+
+fn name_and_type(nat: Option<&ConstantNameAndTypeInfo>, pool: &ConstantPool) -> String {
+    match nat {
+        Some(nat) => {
+            let name = nat.name(pool).unwrap_or("?");
+            let desc = nat.desc(pool).unwrap_or("?");
+            format!("{}:{}", quote(name), desc)
+        }
+        None => "?".to_owned(),
+    }
+}
+
+fn ref_kind_name(kind: u8) -> &'static str {
+    match kind {
+        1 => "REF_getField",
+        2 => "REF_getStatic",
+        3 => "REF_putField",
+        4 => "REF_putStatic",
+        5 => "REF_invokeVirtual",
+        6 => "REF_invokeStatic",
+        7 => "REF_invokeSpecial",
+        8 => "REF_newInvokeSpecial",
+        9 => "REF_invokeInterface",
+        _ => "REF_unknown",
+    }
+}
+
+/// Quote names which aren't made of `/`-separated Java identifiers, like javap does.
+pub fn quote(name: &str) -> String {
+    let mut prev = '/';
+    for c in name.chars() {
+        let valid = if prev == '/' {
+            c.is_alphabetic() || c == '_' || c == '$'
+        } else {
+            c == '/' || c.is_alphanumeric() || c == '_' || c == '$'
+        };
+        if !valid {
+            return format!("\"{}\"", escape(name));
+        }
+        prev = c;
+    }
+
+    if name.is_empty() {
+        "\"\"".to_owned()
+    } else {
+        name.to_owned()
+    }
+}
+
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
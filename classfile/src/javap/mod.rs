@@ -0,0 +1,501 @@
+//! javap-like rendering of class files (`javap -c -v`).
+
+mod code;
+mod constant;
+mod module;
+mod types;
+
+use attr::Attr;
+use attr::info::AttrInfo;
+use attr::info::misc::SignatureAttrInfo;
+use constant::ConstantPool;
+use descriptor::MethodDescriptor;
+use field::FieldInfo;
+use method::MethodInfo;
+use self::constant::{describe_entry, describe_value, quote};
+use self::types::{java_name, java_type};
+use signature::{ClassType, ReferenceType, type_params_to_java};
+use std::fmt::Debug;
+use std::io::{self, Write};
+use super::{Classfile, flags};
+use utils::print::Printer;
+
+pub fn print<W: Write>(printer: &mut Printer<W>, cf: &Classfile) -> io::Result<()> {
+    let pool = &cf.constant_pool;
+    let this_class = cf.this_class().and_then(|class| class.name(pool)).unwrap_or("?");
+
+    for attr in cf.attrs.iter() {
+        if let AttrInfo::SourceFile(ref info) = attr.info {
+            try!(printer.write_indent());
+            try!(writeln!(printer, "Compiled from \"{}\"", info.sourcefile(pool).unwrap_or("?")));
+        }
+    }
+
+    try!(print_class_header(printer, cf, this_class));
+
+    {
+        let mut printer = printer.sub_indent(1);
+
+        try!(printer.write_indent());
+        try!(writeln!(printer, "minor version: {}", cf.version.minor));
+
+        try!(printer.write_indent());
+        try!(writeln!(printer, "major version: {}", cf.version.major));
+
+        try!(print_flags(&mut printer, cf.access_flags.bits(), &cf.access_flags));
+
+        try!(printer.write_indent());
+        try!(writeln!(printer, "{:<40}// {}", format!("this_class: #{}", cf.this_class), quote(this_class)));
+
+        try!(printer.write_indent());
+        if cf.super_class != 0 {
+            try!(writeln!(printer, "{:<40}// {}", format!("super_class: #{}", cf.super_class),
+                          describe_class(pool, cf.super_class)));
+        } else {
+            try!(writeln!(printer, "super_class: #0"));
+        }
+
+        try!(printer.write_indent());
+        try!(writeln!(printer, "interfaces: {}, fields: {}, methods: {}, attributes: {}",
+                      cf.interfaces.len(), cf.fields.len(), cf.methods.len(), cf.attrs.len()));
+    }
+
+    try!(constant::print_pool(printer, pool));
+
+    try!(printer.write_indent());
+    try!(writeln!(printer, "{{"));
+
+    {
+        let mut printer = printer.sub_indent(1);
+        let mut first = true;
+
+        for field in cf.fields.iter() {
+            if !first {
+                try!(writeln!(printer, ""));
+            }
+            first = false;
+
+            try!(print_field(&mut printer, field, pool));
+        }
+
+        for method in cf.methods.iter() {
+            if !first {
+                try!(writeln!(printer, ""));
+            }
+            first = false;
+
+            try!(print_method(&mut printer, method, pool, this_class));
+        }
+    }
+
+    try!(printer.write_indent());
+    try!(writeln!(printer, "}}"));
+
+    for attr in cf.attrs.iter() {
+        try!(print_attr(printer, attr, pool));
+    }
+
+    Ok(())
+}
+
+fn print_class_header<W: Write>(printer: &mut Printer<W>, cf: &Classfile, this_class: &str) -> io::Result<()> {
+    if cf.access_flags.contains(flags::AccessFlags::ACC_MODULE) {
+        if let Some(info) = cf.module() {
+            return module::print_module_header(printer, info, &cf.constant_pool);
+        }
+    }
+
+    let pool = &cf.constant_pool;
+    let is_interface = cf.access_flags.contains(flags::AccessFlags::ACC_INTERFACE);
+
+    let mut header = String::new();
+    if cf.access_flags.contains(flags::AccessFlags::ACC_PUBLIC) {
+        header.push_str("public ");
+    }
+    if cf.access_flags.contains(flags::AccessFlags::ACC_ABSTRACT) && !is_interface {
+        header.push_str("abstract ");
+    }
+    if cf.access_flags.contains(flags::AccessFlags::ACC_FINAL) {
+        header.push_str("final ");
+    }
+    header.push_str(if is_interface { "interface " } else { "class " });
+    header.push_str(&java_name(this_class));
+
+    // With a generic signature, javap always names the superclass of classes, even `java.lang.Object`,
+    // and separates interfaces with a space too.
+    let (interfaces, separator) = match signature(&cf.attrs).and_then(|info| info.class_signature(pool).ok()) {
+        Some(sig) => {
+            header.push_str(&type_params_to_java(&sig.type_params));
+            if !is_interface {
+                header.push_str(" extends ");
+                header.push_str(&sig.superclass.to_java());
+            }
+            (sig.interfaces.iter().map(ClassType::to_java).collect(), ", ")
+        }
+        None => {
+            if let Some(super_class) = cf.super_class().and_then(|class| class.name(pool)) {
+                if super_class != "java/lang/Object" {
+                    header.push_str(" extends ");
+                    header.push_str(&java_name(super_class));
+                }
+            }
+            let interfaces: Vec<String> = cf.interfaces()
+                .map(|iface| iface.and_then(|iface| iface.name(pool)).map(java_name).unwrap_or("?".to_owned()))
+                .collect();
+            (interfaces, ",")
+        }
+    };
+    if !interfaces.is_empty() {
+        header.push_str(if is_interface { " extends " } else { " implements " });
+        header.push_str(&interfaces.join(separator));
+    }
+
+    try!(printer.write_indent());
+    try!(writeln!(printer, "{}", header));
+
+    Ok(())
+}
+
+fn print_field<W: Write>(printer: &mut Printer<W>, field: &FieldInfo, pool: &ConstantPool) -> io::Result<()> {
+    use field::flags::AccessFlags;
+
+    let name = field.name(pool).unwrap_or("?");
+    let desc = field.desc(pool).unwrap_or("?");
+    let flags = field.access_flags;
+
+    let modifiers = modifiers(&[
+        (flags.contains(AccessFlags::ACC_PUBLIC), "public"),
+        (flags.contains(AccessFlags::ACC_PROTECTED), "protected"),
+        (flags.contains(AccessFlags::ACC_PRIVATE), "private"),
+        (flags.contains(AccessFlags::ACC_STATIC), "static"),
+        (flags.contains(AccessFlags::ACC_FINAL), "final"),
+        (flags.contains(AccessFlags::ACC_TRANSIENT), "transient"),
+        (flags.contains(AccessFlags::ACC_VOLATILE), "volatile"),
+    ]);
+
+    let ty = match signature(&field.attrs).and_then(|info| info.field_signature(pool).ok()) {
+        Some(sig) => sig.to_java(),
+        None => java_type(desc),
+    };
+
+    try!(printer.write_indent());
+    try!(writeln!(printer, "{}{} {};", modifiers, ty, name));
+
+    let mut printer = printer.sub_indent(1);
+
+    try!(printer.write_indent());
+    try!(writeln!(printer, "descriptor: {}", desc));
+
+    try!(print_flags(&mut printer, flags.bits(), &flags));
+
+    for attr in field.attrs.iter() {
+        try!(print_attr(&mut printer, attr, pool));
+    }
+
+    Ok(())
+}
+
+fn print_method<W: Write>(printer: &mut Printer<W>,
+                          method: &MethodInfo,
+                          pool: &ConstantPool,
+                          this_class: &str) -> io::Result<()> {
+    use method::flags::AccessFlags;
+
+    let name = method.name(pool).unwrap_or("?");
+    let desc = method.desc(pool).unwrap_or("?");
+    let descriptor = MethodDescriptor::parse(desc);
+    let flags = method.access_flags;
+
+    try!(printer.write_indent());
+    if name == "<clinit>" {
+        try!(writeln!(printer, "static {{}};"));
+    } else {
+        let modifiers = modifiers(&[
+            (flags.contains(AccessFlags::ACC_PUBLIC), "public"),
+            (flags.contains(AccessFlags::ACC_PROTECTED), "protected"),
+            (flags.contains(AccessFlags::ACC_PRIVATE), "private"),
+            (flags.contains(AccessFlags::ACC_ABSTRACT), "abstract"),
+            (flags.contains(AccessFlags::ACC_STATIC), "static"),
+            (flags.contains(AccessFlags::ACC_FINAL), "final"),
+            (flags.contains(AccessFlags::ACC_SYNCHRONIZED), "synchronized"),
+            (flags.contains(AccessFlags::ACC_NATIVE), "native"),
+            (flags.contains(AccessFlags::ACC_STRICT), "strictfp"),
+        ]);
+
+        let sig = signature(&method.attrs).and_then(|info| info.method_signature(pool).ok());
+        let (mut params, ret) = match (sig.as_ref(), descriptor.as_ref()) {
+            (Some(sig), _) => (sig.java_params(), sig.ret.to_java()),
+            (None, Ok(descriptor)) => (descriptor.java_params(), descriptor.ret.to_java()),
+            (None, Err(_)) => (vec![desc.to_owned()], String::new()),
+        };
+        let type_params = match sig {
+            Some(ref sig) if !sig.type_params.is_empty() => type_params_to_java(&sig.type_params) + " ",
+            _ => String::new(),
+        };
+        if flags.contains(AccessFlags::ACC_VARARGS) {
+            if let Some(last) = params.last_mut() {
+                if last.ends_with("[]") {
+                    let len = last.len();
+                    last.truncate(len - 2);
+                    last.push_str("...");
+                }
+            }
+        }
+
+        // Like javap, prefer the thrown types of the signature, and show their classes in internal form.
+        let mut throws = Vec::new();
+        if let Some(ref sig) = sig {
+            for ty in sig.throws.iter() {
+                throws.push(match *ty {
+                    ReferenceType::Class(ref class) => class.binary_name(),
+                    ref ty => ty.to_java(),
+                });
+            }
+        }
+        if throws.is_empty() {
+            for attr in method.attrs.iter() {
+                if let AttrInfo::Exceptions(ref info) = attr.info {
+                    for class in info.table(pool) {
+                        throws.push(class.and_then(|class| class.name(pool)).map(java_name).unwrap_or("?".to_owned()));
+                    }
+                }
+            }
+        }
+        let throws = if throws.is_empty() {
+            String::new()
+        } else {
+            format!(" throws {}", throws.join(", "))
+        };
+
+        if name == "<init>" {
+            try!(writeln!(printer, "{}{}{}({}){};", modifiers, type_params, java_name(this_class), params.join(", "), throws));
+        } else {
+            try!(writeln!(printer, "{}{}{} {}({}){};", modifiers, type_params, ret, name, params.join(", "), throws));
+        }
+    }
+
+    let mut printer = printer.sub_indent(1);
+
+    try!(printer.write_indent());
+    try!(writeln!(printer, "descriptor: {}", desc));
+
+    try!(print_flags(&mut printer, flags.bits(), &flags));
+
+    let arg_slots = descriptor.as_ref().map(MethodDescriptor::arg_slots).unwrap_or(0);
+    let args_size = arg_slots + if flags.contains(AccessFlags::ACC_STATIC) { 0 } else { 1 };
+    for attr in method.attrs.iter() {
+        match attr.info {
+            AttrInfo::Code(ref info) => try!(code::print_code(&mut printer, info, pool, this_class, args_size)),
+            _ => try!(print_attr(&mut printer, attr, pool)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Print an attribute that has no dedicated rendering in its enclosing structure.
+fn print_attr<W: Write>(printer: &mut Printer<W>, attr: &Attr, pool: &ConstantPool) -> io::Result<()> {
+    let name = attr.name(pool).unwrap_or("?");
+
+    try!(printer.write_indent());
+    match attr.info {
+        AttrInfo::SourceFile(ref info) => {
+            try!(writeln!(printer, "SourceFile: \"{}\"", info.sourcefile(pool).unwrap_or("?")))
+        }
+        AttrInfo::ConstantValue(ref info) => {
+            let value = match info.value(pool) {
+                Some(entry) => describe_entry(entry, pool, ""),
+                None => "?".to_owned(),
+            };
+            try!(writeln!(printer, "ConstantValue: {}", value))
+        }
+        AttrInfo::Signature(ref info) => {
+            try!(writeln!(printer, "{:<40}// {}", format!("Signature: #{}", info.signature_index()),
+                          info.value(pool).unwrap_or("?")))
+        }
+        AttrInfo::Deprecated(..) => try!(writeln!(printer, "Deprecated: true")),
+        AttrInfo::Syncthetic(..) => try!(writeln!(printer, "Synthetic: true")),
+        AttrInfo::InnerClasses(ref info) => {
+            use attr::info::classfile::inner_classes::flags::AccessFlags;
+
+            try!(writeln!(printer, "InnerClasses:"));
+            for class in info.classes.iter() {
+                let flags = class.inner_class_access_flags;
+                let modifiers = modifiers(&[
+                    (flags.contains(AccessFlags::ACC_PUBLIC), "public"),
+                    (flags.contains(AccessFlags::ACC_PROTECTED), "protected"),
+                    (flags.contains(AccessFlags::ACC_PRIVATE), "private"),
+                    (flags.contains(AccessFlags::ACC_ABSTRACT) &&
+                     !flags.contains(AccessFlags::ACC_INTERFACE), "abstract"),
+                    (flags.contains(AccessFlags::ACC_STATIC), "static"),
+                    (flags.contains(AccessFlags::ACC_FINAL), "final"),
+                ]);
+                let inner = class.inner_class_info(pool).and_then(|class| class.name(pool)).unwrap_or("?");
+
+                let mut printer = printer.sub_indent(1);
+                try!(printer.write_indent());
+                try!(write!(printer, "{}", modifiers));
+                if let Some(name) = class.inner_name(pool) {
+                    try!(write!(printer, "{}=", name));
+                }
+                try!(write!(printer, "class {}", inner));
+                if let Some(outer) = class.outer_class_info(pool).and_then(|class| class.name(pool)) {
+                    try!(write!(printer, " of class {}", outer));
+                }
+                try!(writeln!(printer, ";"));
+            }
+        }
+        AttrInfo::BootstrapMethods(ref info) => {
+            try!(writeln!(printer, "BootstrapMethods:"));
+            for (index, method) in info.methods().enumerate() {
+                let method_ref = match pool.get(method.method_ref_index()) {
+                    Some(entry) => describe_value(entry, pool, ""),
+                    None => "?".to_owned(),
+                };
+
+                let mut printer = printer.sub_indent(1);
+                try!(printer.write_indent());
+                try!(writeln!(printer, "{}: #{} {}", index, method.method_ref_index(), method_ref));
+
+                let mut printer = printer.sub_indent(1);
+                try!(printer.write_indent());
+                try!(writeln!(printer, "Method arguments:"));
+                for &argument in method.argument_indexes() {
+                    let value = match pool.get(argument) {
+                        Some(entry) => describe_value(entry, pool, ""),
+                        None => "?".to_owned(),
+                    };
+
+                    let mut printer = printer.sub_indent(1);
+                    try!(printer.write_indent());
+                    try!(writeln!(printer, "#{} {}", argument, value));
+                }
+            }
+        }
+        AttrInfo::Module(ref info) => try!(module::print_module(printer, info, pool)),
+        AttrInfo::ModulePackages(ref info) => try!(module::print_module_packages(printer, info, pool)),
+        AttrInfo::ModuleMainClass(ref info) => {
+            let main_class = info.main_class(pool).and_then(|class| class.name(pool)).map(java_name);
+            try!(writeln!(printer, "{:<40}// {}", format!("ModuleMainClass: #{}", info.main_class_index()),
+                          main_class.unwrap_or("?".to_owned())))
+        }
+        AttrInfo::NestHost(ref info) => {
+            let host = info.host_class(pool).and_then(|class| class.name(pool)).unwrap_or("?");
+            try!(writeln!(printer, "NestHost: class {}", host))
+        }
+        AttrInfo::NestMembers(ref info) => {
+            try!(writeln!(printer, "NestMembers:"));
+            for class in info.classes(pool) {
+                let mut printer = printer.sub_indent(1);
+                try!(printer.write_indent());
+                try!(writeln!(printer, "{}", class.and_then(|class| class.name(pool)).unwrap_or("?")));
+            }
+        }
+        AttrInfo::PermittedSubclasses(ref info) => {
+            try!(writeln!(printer, "PermittedSubclasses:"));
+            for class in info.classes(pool) {
+                let mut printer = printer.sub_indent(1);
+                try!(printer.write_indent());
+                try!(writeln!(printer, "{}", class.and_then(|class| class.name(pool)).unwrap_or("?")));
+            }
+        }
+        AttrInfo::Record(ref info) => {
+            try!(writeln!(printer, "Record:"));
+            for component in info.components.iter() {
+                let desc = component.desc(pool).unwrap_or("?");
+
+                let mut printer = printer.sub_indent(1);
+                try!(printer.write_indent());
+                let ty = match signature(&component.attrs).and_then(|info| info.field_signature(pool).ok()) {
+                    Some(sig) => sig.to_java(),
+                    None => java_type(desc),
+                };
+                try!(writeln!(printer, "{} {};", ty, component.name(pool).unwrap_or("?")));
+
+                let mut printer = printer.sub_indent(1);
+                try!(printer.write_indent());
+                try!(writeln!(printer, "descriptor: {}", desc));
+                for attr in component.attrs.iter() {
+                    try!(print_attr(&mut printer.by_ref(), attr, pool));
+                }
+                try!(writeln!(printer, ""));
+            }
+        }
+        AttrInfo::Exceptions(ref info) => {
+            try!(writeln!(printer, "Exceptions:"));
+            for class in info.table(pool) {
+                let name = class.and_then(|class| class.name(pool)).map(java_name).unwrap_or("?".to_owned());
+
+                let mut printer = printer.sub_indent(1);
+                try!(printer.write_indent());
+                try!(writeln!(printer, "throws {}", name));
+            }
+        }
+        AttrInfo::MethodParameters(ref info) => {
+            use attr::info::method::parameters::flags::AccessFlags;
+
+            try!(writeln!(printer, "MethodParameters:"));
+
+            let mut printer = printer.sub_indent(1);
+            try!(printer.write_indent());
+            try!(writeln!(printer, "Name                           Flags"));
+            for parameter in info.parameters() {
+                let flags = parameter.access_flags;
+                let modifiers = modifiers(&[
+                    (flags.contains(AccessFlags::ACC_FINAL), "final"),
+                    (flags.contains(AccessFlags::ACC_SYNTHETIC), "synthetic"),
+                    (flags.contains(AccessFlags::ACC_MANDATED), "mandated"),
+                ]);
+                let name = parameter.name(pool).unwrap_or("<no name>");
+
+                try!(printer.write_indent());
+                if modifiers.is_empty() {
+                    try!(writeln!(printer, "{}", name));
+                } else {
+                    try!(writeln!(printer, "{:<30} {}", name, modifiers.trim_end()));
+                }
+            }
+        }
+        _ => {
+            try!(writeln!(printer, "{}:", name));
+            try!(attr.info.print(&mut printer.sub_indent(1), pool));
+        }
+    }
+
+    Ok(())
+}
+
+fn print_flags<W: Write, F: Debug>(printer: &mut Printer<W>, bits: u16, flags: &F) -> io::Result<()> {
+    let names = format!("{:?}", flags);
+
+    try!(printer.write_indent());
+    if bits == 0 {
+        try!(writeln!(printer, "flags: (0x0000)"));
+    } else {
+        try!(writeln!(printer, "flags: ({:#06x}) {}", bits, names.replace(" | ", ", ")));
+    }
+
+    Ok(())
+}
+
+fn signature(attrs: &[Attr]) -> Option<&SignatureAttrInfo> {
+    attrs.iter().filter_map(|attr| match attr.info {
+        AttrInfo::Signature(ref info) => Some(info),
+        _ => None,
+    }).next()
+}
+
+fn modifiers(flags: &[(bool, &str)]) -> String {
+    let mut modifiers = String::new();
+    for &(set, name) in flags {
+        if set {
+            modifiers.push_str(name);
+            modifiers.push(' ');
+        }
+    }
+    modifiers
+}
+
+fn describe_class(pool: &ConstantPool, index: usize) -> String {
+    quote(pool.get_class_info(index).and_then(|class| class.name(pool)).unwrap_or(""))
+}
@@ -0,0 +1,122 @@
+use attr::info::classfile::{ModuleAttrInfo, ModulePackagesAttrInfo};
+use attr::info::classfile::module::flags::ModuleFlags;
+use constant::ConstantPool;
+use std::fmt::Debug;
+use std::io::{self, Write};
+use super::constant::describe_value;
+use utils::print::Printer;
+
+/// Print the `module name@version` declaration replacing the class header for `module-info`.
+pub fn print_module_header<W: Write>(printer: &mut Printer<W>,
+                                     info: &ModuleAttrInfo,
+                                     pool: &ConstantPool) -> io::Result<()> {
+    let name = info.name(pool).and_then(|module| module.name(pool)).unwrap_or("?");
+
+    try!(printer.write_indent());
+    if info.flags.contains(ModuleFlags::ACC_OPEN) {
+        try!(write!(printer, "open "));
+    }
+    try!(write!(printer, "module {}", name));
+    if let Some(version) = info.version(pool) {
+        try!(write!(printer, "@{}", version));
+    }
+    try!(writeln!(printer, ""));
+
+    Ok(())
+}
+
+pub fn print_module<W: Write>(printer: &mut Printer<W>, info: &ModuleAttrInfo, pool: &ConstantPool) -> io::Result<()> {
+    try!(writeln!(printer, "Module:"));
+
+    let mut printer = printer.sub_indent(1);
+
+    try!(print_line(&mut printer, format!("#{},{:x}", info.name_index(), info.flags.bits()),
+                    Some(value(pool, info.name_index()) + &flag_names(info.flags.bits(), &info.flags))));
+    try!(print_version(&mut printer, info.version_index(), pool));
+
+    try!(print_line(&mut printer, info.requires.len().to_string(), Some("requires".to_owned())));
+    for entry in info.requires.iter() {
+        let mut printer = printer.sub_indent(1);
+        try!(print_line(&mut printer, format!("#{},{:x}", entry.module_index(), entry.flags.bits()),
+                        Some(value(pool, entry.module_index()) + &flag_names(entry.flags.bits(), &entry.flags))));
+        try!(print_version(&mut printer, entry.version_index(), pool));
+    }
+
+    for &(kind, entries) in [("exports", &info.exports), ("opens", &info.opens)].iter() {
+        try!(print_line(&mut printer, entries.len().to_string(), Some(kind.to_owned())));
+        for entry in entries.iter() {
+            let mut comment = value(pool, entry.package_index()) + &flag_names(entry.flags.bits(), &entry.flags);
+            if !entry.to_indexes().is_empty() {
+                comment.push_str(&format!(" to ... {}", entry.to_indexes().len()));
+            }
+
+            let mut printer = printer.sub_indent(1);
+            try!(print_line(&mut printer, format!("#{},{:x}", entry.package_index(), entry.flags.bits()), Some(comment)));
+            for &index in entry.to_indexes() {
+                try!(print_line(&mut printer.sub_indent(1), format!("#{}", index),
+                                Some(format!("... to {}", value(pool, index)))));
+            }
+        }
+    }
+
+    try!(print_line(&mut printer, info.uses_indexes().len().to_string(), Some("uses".to_owned())));
+    for &index in info.uses_indexes() {
+        try!(print_line(&mut printer.sub_indent(1), format!("#{}", index), Some(value(pool, index))));
+    }
+
+    try!(print_line(&mut printer, info.provides.len().to_string(), Some("provides".to_owned())));
+    for entry in info.provides.iter() {
+        let comment = format!("{} with ... {}", value(pool, entry.class_index()), entry.with_indexes().len());
+
+        let mut printer = printer.sub_indent(1);
+        try!(print_line(&mut printer, format!("#{}", entry.class_index()), Some(comment)));
+        for &index in entry.with_indexes() {
+            try!(print_line(&mut printer.sub_indent(1), format!("#{}", index),
+                            Some(format!("... with {}", value(pool, index)))));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn print_module_packages<W: Write>(printer: &mut Printer<W>,
+                                       info: &ModulePackagesAttrInfo,
+                                       pool: &ConstantPool) -> io::Result<()> {
+    try!(writeln!(printer, "ModulePackages:"));
+
+    let mut printer = printer.sub_indent(1);
+    for &index in info.package_indexes() {
+        let name = pool.get_package_info(index).and_then(|package| package.name(pool)).unwrap_or("?");
+        try!(print_line(&mut printer, format!("#{}", index), Some(name.replace('/', "."))));
+    }
+
+    Ok(())
+}
+
+fn print_line<W: Write>(printer: &mut Printer<W>, value: String, comment: Option<String>) -> io::Result<()> {
+    try!(printer.write_indent());
+    match comment {
+        Some(comment) => writeln!(printer, "{:<40}// {}", value, comment),
+        None => writeln!(printer, "{}", value),
+    }
+}
+
+fn print_version<W: Write>(printer: &mut Printer<W>, index: usize, pool: &ConstantPool) -> io::Result<()> {
+    let version = if index != 0 { pool.get_str(index).map(|version| version.to_owned()) } else { None };
+    print_line(printer, format!("#{}", index), version)
+}
+
+fn value(pool: &ConstantPool, index: usize) -> String {
+    match pool.get(index) {
+        Some(entry) => describe_value(entry, pool, ""),
+        None => "?".to_owned(),
+    }
+}
+
+fn flag_names<F: Debug>(bits: u16, flags: &F) -> String {
+    if bits == 0 {
+        String::new()
+    } else {
+        format!(" {}", format!("{:?}", flags).replace(" | ", " "))
+    }
+}
@@ -0,0 +1,18 @@
+//! Conversion of raw descriptors to Java source syntax.
+
+use descriptor::FieldType;
+
+/// Turn an internal binary name (`java/lang/String`) into its Java form (`java.lang.String`).
+pub fn java_name(name: &str) -> String {
+    name.replace('/', ".")
+}
+
+/// Render a field descriptor (e.g. `[Ljava/lang/String;`) as a Java type (`java.lang.String[]`).
+///
+/// Malformed descriptors are returned untouched.
+pub fn java_type(desc: &str) -> String {
+    match FieldType::parse(desc) {
+        Ok(ty) => ty.to_java(),
+        Err(_) => desc.to_owned(),
+    }
+}
@@ -4,22 +4,31 @@ extern crate byteorder;
 
 #[macro_use] mod utils;
 pub mod attr;
+pub mod bytecode;
 pub mod constant;
+pub mod descriptor;
 pub mod error;
 pub mod field;
+mod javap;
 pub mod method;
+pub mod signature;
+mod validate;
 pub mod version;
+pub mod view;
 
 use attr::Attr;
-use byteorder::{ReadBytesExt, BigEndian};
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
 use constant::ConstantPool;
 use error::*;
 use field::FieldInfo;
 use method::MethodInfo;
 use version::Version;
 use std::io;
+use utils::io::Counter;
 use utils::print::Printer;
 
+pub use view::ClassfileRef;
+
 const MAGIC_VALUE: u32 = 0xCAFEBABE;
 
 #[derive(Debug)]
@@ -36,7 +45,28 @@ pub struct Classfile {
 }
 
 impl Classfile {
+    /// Read a class file. Errors are located with `ErrorKind::Malformed`, giving where in the
+    /// class file reading stopped.
     pub fn read<R: io::Read>(reader: &mut R) -> Result<Classfile> {
+        Classfile::read_with(reader, &mut Diagnostics::strict())
+    }
+
+    /// Read a class file, recovering from malformed attributes and unknown access flags.
+    /// The errors recovered from are returned along with the class.
+    pub fn read_lenient<R: io::Read>(reader: &mut R) -> Result<(Classfile, Vec<Error>)> {
+        let mut diagnostics = Diagnostics::lenient();
+        let classfile = try!(Classfile::read_with(reader, &mut diagnostics));
+        Ok((classfile, diagnostics.into_errors()))
+    }
+
+    /// Read a class file, dealing with malformed parts as `diagnostics` says.
+    pub fn read_with<R: io::Read>(reader: &mut R, diagnostics: &mut Diagnostics) -> Result<Classfile> {
+        let mut reader = Counter::new(reader);
+        let result = Classfile::read_counted(&mut reader, diagnostics);
+        result.at_offset(reader.count())
+    }
+
+    fn read_counted<R: io::Read>(reader: &mut R, diagnostics: &mut Diagnostics) -> Result<Classfile> {
         // Read magic value
         let magic = try!(reader.read_u32::<BigEndian>());
         if magic != MAGIC_VALUE {
@@ -48,14 +78,21 @@ impl Classfile {
         let major = try!(reader.read_u16::<BigEndian>());
 
         // Read constant pool
-        let constant_pool = try!(ConstantPool::read(reader));
+        let constant_pool = match ConstantPool::read(reader) {
+            Ok(constant_pool) => constant_pool,
+            Err(err) => {
+                let segment = match *err.kind() {
+                    constant::error::ErrorKind::BadEntry(index) => format!("constant_pool[{}]", index),
+                    _ => "constant_pool".to_owned(),
+                };
+                return Err(err).within(|| segment);
+            }
+        };
 
         // Read access flags
         let access_flags = try!(reader.read_u16::<BigEndian>());
-        let access_flags = match flags::AccessFlags::from_bits(access_flags) {
-            Some(flags) => flags,
-            None => bail!(ErrorKind::BadAccessFlags(access_flags)),
-        };
+        let access_flags = try!(diagnostics.access_flags(access_flags, flags::AccessFlags::from_bits,
+                                                         flags::AccessFlags::from_bits_unchecked));
 
         // Read indexes
         let this_class = try!(reader.read_u16::<BigEndian>()) as usize;
@@ -65,33 +102,32 @@ impl Classfile {
         let interfaces_count = try!(reader.read_u16::<BigEndian>()) as usize;
         let mut interfaces = Vec::with_capacity(interfaces_count);
         for _ in 0..interfaces_count {
-            let interface_index = try!(reader.read_u16::<BigEndian>()) as usize;
+            let interface_index = try!(reader.read_u16::<BigEndian>().within(|| "interfaces".to_owned())) as usize;
             interfaces.push(interface_index);
         }
 
         // Read fields
         let fields_count = try!(reader.read_u16::<BigEndian>()) as usize;
         let mut fields = Vec::with_capacity(fields_count);
-        for _ in 0..fields_count {
-            let field = try!(FieldInfo::read(reader, &constant_pool));
+        for i in 0..fields_count {
+            let field = try!(diagnostics.within(|| format!("fields[{}]", i), |diagnostics| {
+                FieldInfo::read(reader, &constant_pool, diagnostics)
+            }));
             fields.push(field);
         }
 
         // Read methods
         let methods_count = try!(reader.read_u16::<BigEndian>()) as usize;
         let mut methods = Vec::with_capacity(methods_count);
-        for _ in 0..methods_count {
-            let method = try!(MethodInfo::read(reader, &constant_pool));
+        for i in 0..methods_count {
+            let method = try!(diagnostics.within(|| format!("methods[{}]", i), |diagnostics| {
+                MethodInfo::read(reader, &constant_pool, diagnostics)
+            }));
             methods.push(method);
         }
 
         // Read attributes
-        let attrs_count = try!(reader.read_u16::<BigEndian>()) as usize;
-        let mut attrs = Vec::with_capacity(attrs_count);
-        for _ in 0..attrs_count {
-            let attr = try!(Attr::read(reader, &constant_pool));
-            attrs.push(attr);
-        }
+        let attrs = try!(attr::read_attrs(reader, &constant_pool, diagnostics));
 
         Ok(Classfile {
             version: Version::new(major, minor),
@@ -106,6 +142,48 @@ impl Classfile {
         })
     }
 
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> Result<()> {
+        // Write magic value
+        try!(writer.write_u32::<BigEndian>(MAGIC_VALUE));
+
+        // Write version
+        try!(writer.write_u16::<BigEndian>(self.version.minor));
+        try!(writer.write_u16::<BigEndian>(self.version.major));
+
+        // Write constant pool
+        try!(self.constant_pool.write(writer));
+
+        // Write access flags
+        try!(writer.write_u16::<BigEndian>(self.access_flags.bits()));
+
+        // Write indexes
+        try!(writer.write_u16::<BigEndian>(self.this_class as u16));
+        try!(writer.write_u16::<BigEndian>(self.super_class as u16));
+
+        // Write interfaces
+        try!(writer.write_u16::<BigEndian>(self.interfaces.len() as u16));
+        for &interface_index in self.interfaces.iter() {
+            try!(writer.write_u16::<BigEndian>(interface_index as u16));
+        }
+
+        // Write fields
+        try!(writer.write_u16::<BigEndian>(self.fields.len() as u16));
+        for field in self.fields.iter() {
+            try!(field.write(writer));
+        }
+
+        // Write methods
+        try!(writer.write_u16::<BigEndian>(self.methods.len() as u16));
+        for method in self.methods.iter() {
+            try!(method.write(writer));
+        }
+
+        // Write attributes
+        try!(attr::write_attrs(writer, &self.attrs));
+
+        Ok(())
+    }
+
     pub fn this_class(&self) -> Option<&constant::ConstantClassInfo> {
         self.constant_pool.get_class_info(self.this_class)
     }
@@ -118,9 +196,43 @@ impl Classfile {
         Interfaces::new(self)
     }
 
-    pub fn dump(&self) {
+    pub fn bootstrap_methods(&self) -> Option<&attr::info::classfile::BootstrapMethodsAttrInfo> {
+        self.attrs.iter().filter_map(|attr| match attr.info {
+            attr::info::AttrInfo::BootstrapMethods(ref info) => Some(info),
+            _ => None,
+        }).next()
+    }
+
+    /// The `Module` attribute of a `module-info` class.
+    pub fn module(&self) -> Option<&attr::info::classfile::ModuleAttrInfo> {
+        self.attrs.iter().filter_map(|attr| match attr.info {
+            attr::info::AttrInfo::Module(ref info) => Some(info),
+            _ => None,
+        }).next()
+    }
+
+    /// Resolve the bootstrap method of an `invokedynamic` call site through this class's
+    /// `BootstrapMethods` attribute.
+    pub fn bootstrap_method(&self, info: &constant::ConstantInvokedDynamicInfo)
+        -> Option<&attr::info::classfile::bootstrap_methods::BootstrapMethod>
+    {
+        self.bootstrap_methods().and_then(|methods| info.bootstrap_method(methods))
+    }
+
+    /// Check the class against the format checks of JVMS §4.8, returning every violation found.
+    pub fn validate(&self) -> Vec<Error> {
+        validate::validate(self)
+    }
+
+    pub fn dump(&self) -> io::Result<()> {
+        let mut printer = Printer::default();
+        self.print(&mut printer)
+    }
+
+    /// Print the class the way `javap -c -v` does.
+    pub fn disassemble(&self) -> io::Result<()> {
         let mut printer = Printer::default();
-        self.print(&mut printer).unwrap();
+        javap::print(&mut printer, self)
     }
 }
 
@@ -132,17 +244,19 @@ impl_print! {
         try!(printer.write_indent());
         try!(writeln!(printer, "Access flags: {:?}", self.access_flags));
 
-        let this_class = self.this_class().expect("Invalid class index");
+        let this_class = try!(self.this_class().ok_or_else(|| ::utils::print::invalid("Invalid class index")));
         try!(printer.write_indent());
         try!(write!(printer, "This class: "));
         try!(this_class.print(printer, &self.constant_pool));
         try!(writeln!(printer, ""));
 
-        let super_class = self.super_class().expect("Invalid class index");
-        try!(printer.write_indent());
-        try!(write!(printer, "Super class: "));
-        try!(super_class.print(printer, &self.constant_pool));
-        try!(writeln!(printer, ""));
+        // `module-info` classes have no super class.
+        if let Some(super_class) = self.super_class() {
+            try!(printer.write_indent());
+            try!(write!(printer, "Super class: "));
+            try!(super_class.print(printer, &self.constant_pool));
+            try!(writeln!(printer, ""));
+        }
 
         try!(printer.write_indent());
         try!(writeln!(printer, "Constants:"));
@@ -217,6 +331,8 @@ pub mod flags {
             const ACC_ANNOTATION  = 0x2000;
             #[doc = "Declared as an enum type."]
             const ACC_ENUM        = 0x4000;
+            #[doc = "Is a module, not a class or interface."]
+            const ACC_MODULE      = 0x8000;
         }
     }
 }
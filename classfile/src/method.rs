@@ -1,8 +1,9 @@
-use byteorder::{ReadBytesExt, BigEndian};
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
 use error::*;
-use std::io::Read;
-use super::attr::Attr;
-use super::constant::ConstantPool;
+use std::io::{Read, Write};
+use super::attr::{self, Attr};
+use super::constant::{ConstantPool, StrPool};
+use super::descriptor::MethodDescriptor;
 
 #[derive(Debug)]
 pub struct MethodInfo {
@@ -13,25 +14,18 @@ pub struct MethodInfo {
 }
 
 impl MethodInfo {
-    pub fn read<R: Read>(reader: &mut R, cp: &ConstantPool) -> Result<MethodInfo> {
+    pub fn read<R: Read>(reader: &mut R, cp: &dyn StrPool, diagnostics: &mut Diagnostics) -> Result<MethodInfo> {
         // Read access flags
         let access_flags = try!(reader.read_u16::<BigEndian>());
-        let access_flags = match flags::AccessFlags::from_bits(access_flags) {
-            Some(flags) => flags,
-            None => bail!(ErrorKind::BadAccessFlags(access_flags)),
-        };
+        let access_flags = try!(diagnostics.access_flags(access_flags, flags::AccessFlags::from_bits,
+                                                         flags::AccessFlags::from_bits_unchecked));
 
         // Read indexes
         let name_index = try!(reader.read_u16::<BigEndian>()) as usize;
         let desc_index = try!(reader.read_u16::<BigEndian>()) as usize;
 
         // Read attributes
-        let attrs_count = try!(reader.read_u16::<BigEndian>()) as usize;
-        let mut attrs = Vec::with_capacity(attrs_count);
-        for _ in 0..attrs_count {
-            let attr = try!(Attr::read(reader, cp));
-            attrs.push(attr);
-        }
+        let attrs = try!(attr::read_attrs(reader, cp, diagnostics));
 
         Ok(MethodInfo {
             access_flags: access_flags,
@@ -41,6 +35,28 @@ impl MethodInfo {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        // Write access flags
+        try!(writer.write_u16::<BigEndian>(self.access_flags.bits()));
+
+        // Write indexes
+        try!(writer.write_u16::<BigEndian>(self.name_index as u16));
+        try!(writer.write_u16::<BigEndian>(self.desc_index as u16));
+
+        // Write attributes
+        try!(attr::write_attrs(writer, &self.attrs));
+
+        Ok(())
+    }
+
+    pub fn name_index(&self) -> usize {
+        self.name_index
+    }
+
+    pub fn desc_index(&self) -> usize {
+        self.desc_index
+    }
+
     pub fn name<'a>(&self, pool: &'a ConstantPool) -> Option<&'a str> {
         pool.get_str(self.name_index)
     }
@@ -48,12 +64,17 @@ impl MethodInfo {
     pub fn desc<'a>(&self, pool: &'a ConstantPool) -> Option<&'a str> {
         pool.get_str(self.desc_index)
     }
+
+    /// Parsed descriptor of the method. A missing descriptor is reported as an empty one.
+    pub fn descriptor(&self, pool: &ConstantPool) -> Result<MethodDescriptor> {
+        MethodDescriptor::parse(self.desc(pool).unwrap_or(""))
+    }
 }
 
 impl_print! {
     MethodInfo(self, printer, constant_pool: &ConstantPool) {
-        let name = self.name(constant_pool).expect("Invalid name index");
-        let desc = self.desc(constant_pool).expect("Invalid desc index");
+        let name = try!(self.name(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid name index")));
+        let desc = try!(self.desc(constant_pool).ok_or_else(|| ::utils::print::invalid("Invalid desc index")));
 
         try!(printer.write_indent());
         try!(writeln!(printer, "Method `{}` [{}]:", name, desc));
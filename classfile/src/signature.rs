@@ -0,0 +1,632 @@
+//! Generic signatures (JVMS §4.7.9.1), as found in `Signature` attributes.
+
+use descriptor::BaseType;
+use error::*;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum JavaType {
+    Base(BaseType),
+    Reference(ReferenceType),
+}
+
+impl JavaType {
+    /// Render the type in Java source syntax, e.g. `java.util.List<T>[]`.
+    pub fn to_java(&self) -> String {
+        match *self {
+            JavaType::Base(ref base) => base.java_name().to_owned(),
+            JavaType::Reference(ref ty) => ty.to_java(),
+        }
+    }
+}
+
+impl fmt::Display for JavaType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JavaType::Base(ref base) => write!(f, "{}", base.as_char()),
+            JavaType::Reference(ref ty) => write!(f, "{}", ty),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReferenceType {
+    Class(ClassType),
+    /// Use of a type variable, by its name.
+    TypeVariable(String),
+    Array(Box<JavaType>),
+}
+
+impl ReferenceType {
+    /// Parse a field signature, e.g. `Ljava/util/List<TT;>;`.
+    pub fn parse(sig: &str) -> Result<ReferenceType> {
+        Parser::new(sig).parse_all(|parser| parser.reference_type())
+    }
+
+    pub fn to_java(&self) -> String {
+        match *self {
+            ReferenceType::Class(ref ty) => ty.to_java(),
+            ReferenceType::TypeVariable(ref name) => name.clone(),
+            ReferenceType::Array(ref component) => component.to_java() + "[]",
+        }
+    }
+}
+
+impl fmt::Display for ReferenceType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReferenceType::Class(ref ty) => write!(f, "{}", ty),
+            ReferenceType::TypeVariable(ref name) => write!(f, "T{};", name),
+            ReferenceType::Array(ref component) => write!(f, "[{}", component),
+        }
+    }
+}
+
+/// Class or interface type, possibly parameterized, e.g. `java/util/Map<TK;TV;>.Entry`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClassType {
+    /// Package in internal form (e.g. `java/util`), empty for the unnamed package.
+    pub package: String,
+    /// The outermost class followed by its nested member classes, each with its own type arguments.
+    pub classes: Vec<SimpleClassType>,
+}
+
+impl ClassType {
+    /// Binary name of the class in internal form, e.g. `java/util/Map$Entry`.
+    pub fn binary_name(&self) -> String {
+        let mut name = self.package.clone();
+        if !name.is_empty() {
+            name.push('/');
+        }
+
+        let classes: Vec<&str> = self.classes.iter().map(|class| &class.name[..]).collect();
+        name.push_str(&classes.join("$"));
+        name
+    }
+
+    pub fn to_java(&self) -> String {
+        let mut java = self.package.replace('/', ".");
+        if !java.is_empty() {
+            java.push('.');
+        }
+
+        let classes: Vec<String> = self.classes.iter().map(SimpleClassType::to_java).collect();
+        java.push_str(&classes.join("."));
+        java
+    }
+}
+
+impl fmt::Display for ClassType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "L"));
+        if !self.package.is_empty() {
+            try!(write!(f, "{}/", self.package));
+        }
+        for (i, class) in self.classes.iter().enumerate() {
+            if i > 0 {
+                try!(write!(f, "."));
+            }
+            try!(write!(f, "{}", class));
+        }
+        write!(f, ";")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SimpleClassType {
+    pub name: String,
+    pub args: Vec<TypeArgument>,
+}
+
+impl SimpleClassType {
+    pub fn to_java(&self) -> String {
+        if self.args.is_empty() {
+            self.name.clone()
+        } else {
+            let args: Vec<String> = self.args.iter().map(TypeArgument::to_java).collect();
+            format!("{}<{}>", self.name, args.join(", "))
+        }
+    }
+}
+
+impl fmt::Display for SimpleClassType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "{}", self.name));
+        if !self.args.is_empty() {
+            try!(write!(f, "<"));
+            for arg in self.args.iter() {
+                try!(write!(f, "{}", arg));
+            }
+            try!(write!(f, ">"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeArgument {
+    /// Unbounded wildcard `?`.
+    Any,
+    Exact(ReferenceType),
+    /// Wildcard with an upper bound, `? extends T`.
+    Extends(ReferenceType),
+    /// Wildcard with a lower bound, `? super T`.
+    Super(ReferenceType),
+}
+
+impl TypeArgument {
+    pub fn to_java(&self) -> String {
+        match *self {
+            TypeArgument::Any => "?".to_owned(),
+            TypeArgument::Exact(ref ty) => ty.to_java(),
+            TypeArgument::Extends(ref ty) => format!("? extends {}", ty.to_java()),
+            TypeArgument::Super(ref ty) => format!("? super {}", ty.to_java()),
+        }
+    }
+}
+
+impl fmt::Display for TypeArgument {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TypeArgument::Any => write!(f, "*"),
+            TypeArgument::Exact(ref ty) => write!(f, "{}", ty),
+            TypeArgument::Extends(ref ty) => write!(f, "+{}", ty),
+            TypeArgument::Super(ref ty) => write!(f, "-{}", ty),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TypeParameter {
+    pub name: String,
+    pub class_bound: Option<ReferenceType>,
+    pub interface_bounds: Vec<ReferenceType>,
+}
+
+impl TypeParameter {
+    /// Render the parameter declaration, e.g. `K extends java.lang.Comparable<K> & java.io.Serializable`.
+    pub fn to_java(&self) -> String {
+        let bounds: Vec<String> = self.class_bound.iter()
+            .chain(self.interface_bounds.iter())
+            .map(ReferenceType::to_java)
+            .collect();
+
+        if bounds.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} extends {}", self.name, bounds.join(" & "))
+        }
+    }
+}
+
+impl fmt::Display for TypeParameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "{}:", self.name));
+        if let Some(ref bound) = self.class_bound {
+            try!(write!(f, "{}", bound));
+        }
+        for bound in self.interface_bounds.iter() {
+            try!(write!(f, ":{}", bound));
+        }
+        Ok(())
+    }
+}
+
+/// Render type parameters in Java source syntax, e.g. `<T extends java.lang.Object>`.
+/// Returns an empty string if there are none.
+pub fn type_params_to_java(params: &[TypeParameter]) -> String {
+    if params.is_empty() {
+        String::new()
+    } else {
+        let params: Vec<String> = params.iter().map(TypeParameter::to_java).collect();
+        format!("<{}>", params.join(", "))
+    }
+}
+
+fn write_type_params(f: &mut fmt::Formatter, params: &[TypeParameter]) -> fmt::Result {
+    if !params.is_empty() {
+        try!(write!(f, "<"));
+        for param in params.iter() {
+            try!(write!(f, "{}", param));
+        }
+        try!(write!(f, ">"));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClassSignature {
+    pub type_params: Vec<TypeParameter>,
+    pub superclass: ClassType,
+    pub interfaces: Vec<ClassType>,
+}
+
+impl ClassSignature {
+    /// Parse a class signature, e.g. `<T:Ljava/lang/Object;>Ljava/lang/Object;Ljava/lang/Comparable<TT;>;`.
+    pub fn parse(sig: &str) -> Result<ClassSignature> {
+        Parser::new(sig).parse_all(|parser| {
+            let type_params = parser.type_params()?;
+            let superclass = parser.class_type()?;
+            let mut interfaces = Vec::new();
+            while !parser.is_empty() {
+                interfaces.push(parser.class_type()?);
+            }
+
+            Some(ClassSignature {
+                type_params: type_params,
+                superclass: superclass,
+                interfaces: interfaces,
+            })
+        })
+    }
+}
+
+impl fmt::Display for ClassSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write_type_params(f, &self.type_params));
+        try!(write!(f, "{}", self.superclass));
+        for interface in self.interfaces.iter() {
+            try!(write!(f, "{}", interface));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReturnType {
+    Void,
+    Type(JavaType),
+}
+
+impl ReturnType {
+    pub fn to_java(&self) -> String {
+        match *self {
+            ReturnType::Void => "void".to_owned(),
+            ReturnType::Type(ref ty) => ty.to_java(),
+        }
+    }
+}
+
+impl fmt::Display for ReturnType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReturnType::Void => write!(f, "V"),
+            ReturnType::Type(ref ty) => write!(f, "{}", ty),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MethodSignature {
+    pub type_params: Vec<TypeParameter>,
+    pub params: Vec<JavaType>,
+    pub ret: ReturnType,
+    /// Thrown types, either class types or type variables.
+    pub throws: Vec<ReferenceType>,
+}
+
+impl MethodSignature {
+    /// Parse a method signature, e.g. `<E:Ljava/lang/Exception;>(Ljava/util/List<TE;>;)V^TE;`.
+    pub fn parse(sig: &str) -> Result<MethodSignature> {
+        Parser::new(sig).parse_all(|parser| {
+            let type_params = parser.type_params()?;
+
+            parser.expect(b'(')?;
+            let mut params = Vec::new();
+            while !parser.eat(b')') {
+                params.push(parser.java_type()?);
+            }
+
+            let ret = if parser.eat(b'V') {
+                ReturnType::Void
+            } else {
+                ReturnType::Type(parser.java_type()?)
+            };
+
+            let mut throws = Vec::new();
+            while parser.eat(b'^') {
+                match parser.reference_type()? {
+                    ReferenceType::Array(..) => return None,
+                    ty => throws.push(ty),
+                }
+            }
+
+            Some(MethodSignature {
+                type_params: type_params,
+                params: params,
+                ret: ret,
+                throws: throws,
+            })
+        })
+    }
+
+    pub fn java_params(&self) -> Vec<String> {
+        self.params.iter().map(JavaType::to_java).collect()
+    }
+
+    /// Render a declaration of a method named `name` in Java source syntax,
+    /// e.g. `<E extends java.lang.Exception> void run(java.util.List<E>) throws E`.
+    pub fn to_java(&self, name: &str) -> String {
+        let mut java = type_params_to_java(&self.type_params);
+        if !java.is_empty() {
+            java.push(' ');
+        }
+
+        java.push_str(&format!("{} {}({})", self.ret.to_java(), name, self.java_params().join(", ")));
+        if !self.throws.is_empty() {
+            let throws: Vec<String> = self.throws.iter().map(ReferenceType::to_java).collect();
+            java.push_str(" throws ");
+            java.push_str(&throws.join(", "));
+        }
+        java
+    }
+}
+
+impl fmt::Display for MethodSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write_type_params(f, &self.type_params));
+        try!(write!(f, "("));
+        for param in self.params.iter() {
+            try!(write!(f, "{}", param));
+        }
+        try!(write!(f, "){}", self.ret));
+        for ty in self.throws.iter() {
+            try!(write!(f, "^{}", ty));
+        }
+        Ok(())
+    }
+}
+
+/// Recursive descent parser over the bytes of a signature. Every method returns `None`
+/// on malformed input, which is then reported as a `BadSignature` error.
+struct Parser<'a> {
+    sig: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(sig: &'a str) -> Parser<'a> {
+        Parser {
+            sig: sig,
+            pos: 0,
+        }
+    }
+
+    /// Run `parse` and check that it consumed the whole signature.
+    fn parse_all<T, F>(mut self, parse: F) -> Result<T>
+        where F: FnOnce(&mut Parser<'a>) -> Option<T>
+    {
+        match parse(&mut self) {
+            Some(value) if self.is_empty() => Ok(value),
+            _ => bail!(ErrorKind::BadSignature(self.sig.to_owned())),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.sig.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.sig.as_bytes().get(self.pos).cloned()
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, b: u8) -> Option<()> {
+        if self.eat(b) { Some(()) } else { None }
+    }
+
+    fn identifier(&mut self) -> Option<&'a str> {
+        let start = self.pos;
+        while let Some(b) = self.peek() {
+            match b {
+                b'.' | b';' | b'[' | b'/' | b'<' | b'>' | b':' => break,
+                _ => self.pos += 1,
+            }
+        }
+
+        if self.pos > start {
+            Some(&self.sig[start..self.pos])
+        } else {
+            None
+        }
+    }
+
+    fn type_params(&mut self) -> Option<Vec<TypeParameter>> {
+        let mut params = Vec::new();
+        if !self.eat(b'<') {
+            return Some(params);
+        }
+
+        while !self.eat(b'>') {
+            let name = self.identifier()?;
+            self.expect(b':')?;
+
+            let class_bound = match self.peek() {
+                Some(b'L') | Some(b'T') | Some(b'[') => Some(self.reference_type()?),
+                _ => None,
+            };
+            let mut interface_bounds = Vec::new();
+            while self.eat(b':') {
+                interface_bounds.push(self.reference_type()?);
+            }
+
+            params.push(TypeParameter {
+                name: name.to_owned(),
+                class_bound: class_bound,
+                interface_bounds: interface_bounds,
+            });
+        }
+
+        if params.is_empty() { None } else { Some(params) }
+    }
+
+    fn java_type(&mut self) -> Option<JavaType> {
+        let base = self.peek().and_then(|b| BaseType::from_char(b as char));
+        match base {
+            Some(base) => {
+                self.pos += 1;
+                Some(JavaType::Base(base))
+            }
+            None => self.reference_type().map(JavaType::Reference),
+        }
+    }
+
+    fn reference_type(&mut self) -> Option<ReferenceType> {
+        match self.peek()? {
+            b'L' => self.class_type().map(ReferenceType::Class),
+            b'T' => {
+                self.pos += 1;
+                let name = self.identifier()?;
+                self.expect(b';')?;
+                Some(ReferenceType::TypeVariable(name.to_owned()))
+            }
+            b'[' => {
+                self.pos += 1;
+                let component = self.java_type()?;
+                Some(ReferenceType::Array(Box::new(component)))
+            }
+            _ => None,
+        }
+    }
+
+    fn class_type(&mut self) -> Option<ClassType> {
+        self.expect(b'L')?;
+
+        // The package is made of all the identifiers followed by a `/`.
+        let mut package = String::new();
+        let mut name = self.identifier()?;
+        while self.eat(b'/') {
+            if !package.is_empty() {
+                package.push('/');
+            }
+            package.push_str(name);
+            name = self.identifier()?;
+        }
+
+        let mut classes = vec![self.simple_class_type(name)?];
+        while self.eat(b'.') {
+            let name = self.identifier()?;
+            classes.push(self.simple_class_type(name)?);
+        }
+        self.expect(b';')?;
+
+        Some(ClassType {
+            package: package,
+            classes: classes,
+        })
+    }
+
+    fn simple_class_type(&mut self, name: &str) -> Option<SimpleClassType> {
+        let mut args = Vec::new();
+        if self.eat(b'<') {
+            while !self.eat(b'>') {
+                let arg = if self.eat(b'*') {
+                    TypeArgument::Any
+                } else if self.eat(b'+') {
+                    TypeArgument::Extends(self.reference_type()?)
+                } else if self.eat(b'-') {
+                    TypeArgument::Super(self.reference_type()?)
+                } else {
+                    TypeArgument::Exact(self.reference_type()?)
+                };
+                args.push(arg);
+            }
+
+            if args.is_empty() {
+                return None;
+            }
+        }
+
+        Some(SimpleClassType {
+            name: name.to_owned(),
+            args: args,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interface_bounds() {
+        let sig = "<T::Ljava/lang/Comparable<TT;>;K:Ljava/lang/Number;:Ljava/io/Serializable;>Ljava/lang/Object;";
+        let class = ClassSignature::parse(sig).unwrap();
+        assert_eq!(class.to_string(), sig);
+        assert_eq!(class.type_params[0].class_bound, None);
+        assert_eq!(class.type_params[0].interface_bounds, [ReferenceType::parse("Ljava/lang/Comparable<TT;>;").unwrap()]);
+        assert_eq!(type_params_to_java(&class.type_params),
+                   "<T extends java.lang.Comparable<T>, K extends java.lang.Number & java.io.Serializable>");
+        assert_eq!(class.superclass.binary_name(), "java/lang/Object");
+    }
+
+    #[test]
+    fn wildcards() {
+        let sig = "Ljava/util/Map<+Ljava/lang/Number;-TT;>;";
+        let ty = ReferenceType::parse(sig).unwrap();
+        assert_eq!(ty.to_string(), sig);
+        assert_eq!(ty.to_java(), "java.util.Map<? extends java.lang.Number, ? super T>");
+
+        let ty = ReferenceType::parse("[Ljava/util/List<*>;").unwrap();
+        match ty {
+            ReferenceType::Array(ref component) => match **component {
+                JavaType::Reference(ReferenceType::Class(ref class)) => assert_eq!(class.classes[0].args, [TypeArgument::Any]),
+                ref component => panic!("Unexpected component: {}", component),
+            },
+            ref ty => panic!("Unexpected type: {}", ty),
+        }
+        assert_eq!(ty.to_java(), "java.util.List<?>[]");
+    }
+
+    #[test]
+    fn inner_classes() {
+        let sig = "Lp/Outer<TT;>.Inner<TU;>;";
+        let class = match ReferenceType::parse(sig).unwrap() {
+            ReferenceType::Class(class) => class,
+            ty => panic!("Unexpected type: {}", ty),
+        };
+        assert_eq!(class.package, "p");
+        assert_eq!(class.classes.len(), 2);
+        assert_eq!(class.classes[1].args, [TypeArgument::Exact(ReferenceType::TypeVariable("U".to_owned()))]);
+        assert_eq!(class.binary_name(), "p/Outer$Inner");
+        assert_eq!(class.to_java(), "p.Outer<T>.Inner<U>");
+        assert_eq!(class.to_string(), sig);
+
+        // Unnamed package
+        let class = ClassSignature::parse("LOuter.Inner;").unwrap().superclass;
+        assert_eq!(class.binary_name(), "Outer$Inner");
+    }
+
+    #[test]
+    fn throws() {
+        let sig = "<E:Ljava/lang/Exception;>(Ljava/util/List<TE;>;[IJ)V^TE;^Ljava/io/IOException;";
+        let method = MethodSignature::parse(sig).unwrap();
+        assert_eq!(method.to_string(), sig);
+        assert_eq!(method.throws.len(), 2);
+        assert_eq!(method.to_java("run"),
+                   "<E extends java.lang.Exception> void run(java.util.List<E>, int[], long) throws E, java.io.IOException");
+        assert_eq!(MethodSignature::parse("()TT;").unwrap().to_java("get"), "T get()");
+    }
+
+    #[test]
+    fn malformed_signatures() {
+        for sig in ["", "I", "Ljava/util/List", "Ljava/util/List<>;", "Ljava/util/List<TT>;", "TT", "Lfoo;;"].iter() {
+            assert!(ReferenceType::parse(sig).is_err(), "{}", sig);
+        }
+        for sig in ["<>Ljava/lang/Object;", "<T>Ljava/lang/Object;", "Ljava/lang/Object;I"].iter() {
+            assert!(ClassSignature::parse(sig).is_err(), "{}", sig);
+        }
+        for sig in ["(I", "()", "()VV", "(V)V", "()V^[Ljava/lang/Exception;", "()V^"].iter() {
+            assert!(MethodSignature::parse(sig).is_err(), "{}", sig);
+        }
+        assert_eq!(ReferenceType::parse("TT").unwrap_err().to_string(), "Bad signature: `TT`");
+    }
+}
@@ -1,13 +1,44 @@
-use std::io::Read;
+use std::io::{self, Read};
 use std::io::Result;
-use utils::vec;
 
 pub trait ReadExt: Read {
-    fn read_vec(&mut self, size: usize) -> Result<Vec<u8>> {
-        let mut data = unsafe { vec::uninitialized(size) };
-        try!(self.read_exact(&mut data));
+    /// Read exactly `size` bytes. The buffer grows as data comes in, so a bogus size in a
+    /// malformed class file doesn't allocate more than what's actually there.
+    fn read_vec(&mut self, size: usize) -> Result<Vec<u8>> where Self: Sized {
+        let mut data = Vec::new();
+        try!(self.by_ref().take(size as u64).read_to_end(&mut data));
+        if data.len() < size {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer"));
+        }
         Ok(data)
     }
 }
 
 impl<T: Read> ReadExt for T {}
+
+/// Reader keeping track of the number of bytes read through it.
+pub struct Counter<R> {
+    inner: R,
+    count: usize,
+}
+
+impl<R: Read> Counter<R> {
+    pub fn new(inner: R) -> Counter<R> {
+        Counter {
+            inner: inner,
+            count: 0,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl<R: Read> Read for Counter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let size = try!(self.inner.read(buf));
+        self.count += size;
+        Ok(size)
+    }
+}
@@ -22,3 +22,15 @@ macro_rules! impl_read {
         }
     };
 }
+
+macro_rules! impl_write {
+    ($name:ident($selfname:ident, $writer:ident) -> $result:ty = $body:block) => {
+        impl $name {
+            #[allow(unused_imports)]
+            pub fn write<W: ::std::io::Write>(&$selfname, $writer: &mut W) -> $result {
+                use byteorder::{WriteBytesExt, BigEndian};
+                $body
+            }
+        }
+    };
+}
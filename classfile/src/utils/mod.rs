@@ -1,4 +1,3 @@
 #[macro_use] pub mod macros;
 pub mod io;
-pub mod vec;
 #[macro_use] pub mod print;
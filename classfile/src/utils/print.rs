@@ -106,3 +106,9 @@ macro_rules! impl_print {
         }
     };
 }
+
+/// Error for constant pool references that don't resolve, so that malformed classes can be
+/// printed without panicking.
+pub fn invalid(what: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, what)
+}
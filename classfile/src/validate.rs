@@ -0,0 +1,697 @@
+//! Format checks of class files (JVMS §4.8).
+
+use attr::Attr;
+use attr::info::AttrInfo;
+use attr::info::method::CodeAttrInfo;
+use constant::{ConstantPool, ConstantPoolEntry, ConstantNameAndTypeInfo};
+use descriptor::{self, BaseType, FieldType, MethodDescriptor, ReturnType};
+use error::*;
+use field::FieldInfo;
+use method::{self, MethodInfo};
+use std::collections::HashSet;
+use super::{flags, Classfile};
+use version::Version;
+
+/// Where an attribute is found, to check it's allowed there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Owner {
+    Class,
+    Field,
+    Method,
+    Code,
+    RecordComponent,
+}
+
+/// Check `class` and return every violation found, located like parse errors.
+pub fn validate(class: &Classfile) -> Vec<Error> {
+    let mut validator = Validator {
+        class: class,
+        pool: &class.constant_pool,
+        errors: Vec::new(),
+    };
+
+    validator.constant_pool();
+    validator.class();
+
+    let mut fields = HashSet::new();
+    for (i, field) in class.fields.iter().enumerate() {
+        let path = format!("fields[{}]", i);
+        validator.field(&path, field);
+        if let (Some(name), Some(desc)) = (field.name(validator.pool), field.desc(validator.pool)) {
+            if !fields.insert((name, desc)) {
+                validator.report(path, ErrorKind::DuplicateMember(name.to_owned(), desc.to_owned()));
+            }
+        }
+    }
+
+    let mut methods = HashSet::new();
+    for (i, method) in class.methods.iter().enumerate() {
+        let path = format!("methods[{}]", i);
+        validator.method(&path, method);
+        if let (Some(name), Some(desc)) = (method.name(validator.pool), method.desc(validator.pool)) {
+            if !methods.insert((name, desc)) {
+                validator.report(path, ErrorKind::DuplicateMember(name.to_owned(), desc.to_owned()));
+            }
+        }
+    }
+
+    validator.attrs("", &class.attrs, Owner::Class);
+
+    validator.errors
+}
+
+struct Validator<'a> {
+    class: &'a Classfile,
+    pool: &'a ConstantPool,
+    errors: Vec<Error>,
+}
+
+impl<'a> Validator<'a> {
+    fn report<E: Into<Error>>(&mut self, path: String, err: E) {
+        self.errors.push(err.into().within(path));
+    }
+
+    fn is_interface(&self) -> bool {
+        self.class.access_flags.contains(flags::AccessFlags::ACC_INTERFACE)
+    }
+
+    fn version(&self) -> &'a Version {
+        &self.class.version
+    }
+
+    /// Check that `index` points at a `Utf8` constant, and return its value.
+    fn utf8(&mut self, path: &str, index: usize) -> Option<&'a str> {
+        let value = self.pool.get_str(index);
+        if value.is_none() {
+            self.report(path.to_owned(), ErrorKind::BadConstantIndex(index, "Utf8"));
+        }
+        value
+    }
+
+    /// Check that `index` points at a `Class` constant, and return the class name.
+    fn class_ref(&mut self, path: &str, index: usize) -> Option<&'a str> {
+        match self.pool.get_class_info(index) {
+            Some(info) => info.name(self.pool),
+            None => {
+                self.report(path.to_owned(), ErrorKind::BadConstantIndex(index, "Class"));
+                None
+            }
+        }
+    }
+
+    /// Check that `index` points at a `NameAndType` constant. Its own indexes are checked
+    /// along with the rest of the constant pool.
+    fn name_and_type(&mut self, path: &str, index: usize) -> Option<(&'a str, &'a str)> {
+        match self.pool.get(index) {
+            Some(ConstantPoolEntry::NameAndType(info)) => name_and_type(info, self.pool),
+            _ => {
+                self.report(path.to_owned(), ErrorKind::BadConstantIndex(index, "NameAndType"));
+                None
+            }
+        }
+    }
+
+    fn field_descriptor(&mut self, path: &str, desc: &str) -> Option<FieldType> {
+        match FieldType::parse(desc) {
+            Ok(ty) => Some(ty),
+            Err(err) => {
+                self.report(path.to_owned(), err);
+                None
+            }
+        }
+    }
+
+    fn method_descriptor(&mut self, path: &str, desc: &str) -> Option<MethodDescriptor> {
+        match MethodDescriptor::parse(desc) {
+            Ok(desc) => Some(desc),
+            Err(err) => {
+                self.report(path.to_owned(), err);
+                None
+            }
+        }
+    }
+
+    fn constant_pool(&mut self) {
+        let is_module = self.class.access_flags.contains(flags::AccessFlags::ACC_MODULE);
+        let bootstrap_methods = self.class.bootstrap_methods().map_or(0, |info| info.methods().count());
+
+        for (index, entry) in self.pool.entries() {
+            let path = format!("constant_pool[{}]", index);
+            match *entry {
+                ConstantPoolEntry::Class(ref info) => {
+                    if let Some(name) = self.utf8(&path, info.name_index()) {
+                        if !is_class_name(name) {
+                            self.report(path, ErrorKind::BadName(name.to_owned()));
+                        }
+                    }
+                }
+                ConstantPoolEntry::FieldRef(ref info) => {
+                    self.class_ref(&path, info.class_index());
+                    if let Some((name, desc)) = self.name_and_type(&path, info.name_and_type_index()) {
+                        if !is_unqualified_name(name) {
+                            self.report(path.clone(), ErrorKind::BadName(name.to_owned()));
+                        }
+                        self.field_descriptor(&path, desc);
+                    }
+                }
+                ConstantPoolEntry::MethodRef(ref info) => {
+                    self.class_ref(&path, info.class_index());
+                    self.method_ref(&path, info.name_and_type_index());
+                }
+                ConstantPoolEntry::InterfaceMethodRef(ref info) => {
+                    self.class_ref(&path, info.class_index());
+                    self.method_ref(&path, info.name_and_type_index());
+                }
+                ConstantPoolEntry::String(ref info) => {
+                    self.utf8(&path, info.string_index());
+                }
+                ConstantPoolEntry::NameAndType(ref info) => {
+                    self.utf8(&path, info.name_index());
+                    self.utf8(&path, info.desc_index());
+                }
+                ConstantPoolEntry::MethodHandle(ref info) => self.method_handle(&path, info.ref_kind(), info.ref_index()),
+                ConstantPoolEntry::MethodType(ref info) => {
+                    if let Some(desc) = self.utf8(&path, info.desc_index()) {
+                        self.method_descriptor(&path, desc);
+                    }
+                }
+                ConstantPoolEntry::Dynamic(ref info) => {
+                    if info.bootstrap_method_attr_index() >= bootstrap_methods {
+                        self.report(path.clone(), ErrorKind::FormatViolation("Bootstrap method index out of range"));
+                    }
+                    if let Some((name, desc)) = self.name_and_type(&path, info.name_and_type_index()) {
+                        if !is_unqualified_name(name) {
+                            self.report(path.clone(), ErrorKind::BadName(name.to_owned()));
+                        }
+                        self.field_descriptor(&path, desc);
+                    }
+                }
+                ConstantPoolEntry::InvokedDynamic(ref info) => {
+                    if info.bootstrap_method_attr_index() >= bootstrap_methods {
+                        self.report(path.clone(), ErrorKind::FormatViolation("Bootstrap method index out of range"));
+                    }
+                    if let Some((name, desc)) = self.name_and_type(&path, info.name_and_type_index()) {
+                        if !is_method_name(name) {
+                            self.report(path.clone(), ErrorKind::BadName(name.to_owned()));
+                        }
+                        self.method_descriptor(&path, desc);
+                    }
+                }
+                ConstantPoolEntry::Module(ref info) => {
+                    if !is_module {
+                        self.report(path.clone(), ErrorKind::FormatViolation("Module constant outside of a module"));
+                    }
+                    self.utf8(&path, info.name_index());
+                }
+                ConstantPoolEntry::Package(ref info) => {
+                    if !is_module {
+                        self.report(path.clone(), ErrorKind::FormatViolation("Package constant outside of a module"));
+                    }
+                    if let Some(name) = self.utf8(&path, info.name_index()) {
+                        if !descriptor::is_binary_name(name) {
+                            self.report(path, ErrorKind::BadName(name.to_owned()));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Check the name and type of a `Methodref` or `InterfaceMethodref` (JVMS §4.4.2).
+    fn method_ref(&mut self, path: &str, name_and_type_index: usize) {
+        if let Some((name, desc)) = self.name_and_type(path, name_and_type_index) {
+            if name != "<init>" && !is_method_name(name) {
+                self.report(path.to_owned(), ErrorKind::BadName(name.to_owned()));
+            }
+            if let Some(desc) = self.method_descriptor(path, desc) {
+                if name == "<init>" && desc.ret != ReturnType::Void {
+                    self.report(path.to_owned(), ErrorKind::FormatViolation("Instance initializer must return void"));
+                }
+            }
+        }
+    }
+
+    /// Check a `MethodHandle` against its reference kind (JVMS §4.4.8).
+    fn method_handle(&mut self, path: &str, kind: u8, index: usize) {
+        let entry = self.pool.get(index);
+        let name = entry.and_then(|entry| ref_name(entry, self.pool));
+        let valid = match (kind, entry) {
+            (1..=4, Some(&ConstantPoolEntry::FieldRef(..))) => true,
+            (5, Some(&ConstantPoolEntry::MethodRef(..))) | (8, Some(&ConstantPoolEntry::MethodRef(..))) => true,
+            (6, Some(&ConstantPoolEntry::MethodRef(..))) | (7, Some(&ConstantPoolEntry::MethodRef(..))) => true,
+            (6, Some(&ConstantPoolEntry::InterfaceMethodRef(..))) |
+            (7, Some(&ConstantPoolEntry::InterfaceMethodRef(..))) => self.version().allows_interface_method_refs(),
+            (9, Some(&ConstantPoolEntry::InterfaceMethodRef(..))) => true,
+            (1..=9, _) => false,
+            _ => {
+                self.report(path.to_owned(), ErrorKind::FormatViolation("Bad method handle reference kind"));
+                return;
+            }
+        };
+
+        if !valid {
+            let expected = match kind {
+                1..=4 => "Fieldref",
+                5 | 8 => "Methodref",
+                9 => "InterfaceMethodref",
+                _ => "Methodref or InterfaceMethodref",
+            };
+            self.report(path.to_owned(), ErrorKind::BadConstantIndex(index, expected));
+        } else if kind == 8 && name != Some("<init>") {
+            self.report(path.to_owned(), ErrorKind::FormatViolation("REF_newInvokeSpecial must reference <init>"));
+        } else if kind != 8 && (name == Some("<init>") || name == Some("<clinit>")) {
+            self.report(path.to_owned(), ErrorKind::FormatViolation("Method handle references an initializer"));
+        }
+    }
+
+    /// Check the class access flags, and `this_class`, `super_class` and `interfaces` (JVMS §4.1).
+    fn class(&mut self) {
+        use flags::AccessFlags;
+
+        let access_flags = self.class.access_flags;
+        let violation = if access_flags.contains(AccessFlags::ACC_MODULE) {
+            if access_flags != AccessFlags::ACC_MODULE {
+                Some("ACC_MODULE set along with other flags")
+            } else {
+                None
+            }
+        } else if access_flags.contains(AccessFlags::ACC_INTERFACE) {
+            if !access_flags.contains(AccessFlags::ACC_ABSTRACT) {
+                Some("Interface not abstract")
+            } else if access_flags.intersects(AccessFlags::ACC_FINAL | AccessFlags::ACC_SUPER | AccessFlags::ACC_ENUM) {
+                Some("Interface with ACC_FINAL, ACC_SUPER or ACC_ENUM set")
+            } else {
+                None
+            }
+        } else if access_flags.contains(AccessFlags::ACC_ANNOTATION) {
+            Some("Annotation type not an interface")
+        } else if access_flags.contains(AccessFlags::ACC_FINAL | AccessFlags::ACC_ABSTRACT) {
+            Some("Class both final and abstract")
+        } else {
+            None
+        };
+        if let Some(reason) = violation {
+            self.report("access_flags".to_owned(), ErrorKind::IllegalAccessFlags(access_flags.bits(), reason));
+        }
+
+        let this_class = self.class_ref("this_class", self.class.this_class);
+        if self.class.super_class == 0 {
+            if this_class != Some("java/lang/Object") && !access_flags.contains(AccessFlags::ACC_MODULE) {
+                self.report("super_class".to_owned(), ErrorKind::BadConstantIndex(0, "Class"));
+            }
+        } else if let Some(super_class) = self.class_ref("super_class", self.class.super_class) {
+            if self.is_interface() && super_class != "java/lang/Object" {
+                self.report("super_class".to_owned(), ErrorKind::FormatViolation("Interface superclass must be java/lang/Object"));
+            }
+        }
+
+        for (i, &index) in self.class.interfaces.iter().enumerate() {
+            self.class_ref(&format!("interfaces[{}]", i), index);
+        }
+    }
+
+    /// Check a field (JVMS §4.5).
+    fn field(&mut self, path: &str, field: &FieldInfo) {
+        use field::flags::AccessFlags;
+
+        let access_flags = field.access_flags;
+        let visibility = AccessFlags::ACC_PUBLIC | AccessFlags::ACC_PRIVATE | AccessFlags::ACC_PROTECTED;
+        let violation = if (access_flags & visibility).bits().count_ones() > 1 {
+            Some("More than one of ACC_PUBLIC, ACC_PRIVATE and ACC_PROTECTED set")
+        } else if access_flags.contains(AccessFlags::ACC_FINAL | AccessFlags::ACC_VOLATILE) {
+            Some("Field both final and volatile")
+        } else if self.is_interface() {
+            let required = AccessFlags::ACC_PUBLIC | AccessFlags::ACC_STATIC | AccessFlags::ACC_FINAL;
+            if !access_flags.contains(required) || !(required | AccessFlags::ACC_SYNTHETIC).contains(access_flags) {
+                Some("Interface field not public static final")
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+        if let Some(reason) = violation {
+            self.report(format!("{}.access_flags", path), ErrorKind::IllegalAccessFlags(access_flags.bits(), reason));
+        }
+
+        if let Some(name) = self.utf8(&format!("{}.name", path), field.name_index()) {
+            if !is_unqualified_name(name) {
+                self.report(format!("{}.name", path), ErrorKind::BadName(name.to_owned()));
+            }
+        }
+        let desc_path = format!("{}.descriptor", path);
+        let ty = self.utf8(&desc_path, field.desc_index()).and_then(|desc| self.field_descriptor(&desc_path, desc));
+
+        let attrs_path = format!("{}.", path);
+        self.attrs(&attrs_path, &field.attrs, Owner::Field);
+
+        let values: Vec<_> = field.attrs.iter().filter_map(|attr| match attr.info {
+            AttrInfo::ConstantValue(ref info) => Some(info),
+            _ => None,
+        }).collect();
+        if values.len() > 1 {
+            self.report(format!("{}attrs[ConstantValue]", attrs_path), ErrorKind::UnexpectedAttr("ConstantValue".to_owned()));
+        }
+        if let (Some(value), Some(ty)) = (values.first(), ty) {
+            let matches = match (value.value(self.pool), ty) {
+                (Some(&ConstantPoolEntry::Integer(..)), FieldType::Base(base)) => {
+                    base != BaseType::Long && base != BaseType::Float && base != BaseType::Double
+                }
+                (Some(&ConstantPoolEntry::Long(..)), FieldType::Base(BaseType::Long)) => true,
+                (Some(&ConstantPoolEntry::Float(..)), FieldType::Base(BaseType::Float)) => true,
+                (Some(&ConstantPoolEntry::Double(..)), FieldType::Base(BaseType::Double)) => true,
+                (Some(&ConstantPoolEntry::String(..)), FieldType::Object(ref name)) => name == "java/lang/String",
+                _ => false,
+            };
+            if !matches {
+                self.report(format!("{}attrs[ConstantValue]", attrs_path),
+                            ErrorKind::FormatViolation("Constant value doesn't match the field type"));
+            }
+        }
+    }
+
+    /// Check a method (JVMS §4.6).
+    fn method(&mut self, path: &str, method: &MethodInfo) {
+        use method::flags::AccessFlags;
+
+        let name = self.utf8(&format!("{}.name", path), method.name_index());
+        if let Some(name) = name {
+            if name != "<init>" && name != "<clinit>" && !is_method_name(name) {
+                self.report(format!("{}.name", path), ErrorKind::BadName(name.to_owned()));
+            }
+        }
+        let desc_path = format!("{}.descriptor", path);
+        let desc = self.utf8(&desc_path, method.desc_index()).and_then(|desc| self.method_descriptor(&desc_path, desc));
+        if let Some(ref desc) = desc {
+            if (name == Some("<init>") || name == Some("<clinit>")) && desc.ret != ReturnType::Void {
+                self.report(desc_path.clone(), ErrorKind::FormatViolation("Initializer must return void"));
+            }
+        }
+
+        let access_flags = method.access_flags;
+        let is_clinit = name == Some("<clinit>") && self.version().requires_static_clinit();
+        if is_clinit {
+            // Other flags are ignored
+            if !access_flags.contains(AccessFlags::ACC_STATIC) {
+                self.report(format!("{}.access_flags", path),
+                            ErrorKind::IllegalAccessFlags(access_flags.bits(), "Class initializer not static"));
+            }
+            if desc.map_or(0, |desc| desc.params.len()) > 0 {
+                self.report(desc_path, ErrorKind::FormatViolation("Class initializer takes arguments"));
+            }
+        } else if let Some(reason) = self.method_flags_violation(access_flags, name == Some("<init>")) {
+            self.report(format!("{}.access_flags", path), ErrorKind::IllegalAccessFlags(access_flags.bits(), reason));
+        }
+
+        let attrs_path = format!("{}.", path);
+        self.attrs(&attrs_path, &method.attrs, Owner::Method);
+
+        let code: Vec<_> = method.attrs.iter().filter_map(|attr| match attr.info {
+            AttrInfo::Code(ref info) => Some(info),
+            _ => None,
+        }).collect();
+        let has_body = !access_flags.intersects(AccessFlags::ACC_ABSTRACT | AccessFlags::ACC_NATIVE);
+        if !has_body && !code.is_empty() || code.len() > 1 {
+            self.report(format!("{}attrs[Code]", attrs_path), ErrorKind::UnexpectedAttr("Code".to_owned()));
+        } else if has_body && code.is_empty() {
+            self.report(path.to_owned(), ErrorKind::MissingAttr("Code"));
+        }
+        if let Some(code) = code.first() {
+            self.code(&format!("{}attrs[Code]", attrs_path), code);
+        }
+    }
+
+    fn method_flags_violation(&self, access_flags: method::flags::AccessFlags, is_init: bool) -> Option<&'static str> {
+        use method::flags::AccessFlags;
+
+        let visibility = AccessFlags::ACC_PUBLIC | AccessFlags::ACC_PRIVATE | AccessFlags::ACC_PROTECTED;
+        if (access_flags & visibility).bits().count_ones() > 1 {
+            return Some("More than one of ACC_PUBLIC, ACC_PRIVATE and ACC_PROTECTED set");
+        }
+
+        if self.is_interface() {
+            if is_init {
+                return Some("Instance initializer in an interface");
+            }
+            if !self.version().allows_interface_method_bodies() {
+                let others = AccessFlags::ACC_VARARGS | AccessFlags::ACC_BRIDGE | AccessFlags::ACC_SYNTHETIC;
+                let required = AccessFlags::ACC_PUBLIC | AccessFlags::ACC_ABSTRACT;
+                if !access_flags.contains(required) || !(required | others).contains(access_flags) {
+                    return Some("Interface method not public abstract");
+                }
+            } else {
+                let forbidden = AccessFlags::ACC_PROTECTED | AccessFlags::ACC_FINAL |
+                                AccessFlags::ACC_SYNCHRONIZED | AccessFlags::ACC_NATIVE;
+                if access_flags.intersects(forbidden) {
+                    return Some("Interface method protected, final, synchronized or native");
+                }
+                if !access_flags.intersects(AccessFlags::ACC_PUBLIC | AccessFlags::ACC_PRIVATE) {
+                    return Some("Interface method neither public nor private");
+                }
+            }
+        }
+
+        if access_flags.contains(AccessFlags::ACC_ABSTRACT) {
+            let forbidden = AccessFlags::ACC_PRIVATE | AccessFlags::ACC_STATIC | AccessFlags::ACC_FINAL |
+                            AccessFlags::ACC_SYNCHRONIZED | AccessFlags::ACC_NATIVE;
+            if access_flags.intersects(forbidden) {
+                return Some("Abstract method private, static, final, synchronized or native");
+            }
+        }
+
+        if is_init {
+            let allowed = AccessFlags::ACC_PUBLIC | AccessFlags::ACC_PRIVATE | AccessFlags::ACC_PROTECTED |
+                          AccessFlags::ACC_VARARGS | AccessFlags::ACC_STRICT | AccessFlags::ACC_SYNTHETIC;
+            if !allowed.contains(access_flags) {
+                return Some("Instance initializer with flags other than visibility, varargs, strict or synthetic");
+            }
+        }
+
+        None
+    }
+
+    /// Check the exception table and the attributes of a `Code` attribute (JVMS §4.7.3).
+    fn code(&mut self, path: &str, code: &CodeAttrInfo) {
+        if code.code.is_empty() {
+            self.report(path.to_owned(), ErrorKind::FormatViolation("Empty code"));
+        }
+
+        for (i, handler) in code.exception_handlers.iter().enumerate() {
+            let handler_path = format!("{}.exception_table[{}]", path, i);
+            if handler.start_pc >= handler.end_pc || handler.end_pc > code.code.len() {
+                self.report(handler_path.clone(), ErrorKind::FormatViolation("Bad exception handler range"));
+            }
+            if handler.handler_pc >= code.code.len() {
+                self.report(handler_path.clone(), ErrorKind::FormatViolation("Exception handler out of code"));
+            }
+            if handler.catch_type_index() != 0 {
+                self.class_ref(&handler_path, handler.catch_type_index());
+            }
+        }
+
+        self.attrs(&format!("{}.", path), &code.attrs, Owner::Code);
+    }
+
+    /// Check that the attributes are allowed where they are, and their references to the constant pool.
+    /// `prefix` is the path of the owner of the attributes, followed by a dot.
+    fn attrs(&mut self, prefix: &str, attrs: &[Attr], owner: Owner) {
+        for attr in attrs {
+            let name = attr.name(self.pool).unwrap_or("?");
+            let path = format!("{}attrs[{}]", prefix, name);
+
+            let allowed = match attr.info {
+                AttrInfo::SourceFile(..) | AttrInfo::InnerClasses(..) | AttrInfo::EnclosingMethod(..) |
+                AttrInfo::SourceDebugExtension(..) | AttrInfo::BootstrapMethods(..) | AttrInfo::Module(..) |
+                AttrInfo::ModulePackages(..) | AttrInfo::ModuleMainClass(..) | AttrInfo::NestHost(..) |
+                AttrInfo::NestMembers(..) | AttrInfo::PermittedSubclasses(..) | AttrInfo::Record(..) => owner == Owner::Class,
+                AttrInfo::ConstantValue(..) => owner == Owner::Field,
+                AttrInfo::Code(..) | AttrInfo::Exceptions(..) | AttrInfo::RuntimeVisibleParameterAnnotations(..) |
+                AttrInfo::RuntimeInvisibleParameterAnnotations(..) | AttrInfo::AnnotationDefault(..) |
+                AttrInfo::MethodParameters(..) => owner == Owner::Method,
+                AttrInfo::LineNumberTable(..) | AttrInfo::LocalVariableTable(..) |
+                AttrInfo::LocalVariableTypeTable(..) | AttrInfo::StackMapTable(..) => owner == Owner::Code,
+                AttrInfo::Syncthetic(..) | AttrInfo::Deprecated(..) => {
+                    owner != Owner::Code && owner != Owner::RecordComponent
+                }
+                AttrInfo::Signature(..) | AttrInfo::RuntimeVisibleAnnotations(..) |
+                AttrInfo::RuntimeInvisibleAnnotations(..) => owner != Owner::Code,
+                AttrInfo::RuntimeVisibleTypeAnnotations(..) | AttrInfo::RuntimeInvisibleTypeAnnotations(..) |
+                AttrInfo::Unknown(..) => true,
+            };
+            if !allowed {
+                self.report(path, ErrorKind::UnexpectedAttr(name.to_owned()));
+                continue;
+            }
+
+            match attr.info {
+                AttrInfo::SourceFile(ref info) if info.sourcefile(self.pool).is_none() => {
+                    self.report(path, ErrorKind::BadConstantIndex(info.sourcefile_index(), "Utf8"));
+                }
+                AttrInfo::Signature(ref info) => {
+                    let result = match owner {
+                        Owner::Class => info.class_signature(self.pool).map(|_| ()),
+                        Owner::Method => info.method_signature(self.pool).map(|_| ()),
+                        _ => info.field_signature(self.pool).map(|_| ()),
+                    };
+                    if let Err(err) = result {
+                        self.report(path, err);
+                    }
+                }
+                AttrInfo::Exceptions(ref info) => {
+                    for (i, &index) in info.indexes().iter().enumerate() {
+                        self.class_ref(&format!("{}.exceptions[{}]", path, i), index);
+                    }
+                }
+                AttrInfo::Record(ref info) => {
+                    for (i, component) in info.components.iter().enumerate() {
+                        let component_path = format!("{}.components[{}]", path, i);
+                        if let Some(name) = self.utf8(&format!("{}.name", component_path), component.name_index()) {
+                            if !is_unqualified_name(name) {
+                                self.report(format!("{}.name", component_path), ErrorKind::BadName(name.to_owned()));
+                            }
+                        }
+                        let desc_path = format!("{}.descriptor", component_path);
+                        if let Some(desc) = self.utf8(&desc_path, component.desc_index()) {
+                            self.field_descriptor(&desc_path, desc);
+                        }
+                        self.attrs(&format!("{}.", component_path), &component.attrs, Owner::RecordComponent);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn name_and_type<'a>(info: &ConstantNameAndTypeInfo, pool: &'a ConstantPool) -> Option<(&'a str, &'a str)> {
+    match (info.name(pool), info.desc(pool)) {
+        (Some(name), Some(desc)) => Some((name, desc)),
+        _ => None,
+    }
+}
+
+/// Name of the member referenced by a `Fieldref`, `Methodref` or `InterfaceMethodref` constant.
+fn ref_name<'a>(entry: &ConstantPoolEntry, pool: &'a ConstantPool) -> Option<&'a str> {
+    let name_and_type = match *entry {
+        ConstantPoolEntry::FieldRef(ref info) => info.name_and_type(pool),
+        ConstantPoolEntry::MethodRef(ref info) => info.name_and_type(pool),
+        ConstantPoolEntry::InterfaceMethodRef(ref info) => info.name_and_type(pool),
+        _ => None,
+    };
+    name_and_type.and_then(|info| info.name(pool))
+}
+
+/// Whether `name` is a valid unqualified name, for fields and local variables (JVMS §4.2.2).
+fn is_unqualified_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(&['.', ';', '[', '/'][..])
+}
+
+/// Whether `name` is a valid method name, other than the initializers (JVMS §4.2.2).
+fn is_method_name(name: &str) -> bool {
+    is_unqualified_name(name) && !name.contains(&['<', '>'][..])
+}
+
+/// Whether `name` is a valid name for a `Class` constant: a binary name, or an array type (JVMS §4.4.1).
+fn is_class_name(name: &str) -> bool {
+    if name.starts_with('[') {
+        FieldType::parse(name).is_ok()
+    } else {
+        descriptor::is_binary_name(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use super::super::Classfile;
+
+    /// Entries of the constant pool of every fixture, from #1, followed by those of the test.
+    const POOL: &[&[u8]] = &[
+        b"\x01\x00\x01A",                   // #1 Utf8 A
+        b"\x07\x00\x01",                    // #2 Class #1
+        b"\x01\x00\x10java/lang/Object",    // #3 Utf8 java/lang/Object
+        b"\x07\x00\x03",                    // #4 Class #3
+        b"\x01\x00\x01m",                   // #5 Utf8 m
+        b"\x01\x00\x03()V",                 // #6 Utf8 ()V
+        b"\x01\x00\x04Code",                // #7 Utf8 Code
+        b"\x01\x00\x0dConstantValue",       // #8 Utf8 ConstantValue
+        b"\x03\x00\x00\x00\x01",            // #9 Integer 1
+    ];
+
+    /// `Code` attribute of a method made of a single `return`.
+    const CODE: &[u8] = b"\x00\x07\x00\x00\x00\x0d\x00\x00\x00\x01\x00\x00\x00\x01\xb1\x00\x00\x00\x00";
+
+    /// Class `A` with the given flags, constant pool entries from #10, and a method `m()V` with the
+    /// given flags and attributes.
+    fn class(access_flags: u16, pool: &[&[u8]], method_flags: u16, method_attrs: &[&[u8]]) -> Classfile {
+        let mut data = b"\xca\xfe\xba\xbe\x00\x00\x00\x34".to_vec();
+        data.extend(&((POOL.len() + pool.len() + 1) as u16).to_be_bytes());
+        for entry in POOL.iter().chain(pool) {
+            data.extend(*entry);
+        }
+        data.extend(&access_flags.to_be_bytes());
+        data.extend(b"\x00\x02\x00\x04\x00\x00\x00\x00\x00\x01");
+        data.extend(&method_flags.to_be_bytes());
+        data.extend(b"\x00\x05\x00\x06");
+        data.extend(&(method_attrs.len() as u16).to_be_bytes());
+        for attr in method_attrs {
+            data.extend(*attr);
+        }
+        data.extend(b"\x00\x00");
+
+        Classfile::read(&mut Cursor::new(data)).unwrap()
+    }
+
+    /// Path and description of the violations found in `class`.
+    fn violations(class: &Classfile) -> Vec<(String, String)> {
+        class.validate().iter().map(|err| {
+            let cause = err.iter().nth(1).map(|cause| cause.to_string()).unwrap_or_default();
+            (err.path().unwrap_or("").to_owned(), cause)
+        }).collect()
+    }
+
+    fn violation(path: &str, description: &str) -> Vec<(String, String)> {
+        vec![(path.to_owned(), description.to_owned())]
+    }
+
+    #[test]
+    fn valid_class() {
+        assert_eq!(violations(&class(0x0021, &[], 0x0001, &[CODE])), []);
+    }
+
+    #[test]
+    fn bad_constant_kind() {
+        // A `Class` naming an `Integer`, and a `REF_getField` handle to a method
+        let pool: &[&[u8]] = &[b"\x07\x00\x09", b"\x0c\x00\x05\x00\x06", b"\x0a\x00\x04\x00\x0b",
+                               b"\x0f\x01\x00\x0c"];
+        assert_eq!(violations(&class(0x0021, pool, 0x0001, &[CODE])), [
+            ("constant_pool[10]".to_owned(), "Bad constant pool index #9: expected Utf8".to_owned()),
+            ("constant_pool[13]".to_owned(), "Bad constant pool index #12: expected Fieldref".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn illegal_flags() {
+        assert_eq!(violations(&class(0x0431, &[], 0x0001, &[CODE])),
+                   violation("access_flags", "Illegal access flags 0x431: Class both final and abstract"));
+        assert_eq!(violations(&class(0x0021, &[], 0x0003, &[CODE])),
+                   violation("methods[0].access_flags",
+                             "Illegal access flags 0x3: More than one of ACC_PUBLIC, ACC_PRIVATE and ACC_PROTECTED set"));
+    }
+
+    #[test]
+    fn code_of_abstract_method() {
+        assert_eq!(violations(&class(0x0421, &[], 0x0401, &[CODE])),
+                   violation("methods[0].attrs[Code]", "Unexpected `Code` attribute"));
+        assert_eq!(violations(&class(0x0021, &[], 0x0001, &[])),
+                   violation("methods[0]", "Missing `Code` attribute"));
+    }
+
+    #[test]
+    fn misplaced_attribute() {
+        let value: &[u8] = b"\x00\x08\x00\x00\x00\x02\x00\x09";
+        assert_eq!(violations(&class(0x0021, &[], 0x0001, &[CODE, value])),
+                   violation("methods[0].attrs[ConstantValue]", "Unexpected `ConstantValue` attribute"));
+    }
+}
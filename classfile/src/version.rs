@@ -13,6 +13,28 @@ impl Version {
             minor: minor,
         }
     }
+
+    /// Whether methods are verified by type checking against their `StackMapTable` frames,
+    /// rather than by type inference (JVMS §4.10).
+    pub fn uses_type_checking(&self) -> bool {
+        self.major >= 50
+    }
+
+    /// Whether `<clinit>` must be static and take no arguments to be the class initializer (JVMS §2.9.2).
+    pub fn requires_static_clinit(&self) -> bool {
+        self.major >= 51
+    }
+
+    /// Whether `invokespecial`, `invokestatic` and their method handles may reference interface methods
+    /// (JVMS §4.4.8, §4.9.1).
+    pub fn allows_interface_method_refs(&self) -> bool {
+        self.major >= 52
+    }
+
+    /// Whether interface methods may be non-abstract: default, static or private (JVMS §4.6).
+    pub fn allows_interface_method_bodies(&self) -> bool {
+        self.major >= 52
+    }
 }
 
 impl fmt::Display for Version {
@@ -0,0 +1,485 @@
+//! Lazy, zero-copy view over the bytes of a class file.
+//!
+//! `ClassfileRef::parse` only walks the class file once to find where the constant pool
+//! entries, members and attributes are. Strings are borrowed from the input whenever their
+//! modified UTF-8 encoding is also valid UTF-8, and attributes are decoded on demand into the
+//! types used by `Classfile`.
+
+use attr::Attr;
+use byteorder::{ReadBytesExt, BigEndian};
+use constant::{self, ConstantPool, ConstantPoolEntry, StrPool, mutf8};
+use constant::tag;
+use error::*;
+use field::FieldInfo;
+use method::MethodInfo;
+use std::borrow::Cow;
+use std::io::{self, Cursor};
+use std::str;
+use super::{Classfile, MAGIC_VALUE};
+use super::flags::AccessFlags;
+use version::Version;
+
+#[derive(Debug)]
+pub struct ClassfileRef<'a> {
+    data: &'a [u8],
+    pub version: Version,
+    constant_pool: ConstantPoolRef<'a>,
+    pub access_flags: AccessFlags,
+    this_class: usize,
+    super_class: usize,
+    interfaces: &'a [u8],
+    fields: Vec<MemberRef<'a>>,
+    methods: Vec<MemberRef<'a>>,
+    attrs: Vec<AttrRef<'a>>,
+}
+
+impl<'a> ClassfileRef<'a> {
+    /// Index the class file. As with `Classfile::read`, errors are located with
+    /// `ErrorKind::Malformed`.
+    pub fn parse(data: &'a [u8]) -> Result<ClassfileRef<'a>> {
+        let mut reader = Cursor::new(data);
+        let result = ClassfileRef::parse_cursor(&mut reader);
+        result.at_offset(reader.position() as usize)
+    }
+
+    fn parse_cursor(reader: &mut Cursor<&'a [u8]>) -> Result<ClassfileRef<'a>> {
+        let data = *reader.get_ref();
+
+        // Read magic value
+        let magic = try!(reader.read_u32::<BigEndian>());
+        if magic != MAGIC_VALUE {
+            bail!(ErrorKind::BadMagicValue(magic));
+        }
+
+        // Read version
+        let minor = try!(reader.read_u16::<BigEndian>());
+        let major = try!(reader.read_u16::<BigEndian>());
+
+        // Index constant pool
+        let constant_pool = try!(ConstantPoolRef::parse(reader).within(|| "constant_pool".to_owned()));
+
+        // Read access flags
+        let access_flags = try!(reader.read_u16::<BigEndian>());
+        let access_flags = match AccessFlags::from_bits(access_flags) {
+            Some(flags) => flags,
+            None => return Err(ErrorKind::BadAccessFlags(access_flags)).within(|| "access_flags".to_owned()),
+        };
+
+        // Read indexes
+        let this_class = try!(reader.read_u16::<BigEndian>()) as usize;
+        let super_class = try!(reader.read_u16::<BigEndian>()) as usize;
+
+        // Borrow interfaces
+        let interfaces_count = try!(reader.read_u16::<BigEndian>()) as usize;
+        let interfaces = try!(take(reader, interfaces_count * 2).within(|| "interfaces".to_owned()));
+
+        // Index members and attributes
+        let fields = try!(MemberRef::parse_table(reader, "fields"));
+        let methods = try!(MemberRef::parse_table(reader, "methods"));
+        let attrs = try!(AttrRef::parse_table(reader));
+
+        Ok(ClassfileRef {
+            data: data,
+            version: Version::new(major, minor),
+            constant_pool: constant_pool,
+            access_flags: access_flags,
+            this_class: this_class,
+            super_class: super_class,
+            interfaces: interfaces,
+            fields: fields,
+            methods: methods,
+            attrs: attrs,
+        })
+    }
+
+    /// Fully decode the class file.
+    pub fn decode(&self) -> Result<Classfile> {
+        Classfile::read(&mut Cursor::new(self.data))
+    }
+
+    pub fn constant_pool(&self) -> &ConstantPoolRef<'a> {
+        &self.constant_pool
+    }
+
+    pub fn this_class(&self) -> Option<Cow<'a, str>> {
+        self.constant_pool.get_class_name(self.this_class)
+    }
+
+    /// Name of the super class, `None` for `java/lang/Object` and `module-info`.
+    pub fn super_class(&self) -> Option<Cow<'a, str>> {
+        self.constant_pool.get_class_name(self.super_class)
+    }
+
+    pub fn interfaces<'b>(&'b self) -> Interfaces<'b, 'a> {
+        Interfaces {
+            iter: self.interfaces.chunks(2),
+            constant_pool: &self.constant_pool,
+        }
+    }
+
+    pub fn fields(&self) -> &[MemberRef<'a>] {
+        &self.fields
+    }
+
+    pub fn methods(&self) -> &[MemberRef<'a>] {
+        &self.methods
+    }
+
+    pub fn attrs(&self) -> &[AttrRef<'a>] {
+        &self.attrs
+    }
+
+    /// Find the first class attribute named `name`.
+    pub fn attr(&self, name: &str) -> Option<&AttrRef<'a>> {
+        find_attr(&self.attrs, name, &self.constant_pool)
+    }
+}
+
+/// Iterator over the names of the interfaces of a class.
+pub struct Interfaces<'b, 'a: 'b> {
+    iter: ::std::slice::Chunks<'a, u8>,
+    constant_pool: &'b ConstantPoolRef<'a>,
+}
+
+impl<'b, 'a> Iterator for Interfaces<'b, 'a> {
+    type Item = Option<Cow<'a, str>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|index| {
+            let index = (index[0] as usize) << 8 | index[1] as usize;
+            self.constant_pool.get_class_name(index)
+        })
+    }
+}
+
+/// Constant pool indexed by the offset of each entry.
+#[derive(Debug)]
+pub struct ConstantPoolRef<'a> {
+    data: &'a [u8],
+    /// Offset of the tag of each entry in `data`, `None` for the slots following long and double entries.
+    offsets: Vec<Option<usize>>,
+}
+
+impl<'a> ConstantPoolRef<'a> {
+    fn parse(reader: &mut Cursor<&'a [u8]>) -> Result<ConstantPoolRef<'a>> {
+        let data = *reader.get_ref();
+        let start = reader.position() as usize;
+
+        let entries_count = try!(reader.read_u16::<BigEndian>()) as usize;
+        let mut offsets = Vec::with_capacity(entries_count.saturating_sub(1));
+
+        let mut index = 1;
+        while index < entries_count {
+            let offset = reader.position() as usize - start;
+            let tag = try!(reader.read_u8());
+            let size = match tag {
+                tag::CONSTANT_UTF8 => try!(reader.read_u16::<BigEndian>()) as usize,
+                tag::CONSTANT_CLASS | tag::CONSTANT_STRING | tag::CONSTANT_METHODTYPE |
+                tag::CONSTANT_MODULE | tag::CONSTANT_PACKAGE => 2,
+                tag::CONSTANT_METHODHANDLE => 3,
+                tag::CONSTANT_INTEGER | tag::CONSTANT_FLOAT | tag::CONSTANT_FIELDREF |
+                tag::CONSTANT_METHODREF | tag::CONSTANT_INTERFACEMETHODREF |
+                tag::CONSTANT_NAMEANDTYPE | tag::CONSTANT_DYNAMIC | tag::CONSTANT_INVOKEDYNAMIC => 4,
+                tag::CONSTANT_LONG | tag::CONSTANT_DOUBLE => 8,
+                _ => bail!(ErrorKind::ConstantPool(constant::error::ErrorKind::BadTagValue(tag))),
+            };
+            try!(take(reader, size));
+
+            offsets.push(Some(offset));
+            index += 1;
+
+            if tag == tag::CONSTANT_LONG || tag == tag::CONSTANT_DOUBLE {
+                offsets.push(None);
+                index += 1;
+            }
+        }
+
+        let end = reader.position() as usize;
+        Ok(ConstantPoolRef {
+            data: &data[start..end],
+            offsets: offsets,
+        })
+    }
+
+    /// Fully decode the constant pool.
+    pub fn decode(&self) -> Result<ConstantPool> {
+        Ok(try!(ConstantPool::read(&mut Cursor::new(self.data))))
+    }
+
+    /// Number of slots in the pool, including the unusable ones following long and double entries.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Bytes of the entry at `index`, starting with its tag.
+    fn entry_data(&self, index: usize) -> Option<&'a [u8]> {
+        let data = self.data;
+        index.checked_sub(1)
+            .and_then(|index| self.offsets.get(index))
+            .and_then(|&offset| offset)
+            .map(|offset| &data[offset..])
+    }
+
+    pub fn tag(&self, index: usize) -> Option<u8> {
+        self.entry_data(index).map(|data| data[0])
+    }
+
+    /// Decode the entry at `index`. Returns `None` for invalid indexes and malformed entries.
+    pub fn get(&self, index: usize) -> Option<ConstantPoolEntry> {
+        self.entry_data(index).and_then(|data| ConstantPoolEntry::read(&mut Cursor::new(data)).ok())
+    }
+
+    /// Raw modified UTF-8 bytes of the `Utf8` entry at `index`.
+    pub fn get_bytes(&self, index: usize) -> Option<&'a [u8]> {
+        self.entry_data(index).and_then(|data| {
+            if data[0] == tag::CONSTANT_UTF8 {
+                let len = (data[1] as usize) << 8 | data[2] as usize;
+                Some(&data[3..3 + len])
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Value of the `Utf8` entry at `index`, borrowed from the class file unless the string
+    /// contains characters encoded differently in modified UTF-8 (`\0` and supplementary characters).
+    pub fn get_str(&self, index: usize) -> Option<Cow<'a, str>> {
+        self.get_bytes(index).and_then(|bytes| match str::from_utf8(bytes) {
+            Ok(value) => Some(Cow::Borrowed(value)),
+            Err(_) => mutf8::decode(bytes).ok().map(Cow::Owned),
+        })
+    }
+
+    /// Index of the `Utf8` entry referenced by the `Class`, `String`, `MethodType`, `Module` or
+    /// `Package` entry at `index`.
+    fn get_ref_index(&self, index: usize, expected_tag: u8) -> Option<usize> {
+        self.entry_data(index).and_then(|data| {
+            if data[0] == expected_tag {
+                Some((data[1] as usize) << 8 | data[2] as usize)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn get_class_name(&self, index: usize) -> Option<Cow<'a, str>> {
+        self.get_ref_index(index, tag::CONSTANT_CLASS).and_then(|index| self.get_str(index))
+    }
+
+    pub fn get_string(&self, index: usize) -> Option<Cow<'a, str>> {
+        self.get_ref_index(index, tag::CONSTANT_STRING).and_then(|index| self.get_str(index))
+    }
+
+    /// Whether the `Utf8` entry at `index` is `value`. The entry is only decoded if its bytes differ,
+    /// in case `value` has characters encoded differently in modified UTF-8.
+    pub fn str_eq(&self, index: usize, value: &str) -> bool {
+        match self.get_bytes(index) {
+            Some(bytes) => bytes == value.as_bytes() || self.get_str(index).as_ref().map(|s| &s[..]) == Some(value),
+            None => false,
+        }
+    }
+}
+
+impl<'a> StrPool for ConstantPoolRef<'a> {
+    fn get_utf8(&self, index: usize) -> Option<Cow<'_, str>> {
+        self.get_str(index)
+    }
+}
+
+/// Field or method, with its attributes.
+#[derive(Debug)]
+pub struct MemberRef<'a> {
+    data: &'a [u8],
+    /// Offset of `data` in the class file.
+    offset: usize,
+    access_flags: u16,
+    name_index: usize,
+    desc_index: usize,
+    attrs: Vec<AttrRef<'a>>,
+}
+
+impl<'a> MemberRef<'a> {
+    fn parse_table(reader: &mut Cursor<&'a [u8]>, table: &str) -> Result<Vec<MemberRef<'a>>> {
+        let count = try!(reader.read_u16::<BigEndian>()) as usize;
+        let mut members = Vec::with_capacity(count);
+        for i in 0..count {
+            let member = try!(MemberRef::parse(reader).within(|| format!("{}[{}]", table, i)));
+            members.push(member);
+        }
+
+        Ok(members)
+    }
+
+    fn parse(reader: &mut Cursor<&'a [u8]>) -> Result<MemberRef<'a>> {
+        let start = reader.position() as usize;
+
+        let access_flags = try!(reader.read_u16::<BigEndian>());
+        let name_index = try!(reader.read_u16::<BigEndian>()) as usize;
+        let desc_index = try!(reader.read_u16::<BigEndian>()) as usize;
+        let attrs = try!(AttrRef::parse_table(reader));
+
+        let end = reader.position() as usize;
+        Ok(MemberRef {
+            data: &reader.get_ref()[start..end],
+            offset: start,
+            access_flags: access_flags,
+            name_index: name_index,
+            desc_index: desc_index,
+            attrs: attrs,
+        })
+    }
+
+    /// Raw access flags, see `field::flags` and `method::flags`.
+    pub fn access_flags(&self) -> u16 {
+        self.access_flags
+    }
+
+    pub fn name(&self, pool: &ConstantPoolRef<'a>) -> Option<Cow<'a, str>> {
+        pool.get_str(self.name_index)
+    }
+
+    pub fn desc(&self, pool: &ConstantPoolRef<'a>) -> Option<Cow<'a, str>> {
+        pool.get_str(self.desc_index)
+    }
+
+    pub fn attrs(&self) -> &[AttrRef<'a>] {
+        &self.attrs
+    }
+
+    /// Find the first attribute of the member named `name`.
+    pub fn attr(&self, name: &str, pool: &ConstantPoolRef<'a>) -> Option<&AttrRef<'a>> {
+        find_attr(&self.attrs, name, pool)
+    }
+
+    /// Decode the field, resolving names through `pool`, which needn't be decoded first.
+    pub fn decode_field(&self, pool: &dyn StrPool) -> Result<FieldInfo> {
+        let mut reader = Cursor::new(self.data);
+        let result = FieldInfo::read(&mut reader, pool, &mut Diagnostics::strict());
+        result.at_offset(self.offset + reader.position() as usize)
+    }
+
+    /// Decode the method, resolving names through `pool`, which needn't be decoded first.
+    pub fn decode_method(&self, pool: &dyn StrPool) -> Result<MethodInfo> {
+        let mut reader = Cursor::new(self.data);
+        let result = MethodInfo::read(&mut reader, pool, &mut Diagnostics::strict());
+        result.at_offset(self.offset + reader.position() as usize)
+    }
+}
+
+/// Undecoded attribute.
+#[derive(Debug)]
+pub struct AttrRef<'a> {
+    data: &'a [u8],
+    /// Offset of `data` in the class file.
+    offset: usize,
+    name_index: usize,
+}
+
+impl<'a> AttrRef<'a> {
+    fn parse_table(reader: &mut Cursor<&'a [u8]>) -> Result<Vec<AttrRef<'a>>> {
+        let count = try!(reader.read_u16::<BigEndian>()) as usize;
+        let mut attrs = Vec::with_capacity(count);
+        for _ in 0..count {
+            let start = reader.position() as usize;
+            let name_index = try!(reader.read_u16::<BigEndian>()) as usize;
+            let size = try!(reader.read_u32::<BigEndian>()) as usize;
+            try!(take(reader, size));
+            let end = reader.position() as usize;
+
+            attrs.push(AttrRef {
+                data: &reader.get_ref()[start..end],
+                offset: start,
+                name_index: name_index,
+            });
+        }
+
+        Ok(attrs)
+    }
+
+    pub fn name(&self, pool: &ConstantPoolRef<'a>) -> Option<Cow<'a, str>> {
+        pool.get_str(self.name_index)
+    }
+
+    pub fn name_index(&self) -> usize {
+        self.name_index
+    }
+
+    /// Raw content of the attribute, without its name and size.
+    pub fn info(&self) -> &'a [u8] {
+        &self.data[6..]
+    }
+
+    /// Decode the attribute, resolving names through `pool`, which needn't be decoded first.
+    pub fn decode(&self, pool: &dyn StrPool) -> Result<Attr> {
+        let mut reader = Cursor::new(self.data);
+        let result = Attr::read(&mut reader, pool, &mut Diagnostics::strict());
+        result.at_offset(self.offset + reader.position() as usize)
+    }
+}
+
+fn find_attr<'b, 'a>(attrs: &'b [AttrRef<'a>], name: &str, pool: &ConstantPoolRef<'a>) -> Option<&'b AttrRef<'a>> {
+    attrs.iter().find(|attr| pool.str_eq(attr.name_index, name))
+}
+
+/// Borrow the next `size` bytes of the reader.
+fn take<'a>(reader: &mut Cursor<&'a [u8]>, size: usize) -> io::Result<&'a [u8]> {
+    let data = *reader.get_ref();
+    let start = reader.position() as usize;
+    if start + size > data.len() {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer"));
+    }
+
+    reader.set_position((start + size) as u64);
+    Ok(&data[start..start + size])
+}
+
+#[cfg(test)]
+mod tests {
+    use attr::info::AttrInfo;
+    use super::*;
+
+    /// `class A { void m() { return; } }`, reduced to what the view needs.
+    const CLASS: &[u8] = &[
+        0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 52,
+        0, 8,                                           // constant_pool_count
+        1, 0, 1, b'A',                                  // #1 Utf8 A
+        7, 0, 1,                                        // #2 Class #1
+        1, 0, 16, b'j', b'a', b'v', b'a', b'/', b'l', b'a', b'n', b'g', b'/',
+        b'O', b'b', b'j', b'e', b'c', b't',             // #3 Utf8 java/lang/Object
+        7, 0, 3,                                        // #4 Class #3
+        1, 0, 1, b'm',                                  // #5 Utf8 m
+        1, 0, 3, b'(', b')', b'V',                      // #6 Utf8 ()V
+        1, 0, 4, b'C', b'o', b'd', b'e',                // #7 Utf8 Code
+        0, 0x20, 0, 2, 0, 4,                            // flags, this_class, super_class
+        0, 0,                                           // interfaces
+        0, 0,                                           // fields
+        0, 1,                                           // methods
+        0, 0, 0, 5, 0, 6,                               // m ()V
+        0, 1, 0, 7, 0, 0, 0, 13,                        // Code
+        0, 0, 0, 1, 0, 0, 0, 1, 0xb1, 0, 0, 0, 0,       // return
+        0, 0,                                           // attributes
+    ];
+
+    #[test]
+    fn decode_without_decoding_the_pool() {
+        let class = ClassfileRef::parse(CLASS).unwrap();
+        let pool = class.constant_pool();
+        assert_eq!(class.this_class().as_ref().map(|name| &name[..]), Some("A"));
+
+        let method = &class.methods()[0];
+        let code = method.attr("Code", pool).unwrap();
+        match code.decode(pool).unwrap().info {
+            AttrInfo::Code(ref info) => assert_eq!(info.code, [0xb1]),
+            ref info => panic!("unexpected attribute: {:?}", info),
+        }
+
+        let method = method.decode_method(pool).unwrap();
+        let decoded = pool.decode().unwrap();
+        assert_eq!(method.name(&decoded), Some("m"));
+        assert_eq!(method.attrs.len(), 1);
+    }
+}
@@ -1,10 +1,32 @@
 #[macro_use] extern crate clap;
 extern crate env_logger;
+extern crate error_chain;
 extern crate jvm;
 
+use error_chain::ChainedError;
 use jvm::classfile::Classfile;
-use std::fs::File;
-use std::path::Path;
+use jvm::classpath::Classpath;
+use jvm::interpreter::{heap, Heap, Interpreter};
+use jvm::verifier::{self, ClasspathHierarchy};
+use std::io::Cursor;
+use std::process;
+
+/// Report `err` along with its causes, and exit.
+fn fail<E: ChainedError>(err: E) -> ! {
+    eprint!("{}", err.display_chain());
+    process::exit(1);
+}
+
+/// Parse a number of bytes, optionally followed by a `k`, `m` or `g` unit as in `-Xmx`.
+fn parse_size(size: &str) -> Option<usize> {
+    let (digits, unit) = match size.char_indices().last() {
+        Some((index, 'k')) | Some((index, 'K')) => (&size[..index], 1 << 10),
+        Some((index, 'm')) | Some((index, 'M')) => (&size[..index], 1 << 20),
+        Some((index, 'g')) | Some((index, 'G')) => (&size[..index], 1 << 30),
+        _ => (size, 1),
+    };
+    digits.parse::<usize>().ok().and_then(|size| size.checked_mul(unit))
+}
 
 fn main() {
     env_logger::init();
@@ -15,19 +37,96 @@ fn main() {
         .arg(clap::Arg::with_name("CLASSPATH")
              .short("c").long("classpath")
              .takes_value(true))
+        .arg(clap::Arg::with_name("DUMP")
+             .short("d").long("dump")
+             .help("Prints the parsed class instead of running it"))
+        .arg(clap::Arg::with_name("JAVAP")
+             .short("p").long("javap")
+             .help("Disassembles the class like `javap -c -v`"))
+        .arg(clap::Arg::with_name("LENIENT")
+             .short("l").long("lenient")
+             .help("Recovers from malformed attributes and unknown access flags"))
+        .arg(clap::Arg::with_name("VALIDATE")
+             .long("validate")
+             .help("Checks the class against the format checks of JVMS §4.8 instead of running it"))
+        .arg(clap::Arg::with_name("VERIFY")
+             .long("verify")
+             .help("Verifies the bytecode of the class instead of running it"))
+        .arg(clap::Arg::with_name("MAX_HEAP")
+             .long("max-heap")
+             .takes_value(true).value_name("SIZE")
+             .validator(|size| parse_size(&size).map(|_| ()).ok_or_else(|| format!("Invalid size: {}", size)))
+             .help("Sets the maximum size of the heap, e.g. 512k or 64m"))
+        .arg(clap::Arg::with_name("GC")
+             .long("gc")
+             .takes_value(true).value_name("COLLECTOR")
+             .possible_values(&["mark-sweep", "generational"])
+             .help("Selects the garbage collector, mark-sweep by default"))
         .arg(clap::Arg::with_name("CLASS")
              .required(true))
         .get_matches();
 
-    let class = matches.value_of("CLASS").unwrap();
+    let classpath = matches.value_of("CLASSPATH").unwrap_or(".");
+    let classpath = Classpath::parse(classpath).unwrap_or_else(|err| fail(err));
 
-    let path = Path::new(class).with_extension("class");
-    println!("Opening: {}", path.display());
+    // `Foo.class` names the class `Foo`, not `class` in the package `Foo`
+    let class = matches.value_of("CLASS").unwrap();
+    let class = class.strip_suffix(".class").unwrap_or(class).replace('.', "/");
+    let dump = matches.is_present("DUMP");
+    let javap = matches.is_present("JAVAP");
+    let validate = matches.is_present("VALIDATE");
+    let verify = matches.is_present("VERIFY");
+    if dump {
+        println!("Opening: {}", class);
+    }
 
     let cf = {
-        let mut file = File::open(path).unwrap();
-        Classfile::read(&mut file).unwrap()
+        let data = classpath.read_class(&class).unwrap_or_else(|err| fail(err));
+        let mut reader = Cursor::new(data);
+        if matches.is_present("LENIENT") {
+            let (cf, errors) = Classfile::read_lenient(&mut reader).unwrap_or_else(|err| fail(err));
+            for err in errors {
+                eprint!("Warning: {}", err.display_chain());
+            }
+            cf
+        } else {
+            Classfile::read(&mut reader).unwrap_or_else(|err| fail(err))
+        }
     };
 
-    cf.dump();
+    if validate {
+        let violations = cf.validate();
+        for err in violations.iter() {
+            eprint!("Violation: {}", err.display_chain());
+        }
+        process::exit(if violations.is_empty() { 0 } else { 1 });
+    }
+
+    if verify {
+        let hierarchy = ClasspathHierarchy::new(&classpath);
+        verifier::verify(&cf, &hierarchy).unwrap_or_else(|err| fail(err));
+        return;
+    }
+
+    if javap || dump {
+        let result = if javap {
+            cf.disassemble()
+        } else {
+            cf.dump()
+        };
+        if let Err(err) = result {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    let limit = matches.value_of("MAX_HEAP").and_then(parse_size).unwrap_or(heap::DEFAULT_LIMIT);
+    let heap = match matches.value_of("GC") {
+        Some("generational") => Heap::new(Box::new(heap::Generational::new(limit))),
+        _ => Heap::new(Box::new(heap::MarkSweep::new(limit))),
+    };
+    let mut interpreter = Interpreter::new(&classpath, heap);
+    let class = interpreter.method_area().define(cf).unwrap_or_else(|err| fail(err));
+    interpreter.run_main(&class).unwrap_or_else(|err| fail(err));
 }
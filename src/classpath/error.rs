@@ -0,0 +1,16 @@
+use std::io;
+use zip::result::ZipError;
+
+error_chain! {
+    foreign_links {
+        Io(io::Error);
+        Zip(ZipError);
+    }
+
+    errors {
+        ClassNotFound(name: String) {
+            description("Class not found")
+            display("Class not found: {}", name)
+        }
+    }
+}
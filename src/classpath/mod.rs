@@ -0,0 +1,197 @@
+pub mod error;
+
+use self::error::*;
+use std::cell::RefCell;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+use zip::result::ZipError;
+
+pub const SEPARATOR: char = ':';
+
+#[derive(Debug)]
+pub struct Classpath {
+    entries: Vec<ClasspathEntry>,
+}
+
+impl Classpath {
+    pub fn new() -> Classpath {
+        Classpath {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Parse a `:`-separated classpath string, e.g. `classes:lib/foo.jar:lib/*`.
+    pub fn parse(value: &str) -> Result<Classpath> {
+        let mut classpath = Classpath::new();
+
+        for path in value.split(SEPARATOR).filter(|path| !path.is_empty()) {
+            if path == "*" || path.ends_with("/*") {
+                let dir = Path::new(&path[..path.len() - 1]);
+                try!(classpath.push_wildcard(if path == "*" { Path::new(".") } else { dir }));
+            } else if Path::new(path).exists() {
+                // Like the JVM, silently ignore entries that don't exist.
+                try!(classpath.push(path));
+            }
+        }
+
+        Ok(classpath)
+    }
+
+    pub fn push<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let entry = try!(ClasspathEntry::open(path.as_ref()));
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Add every JAR/ZIP archive found in `dir`, as the JVM does for `dir/*` entries.
+    fn push_wildcard(&mut self, dir: &Path) -> Result<()> {
+        let mut paths = Vec::new();
+        for entry in try!(fs::read_dir(dir)) {
+            let path = try!(entry).path();
+            if path.is_file() && is_archive(&path) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        for path in paths {
+            try!(self.push(path));
+        }
+
+        Ok(())
+    }
+
+    pub fn entries(&self) -> &[ClasspathEntry] {
+        &self.entries
+    }
+
+    /// Look up a binary class name like `java/lang/String` in each entry, in order,
+    /// and return the raw content of the first matching class file.
+    pub fn read_class(&self, name: &str) -> Result<Vec<u8>> {
+        for entry in self.entries.iter() {
+            if let Some(data) = try!(entry.read_class(name)) {
+                return Ok(data);
+            }
+        }
+
+        bail!(ErrorKind::ClassNotFound(name.to_owned()))
+    }
+}
+
+#[derive(Debug)]
+pub enum ClasspathEntry {
+    Dir(DirEntry),
+    Zip(ZipEntry),
+}
+
+impl ClasspathEntry {
+    pub fn open(path: &Path) -> Result<ClasspathEntry> {
+        if path.is_dir() {
+            Ok(ClasspathEntry::Dir(DirEntry::new(path)))
+        } else {
+            ZipEntry::open(path).map(ClasspathEntry::Zip)
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match *self {
+            ClasspathEntry::Dir(ref entry) => entry.path(),
+            ClasspathEntry::Zip(ref entry) => entry.path(),
+        }
+    }
+
+    pub fn read_class(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        match *self {
+            ClasspathEntry::Dir(ref entry) => entry.read_class(name),
+            ClasspathEntry::Zip(ref entry) => entry.read_class(name),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DirEntry {
+    path: PathBuf,
+}
+
+impl DirEntry {
+    pub fn new<P: Into<PathBuf>>(path: P) -> DirEntry {
+        DirEntry {
+            path: path.into(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn read_class(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.path.join(class_file_name(name));
+
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut data = Vec::new();
+        try!(file.read_to_end(&mut data));
+        Ok(Some(data))
+    }
+}
+
+pub struct ZipEntry {
+    path: PathBuf,
+    archive: RefCell<ZipArchive<File>>,
+}
+
+impl ZipEntry {
+    pub fn open<P: Into<PathBuf>>(path: P) -> Result<ZipEntry> {
+        let path = path.into();
+        let file = try!(File::open(&path));
+        let archive = try!(ZipArchive::new(file));
+
+        Ok(ZipEntry {
+            path: path,
+            archive: RefCell::new(archive),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn read_class(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        let mut archive = self.archive.borrow_mut();
+
+        let mut file = match archive.by_name(&class_file_name(name)) {
+            Ok(file) => file,
+            Err(ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut data = Vec::with_capacity(file.size() as usize);
+        try!(file.read_to_end(&mut data));
+        Ok(Some(data))
+    }
+}
+
+impl ::std::fmt::Debug for ZipEntry {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.debug_struct("ZipEntry")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+fn class_file_name(name: &str) -> String {
+    format!("{}.class", name)
+}
+
+fn is_archive(path: &Path) -> bool {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => ext.eq_ignore_ascii_case("jar") || ext.eq_ignore_ascii_case("zip"),
+        None => false,
+    }
+}
@@ -0,0 +1,343 @@
+use classfile::{field, flags, method, Classfile};
+use classfile::attr::info::AttrInfo;
+use classfile::attr::info::method::CodeAttrInfo;
+use classfile::descriptor::{FieldType, MethodDescriptor};
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::ThreadId;
+use super::constant_pool::RuntimeConstantPool;
+use super::value::Value;
+
+/// Initialization state of a class (JVMS §5.5).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum State {
+    /// Linked, but not initialized yet.
+    Linked,
+    BeingInitialized(ThreadId),
+    Initialized,
+    /// Initialization failed, the class can't be used.
+    Erroneous,
+}
+
+/// What a thread has to do to get a class initialized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Initialization {
+    /// Initialize the class, then call `Class::finish_initialization`.
+    Required,
+    /// The class is initialized, or being initialized by the same thread.
+    Done,
+    Failed,
+}
+
+/// Accessibility of a class member (JVMS §5.4.4).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Visibility {
+    Public,
+    Protected,
+    /// Accessible from the same runtime package only.
+    Package,
+    Private,
+}
+
+#[derive(Debug)]
+pub struct Field {
+    pub name: String,
+    pub desc: String,
+    pub ty: FieldType,
+    pub access_flags: field::flags::AccessFlags,
+    /// Index of the field among the static fields of its class, or among the instance fields of its
+    /// objects, those of the super classes first.
+    pub slot: usize,
+}
+
+impl Field {
+    pub fn is_static(&self) -> bool {
+        self.access_flags.contains(field::flags::AccessFlags::ACC_STATIC)
+    }
+
+    pub fn visibility(&self) -> Visibility {
+        visibility(self.access_flags.contains(field::flags::AccessFlags::ACC_PUBLIC),
+                   self.access_flags.contains(field::flags::AccessFlags::ACC_PROTECTED),
+                   self.access_flags.contains(field::flags::AccessFlags::ACC_PRIVATE))
+    }
+}
+
+#[derive(Debug)]
+pub struct Method {
+    pub name: String,
+    pub desc: String,
+    pub descriptor: MethodDescriptor,
+    pub access_flags: method::flags::AccessFlags,
+    /// Index of the method in the class file.
+    pub index: usize,
+}
+
+impl Method {
+    pub fn is_static(&self) -> bool {
+        self.access_flags.contains(method::flags::AccessFlags::ACC_STATIC)
+    }
+
+    pub fn visibility(&self) -> Visibility {
+        visibility(self.access_flags.contains(method::flags::AccessFlags::ACC_PUBLIC),
+                   self.access_flags.contains(method::flags::AccessFlags::ACC_PROTECTED),
+                   self.access_flags.contains(method::flags::AccessFlags::ACC_PRIVATE))
+    }
+}
+
+/// Visibility of a member with the given access flags set, fields and methods having their own flags type.
+fn visibility(public: bool, protected: bool, private: bool) -> Visibility {
+    if public {
+        Visibility::Public
+    } else if protected {
+        Visibility::Protected
+    } else if private {
+        Visibility::Private
+    } else {
+        Visibility::Package
+    }
+}
+
+/// Class loaded in the method area, linked to its super class and interfaces.
+pub struct Class {
+    pub name: String,
+    pub access_flags: flags::AccessFlags,
+    pub super_class: Option<Arc<Class>>,
+    pub interfaces: Vec<Arc<Class>>,
+    pub fields: Vec<Field>,
+    pub methods: Vec<Method>,
+    /// Types of the instance fields of the objects of the class by slot, inherited ones first.
+    pub instance_layout: Vec<FieldType>,
+    pub classfile: Classfile,
+    pub constant_pool: RuntimeConstantPool,
+    statics: Mutex<Vec<Value>>,
+    state: Mutex<State>,
+    state_changed: Condvar,
+}
+
+impl Class {
+    pub fn new(name: String, classfile: Classfile, super_class: Option<Arc<Class>>, interfaces: Vec<Arc<Class>>,
+               fields: Vec<Field>, methods: Vec<Method>, statics: Vec<Value>) -> Class
+    {
+        let mut instance_layout = super_class.as_ref().map_or_else(Vec::new, |class| class.instance_layout.clone());
+        instance_layout.extend(fields.iter().filter(|field| !field.is_static()).map(|field| field.ty.clone()));
+
+        Class {
+            name: name,
+            access_flags: classfile.access_flags,
+            super_class: super_class,
+            interfaces: interfaces,
+            fields: fields,
+            methods: methods,
+            instance_layout: instance_layout,
+            constant_pool: RuntimeConstantPool::new(classfile.constant_pool.len()),
+            classfile: classfile,
+            statics: Mutex::new(statics),
+            state: Mutex::new(State::Linked),
+            state_changed: Condvar::new(),
+        }
+    }
+
+    pub fn is_interface(&self) -> bool {
+        self.access_flags.contains(flags::AccessFlags::ACC_INTERFACE)
+    }
+
+    /// Name of the class in Java source, e.g. `java.lang.Object`.
+    pub fn java_name(&self) -> String {
+        self.name.replace('/', ".")
+    }
+
+    /// Name of a method of the class, along with its descriptor, e.g. `Foo.bar(I)V`.
+    pub fn method_name(&self, method: usize) -> String {
+        let method = &self.methods[method];
+        format!("{}.{}{}", self.java_name(), method.name, method.desc)
+    }
+
+    /// Name of the runtime package of the class, e.g. `java/lang`, there being a single class loader.
+    pub fn package(&self) -> &str {
+        self.name.rfind('/').map_or("", |end| &self.name[..end])
+    }
+
+    /// Name of the host of the nest the class belongs to, itself unless it has a `NestHost` attribute.
+    pub fn nest_host(&self) -> &str {
+        let pool = &self.classfile.constant_pool;
+        self.classfile.attrs.iter().filter_map(|attr| match attr.info {
+            AttrInfo::NestHost(ref info) => info.host_class(pool).and_then(|info| info.name(pool)),
+            _ => None,
+        }).next().unwrap_or(&self.name)
+    }
+
+    /// Whether this class is `name` or one of its subclasses or implementations.
+    pub fn is_subclass_of(&self, name: &str) -> bool {
+        self.name == name ||
+            self.super_class.as_ref().is_some_and(|class| class.is_subclass_of(name)) ||
+            self.interfaces.iter().any(|class| class.is_subclass_of(name))
+    }
+
+    /// Index of the method declared by this class with the given name and descriptor.
+    pub fn find_method(&self, name: &str, desc: &str) -> Option<usize> {
+        self.methods.iter().position(|method| method.name == name && method.desc == desc)
+    }
+
+    /// Look up a method in a class, its super classes and then its superinterfaces (JVMS §5.4.3.3),
+    /// returning the class declaring it along with its index.
+    pub fn lookup_method(class: &Arc<Class>, name: &str, desc: &str) -> Option<(Arc<Class>, usize)> {
+        let mut current = Some(class);
+        while let Some(class) = current {
+            if let Some(index) = class.find_method(name, desc) {
+                return Some((class.clone(), index));
+            }
+            current = class.super_class.as_ref();
+        }
+        Class::lookup_superinterface_method(class, name, desc)
+    }
+
+    /// Look up a method in an interface, the public instance methods of `java/lang/Object` and then its
+    /// superinterfaces (JVMS §5.4.3.4), returning the class declaring it along with its index.
+    pub fn lookup_interface_method(interface: &Arc<Class>, name: &str, desc: &str) -> Option<(Arc<Class>, usize)> {
+        if let Some(index) = interface.find_method(name, desc) {
+            return Some((interface.clone(), index));
+        }
+        if let Some(ref object) = interface.super_class {
+            if let Some(index) = object.find_method(name, desc) {
+                let method = &object.methods[index];
+                if method.visibility() == Visibility::Public && !method.is_static() {
+                    return Some((object.clone(), index));
+                }
+            }
+        }
+        Class::lookup_superinterface_method(interface, name, desc)
+    }
+
+    /// Select the method invoked on an object of `class` for the resolved method `method` of `resolved`
+    /// (JVMS §5.4.6), returning the class declaring it along with its index.
+    pub fn select_method(class: &Arc<Class>, resolved: &Arc<Class>, method: usize) -> Option<(Arc<Class>, usize)> {
+        let resolved_method = &resolved.methods[method];
+        if resolved_method.visibility() == Visibility::Private {
+            return Some((resolved.clone(), method));
+        }
+
+        let mut current = Some(class);
+        while let Some(class) = current {
+            if let Some(index) = class.find_method(&resolved_method.name, &resolved_method.desc) {
+                let candidate = &class.methods[index];
+                let overrides = class.name == resolved.name || match resolved_method.visibility() {
+                    _ if candidate.visibility() == Visibility::Private => false,
+                    Visibility::Package => class.package() == resolved.package(),
+                    _ => true,
+                };
+                if overrides && !candidate.is_static() {
+                    return Some((class.clone(), index));
+                }
+            }
+            current = class.super_class.as_ref();
+        }
+        Class::lookup_superinterface_method(class, &resolved_method.name, &resolved_method.desc)
+    }
+
+    /// Choose among the non-private instance methods declared by the superinterfaces of a class, preferring
+    /// the only maximally-specific one that isn't abstract.
+    fn lookup_superinterface_method(class: &Arc<Class>, name: &str, desc: &str) -> Option<(Arc<Class>, usize)> {
+        let mut candidates = Vec::new();
+        class.collect_superinterface_methods(name, desc, &mut candidates);
+
+        let maximally_specific: Vec<_> = candidates.iter().filter(|(interface, _)| {
+            !candidates.iter().any(|(other, _)| other.name != interface.name && other.is_subclass_of(&interface.name))
+        }).collect();
+        let mut concrete = maximally_specific.iter().filter(|(interface, index)| {
+            !interface.methods[*index].access_flags.contains(method::flags::AccessFlags::ACC_ABSTRACT)
+        });
+
+        match (concrete.next(), concrete.next()) {
+            (Some(&&(ref interface, index)), None) => Some((interface.clone(), index)),
+            _ => candidates.into_iter().next(),
+        }
+    }
+
+    fn collect_superinterface_methods(&self, name: &str, desc: &str, candidates: &mut Vec<(Arc<Class>, usize)>) {
+        for interface in self.interfaces.iter() {
+            if let Some(index) = interface.find_method(name, desc) {
+                let method = &interface.methods[index];
+                let is_candidate = method.visibility() != Visibility::Private && !method.is_static();
+                if is_candidate && !candidates.iter().any(|(other, _)| other.name == interface.name) {
+                    candidates.push((interface.clone(), index));
+                }
+            }
+            interface.collect_superinterface_methods(name, desc, candidates);
+        }
+        if let Some(ref super_class) = self.super_class {
+            super_class.collect_superinterface_methods(name, desc, candidates);
+        }
+    }
+
+    /// Look up a field in this class, its superinterfaces and then its super classes (JVMS §5.4.3.2),
+    /// returning the class declaring it along with its index.
+    pub fn lookup_field(class: &Arc<Class>, name: &str, desc: &str) -> Option<(Arc<Class>, usize)> {
+        if let Some(index) = class.fields.iter().position(|field| field.name == name && field.desc == desc) {
+            return Some((class.clone(), index));
+        }
+        class.interfaces.iter().chain(class.super_class.iter())
+            .filter_map(|class| Class::lookup_field(class, name, desc))
+            .next()
+    }
+
+    /// Code of a method, `None` for native and abstract methods.
+    pub fn code(&self, method: usize) -> Option<&CodeAttrInfo> {
+        self.classfile.methods[self.methods[method].index].attrs.iter().filter_map(|attr| match attr.info {
+            AttrInfo::Code(ref info) => Some(info),
+            _ => None,
+        }).next()
+    }
+
+    pub fn get_static(&self, slot: usize) -> Value {
+        self.statics.lock().expect("Poisoned statics")[slot]
+    }
+
+    pub fn set_static(&self, slot: usize, value: Value) {
+        self.statics.lock().expect("Poisoned statics")[slot] = value;
+    }
+
+    /// Values of all the static fields by slot, locked until the guard is dropped.
+    pub fn statics(&self) -> MutexGuard<'_, Vec<Value>> {
+        self.statics.lock().expect("Poisoned statics")
+    }
+
+    pub fn state(&self) -> State {
+        *self.state.lock().expect("Poisoned class state")
+    }
+
+    /// Acquire the initialization of the class for `thread`, following steps 1 to 5 of JVMS §5.5.
+    ///
+    /// Waits while another thread initializes the class.
+    pub fn start_initialization(&self, thread: ThreadId) -> Initialization {
+        let mut state = self.state.lock().expect("Poisoned class state");
+        loop {
+            match *state {
+                State::BeingInitialized(other) if other != thread => {
+                    state = self.state_changed.wait(state).expect("Poisoned class state");
+                }
+                State::Linked => {
+                    *state = State::BeingInitialized(thread);
+                    return Initialization::Required;
+                }
+                State::BeingInitialized(_) | State::Initialized => return Initialization::Done,
+                State::Erroneous => return Initialization::Failed,
+            }
+        }
+    }
+
+    /// Mark the class as initialized, or erroneous, and wake up threads waiting for it.
+    pub fn finish_initialization(&self, success: bool) {
+        let mut state = self.state.lock().expect("Poisoned class state");
+        *state = if success { State::Initialized } else { State::Erroneous };
+        self.state_changed.notify_all();
+    }
+}
+
+impl fmt::Debug for Class {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Class")
+            .field("name", &self.name)
+            .field("state", &self.state())
+            .finish()
+    }
+}
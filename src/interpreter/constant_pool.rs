@@ -0,0 +1,195 @@
+//! Runtime constant pool (JVMS §5.1), resolving symbolic references on first use (JVMS §5.4.3).
+
+use classfile::constant::{ConstantClassInfo, ConstantNameAndTypeInfo, ConstantPool, ConstantPoolEntry};
+use classfile::flags;
+use classfile::error::{Error as ClassfileError, ErrorKind as ClassfileErrorKind};
+use std::sync::{Arc, Mutex};
+use super::class::{Class, Visibility};
+use super::error::*;
+use super::method_area::MethodArea;
+
+/// Symbolic reference turned into a direct one.
+///
+/// Classes are never unloaded, so that the reference cycles between classes this creates don't matter.
+#[derive(Debug, Clone)]
+pub enum Resolved {
+    Class(Arc<Class>),
+    /// Class declaring a field, and the index of the field.
+    Field(Arc<Class>, usize),
+    /// Class declaring a method, and the index of the method.
+    Method(Arc<Class>, usize),
+    /// Class and message of the exception resolution failed with, which it fails with again.
+    Failed(String, String),
+}
+
+/// Resolved entries of the constant pool of a class.
+#[derive(Debug)]
+pub struct RuntimeConstantPool {
+    entries: Mutex<Vec<Option<Resolved>>>,
+}
+
+impl RuntimeConstantPool {
+    /// Pool with nothing resolved yet, for a class file constant pool of `len` entries.
+    pub fn new(len: usize) -> RuntimeConstantPool {
+        RuntimeConstantPool {
+            entries: Mutex::new(vec![None; len + 1]),
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<Resolved> {
+        self.entries.lock().expect("Poisoned constant pool").get(index).cloned().and_then(|entry| entry)
+    }
+
+    fn set(&self, index: usize, resolved: Resolved) {
+        if let Some(entry) = self.entries.lock().expect("Poisoned constant pool").get_mut(index) {
+            *entry = Some(resolved);
+        }
+    }
+}
+
+/// Resolve the `Class` constant at `index` in the constant pool of `from` (JVMS §5.4.3.1).
+pub fn resolve_class(area: &mut MethodArea, from: &Class, index: usize) -> Result<Arc<Class>> {
+    let resolved = try!(cached(from, index, || {
+        let info = try!(from.classfile.constant_pool.get_class_info(index).ok_or_else(|| bad_constant(index, "Class")));
+        load_class(area, from, info, index).map(Resolved::Class)
+    }));
+
+    match resolved {
+        Resolved::Class(class) => Ok(class),
+        _ => bail!(bad_constant(index, "Class")),
+    }
+}
+
+/// Resolve the `Fieldref` constant at `index` in the constant pool of `from` (JVMS §5.4.3.2), returning the
+/// class declaring the field along with its index.
+pub fn resolve_field(area: &mut MethodArea, from: &Class, index: usize) -> Result<(Arc<Class>, usize)> {
+    let resolved = try!(cached(from, index, || {
+        let pool = &from.classfile.constant_pool;
+        let (class_info, info) = match pool.get(index) {
+            Some(ConstantPoolEntry::FieldRef(info)) => (info.class(pool), info.name_and_type(pool)),
+            _ => bail!(bad_constant(index, "Fieldref")),
+        };
+        let class_info = try!(class_info.ok_or_else(|| bad_constant(index, "Fieldref")));
+        let (name, desc) = try!(name_and_type(pool, info, index));
+
+        let class = try!(load_class(area, from, class_info, index));
+        let (declaring, field) = try!(Class::lookup_field(&class, name, desc)
+            .ok_or_else(|| exception("java/lang/NoSuchFieldError", name)));
+
+        let visibility = declaring.fields[field].visibility();
+        if !is_member_accessible(from, &declaring, visibility) {
+            bail!(exception("java/lang/IllegalAccessError",
+                            format!("class {} tried to access field {}.{}", from.java_name(), declaring.java_name(), name)));
+        }
+        Ok(Resolved::Field(declaring, field))
+    }));
+
+    match resolved {
+        Resolved::Field(class, field) => Ok((class, field)),
+        _ => bail!(bad_constant(index, "Fieldref")),
+    }
+}
+
+/// Resolve the `Methodref` (JVMS §5.4.3.3) or `InterfaceMethodref` (JVMS §5.4.3.4) constant at `index` in
+/// the constant pool of `from`, returning the class declaring the method along with its index.
+pub fn resolve_method(area: &mut MethodArea, from: &Class, index: usize) -> Result<(Arc<Class>, usize)> {
+    let resolved = try!(cached(from, index, || {
+        let pool = &from.classfile.constant_pool;
+        let (class_info, info, interface) = match pool.get(index) {
+            Some(ConstantPoolEntry::MethodRef(info)) => (info.class(pool), info.name_and_type(pool), false),
+            Some(ConstantPoolEntry::InterfaceMethodRef(info)) => (info.class(pool), info.name_and_type(pool), true),
+            _ => bail!(bad_constant(index, "Methodref")),
+        };
+        let class_info = try!(class_info.ok_or_else(|| bad_constant(index, "Methodref")));
+        let (name, desc) = try!(name_and_type(pool, info, index));
+
+        let class = try!(load_class(area, from, class_info, index));
+        let found = if interface {
+            if !class.is_interface() {
+                bail!(exception("java/lang/IncompatibleClassChangeError",
+                                format!("Found class {}, but interface was expected", class.java_name())));
+            }
+            Class::lookup_interface_method(&class, name, desc)
+        } else {
+            if class.is_interface() {
+                bail!(exception("java/lang/IncompatibleClassChangeError",
+                                format!("Found interface {}, but class was expected", class.java_name())));
+            }
+            Class::lookup_method(&class, name, desc)
+        };
+        let (declaring, method) = try!(found.ok_or_else(|| {
+            exception("java/lang/NoSuchMethodError", format!("{}.{}{}", class.java_name(), name, desc))
+        }));
+
+        if !is_member_accessible(from, &declaring, declaring.methods[method].visibility()) {
+            bail!(exception("java/lang/IllegalAccessError",
+                            format!("class {} tried to access method {}", from.java_name(), declaring.method_name(method))));
+        }
+        Ok(Resolved::Method(declaring, method))
+    }));
+
+    match resolved {
+        Resolved::Method(class, method) => Ok((class, method)),
+        _ => bail!(bad_constant(index, "Methodref")),
+    }
+}
+
+/// Resolve the entry at `index` with `resolve` unless it already was, recording the outcome.
+fn cached<F>(from: &Class, index: usize, resolve: F) -> Result<Resolved>
+    where F: FnOnce() -> Result<Resolved>
+{
+    match from.constant_pool.get(index) {
+        Some(Resolved::Failed(class, message)) => bail!(exception(&class, message)),
+        Some(resolved) => return Ok(resolved),
+        None => {}
+    }
+
+    match resolve() {
+        Ok(resolved) => {
+            from.constant_pool.set(index, resolved.clone());
+            Ok(resolved)
+        }
+        Err(err) => {
+            if let Some((class, message)) = err.java_exception() {
+                from.constant_pool.set(index, Resolved::Failed(class.to_owned(), message.to_owned()));
+            }
+            Err(err)
+        }
+    }
+}
+
+/// Load the class named by `info`, checking that `from` can access it (JVMS §5.4.4).
+fn load_class(area: &mut MethodArea, from: &Class, info: &ConstantClassInfo, index: usize) -> Result<Arc<Class>> {
+    let name = try!(info.name(&from.classfile.constant_pool).ok_or_else(|| bad_constant(index, "Class")));
+    let class = try!(area.load(name));
+
+    if !class.access_flags.contains(flags::AccessFlags::ACC_PUBLIC) && class.package() != from.package() {
+        bail!(exception("java/lang/IllegalAccessError",
+                        format!("failed to access class {} from class {}", class.java_name(), from.java_name())));
+    }
+    Ok(class)
+}
+
+/// Whether `from` can access a member of `declaring` with the given visibility (JVMS §5.4.4).
+fn is_member_accessible(from: &Class, declaring: &Class, visibility: Visibility) -> bool {
+    match visibility {
+        Visibility::Public => true,
+        Visibility::Protected => from.is_subclass_of(&declaring.name) || from.package() == declaring.package(),
+        Visibility::Package => from.package() == declaring.package(),
+        Visibility::Private => from.name == declaring.name || from.nest_host() == declaring.nest_host(),
+    }
+}
+
+/// Name and descriptor of the member referenced by the constant at `index`.
+fn name_and_type<'a>(pool: &'a ConstantPool, info: Option<&ConstantNameAndTypeInfo>, index: usize)
+    -> Result<(&'a str, &'a str)>
+{
+    match (info.and_then(|info| info.name(pool)), info.and_then(|info| info.desc(pool))) {
+        (Some(name), Some(desc)) => Ok((name, desc)),
+        _ => bail!(bad_constant(index, "NameAndType")),
+    }
+}
+
+fn bad_constant(index: usize, expected: &'static str) -> Error {
+    ClassfileError::from(ClassfileErrorKind::BadConstantIndex(index, expected)).into()
+}
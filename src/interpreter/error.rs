@@ -0,0 +1,55 @@
+use classfile;
+use classpath;
+
+error_chain! {
+    links {
+        Classfile(classfile::error::Error, classfile::error::ErrorKind);
+        Classpath(classpath::error::Error, classpath::error::ErrorKind);
+    }
+
+    errors {
+        BadConstant(pc: usize, index: usize) {
+            description("Bad constant pool reference")
+            display("Bad constant pool reference #{} at pc {}", index, pc)
+        }
+        Exception(class: String, message: String) {
+            description("Uncaught exception")
+            display("{}{}{}", class.replace('/', "."), if message.is_empty() { "" } else { ": " }, message)
+        }
+        InvalidOperand(pc: usize, expected: &'static str) {
+            description("Invalid operand")
+            display("Invalid operand at pc {}: expected {}", pc, expected)
+        }
+        MainNotFound(class: String) {
+            description("Main method not found")
+            display("No `public static void main(String[])` method in class {}", class)
+        }
+        Method(class: String, name: String, desc: String) {
+            description("Error in method")
+            display("In method {}.{}{}", class, name, desc)
+        }
+        Unsupported(pc: usize, what: String) {
+            description("Unsupported operation")
+            display("Unsupported operation at pc {}: {}", pc, what)
+        }
+    }
+}
+
+/// Java exception of class `class`, with the given detail message.
+pub fn exception<S: Into<String>>(class: &str, message: S) -> Error {
+    ErrorKind::Exception(class.to_owned(), message.into()).into()
+}
+
+impl Error {
+    /// Class and message of the Java exception this error is, looking through the methods it was thrown
+    /// from.
+    pub fn java_exception(&self) -> Option<(&str, &str)> {
+        match *self.kind() {
+            ErrorKind::Exception(ref class, ref message) => Some((class, message)),
+            ErrorKind::Method(..) => {
+                self.1.next_error.as_ref().and_then(|err| err.downcast_ref::<Error>()).and_then(Error::java_exception)
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use classfile::attr::info::method::CodeAttrInfo;
+use std::sync::Arc;
+use super::class::Class;
+use super::error::*;
+use super::value::Value;
+
+/// Activation of a method: its local variables, operand stack and current instruction.
+#[derive(Debug)]
+pub struct Frame {
+    pub class: Arc<Class>,
+    /// Index of the method in its class.
+    pub method: usize,
+    pub locals: Vec<Value>,
+    pub stack: Vec<Value>,
+    pub pc: usize,
+}
+
+impl Frame {
+    /// Frame starting a method of `class` with the given arguments, `this` first for instance methods.
+    pub fn new(class: Arc<Class>, method: usize, code: &CodeAttrInfo, args: &[Value]) -> Result<Frame> {
+        let mut frame = Frame {
+            class: class,
+            method: method,
+            locals: vec![Value::Int(0); code.max_locals],
+            stack: Vec::with_capacity(code.max_stack),
+            pc: 0,
+        };
+
+        let mut index = 0;
+        for &arg in args {
+            try!(frame.store(index, arg));
+            index += if arg.is_category2() { 2 } else { 1 };
+        }
+        Ok(frame)
+    }
+
+    /// Code of the method, which has some for a frame to exist.
+    pub fn code(&self) -> &CodeAttrInfo {
+        self.class.code(self.method).expect("Frame of a method without code")
+    }
+
+    pub fn push(&mut self, value: Value) {
+        self.stack.push(value);
+    }
+
+    pub fn pop(&mut self) -> Result<Value> {
+        self.stack.pop().ok_or_else(|| ErrorKind::InvalidOperand(self.pc, "value").into())
+    }
+
+    pub fn pop_int(&mut self) -> Result<i32> {
+        match try!(self.pop()) {
+            Value::Int(value) => Ok(value),
+            _ => bail!(ErrorKind::InvalidOperand(self.pc, "int")),
+        }
+    }
+
+    pub fn pop_long(&mut self) -> Result<i64> {
+        match try!(self.pop()) {
+            Value::Long(value) => Ok(value),
+            _ => bail!(ErrorKind::InvalidOperand(self.pc, "long")),
+        }
+    }
+
+    pub fn pop_float(&mut self) -> Result<f32> {
+        match try!(self.pop()) {
+            Value::Float(value) => Ok(value),
+            _ => bail!(ErrorKind::InvalidOperand(self.pc, "float")),
+        }
+    }
+
+    pub fn pop_double(&mut self) -> Result<f64> {
+        match try!(self.pop()) {
+            Value::Double(value) => Ok(value),
+            _ => bail!(ErrorKind::InvalidOperand(self.pc, "double")),
+        }
+    }
+
+    /// Pop the arguments of a method taking `count` parameters, in order.
+    pub fn pop_args(&mut self, count: usize) -> Result<Vec<Value>> {
+        if count > self.stack.len() {
+            bail!(ErrorKind::InvalidOperand(self.pc, "method arguments"));
+        }
+        let len = self.stack.len() - count;
+        Ok(self.stack.split_off(len))
+    }
+
+    pub fn load(&self, index: usize) -> Result<Value> {
+        self.locals.get(index).cloned().ok_or_else(|| ErrorKind::InvalidOperand(self.pc, "local variable").into())
+    }
+
+    pub fn store(&mut self, index: usize, value: Value) -> Result<()> {
+        let size = if value.is_category2() { 2 } else { 1 };
+        if index + size > self.locals.len() {
+            bail!(ErrorKind::InvalidOperand(self.pc, "local variable"));
+        }
+        self.locals[index] = value;
+        Ok(())
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::mem;
+use super::{Collector, Object, Reference};
+
+/// Bit of a reference set for objects in the nursery, the others being in the old space.
+const NURSERY: usize = 1;
+
+/// Part of the heap taken by the nursery, e.g. a quarter.
+const NURSERY_RATIO: usize = 4;
+
+/// Number of consecutive objects of the old space covered by a card.
+const CARD_OBJECTS: usize = 64;
+
+/// Generational collector, allocating objects in a nursery from which those that survive a collection
+/// are copied into an old space.
+///
+/// Minor collections only copy the objects of the nursery, reachable from the roots or from the old
+/// objects on a card a write barrier marked. Full collections copy the reachable objects of both spaces
+/// into a new old space, compacting it. Objects too large for the nursery are allocated in the old space
+/// directly.
+#[derive(Debug)]
+pub struct Generational {
+    nursery: Vec<Object>,
+    nursery_size: usize,
+    nursery_limit: usize,
+    old: Vec<Object>,
+    old_size: usize,
+    old_limit: usize,
+    /// Cards of the old space, set when a reference is stored in one of the objects they cover.
+    cards: Vec<bool>,
+}
+
+impl Generational {
+    /// Empty heap holding at most `limit` bytes of objects.
+    pub fn new(limit: usize) -> Generational {
+        let nursery_limit = limit / NURSERY_RATIO;
+        Generational {
+            nursery: Vec::new(),
+            nursery_size: 0,
+            nursery_limit: nursery_limit,
+            old: Vec::new(),
+            old_size: 0,
+            old_limit: limit - nursery_limit,
+            cards: Vec::new(),
+        }
+    }
+
+    /// Whether an object of `size` bytes goes directly in the old space.
+    fn is_large(&self, size: usize) -> bool {
+        size > self.nursery_limit / 2
+    }
+
+    fn dirty_cards(&self) -> Vec<usize> {
+        self.cards.iter().enumerate().filter(|&(_, &dirty)| dirty).map(|(card, _)| card).collect()
+    }
+}
+
+impl Collector for Generational {
+    fn name(&self) -> &'static str {
+        "generational"
+    }
+
+    fn size(&self) -> usize {
+        self.nursery_size + self.old_size
+    }
+
+    fn limit(&self) -> usize {
+        self.nursery_limit + self.old_limit
+    }
+
+    fn has_room(&self, size: usize) -> bool {
+        let (used, limit) = if self.is_large(size) {
+            (self.old_size, self.old_limit)
+        } else {
+            (self.nursery_size, self.nursery_limit)
+        };
+        self.old_size <= self.old_limit && used.checked_add(size).is_some_and(|size| size <= limit)
+    }
+
+    fn insert(&mut self, object: Object) -> Reference {
+        let size = object.size();
+        if self.is_large(size) {
+            self.old_size += size;
+            self.old.push(object);
+            self.cards.resize(card_count(self.old.len()), false);
+            old_reference(self.old.len() - 1)
+        } else {
+            self.nursery_size += size;
+            self.nursery.push(object);
+            Reference((self.nursery.len() - 1) << 1 | NURSERY)
+        }
+    }
+
+    fn get(&self, reference: Reference) -> Option<&Object> {
+        if reference.0 & NURSERY != 0 {
+            self.nursery.get(reference.0 >> 1)
+        } else {
+            self.old.get(reference.0 >> 1)
+        }
+    }
+
+    fn get_mut(&mut self, reference: Reference) -> Option<&mut Object> {
+        if reference.0 & NURSERY != 0 {
+            self.nursery.get_mut(reference.0 >> 1)
+        } else {
+            self.old.get_mut(reference.0 >> 1)
+        }
+    }
+
+    fn write_barrier(&mut self, object: Reference) {
+        if object.0 & NURSERY == 0 {
+            if let Some(card) = self.cards.get_mut((object.0 >> 1) / CARD_OBJECTS) {
+                *card = true;
+            }
+        }
+    }
+
+    fn collect(&mut self, roots: &mut [&mut Reference], full: bool) -> usize {
+        let before = self.size();
+        // The survivors of the nursery may not fit in the old space
+        let full = full || self.old_size + self.nursery_size > self.old_limit;
+        debug!("{} collection of the generational heap", if full { "Full" } else { "Minor" });
+
+        let dirty_cards = if full { Vec::new() } else { self.dirty_cards() };
+        let old = mem::take(&mut self.old);
+        let start = if full { 0 } else { old.len() };
+        let mut evacuation = Evacuation {
+            nursery: mem::take(&mut self.nursery).into_iter().map(Some).collect(),
+            old: None,
+            forwarding: HashMap::new(),
+            to: Vec::new(),
+            size: 0,
+        };
+        if full {
+            evacuation.old = Some(old.into_iter().map(Some).collect());
+        } else {
+            evacuation.to = old;
+        }
+
+        for root in roots.iter_mut() {
+            **root = evacuation.evacuate(**root);
+        }
+        // Old objects on dirty cards may refer to objects of the nursery
+        for card in dirty_cards {
+            for index in card * CARD_OBJECTS..start.min((card + 1) * CARD_OBJECTS) {
+                evacuation.update(index);
+            }
+        }
+        evacuation.scan(start);
+
+        // No object of the old space refers to the empty nursery anymore
+        self.old = evacuation.to;
+        self.old_size = if full { evacuation.size } else { self.old_size + evacuation.size };
+        self.nursery_size = 0;
+        self.cards = vec![false; card_count(self.old.len())];
+        before - self.size()
+    }
+}
+
+/// Copy of the objects reachable from the roots into the old space, following Cheney's algorithm.
+struct Evacuation {
+    /// Objects of the nursery, `None` once copied.
+    nursery: Vec<Option<Object>>,
+    /// Objects of the old space in a full collection, `None` once copied.
+    old: Option<Vec<Option<Object>>>,
+    /// References of the objects copied, by their reference before the collection.
+    forwarding: HashMap<Reference, Reference>,
+    /// Old space the objects are copied into.
+    to: Vec<Object>,
+    /// Total size of the objects copied, in bytes.
+    size: usize,
+}
+
+impl Evacuation {
+    /// Copy the object `reference` unless it already was or stays in place, returning its new reference.
+    fn evacuate(&mut self, reference: Reference) -> Reference {
+        if let Some(&forwarded) = self.forwarding.get(&reference) {
+            return forwarded;
+        }
+
+        let space = if reference.0 & NURSERY != 0 {
+            Some(&mut self.nursery)
+        } else {
+            self.old.as_mut()
+        };
+        match space.and_then(|space| space.get_mut(reference.0 >> 1)).and_then(Option::take) {
+            Some(object) => {
+                self.size += object.size();
+                self.to.push(object);
+                let forwarded = old_reference(self.to.len() - 1);
+                self.forwarding.insert(reference, forwarded);
+                forwarded
+            }
+            None => reference,
+        }
+    }
+
+    /// Evacuate the objects the copied object at `index` refers to, updating its references.
+    fn update(&mut self, index: usize) {
+        let references = self.to[index].references();
+        let forwarded: Vec<_> = references.into_iter().map(|reference| self.evacuate(reference)).collect();
+        for (reference, forwarded) in self.to[index].references_mut().into_iter().zip(forwarded) {
+            *reference = forwarded;
+        }
+    }
+
+    /// Update the copied objects from `start` on, including those copied meanwhile.
+    fn scan(&mut self, start: usize) {
+        let mut index = start;
+        while index < self.to.len() {
+            self.update(index);
+            index += 1;
+        }
+    }
+}
+
+fn old_reference(index: usize) -> Reference {
+    Reference(index << 1)
+}
+
+/// Number of cards covering an old space of `len` objects.
+fn card_count(len: usize) -> usize {
+    len.div_ceil(CARD_OBJECTS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::*;
+    use super::super::Collector;
+    use super::Generational;
+
+    #[test]
+    fn minor_collections_promote_reachable_objects() {
+        let mut heap = Generational::new(1 << 20);
+        let young = heap.insert(tagged(1));
+        let garbage = heap.insert(tagged(2));
+        let size = heap.get(garbage).unwrap().size();
+
+        let mut root = young;
+        assert_eq!(heap.collect(&mut [&mut root], false), size);
+        assert_ne!(root, young);
+        assert_eq!(tag(&heap, root), Some(1));
+        assert_eq!(heap.nursery_size, 0);
+    }
+
+    #[test]
+    fn card_marked_references_survive_minor_collections() {
+        let mut heap = Generational::new(1 << 20);
+        let mut holder = heap.insert(objects(1));
+        heap.collect(&mut [&mut holder], false);
+
+        // Only the old holder refers to the young object
+        let young = heap.insert(tagged(1));
+        store(&mut heap, holder, 0, young);
+        let old = holder;
+        heap.collect(&mut [&mut holder], false);
+
+        assert_eq!(holder, old);
+        let promoted = element(&heap, holder, 0).unwrap();
+        assert_ne!(promoted, young);
+        assert_eq!(tag(&heap, promoted), Some(1));
+    }
+
+    #[test]
+    fn full_collections_reclaim_old_objects() {
+        let mut heap = Generational::new(1 << 20);
+        let mut root = heap.insert(objects(1));
+        let mut child = heap.insert(tagged(1));
+        heap.collect(&mut [&mut root, &mut child], false);
+        let size = heap.size();
+        let child_size = heap.get(child).unwrap().size();
+
+        // Minor collections leave the old space alone
+        assert_eq!(heap.collect(&mut [&mut root], false), 0);
+        assert_eq!(heap.size(), size);
+
+        assert_eq!(heap.collect(&mut [&mut root], true), child_size);
+        assert_eq!(heap.size(), size - child_size);
+        assert!(heap.get(root).is_some());
+    }
+
+    #[test]
+    fn out_of_memory() {
+        runs_out_of_memory(Box::new(Generational::new(1 << 16)));
+    }
+
+    #[test]
+    fn allocations_beyond_the_limit() {
+        allocates_more_than_the_limit(Box::new(Generational::new(1 << 20)));
+    }
+}
@@ -0,0 +1,142 @@
+use super::{Collector, Object, Reference};
+
+/// Non-moving collector, marking the objects reachable from the roots and then sweeping the others into
+/// a free list.
+#[derive(Debug)]
+pub struct MarkSweep {
+    /// Objects by reference, `None` where they were reclaimed.
+    objects: Vec<Option<Object>>,
+    /// Slots of `objects` free to reuse.
+    free: Vec<usize>,
+    size: usize,
+    limit: usize,
+}
+
+impl MarkSweep {
+    /// Empty heap holding at most `limit` bytes of objects.
+    pub fn new(limit: usize) -> MarkSweep {
+        MarkSweep {
+            objects: Vec::new(),
+            free: Vec::new(),
+            size: 0,
+            limit: limit,
+        }
+    }
+
+    /// Find the objects reachable from `roots`.
+    fn mark(&self, roots: &[&mut Reference]) -> Vec<bool> {
+        let mut marked = vec![false; self.objects.len()];
+        let mut pending: Vec<Reference> = roots.iter().map(|reference| **reference).collect();
+
+        while let Some(reference) = pending.pop() {
+            if marked[reference.0] {
+                continue;
+            }
+            marked[reference.0] = true;
+            if let Some(object) = self.get(reference) {
+                pending.extend(object.references());
+            }
+        }
+        marked
+    }
+}
+
+impl Collector for MarkSweep {
+    fn name(&self) -> &'static str {
+        "mark-sweep"
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn limit(&self) -> usize {
+        self.limit
+    }
+
+    fn has_room(&self, size: usize) -> bool {
+        self.size.checked_add(size).is_some_and(|size| size <= self.limit)
+    }
+
+    fn insert(&mut self, object: Object) -> Reference {
+        self.size += object.size();
+        match self.free.pop() {
+            Some(slot) => {
+                self.objects[slot] = Some(object);
+                Reference(slot)
+            }
+            None => {
+                self.objects.push(Some(object));
+                Reference(self.objects.len() - 1)
+            }
+        }
+    }
+
+    fn get(&self, reference: Reference) -> Option<&Object> {
+        self.objects.get(reference.0).and_then(|object| object.as_ref())
+    }
+
+    fn get_mut(&mut self, reference: Reference) -> Option<&mut Object> {
+        self.objects.get_mut(reference.0).and_then(|object| object.as_mut())
+    }
+
+    fn collect(&mut self, roots: &mut [&mut Reference], _full: bool) -> usize {
+        let marked = self.mark(roots);
+
+        let before = self.size;
+        for (slot, object) in self.objects.iter_mut().enumerate() {
+            if !marked[slot] && object.is_some() {
+                self.size -= object.take().map_or(0, |object| object.size());
+                self.free.push(slot);
+            }
+        }
+        before - self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::*;
+    use super::super::Collector;
+    use super::MarkSweep;
+
+    #[test]
+    fn reclaims_unreachable_objects() {
+        let mut heap = MarkSweep::new(1 << 20);
+        let root = heap.insert(objects(2));
+        let reachable = heap.insert(tagged(1));
+        store(&mut heap, root, 0, reachable);
+        let unreachable = heap.insert(tagged(2));
+        // A cycle unreachable from the roots
+        let (first, second) = (heap.insert(objects(1)), heap.insert(objects(1)));
+        store(&mut heap, first, 0, second);
+        store(&mut heap, second, 0, first);
+
+        let size = heap.size();
+        let garbage: usize = [unreachable, first, second].iter().map(|&object| heap.get(object).unwrap().size()).sum();
+        let mut roots = root;
+        assert_eq!(heap.collect(&mut [&mut roots], true), garbage);
+        assert_eq!(heap.size(), size - garbage);
+
+        // Objects don't move
+        assert_eq!(roots, root);
+        assert_eq!(element(&heap, root, 0), Some(reachable));
+        assert_eq!(tag(&heap, reachable), Some(1));
+        assert!(heap.get(unreachable).is_none() && heap.get(first).is_none() && heap.get(second).is_none());
+
+        // Their slots are reused
+        let reused = heap.insert(tagged(3));
+        assert!([unreachable, first, second].contains(&reused));
+        assert_eq!(tag(&heap, reused), Some(3));
+    }
+
+    #[test]
+    fn out_of_memory() {
+        runs_out_of_memory(Box::new(MarkSweep::new(1 << 16)));
+    }
+
+    #[test]
+    fn allocations_beyond_the_limit() {
+        allocates_more_than_the_limit(Box::new(MarkSweep::new(1 << 20)));
+    }
+}
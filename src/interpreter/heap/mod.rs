@@ -0,0 +1,242 @@
+//! Heap holding the objects and arrays allocated by the interpreter (JVMS §2.5.3), reclaimed by a
+//! garbage collector.
+
+pub mod generational;
+pub mod mark_sweep;
+pub mod object;
+
+pub use self::generational::Generational;
+pub use self::mark_sweep::MarkSweep;
+pub use self::object::{array_type, ArrayData, Object};
+
+use std::fmt;
+
+/// Default maximum size of the heap, in bytes.
+pub const DEFAULT_LIMIT: usize = 64 * 1024 * 1024;
+
+/// Reference to an object in the heap, whose meaning is up to the collector that allocated it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Reference(usize);
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "@{:x}", self.0)
+    }
+}
+
+/// Strategy to allocate objects and reclaim those that can't be reached anymore.
+pub trait Collector: fmt::Debug {
+    /// Name of the collector, as `rjvm --gc` takes it.
+    fn name(&self) -> &'static str;
+
+    /// Total size of the objects, in bytes.
+    fn size(&self) -> usize;
+
+    /// Maximum total size of the objects, in bytes.
+    fn limit(&self) -> usize;
+
+    /// Whether an object of `size` bytes can be inserted without collecting garbage.
+    fn has_room(&self, size: usize) -> bool;
+
+    /// Add `object` to the heap, regardless of the limit; check it with `has_room` first.
+    fn insert(&mut self, object: Object) -> Reference;
+
+    fn get(&self, reference: Reference) -> Option<&Object>;
+
+    fn get_mut(&mut self, reference: Reference) -> Option<&mut Object>;
+
+    /// Record that a reference was stored in `object`.
+    fn write_barrier(&mut self, _object: Reference) {}
+
+    /// Reclaim the objects that can't be reached from `roots`, returning the number of bytes freed.
+    ///
+    /// Objects may move, in which case the roots are updated. Unless `full` is set, only part of the
+    /// garbage may be reclaimed.
+    fn collect(&mut self, roots: &mut [&mut Reference], full: bool) -> usize;
+}
+
+/// Objects allocated so far, managed by a collector.
+#[derive(Debug)]
+pub struct Heap {
+    collector: Box<dyn Collector>,
+    /// Global references held by native code, which keep their objects alive.
+    handles: Vec<Option<Reference>>,
+}
+
+impl Heap {
+    pub fn new(collector: Box<dyn Collector>) -> Heap {
+        Heap {
+            collector: collector,
+            handles: Vec::new(),
+        }
+    }
+
+    pub fn collector(&self) -> &dyn Collector {
+        &*self.collector
+    }
+
+    pub fn has_room(&self, size: usize) -> bool {
+        self.collector.has_room(size)
+    }
+
+    pub fn insert(&mut self, object: Object) -> Reference {
+        self.collector.insert(object)
+    }
+
+    pub fn get(&self, reference: Reference) -> Option<&Object> {
+        self.collector.get(reference)
+    }
+
+    pub fn get_mut(&mut self, reference: Reference) -> Option<&mut Object> {
+        self.collector.get_mut(reference)
+    }
+
+    /// Record that a reference was stored in `object`, which must follow every such store.
+    pub fn write_barrier(&mut self, object: Reference) {
+        self.collector.write_barrier(object);
+    }
+
+    /// Keep the object `reference` alive until `remove_handle` is called with the returned handle.
+    pub fn add_handle(&mut self, reference: Reference) -> usize {
+        match self.handles.iter().position(|handle| handle.is_none()) {
+            Some(handle) => {
+                self.handles[handle] = Some(reference);
+                handle
+            }
+            None => {
+                self.handles.push(Some(reference));
+                self.handles.len() - 1
+            }
+        }
+    }
+
+    /// Object a handle returned by `add_handle` refers to.
+    pub fn handle(&self, handle: usize) -> Option<Reference> {
+        self.handles.get(handle).and_then(|&reference| reference)
+    }
+
+    pub fn remove_handle(&mut self, handle: usize) {
+        if let Some(reference) = self.handles.get_mut(handle) {
+            *reference = None;
+        }
+    }
+
+    /// Reclaim the objects that can't be reached from `roots` or the handles, returning the number of
+    /// bytes freed.
+    pub fn collect<'a>(&'a mut self, mut roots: Vec<&'a mut Reference>, full: bool) -> usize {
+        roots.extend(self.handles.iter_mut().filter_map(Option::as_mut));
+        let freed = self.collector.collect(&mut roots, full);
+        debug!("{} collection freed {} bytes, {} of {} bytes used",
+               self.collector.name(), freed, self.collector.size(), self.collector.limit());
+        freed
+    }
+}
+
+impl Default for Heap {
+    fn default() -> Heap {
+        Heap::new(Box::new(MarkSweep::new(DEFAULT_LIMIT)))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use classfile::descriptor::{BaseType, FieldType};
+    use classpath::Classpath;
+    use interpreter::{Interpreter, Value};
+    use super::*;
+
+    fn int_array_type() -> FieldType {
+        FieldType::Array(1, Box::new(FieldType::Base(BaseType::Int)))
+    }
+
+    fn object_array_type() -> FieldType {
+        FieldType::Array(1, Box::new(FieldType::Object("java/lang/Object".to_owned())))
+    }
+
+    /// `int[]` holding only `tag`, to tell objects apart once they moved.
+    pub fn tagged(tag: i32) -> Object {
+        let mut object = Object::array(int_array_type(), 1);
+        set(&mut object, 0, Value::Int(tag));
+        object
+    }
+
+    pub fn tag(collector: &dyn Collector, reference: Reference) -> Option<i32> {
+        match collector.get(reference) {
+            Some(Object::Array { data: ArrayData::Int(data), .. }) => data.first().cloned(),
+            _ => None,
+        }
+    }
+
+    /// `Object[]` of `length` null elements.
+    pub fn objects(length: usize) -> Object {
+        Object::array(object_array_type(), length)
+    }
+
+    fn set(object: &mut Object, index: usize, value: Value) {
+        match *object {
+            Object::Array { ref mut data, .. } => assert!(data.set(index, value)),
+            Object::Instance { .. } => panic!("Not an array"),
+        }
+    }
+
+    /// Store `value` at `index` in the `Object[]` array, through the write barrier.
+    pub fn store(collector: &mut dyn Collector, array: Reference, index: usize, value: Reference) {
+        set(collector.get_mut(array).unwrap(), index, Value::Reference(value));
+        collector.write_barrier(array);
+    }
+
+    pub fn element(collector: &dyn Collector, array: Reference, index: usize) -> Option<Reference> {
+        match collector.get(array) {
+            Some(Object::Array { data, .. }) => match data.get(index) {
+                Some(Value::Reference(reference)) => Some(reference),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Allocate 200k arrays, keeping the latest ones alive from an array that outlives them.
+    pub fn allocates_more_than_the_limit(collector: Box<dyn Collector>) {
+        let classpath = Classpath::new();
+        let mut interpreter = Interpreter::new(&classpath, Heap::new(collector));
+        let holder = interpreter.new_array(object_array_type(), 64).unwrap();
+        let holder = interpreter.heap().add_handle(holder);
+
+        for i in 0..200_000 {
+            let array = interpreter.new_array(int_array_type(), 16).unwrap();
+            let heap = interpreter.heap();
+            set(heap.get_mut(array).unwrap(), 0, Value::Int(i));
+            let holder = heap.handle(holder).unwrap();
+            store(&mut *heap.collector, holder, i as usize % 64, array);
+        }
+
+        let heap = interpreter.heap();
+        assert!(heap.collector().size() <= heap.collector().limit());
+        let holder = heap.handle(holder).unwrap();
+        for index in 0..64 {
+            let array = element(heap.collector(), holder, index).unwrap();
+            assert_eq!(tag(heap.collector(), array), Some(200_000 - 64 + index as i32));
+        }
+    }
+
+    /// Allocate arrays kept alive by handles until the heap is full.
+    pub fn runs_out_of_memory(collector: Box<dyn Collector>) {
+        let classpath = Classpath::new();
+        let mut interpreter = Interpreter::new(&classpath, Heap::new(collector));
+        let mut handles = Vec::new();
+        let err = loop {
+            match interpreter.new_array(int_array_type(), 1000) {
+                Ok(array) => handles.push(interpreter.heap().add_handle(array)),
+                Err(err) => break err,
+            }
+        };
+        assert_eq!(err.java_exception(), Some(("java/lang/OutOfMemoryError", "Java heap space")));
+        assert!(!handles.is_empty());
+
+        // Releasing the arrays makes room again
+        for handle in handles {
+            interpreter.heap().remove_handle(handle);
+        }
+        interpreter.new_array(int_array_type(), 1000).unwrap();
+    }
+}
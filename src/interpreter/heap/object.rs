@@ -0,0 +1,202 @@
+use classfile::descriptor::{BaseType, FieldType};
+use std::mem;
+use std::sync::Arc;
+use super::super::class::Class;
+use super::super::value::Value;
+use super::Reference;
+
+/// Bytes taken by the header of every object: its class and its lock.
+const HEADER_SIZE: usize = 16;
+
+/// Bytes taken by a reference in an object or array.
+const REFERENCE_SIZE: usize = 8;
+
+/// Elements of an array, stored as compactly as their type allows.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArrayData {
+    /// `boolean` and `byte` elements.
+    Byte(Vec<i8>),
+    Char(Vec<u16>),
+    Short(Vec<i16>),
+    Int(Vec<i32>),
+    Long(Vec<i64>),
+    Float(Vec<f32>),
+    Double(Vec<f64>),
+    Reference(Vec<Value>),
+}
+
+impl ArrayData {
+    /// Array of `length` elements of type `component`, with their default value.
+    pub fn new(component: &FieldType, length: usize) -> ArrayData {
+        match *component {
+            FieldType::Base(BaseType::Boolean) | FieldType::Base(BaseType::Byte) => ArrayData::Byte(vec![0; length]),
+            FieldType::Base(BaseType::Char) => ArrayData::Char(vec![0; length]),
+            FieldType::Base(BaseType::Short) => ArrayData::Short(vec![0; length]),
+            FieldType::Base(BaseType::Int) => ArrayData::Int(vec![0; length]),
+            FieldType::Base(BaseType::Long) => ArrayData::Long(vec![0; length]),
+            FieldType::Base(BaseType::Float) => ArrayData::Float(vec![0.0; length]),
+            FieldType::Base(BaseType::Double) => ArrayData::Double(vec![0.0; length]),
+            FieldType::Object(..) | FieldType::Array(..) => ArrayData::Reference(vec![Value::Null; length]),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match *self {
+            ArrayData::Byte(ref data) => data.len(),
+            ArrayData::Char(ref data) => data.len(),
+            ArrayData::Short(ref data) => data.len(),
+            ArrayData::Int(ref data) => data.len(),
+            ArrayData::Long(ref data) => data.len(),
+            ArrayData::Float(ref data) => data.len(),
+            ArrayData::Double(ref data) => data.len(),
+            ArrayData::Reference(ref data) => data.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Element at `index`, widened to an operand stack value.
+    pub fn get(&self, index: usize) -> Option<Value> {
+        match *self {
+            ArrayData::Byte(ref data) => data.get(index).map(|&value| Value::Int(value as i32)),
+            ArrayData::Char(ref data) => data.get(index).map(|&value| Value::Int(value as i32)),
+            ArrayData::Short(ref data) => data.get(index).map(|&value| Value::Int(value as i32)),
+            ArrayData::Int(ref data) => data.get(index).map(|&value| Value::Int(value)),
+            ArrayData::Long(ref data) => data.get(index).map(|&value| Value::Long(value)),
+            ArrayData::Float(ref data) => data.get(index).map(|&value| Value::Float(value)),
+            ArrayData::Double(ref data) => data.get(index).map(|&value| Value::Double(value)),
+            ArrayData::Reference(ref data) => data.get(index).cloned(),
+        }
+    }
+
+    /// Store `value` at `index`, narrowing it to the type of the elements. Returns `false` if the index is
+    /// out of bounds or the value of the wrong kind.
+    pub fn set(&mut self, index: usize, value: Value) -> bool {
+        if index >= self.len() {
+            return false;
+        }
+        match (self, value) {
+            (&mut ArrayData::Byte(ref mut data), Value::Int(value)) => data[index] = value as i8,
+            (&mut ArrayData::Char(ref mut data), Value::Int(value)) => data[index] = value as u16,
+            (&mut ArrayData::Short(ref mut data), Value::Int(value)) => data[index] = value as i16,
+            (&mut ArrayData::Int(ref mut data), Value::Int(value)) => data[index] = value,
+            (&mut ArrayData::Long(ref mut data), Value::Long(value)) => data[index] = value,
+            (&mut ArrayData::Float(ref mut data), Value::Float(value)) => data[index] = value,
+            (&mut ArrayData::Double(ref mut data), Value::Double(value)) => data[index] = value,
+            (&mut ArrayData::Reference(ref mut data), value @ Value::Null) |
+            (&mut ArrayData::Reference(ref mut data), value @ Value::Reference(..)) => data[index] = value,
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Object allocated in the heap: an instance of a class, or an array.
+#[derive(Debug)]
+pub enum Object {
+    Instance {
+        class: Arc<Class>,
+        /// Values of the instance fields, by slot.
+        fields: Vec<Value>,
+    },
+    Array {
+        /// Type of the array itself, e.g. `[I`.
+        ty: FieldType,
+        data: ArrayData,
+    },
+}
+
+impl Object {
+    /// Instance of `class` with its fields set to their default value.
+    pub fn instance(class: Arc<Class>) -> Object {
+        let fields = class.instance_layout.iter().map(Value::default_for).collect();
+        Object::Instance {
+            class: class,
+            fields: fields,
+        }
+    }
+
+    /// Array of type `ty` with `length` elements set to their default value.
+    pub fn array(ty: FieldType, length: usize) -> Object {
+        let data = ArrayData::new(&ty.component().expect("Array of a non-array type"), length);
+        Object::Array {
+            ty: ty,
+            data: data,
+        }
+    }
+
+    /// Type of the object, as a field descriptor would give it.
+    pub fn ty(&self) -> FieldType {
+        match *self {
+            Object::Instance { ref class, .. } => FieldType::Object(class.name.clone()),
+            Object::Array { ref ty, .. } => ty.clone(),
+        }
+    }
+
+    /// Number of bytes the object takes in the heap.
+    pub fn size(&self) -> usize {
+        match *self {
+            Object::Instance { ref class, .. } => Object::instance_size(class),
+            Object::Array { ref ty, ref data } => {
+                Object::array_size(&ty.component().expect("Array of a non-array type"), data.len())
+            }
+        }
+    }
+
+    /// Size of an instance of `class`, laid out after the descriptors of its fields.
+    pub fn instance_size(class: &Class) -> usize {
+        HEADER_SIZE + class.instance_layout.iter().map(value_size).sum::<usize>()
+    }
+
+    /// Size of an array of `length` elements of type `component`, saturating rather than overflowing.
+    pub fn array_size(component: &FieldType, length: usize) -> usize {
+        length.saturating_mul(value_size(component)).saturating_add(HEADER_SIZE + mem::size_of::<i32>())
+    }
+
+    /// Objects this object references, through its fields or elements.
+    pub fn references(&self) -> Vec<Reference> {
+        let values = match *self {
+            Object::Instance { ref fields, .. } => fields,
+            Object::Array { data: ArrayData::Reference(ref elements), .. } => elements,
+            Object::Array { .. } => return Vec::new(),
+        };
+        values.iter().filter_map(|value| match *value {
+            Value::Reference(reference) => Some(reference),
+            _ => None,
+        }).collect()
+    }
+
+    /// References held by this object, to update them when the objects they refer to move.
+    pub fn references_mut(&mut self) -> Vec<&mut Reference> {
+        let values = match *self {
+            Object::Instance { ref mut fields, .. } => fields,
+            Object::Array { data: ArrayData::Reference(ref mut elements), .. } => elements,
+            Object::Array { .. } => return Vec::new(),
+        };
+        values.iter_mut().filter_map(|value| match *value {
+            Value::Reference(ref mut reference) => Some(reference),
+            _ => None,
+        }).collect()
+    }
+}
+
+/// Bytes taken by a value of type `ty` in an object or array.
+fn value_size(ty: &FieldType) -> usize {
+    match *ty {
+        FieldType::Base(BaseType::Boolean) | FieldType::Base(BaseType::Byte) => 1,
+        FieldType::Base(BaseType::Char) | FieldType::Base(BaseType::Short) => 2,
+        FieldType::Base(BaseType::Int) | FieldType::Base(BaseType::Float) => 4,
+        FieldType::Base(BaseType::Long) | FieldType::Base(BaseType::Double) => 8,
+        FieldType::Object(..) | FieldType::Array(..) => REFERENCE_SIZE,
+    }
+}
+
+/// Type of the arrays whose elements are of type `component`.
+pub fn array_type(component: FieldType) -> FieldType {
+    match component {
+        FieldType::Array(dimensions, element) => FieldType::Array(dimensions + 1, element),
+        component => FieldType::Array(1, Box::new(component)),
+    }
+}
@@ -0,0 +1,832 @@
+use classfile::flags;
+use classfile::bytecode::{ArrayType, Instruction};
+use classfile::constant::ConstantPoolEntry;
+use classfile::descriptor::{BaseType, FieldType};
+use std::sync::Arc;
+use super::{Action, Interpreter};
+use super::class::Class;
+use super::constant_pool;
+use super::error::*;
+use super::frame::Frame;
+use super::heap::{self, ArrayData, Object, Reference};
+use super::value::Value;
+
+/// Execute the instruction `insn` of the innermost frame, `next` being the offset of the following
+/// instruction.
+///
+/// The frame is left at the instruction if it fails, so that the error can be located, and while the
+/// methods it calls run, so that its exception handlers cover them.
+pub fn execute(interp: &mut Interpreter, insn: &Instruction, next: usize) -> Result<Action> {
+    let (class, pc) = {
+        let frame = interp.frames.last().expect("Executing without a frame");
+        (frame.class.clone(), frame.pc)
+    };
+    let mut step = Step {
+        interp: interp,
+        class: class,
+        pc: pc,
+        next: next,
+    };
+
+    let action = try!(step.execute(insn));
+    match action {
+        Action::Invoke(..) => {}
+        _ => step.frame().pc = step.next,
+    }
+    Ok(action)
+}
+
+struct Step<'a, 'b: 'a> {
+    interp: &'a mut Interpreter<'b>,
+    /// Class of the method being executed.
+    class: Arc<Class>,
+    pc: usize,
+    next: usize,
+}
+
+impl<'a, 'b> Step<'a, 'b> {
+    fn execute(&mut self, insn: &Instruction) -> Result<Action> {
+        match *insn {
+            Instruction::Nop => {}
+            Instruction::AconstNull => self.frame().push(Value::Null),
+            Instruction::IconstM1 => self.frame().push(Value::Int(-1)),
+            Instruction::Iconst0 => self.frame().push(Value::Int(0)),
+            Instruction::Iconst1 => self.frame().push(Value::Int(1)),
+            Instruction::Iconst2 => self.frame().push(Value::Int(2)),
+            Instruction::Iconst3 => self.frame().push(Value::Int(3)),
+            Instruction::Iconst4 => self.frame().push(Value::Int(4)),
+            Instruction::Iconst5 => self.frame().push(Value::Int(5)),
+            Instruction::Lconst0 => self.frame().push(Value::Long(0)),
+            Instruction::Lconst1 => self.frame().push(Value::Long(1)),
+            Instruction::Fconst0 => self.frame().push(Value::Float(0.0)),
+            Instruction::Fconst1 => self.frame().push(Value::Float(1.0)),
+            Instruction::Fconst2 => self.frame().push(Value::Float(2.0)),
+            Instruction::Dconst0 => self.frame().push(Value::Double(0.0)),
+            Instruction::Dconst1 => self.frame().push(Value::Double(1.0)),
+            Instruction::Bipush(value) => self.frame().push(Value::Int(value as i32)),
+            Instruction::Sipush(value) => self.frame().push(Value::Int(value as i32)),
+            Instruction::Ldc(index) | Instruction::LdcW(index) | Instruction::Ldc2W(index) => {
+                let class = self.class.clone();
+                let pool = &class.classfile.constant_pool;
+                let value = match pool.get(index) {
+                    Some(ConstantPoolEntry::Integer(info)) => Value::Int(info.value()),
+                    Some(ConstantPoolEntry::Float(info)) => Value::Float(info.value()),
+                    Some(ConstantPoolEntry::Long(info)) => Value::Long(info.value()),
+                    Some(ConstantPoolEntry::Double(info)) => Value::Double(info.value()),
+                    Some(ConstantPoolEntry::String(info)) => {
+                        let value = try!(info.value(pool).ok_or(ErrorKind::BadConstant(self.pc, index)));
+                        Value::Reference(try!(self.interp.intern(value)))
+                    }
+                    Some(ConstantPoolEntry::Class(info)) => {
+                        try!(self.resolve_type(index));
+                        let name = try!(info.name(pool).ok_or(ErrorKind::BadConstant(self.pc, index)));
+                        Value::Reference(try!(self.interp.class_object(name)))
+                    }
+                    Some(..) => bail!(ErrorKind::Unsupported(self.pc, format!("loading constant #{}", index))),
+                    None => bail!(ErrorKind::BadConstant(self.pc, index)),
+                };
+                self.frame().push(value);
+            }
+
+            Instruction::Iload(index) | Instruction::Lload(index) | Instruction::Fload(index) |
+            Instruction::Dload(index) | Instruction::Aload(index) => try!(self.load(index as usize)),
+            Instruction::Iload0 | Instruction::Lload0 | Instruction::Fload0 | Instruction::Dload0 |
+            Instruction::Aload0 => try!(self.load(0)),
+            Instruction::Iload1 | Instruction::Lload1 | Instruction::Fload1 | Instruction::Dload1 |
+            Instruction::Aload1 => try!(self.load(1)),
+            Instruction::Iload2 | Instruction::Lload2 | Instruction::Fload2 | Instruction::Dload2 |
+            Instruction::Aload2 => try!(self.load(2)),
+            Instruction::Iload3 | Instruction::Lload3 | Instruction::Fload3 | Instruction::Dload3 |
+            Instruction::Aload3 => try!(self.load(3)),
+
+            Instruction::Istore(index) | Instruction::Lstore(index) | Instruction::Fstore(index) |
+            Instruction::Dstore(index) | Instruction::Astore(index) => try!(self.store(index as usize)),
+            Instruction::Istore0 | Instruction::Lstore0 | Instruction::Fstore0 | Instruction::Dstore0 |
+            Instruction::Astore0 => try!(self.store(0)),
+            Instruction::Istore1 | Instruction::Lstore1 | Instruction::Fstore1 | Instruction::Dstore1 |
+            Instruction::Astore1 => try!(self.store(1)),
+            Instruction::Istore2 | Instruction::Lstore2 | Instruction::Fstore2 | Instruction::Dstore2 |
+            Instruction::Astore2 => try!(self.store(2)),
+            Instruction::Istore3 | Instruction::Lstore3 | Instruction::Fstore3 | Instruction::Dstore3 |
+            Instruction::Astore3 => try!(self.store(3)),
+
+            Instruction::Iaload | Instruction::Laload | Instruction::Faload | Instruction::Daload |
+            Instruction::Aaload | Instruction::Baload | Instruction::Caload | Instruction::Saload => {
+                let index = try!(self.frame().pop_int());
+                let array = try!(self.pop_object());
+                let value = {
+                    let data = try!(self.array(array)).1;
+                    let index = try!(element_index(data, index));
+                    data.get(index).expect("Array index out of bounds")
+                };
+                self.frame().push(value);
+            }
+            Instruction::Iastore | Instruction::Lastore | Instruction::Fastore | Instruction::Dastore |
+            Instruction::Aastore | Instruction::Bastore | Instruction::Castore | Instruction::Sastore => {
+                let value = try!(self.frame().pop());
+                let index = try!(self.frame().pop_int());
+                let array = try!(self.pop_object());
+                try!(self.array_store(array, index, value));
+            }
+
+            Instruction::Pop => {
+                try!(self.frame().pop());
+            }
+            Instruction::Pop2 => {
+                if !try!(self.frame().pop()).is_category2() {
+                    try!(self.frame().pop());
+                }
+            }
+            Instruction::Dup => {
+                let value = try!(self.frame().pop());
+                self.push_all(&[value, value]);
+            }
+            Instruction::DupX1 => {
+                let (value1, value2) = (try!(self.frame().pop()), try!(self.frame().pop()));
+                self.push_all(&[value1, value2, value1]);
+            }
+            Instruction::DupX2 => {
+                let (value1, value2) = (try!(self.frame().pop()), try!(self.frame().pop()));
+                if value2.is_category2() {
+                    self.push_all(&[value1, value2, value1]);
+                } else {
+                    let value3 = try!(self.frame().pop());
+                    self.push_all(&[value1, value3, value2, value1]);
+                }
+            }
+            Instruction::Dup2 => {
+                let value1 = try!(self.frame().pop());
+                if value1.is_category2() {
+                    self.push_all(&[value1, value1]);
+                } else {
+                    let value2 = try!(self.frame().pop());
+                    self.push_all(&[value2, value1, value2, value1]);
+                }
+            }
+            Instruction::Dup2X1 => {
+                let (value1, value2) = (try!(self.frame().pop()), try!(self.frame().pop()));
+                if value1.is_category2() {
+                    self.push_all(&[value1, value2, value1]);
+                } else {
+                    let value3 = try!(self.frame().pop());
+                    self.push_all(&[value2, value1, value3, value2, value1]);
+                }
+            }
+            Instruction::Dup2X2 => {
+                let (value1, value2) = (try!(self.frame().pop()), try!(self.frame().pop()));
+                match (value1.is_category2(), value2.is_category2()) {
+                    (true, true) => self.push_all(&[value1, value2, value1]),
+                    (true, false) => {
+                        let value3 = try!(self.frame().pop());
+                        self.push_all(&[value1, value3, value2, value1]);
+                    }
+                    _ => {
+                        let value3 = try!(self.frame().pop());
+                        if value3.is_category2() {
+                            self.push_all(&[value2, value1, value3, value2, value1]);
+                        } else {
+                            let value4 = try!(self.frame().pop());
+                            self.push_all(&[value2, value1, value4, value3, value2, value1]);
+                        }
+                    }
+                }
+            }
+            Instruction::Swap => {
+                let (value1, value2) = (try!(self.frame().pop()), try!(self.frame().pop()));
+                self.push_all(&[value1, value2]);
+            }
+
+            Instruction::Iadd => try!(self.int_op(|a, b| Ok(a.wrapping_add(b)))),
+            Instruction::Isub => try!(self.int_op(|a, b| Ok(a.wrapping_sub(b)))),
+            Instruction::Imul => try!(self.int_op(|a, b| Ok(a.wrapping_mul(b)))),
+            Instruction::Idiv => try!(self.int_op(|a, b| if b == 0 { Err(division_by_zero()) } else { Ok(a.wrapping_div(b)) })),
+            Instruction::Irem => try!(self.int_op(|a, b| if b == 0 { Err(division_by_zero()) } else { Ok(a.wrapping_rem(b)) })),
+            Instruction::Iand => try!(self.int_op(|a, b| Ok(a & b))),
+            Instruction::Ior => try!(self.int_op(|a, b| Ok(a | b))),
+            Instruction::Ixor => try!(self.int_op(|a, b| Ok(a ^ b))),
+            Instruction::Ishl => try!(self.int_op(|a, b| Ok(a.wrapping_shl(b as u32)))),
+            Instruction::Ishr => try!(self.int_op(|a, b| Ok(a.wrapping_shr(b as u32)))),
+            Instruction::Iushr => try!(self.int_op(|a, b| Ok((a as u32).wrapping_shr(b as u32) as i32))),
+            Instruction::Ineg => {
+                let value = try!(self.frame().pop_int());
+                self.frame().push(Value::Int(value.wrapping_neg()));
+            }
+            Instruction::Ladd => try!(self.long_op(|a, b| Ok(a.wrapping_add(b)))),
+            Instruction::Lsub => try!(self.long_op(|a, b| Ok(a.wrapping_sub(b)))),
+            Instruction::Lmul => try!(self.long_op(|a, b| Ok(a.wrapping_mul(b)))),
+            Instruction::Ldiv => try!(self.long_op(|a, b| if b == 0 { Err(division_by_zero()) } else { Ok(a.wrapping_div(b)) })),
+            Instruction::Lrem => try!(self.long_op(|a, b| if b == 0 { Err(division_by_zero()) } else { Ok(a.wrapping_rem(b)) })),
+            Instruction::Land => try!(self.long_op(|a, b| Ok(a & b))),
+            Instruction::Lor => try!(self.long_op(|a, b| Ok(a | b))),
+            Instruction::Lxor => try!(self.long_op(|a, b| Ok(a ^ b))),
+            Instruction::Lshl | Instruction::Lshr | Instruction::Lushr => {
+                let shift = try!(self.frame().pop_int()) as u32;
+                let value = try!(self.frame().pop_long());
+                let result = match *insn {
+                    Instruction::Lshl => value.wrapping_shl(shift),
+                    Instruction::Lshr => value.wrapping_shr(shift),
+                    _ => (value as u64).wrapping_shr(shift) as i64,
+                };
+                self.frame().push(Value::Long(result));
+            }
+            Instruction::Lneg => {
+                let value = try!(self.frame().pop_long());
+                self.frame().push(Value::Long(value.wrapping_neg()));
+            }
+            Instruction::Fadd => try!(self.float_op(|a, b| a + b)),
+            Instruction::Fsub => try!(self.float_op(|a, b| a - b)),
+            Instruction::Fmul => try!(self.float_op(|a, b| a * b)),
+            Instruction::Fdiv => try!(self.float_op(|a, b| a / b)),
+            Instruction::Frem => try!(self.float_op(|a, b| a % b)),
+            Instruction::Fneg => {
+                let value = try!(self.frame().pop_float());
+                self.frame().push(Value::Float(-value));
+            }
+            Instruction::Dadd => try!(self.double_op(|a, b| a + b)),
+            Instruction::Dsub => try!(self.double_op(|a, b| a - b)),
+            Instruction::Dmul => try!(self.double_op(|a, b| a * b)),
+            Instruction::Ddiv => try!(self.double_op(|a, b| a / b)),
+            Instruction::Drem => try!(self.double_op(|a, b| a % b)),
+            Instruction::Dneg => {
+                let value = try!(self.frame().pop_double());
+                self.frame().push(Value::Double(-value));
+            }
+            Instruction::Iinc(index, delta) => {
+                let index = index as usize;
+                match try!(self.frame().load(index)) {
+                    Value::Int(value) => try!(self.frame().store(index, Value::Int(value.wrapping_add(delta as i32)))),
+                    _ => bail!(ErrorKind::InvalidOperand(self.pc, "int")),
+                }
+            }
+
+            // Float to integer conversions saturate and turn NaN into zero, as `as` does
+            Instruction::I2l => {
+                let value = try!(self.frame().pop_int());
+                self.frame().push(Value::Long(value as i64));
+            }
+            Instruction::I2f => {
+                let value = try!(self.frame().pop_int());
+                self.frame().push(Value::Float(value as f32));
+            }
+            Instruction::I2d => {
+                let value = try!(self.frame().pop_int());
+                self.frame().push(Value::Double(value as f64));
+            }
+            Instruction::L2i => {
+                let value = try!(self.frame().pop_long());
+                self.frame().push(Value::Int(value as i32));
+            }
+            Instruction::L2f => {
+                let value = try!(self.frame().pop_long());
+                self.frame().push(Value::Float(value as f32));
+            }
+            Instruction::L2d => {
+                let value = try!(self.frame().pop_long());
+                self.frame().push(Value::Double(value as f64));
+            }
+            Instruction::F2i => {
+                let value = try!(self.frame().pop_float());
+                self.frame().push(Value::Int(value as i32));
+            }
+            Instruction::F2l => {
+                let value = try!(self.frame().pop_float());
+                self.frame().push(Value::Long(value as i64));
+            }
+            Instruction::F2d => {
+                let value = try!(self.frame().pop_float());
+                self.frame().push(Value::Double(value as f64));
+            }
+            Instruction::D2i => {
+                let value = try!(self.frame().pop_double());
+                self.frame().push(Value::Int(value as i32));
+            }
+            Instruction::D2l => {
+                let value = try!(self.frame().pop_double());
+                self.frame().push(Value::Long(value as i64));
+            }
+            Instruction::D2f => {
+                let value = try!(self.frame().pop_double());
+                self.frame().push(Value::Float(value as f32));
+            }
+            Instruction::I2b => {
+                let value = try!(self.frame().pop_int());
+                self.frame().push(Value::Int(value as i8 as i32));
+            }
+            Instruction::I2c => {
+                let value = try!(self.frame().pop_int());
+                self.frame().push(Value::Int(value as u16 as i32));
+            }
+            Instruction::I2s => {
+                let value = try!(self.frame().pop_int());
+                self.frame().push(Value::Int(value as i16 as i32));
+            }
+
+            Instruction::Lcmp => {
+                let (b, a) = (try!(self.frame().pop_long()), try!(self.frame().pop_long()));
+                self.frame().push(Value::Int(compare(a, b, 0)));
+            }
+            Instruction::Fcmpl | Instruction::Fcmpg => {
+                let (b, a) = (try!(self.frame().pop_float()), try!(self.frame().pop_float()));
+                let nan = if *insn == Instruction::Fcmpg { 1 } else { -1 };
+                self.frame().push(Value::Int(compare(a, b, nan)));
+            }
+            Instruction::Dcmpl | Instruction::Dcmpg => {
+                let (b, a) = (try!(self.frame().pop_double()), try!(self.frame().pop_double()));
+                let nan = if *insn == Instruction::Dcmpg { 1 } else { -1 };
+                self.frame().push(Value::Int(compare(a, b, nan)));
+            }
+
+            Instruction::Ifeq(offset) | Instruction::Ifne(offset) | Instruction::Iflt(offset) |
+            Instruction::Ifge(offset) | Instruction::Ifgt(offset) | Instruction::Ifle(offset) => {
+                let value = try!(self.frame().pop_int());
+                let taken = match *insn {
+                    Instruction::Ifeq(..) => value == 0,
+                    Instruction::Ifne(..) => value != 0,
+                    Instruction::Iflt(..) => value < 0,
+                    Instruction::Ifge(..) => value >= 0,
+                    Instruction::Ifgt(..) => value > 0,
+                    _ => value <= 0,
+                };
+                self.branch_if(taken, offset);
+            }
+            Instruction::IfIcmpeq(offset) | Instruction::IfIcmpne(offset) | Instruction::IfIcmplt(offset) |
+            Instruction::IfIcmpge(offset) | Instruction::IfIcmpgt(offset) | Instruction::IfIcmple(offset) => {
+                let (b, a) = (try!(self.frame().pop_int()), try!(self.frame().pop_int()));
+                let taken = match *insn {
+                    Instruction::IfIcmpeq(..) => a == b,
+                    Instruction::IfIcmpne(..) => a != b,
+                    Instruction::IfIcmplt(..) => a < b,
+                    Instruction::IfIcmpge(..) => a >= b,
+                    Instruction::IfIcmpgt(..) => a > b,
+                    _ => a <= b,
+                };
+                self.branch_if(taken, offset);
+            }
+            Instruction::IfAcmpeq(offset) | Instruction::IfAcmpne(offset) => {
+                let (b, a) = (try!(self.frame().pop()), try!(self.frame().pop()));
+                let taken = (a == b) == (*insn == Instruction::IfAcmpeq(offset));
+                self.branch_if(taken, offset);
+            }
+            Instruction::Ifnull(offset) | Instruction::Ifnonnull(offset) => {
+                let is_null = try!(self.frame().pop()) == Value::Null;
+                let taken = is_null == (*insn == Instruction::Ifnull(offset));
+                self.branch_if(taken, offset);
+            }
+            Instruction::Goto(offset) | Instruction::GotoW(offset) => self.branch_if(true, offset),
+            Instruction::Jsr(offset) | Instruction::JsrW(offset) => {
+                let next = self.next;
+                self.frame().push(Value::ReturnAddress(next));
+                self.branch_if(true, offset);
+            }
+            Instruction::Ret(index) => {
+                match try!(self.frame().load(index as usize)) {
+                    Value::ReturnAddress(pc) => self.next = pc,
+                    _ => bail!(ErrorKind::InvalidOperand(self.pc, "returnAddress")),
+                }
+            }
+            Instruction::Tableswitch(ref info) => {
+                let key = try!(self.frame().pop_int());
+                let offset = if key < info.low || key > info.high {
+                    info.default
+                } else {
+                    info.offsets[(key as i64 - info.low as i64) as usize]
+                };
+                self.branch_if(true, offset);
+            }
+            Instruction::Lookupswitch(ref info) => {
+                let key = try!(self.frame().pop_int());
+                let offset = info.pairs.iter().find(|&&(value, _)| value == key).map_or(info.default, |&(_, offset)| offset);
+                self.branch_if(true, offset);
+            }
+
+            Instruction::Ireturn | Instruction::Lreturn | Instruction::Freturn | Instruction::Dreturn |
+            Instruction::Areturn => {
+                let value = try!(self.frame().pop());
+                return Ok(Action::Return(Some(value)));
+            }
+            Instruction::Return => return Ok(Action::Return(None)),
+
+            Instruction::Getfield(index) => {
+                let (class, field) = try!(self.resolve_field(index, false));
+                let object = try!(self.pop_object());
+                let value = *try!(self.field(object, class.fields[field].slot));
+                self.frame().push(value);
+            }
+            Instruction::Putfield(index) => {
+                let (class, field) = try!(self.resolve_field(index, false));
+                let field = &class.fields[field];
+                let value = try!(self.frame().pop());
+                let object = try!(self.pop_object());
+                *try!(self.field(object, field.slot)) = field_value(value, &field.ty);
+                if let Value::Reference(..) = value {
+                    self.interp.heap.write_barrier(object);
+                }
+            }
+            Instruction::Getstatic(index) => {
+                let (class, field) = try!(self.resolve_field(index, true));
+                try!(self.interp.initialize(&class));
+                let value = class.get_static(class.fields[field].slot);
+                self.frame().push(value);
+            }
+            Instruction::Putstatic(index) => {
+                let (class, field) = try!(self.resolve_field(index, true));
+                try!(self.interp.initialize(&class));
+                let field = &class.fields[field];
+                let value = try!(self.frame().pop());
+                class.set_static(field.slot, field_value(value, &field.ty));
+            }
+            Instruction::Invokestatic(index) => {
+                let (class, method) = try!(self.resolve_method(index, true));
+                try!(self.interp.initialize(&class));
+                let params = class.methods[method].descriptor.params.len();
+                let args = try!(self.frame().pop_args(params));
+                return Ok(Action::Invoke(class, method, args));
+            }
+            Instruction::Invokevirtual(index) | Instruction::Invokeinterface(index, _) => {
+                let (class, method) = try!(self.resolve_method(index, false));
+                let args = try!(self.pop_receiver_args(&class, method));
+                let receiver = try!(self.class_of(try!(receiver(&args))));
+                if class.is_interface() && !receiver.is_subclass_of(&class.name) {
+                    bail!(exception("java/lang/IncompatibleClassChangeError",
+                                    format!("Class {} does not implement the requested interface {}",
+                                            receiver.java_name(), class.java_name())));
+                }
+                let (class, method) = try!(Class::select_method(&receiver, &class, method)
+                    .ok_or_else(|| exception("java/lang/AbstractMethodError", class.method_name(method))));
+                return Ok(Action::Invoke(class, method, args));
+            }
+            Instruction::Invokespecial(index) => {
+                let (class, method) = try!(self.resolve_method(index, false));
+                let args = try!(self.pop_receiver_args(&class, method));
+                try!(receiver(&args));
+
+                // Methods of super classes are looked up again from the direct super class (JVMS §6.5)
+                let is_super_call = class.methods[method].name != "<init>" && !class.is_interface() &&
+                    class.name != self.class.name && self.class.is_subclass_of(&class.name) &&
+                    self.class.access_flags.contains(flags::AccessFlags::ACC_SUPER);
+                let found = match self.class.super_class {
+                    Some(ref super_class) if is_super_call => {
+                        let method = &class.methods[method];
+                        Class::lookup_method(super_class, &method.name, &method.desc)
+                    }
+                    _ => Some((class.clone(), method)),
+                };
+                let (class, method) = try!(found
+                    .ok_or_else(|| exception("java/lang/AbstractMethodError", class.method_name(method))));
+                return Ok(Action::Invoke(class, method, args));
+            }
+            Instruction::New(index) => {
+                let class = try!(constant_pool::resolve_class(&mut self.interp.method_area, &self.class, index));
+                if class.access_flags.intersects(flags::AccessFlags::ACC_INTERFACE | flags::AccessFlags::ACC_ABSTRACT) {
+                    bail!(exception("java/lang/InstantiationError", class.java_name()));
+                }
+                try!(self.interp.initialize(&class));
+                let object = try!(self.interp.new_instance(&class));
+                self.frame().push(Value::Reference(object));
+            }
+            Instruction::Newarray(ty) => {
+                let length = try!(self.frame().pop_int());
+                let array = try!(self.interp.new_array(heap::array_type(FieldType::Base(base_type(ty))), length));
+                self.frame().push(Value::Reference(array));
+            }
+            Instruction::Anewarray(index) => {
+                let component = try!(self.resolve_type(index));
+                let length = try!(self.frame().pop_int());
+                let array = try!(self.interp.new_array(heap::array_type(component), length));
+                self.frame().push(Value::Reference(array));
+            }
+            Instruction::Multianewarray(index, dimensions) => {
+                let ty = try!(self.resolve_type(index));
+                let mut lengths = Vec::with_capacity(dimensions as usize);
+                for value in try!(self.frame().pop_args(dimensions as usize)) {
+                    match value {
+                        Value::Int(length) if length < 0 => {
+                            bail!(exception("java/lang/NegativeArraySizeException", length.to_string()))
+                        }
+                        Value::Int(length) => lengths.push(length),
+                        _ => bail!(ErrorKind::InvalidOperand(self.pc, "int")),
+                    }
+                }
+                try!(self.new_multi_array(ty, &lengths));
+            }
+            Instruction::Arraylength => {
+                let array = try!(self.pop_object());
+                let length = try!(self.array(array)).1.len();
+                self.frame().push(Value::Int(length as i32));
+            }
+            Instruction::Athrow => {
+                let object = try!(self.pop_object());
+                let class = try!(self.class_of(object));
+                let message = self.interp.exception_message(object);
+                self.interp.set_thrown(object);
+                bail!(exception(&class.name, message));
+            }
+            // There is a single thread, so that locking always succeeds
+            Instruction::Monitorenter | Instruction::Monitorexit => {
+                try!(self.pop_object());
+            }
+            Instruction::Checkcast(index) => {
+                let ty = try!(self.resolve_type(index));
+                let value = try!(self.frame().pop());
+                if let Value::Reference(object) = value {
+                    let object_ty = try!(self.object(object)).ty();
+                    if !try!(self.is_subtype(&object_ty, &ty)) {
+                        bail!(exception("java/lang/ClassCastException",
+                                        format!("class {} cannot be cast to class {}", class_name(&object_ty), class_name(&ty))));
+                    }
+                }
+                self.frame().push(value);
+            }
+            Instruction::Instanceof(index) => {
+                let ty = try!(self.resolve_type(index));
+                let is_instance = match try!(self.frame().pop()) {
+                    Value::Reference(object) => {
+                        let object_ty = try!(self.object(object)).ty();
+                        try!(self.is_subtype(&object_ty, &ty))
+                    }
+                    _ => false,
+                };
+                self.frame().push(Value::Int(is_instance as i32));
+            }
+
+            Instruction::Invokedynamic(..) => {
+                bail!(ErrorKind::Unsupported(self.pc, format!("`{}` instruction", insn.mnemonic())));
+            }
+        }
+
+        Ok(Action::Continue)
+    }
+
+    fn push_all(&mut self, values: &[Value]) {
+        self.frame().stack.extend_from_slice(values);
+    }
+
+    fn load(&mut self, index: usize) -> Result<()> {
+        let value = try!(self.frame().load(index));
+        self.frame().push(value);
+        Ok(())
+    }
+
+    fn store(&mut self, index: usize) -> Result<()> {
+        let value = try!(self.frame().pop());
+        self.frame().store(index, value)
+    }
+
+    /// Pop a reference to use, throwing `NullPointerException` if it's null.
+    fn pop_object(&mut self) -> Result<Reference> {
+        match try!(self.frame().pop()) {
+            Value::Reference(reference) => Ok(reference),
+            Value::Null => bail!(exception("java/lang/NullPointerException", "")),
+            _ => bail!(ErrorKind::InvalidOperand(self.pc, "reference")),
+        }
+    }
+
+    /// Reference on top of the operand stack, left there.
+    fn peek_object(&mut self) -> Result<Reference> {
+        let object = try!(self.pop_object());
+        self.frame().push(Value::Reference(object));
+        Ok(object)
+    }
+
+    /// Pop the arguments of an instance method, the object it's invoked on first.
+    fn pop_receiver_args(&mut self, class: &Class, method: usize) -> Result<Vec<Value>> {
+        self.frame().pop_args(class.methods[method].descriptor.params.len() + 1)
+    }
+
+    fn object(&mut self, reference: Reference) -> Result<&mut Object> {
+        let pc = self.pc;
+        self.interp.heap.get_mut(reference).ok_or_else(|| ErrorKind::InvalidOperand(pc, "reference").into())
+    }
+
+    /// Type and elements of an array.
+    fn array(&mut self, reference: Reference) -> Result<(&FieldType, &mut ArrayData)> {
+        let pc = self.pc;
+        match *try!(self.object(reference)) {
+            Object::Array { ref ty, ref mut data } => Ok((ty, data)),
+            Object::Instance { .. } => bail!(ErrorKind::InvalidOperand(pc, "array")),
+        }
+    }
+
+    /// Instance field of an object in the given slot.
+    fn field(&mut self, reference: Reference, slot: usize) -> Result<&mut Value> {
+        let pc = self.pc;
+        match *try!(self.object(reference)) {
+            Object::Instance { ref mut fields, .. } if slot < fields.len() => Ok(&mut fields[slot]),
+            _ => bail!(ErrorKind::InvalidOperand(pc, "object")),
+        }
+    }
+
+    /// Class of an object, `java/lang/Object` for arrays.
+    fn class_of(&mut self, reference: Reference) -> Result<Arc<Class>> {
+        try!(self.object(reference));
+        self.interp.class_of(reference)
+    }
+
+    /// Store `value` at `index` in an array, checking that the array can hold it.
+    fn array_store(&mut self, array: Reference, index: i32, value: Value) -> Result<()> {
+        let (index, component) = {
+            let (ty, data) = try!(self.array(array));
+            (try!(element_index(data, index)), ty.component().expect("Array of a non-array type"))
+        };
+
+        let value = match value {
+            Value::Reference(object) => {
+                let object_ty = try!(self.object(object)).ty();
+                if !try!(self.is_subtype(&object_ty, &component)) {
+                    bail!(exception("java/lang/ArrayStoreException", object_ty.to_java()));
+                }
+                value
+            }
+            value => field_value(value, &component),
+        };
+        if !try!(self.array(array)).1.set(index, value) {
+            bail!(ErrorKind::InvalidOperand(self.pc, "array element"));
+        }
+        if let Value::Reference(..) = value {
+            self.interp.heap.write_barrier(array);
+        }
+        Ok(())
+    }
+
+    /// Allocate an array of type `ty` and its sub-arrays, of the given lengths in each dimension, and push
+    /// it.
+    fn new_multi_array(&mut self, ty: FieldType, lengths: &[i32]) -> Result<()> {
+        let array = try!(self.interp.new_array(ty.clone(), lengths[0]));
+        // The array stays on the operand stack while its elements are allocated, which may move it
+        self.frame().push(Value::Reference(array));
+        if lengths.len() > 1 {
+            let component = ty.component().expect("Array of a non-array type");
+            for index in 0..lengths[0] as usize {
+                try!(self.new_multi_array(component.clone(), &lengths[1..]));
+                let element = try!(self.frame().pop());
+                let array = try!(self.peek_object());
+                try!(self.array(array)).1.set(index, element);
+                self.interp.heap.write_barrier(array);
+            }
+        }
+        Ok(())
+    }
+
+    /// Type named by the `Class` constant at `index`, which may be an array type.
+    fn resolve_type(&mut self, index: usize) -> Result<FieldType> {
+        let class = self.class.clone();
+        let pool = &class.classfile.constant_pool;
+        let name = try!(pool.get_class_info(index).and_then(|info| info.name(pool))
+            .ok_or(ErrorKind::BadConstant(self.pc, index)));
+        if !name.starts_with('[') {
+            let class = try!(constant_pool::resolve_class(&mut self.interp.method_area, &class, index));
+            return Ok(FieldType::Object(class.name.clone()));
+        }
+
+        let ty = try!(FieldType::parse(name));
+        if let FieldType::Array(_, ref element) = ty {
+            if let FieldType::Object(ref element) = **element {
+                try!(self.interp.method_area.load(element));
+            }
+        }
+        Ok(ty)
+    }
+
+    /// Whether a value of type `ty` can be assigned to `target`, following the rules of `checkcast`.
+    fn is_subtype(&mut self, ty: &FieldType, target: &FieldType) -> Result<bool> {
+        match (ty, target) {
+            (FieldType::Object(name), FieldType::Object(target)) => {
+                let class = try!(self.interp.method_area.load(name));
+                Ok(class.is_subclass_of(target))
+            }
+            (FieldType::Array(..), FieldType::Object(target)) => {
+                Ok(target == "java/lang/Object" || target == "java/lang/Cloneable" || target == "java/io/Serializable")
+            }
+            (FieldType::Array(..), FieldType::Array(..)) => {
+                let component = ty.component().expect("Array of a non-array type");
+                let target = target.component().expect("Array of a non-array type");
+                if component.is_reference() && target.is_reference() {
+                    self.is_subtype(&component, &target)
+                } else {
+                    Ok(component == target)
+                }
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn branch_if(&mut self, taken: bool, offset: i32) {
+        if taken {
+            self.next = (self.pc as i64 + offset as i64) as usize;
+        }
+    }
+
+    fn int_op<F: Fn(i32, i32) -> Result<i32>>(&mut self, op: F) -> Result<()> {
+        let (b, a) = (try!(self.frame().pop_int()), try!(self.frame().pop_int()));
+        self.frame().push(Value::Int(try!(op(a, b))));
+        Ok(())
+    }
+
+    fn long_op<F: Fn(i64, i64) -> Result<i64>>(&mut self, op: F) -> Result<()> {
+        let (b, a) = (try!(self.frame().pop_long()), try!(self.frame().pop_long()));
+        self.frame().push(Value::Long(try!(op(a, b))));
+        Ok(())
+    }
+
+    fn float_op<F: Fn(f32, f32) -> f32>(&mut self, op: F) -> Result<()> {
+        let (b, a) = (try!(self.frame().pop_float()), try!(self.frame().pop_float()));
+        self.frame().push(Value::Float(op(a, b)));
+        Ok(())
+    }
+
+    fn double_op<F: Fn(f64, f64) -> f64>(&mut self, op: F) -> Result<()> {
+        let (b, a) = (try!(self.frame().pop_double()), try!(self.frame().pop_double()));
+        self.frame().push(Value::Double(op(a, b)));
+        Ok(())
+    }
+
+    /// Resolve the field referenced by a `Fieldref` constant, which must be static or not.
+    fn resolve_field(&mut self, index: usize, is_static: bool) -> Result<(Arc<Class>, usize)> {
+        let (class, field) = try!(constant_pool::resolve_field(&mut self.interp.method_area, &self.class, index));
+        if class.fields[field].is_static() != is_static {
+            bail!(exception("java/lang/IncompatibleClassChangeError",
+                            format!("Expected {}static field {}.{}", if is_static { "" } else { "non-" },
+                                    class.java_name(), class.fields[field].name)));
+        }
+        Ok((class, field))
+    }
+
+    /// Resolve the method referenced by a `Methodref` or `InterfaceMethodref` constant, which must be static
+    /// or not.
+    fn resolve_method(&mut self, index: usize, is_static: bool) -> Result<(Arc<Class>, usize)> {
+        let (class, method) = try!(constant_pool::resolve_method(&mut self.interp.method_area, &self.class, index));
+        if class.methods[method].is_static() != is_static {
+            bail!(exception("java/lang/IncompatibleClassChangeError",
+                            format!("Expected {}static method {}", if is_static { "" } else { "non-" },
+                                    class.method_name(method))));
+        }
+        Ok((class, method))
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.interp.frames.last_mut().expect("Executing without a frame")
+    }
+}
+
+/// Object an instance method is invoked on, throwing `NullPointerException` if it's null.
+fn receiver(args: &[Value]) -> Result<Reference> {
+    match args.first() {
+        Some(&Value::Reference(reference)) => Ok(reference),
+        _ => bail!(exception("java/lang/NullPointerException", "")),
+    }
+}
+
+/// Value to store in a field or array element of type `ty`, narrowed as `putfield` and `bastore` do.
+fn field_value(value: Value, ty: &FieldType) -> Value {
+    match (value, ty) {
+        (Value::Int(value), &FieldType::Base(BaseType::Boolean)) => Value::Int(value & 1),
+        (value, _) => value,
+    }
+}
+
+/// Index of an array element, throwing `ArrayIndexOutOfBoundsException` if it's out of bounds.
+fn element_index(data: &ArrayData, index: i32) -> Result<usize> {
+    if index < 0 || index as usize >= data.len() {
+        bail!(exception("java/lang/ArrayIndexOutOfBoundsException",
+                        format!("Index {} out of bounds for length {}", index, data.len())));
+    }
+    Ok(index as usize)
+}
+
+/// Name of a class or array type as `ClassCastException` messages give it, e.g. `[I`.
+fn class_name(ty: &FieldType) -> String {
+    match *ty {
+        FieldType::Object(ref name) => name.replace('/', "."),
+        _ => ty.to_string().replace('/', "."),
+    }
+}
+
+fn base_type(ty: ArrayType) -> BaseType {
+    match ty {
+        ArrayType::Boolean => BaseType::Boolean,
+        ArrayType::Char => BaseType::Char,
+        ArrayType::Float => BaseType::Float,
+        ArrayType::Double => BaseType::Double,
+        ArrayType::Byte => BaseType::Byte,
+        ArrayType::Short => BaseType::Short,
+        ArrayType::Int => BaseType::Int,
+        ArrayType::Long => BaseType::Long,
+    }
+}
+
+fn division_by_zero() -> Error {
+    exception("java/lang/ArithmeticException", "/ by zero")
+}
+
+/// Compare two values as `lcmp`, `fcmp<op>` and `dcmp<op>` do, `nan` being the result if either is NaN.
+fn compare<T: PartialOrd>(a: T, b: T, nan: i32) -> i32 {
+    if a > b {
+        1
+    } else if a == b {
+        0
+    } else if a < b {
+        -1
+    } else {
+        nan
+    }
+}
@@ -0,0 +1,246 @@
+use classfile::{self, Classfile};
+use classfile::attr::info::AttrInfo;
+use classfile::constant::{ConstantPool, ConstantPoolEntry};
+use classfile::descriptor::{BaseType, FieldType};
+use classfile::field::FieldInfo;
+use classpath::Classpath;
+use classpath::error::{Error as ClasspathError, ErrorKind as ClasspathErrorKind};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::Arc;
+use super::class::{Class, Field, Method};
+use super::error::*;
+use super::value::Value;
+
+/// `public class java.lang.Object` with only a constructor, for programs run without the class library.
+const BOOTSTRAP_OBJECT: &[u8] = &[
+    0xca, 0xfe, 0xba, 0xbe, 0x00, 0x00, 0x00, 0x34,
+    // Constant pool: #1 java/lang/Object, #2 Class #1, #3 <init>, #4 ()V, #5 Code
+    0x00, 0x06,
+    0x01, 0x00, 0x10, b'j', b'a', b'v', b'a', b'/', b'l', b'a', b'n', b'g', b'/', b'O', b'b', b'j', b'e', b'c', b't',
+    0x07, 0x00, 0x01,
+    0x01, 0x00, 0x06, b'<', b'i', b'n', b'i', b't', b'>',
+    0x01, 0x00, 0x03, b'(', b')', b'V',
+    0x01, 0x00, 0x04, b'C', b'o', b'd', b'e',
+    // ACC_PUBLIC | ACC_SUPER, this_class, no super class, interfaces or fields
+    0x00, 0x21, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    // public <init>()V { return; }
+    0x00, 0x01,
+    0x00, 0x01, 0x00, 0x03, 0x00, 0x04, 0x00, 0x01,
+    0x00, 0x05, 0x00, 0x00, 0x00, 0x0d, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0xb1, 0x00, 0x00, 0x00, 0x00,
+    // No attributes
+    0x00, 0x00,
+];
+
+/// Super class and interfaces of a class.
+type Supers = (Option<Arc<Class>>, Vec<Arc<Class>>);
+
+/// Classes loaded from a classpath, each once (JVMS §5.3).
+#[derive(Debug)]
+pub struct MethodArea<'a> {
+    classpath: &'a Classpath,
+    classes: HashMap<String, Arc<Class>>,
+    /// Classes whose super class and interfaces are being loaded, to detect circularity.
+    loading: Vec<String>,
+}
+
+impl<'a> MethodArea<'a> {
+    pub fn new(classpath: &'a Classpath) -> MethodArea<'a> {
+        MethodArea {
+            classpath: classpath,
+            classes: HashMap::new(),
+            loading: Vec::new(),
+        }
+    }
+
+    /// Class already loaded under `name`.
+    pub fn get(&self, name: &str) -> Option<&Arc<Class>> {
+        self.classes.get(name)
+    }
+
+    /// All the classes loaded so far.
+    pub fn classes(&self) -> Vec<Arc<Class>> {
+        self.classes.values().cloned().collect()
+    }
+
+    /// Load and link the class `name` from the classpath, unless it's already loaded.
+    pub fn load(&mut self, name: &str) -> Result<Arc<Class>> {
+        if let Some(class) = self.classes.get(name) {
+            return Ok(class.clone());
+        }
+        if self.loading.iter().any(|loading| loading == name) {
+            bail!(exception("java/lang/ClassCircularityError", java_name(name)));
+        }
+
+        let data = match self.classpath.read_class(name) {
+            Ok(data) => data,
+            Err(ClasspathError(ClasspathErrorKind::ClassNotFound(..), _)) if name == "java/lang/Object" => {
+                debug!("Using the bootstrap java/lang/Object, the classpath has none");
+                BOOTSTRAP_OBJECT.to_vec()
+            }
+            Err(ClasspathError(ClasspathErrorKind::ClassNotFound(..), _)) => {
+                bail!(exception("java/lang/NoClassDefFoundError", name))
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let classfile = try!(Classfile::read(&mut Cursor::new(data)).map_err(|err| format_error(name, err)));
+        self.link(Some(name), classfile)
+    }
+
+    /// Link a class read by other means than loading it from the classpath.
+    pub fn define(&mut self, classfile: Classfile) -> Result<Arc<Class>> {
+        self.link(None, classfile)
+    }
+
+    /// Link `classfile` to its super class and interfaces, loading them as needed, and prepare its
+    /// static fields (JVMS §5.4.2).
+    fn link(&mut self, expected: Option<&str>, classfile: Classfile) -> Result<Arc<Class>> {
+        let name = {
+            let pool = &classfile.constant_pool;
+            let name = try!(classfile.this_class().and_then(|info| info.name(pool))
+                .ok_or_else(|| exception("java/lang/ClassFormatError", "Bad this_class")));
+            name.to_owned()
+        };
+        if let Some(expected) = expected {
+            if name != expected {
+                bail!(exception("java/lang/NoClassDefFoundError",
+                                format!("{} (wrong name: {})", expected, name)));
+            }
+        }
+        if self.classes.contains_key(&name) {
+            bail!(exception("java/lang/LinkageError", format!("duplicate class definition: {}", name)));
+        }
+
+        self.loading.push(name.clone());
+        let supers = self.load_supers(&name, &classfile);
+        self.loading.pop();
+        let (super_class, interfaces) = try!(supers);
+
+        let instance_fields = super_class.as_ref().map_or(0, |class| class.instance_layout.len());
+        let (fields, statics) = try!(prepare(&name, &classfile, instance_fields));
+        let methods = try!(methods(&name, &classfile));
+
+        let class = Arc::new(Class::new(name.clone(), classfile, super_class, interfaces, fields, methods, statics));
+        self.classes.insert(name, class.clone());
+        Ok(class)
+    }
+
+    fn load_supers(&mut self, name: &str, classfile: &Classfile) -> Result<Supers> {
+        let pool = &classfile.constant_pool;
+
+        let super_class = match classfile.super_class() {
+            Some(info) => {
+                let super_name = try!(info.name(pool).ok_or_else(|| format_violation(name, "Bad super_class")));
+                let super_class = try!(self.load(super_name));
+                if super_class.is_interface() {
+                    bail!(exception("java/lang/IncompatibleClassChangeError",
+                                    format!("class {} has interface {} as super class",
+                                            java_name(name), super_class.java_name())));
+                }
+                Some(super_class)
+            }
+            None if name == "java/lang/Object" => None,
+            None => bail!(format_violation(name, "Missing super_class")),
+        };
+
+        let mut interfaces = Vec::new();
+        for info in classfile.interfaces() {
+            let interface_name = try!(info.and_then(|info| info.name(pool))
+                .ok_or_else(|| format_violation(name, "Bad interface")));
+            let interface = try!(self.load(interface_name));
+            if !interface.is_interface() {
+                bail!(exception("java/lang/IncompatibleClassChangeError",
+                                format!("class {} can not implement {}, because it is not an interface",
+                                        java_name(name), interface.java_name())));
+            }
+            interfaces.push(interface);
+        }
+
+        Ok((super_class, interfaces))
+    }
+}
+
+/// Lay out the fields of a class, and give its static fields their initial values.
+fn prepare(name: &str, classfile: &Classfile, instance_fields: usize) -> Result<(Vec<Field>, Vec<Value>)> {
+    let pool = &classfile.constant_pool;
+    let mut fields = Vec::with_capacity(classfile.fields.len());
+    let mut statics = Vec::new();
+    let mut instance_slot = instance_fields;
+
+    for info in classfile.fields.iter() {
+        let field_name = try!(info.name(pool).ok_or_else(|| format_violation(name, "Bad field name")));
+        let desc = try!(info.desc(pool).ok_or_else(|| format_violation(name, "Bad field descriptor")));
+        let ty = try!(info.field_type(pool).map_err(|err| format_error(name, err)));
+
+        let mut field = Field {
+            name: field_name.to_owned(),
+            desc: desc.to_owned(),
+            ty: ty,
+            access_flags: info.access_flags,
+            slot: 0,
+        };
+        if field.is_static() {
+            let value = constant_value(info, &field.ty, pool);
+            field.slot = statics.len();
+            statics.push(value.unwrap_or_else(|| Value::default_for(&field.ty)));
+        } else {
+            field.slot = instance_slot;
+            instance_slot += 1;
+        }
+        fields.push(field);
+    }
+
+    Ok((fields, statics))
+}
+
+/// Value of the `ConstantValue` attribute of a static field, if it has one of the field's type.
+///
+/// `String` constants are left null, for the interpreter to intern them when it initializes the class.
+fn constant_value(field: &FieldInfo, ty: &FieldType, pool: &ConstantPool) -> Option<Value> {
+    let info = field.attrs.iter().filter_map(|attr| match attr.info {
+        AttrInfo::ConstantValue(ref info) => Some(info),
+        _ => None,
+    }).next();
+
+    match (info.and_then(|info| info.value(pool)), ty) {
+        (Some(ConstantPoolEntry::Long(info)), &FieldType::Base(BaseType::Long)) => Some(Value::Long(info.value())),
+        (Some(ConstantPoolEntry::Float(info)), &FieldType::Base(BaseType::Float)) => Some(Value::Float(info.value())),
+        (Some(ConstantPoolEntry::Double(info)), &FieldType::Base(BaseType::Double)) => {
+            Some(Value::Double(info.value()))
+        }
+        (Some(ConstantPoolEntry::Integer(info)), &FieldType::Base(ty)) => match ty {
+            BaseType::Long | BaseType::Float | BaseType::Double => None,
+            _ => Some(Value::Int(info.value())),
+        },
+        _ => None,
+    }
+}
+
+fn methods(name: &str, classfile: &Classfile) -> Result<Vec<Method>> {
+    let pool = &classfile.constant_pool;
+    let mut methods = Vec::with_capacity(classfile.methods.len());
+    for (index, method) in classfile.methods.iter().enumerate() {
+        let method_name = try!(method.name(pool).ok_or_else(|| format_violation(name, "Bad method name")));
+        let desc = try!(method.desc(pool).ok_or_else(|| format_violation(name, "Bad method descriptor")));
+        methods.push(Method {
+            name: method_name.to_owned(),
+            desc: desc.to_owned(),
+            descriptor: try!(method.descriptor(pool).map_err(|err| format_error(name, err))),
+            access_flags: method.access_flags,
+            index: index,
+        });
+    }
+    Ok(methods)
+}
+
+fn java_name(name: &str) -> String {
+    name.replace('/', ".")
+}
+
+fn format_error(name: &str, err: classfile::error::Error) -> Error {
+    exception("java/lang/ClassFormatError", format!("{} ({})", err, name))
+}
+
+fn format_violation(name: &str, message: &str) -> Error {
+    exception("java/lang/ClassFormatError", format!("{} ({})", message, name))
+}
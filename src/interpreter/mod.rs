@@ -0,0 +1,509 @@
+//! Bytecode interpreter (JVMS §2.6, §6.5).
+
+pub mod class;
+pub mod constant_pool;
+pub mod error;
+pub mod frame;
+pub mod heap;
+pub mod method_area;
+pub mod value;
+mod instruction;
+mod native;
+mod string;
+
+pub use self::class::Class;
+pub use self::frame::Frame;
+pub use self::heap::Heap;
+pub use self::method_area::MethodArea;
+pub use self::value::Value;
+
+use classfile::bytecode;
+use classfile::attr::info::AttrInfo;
+use classfile::constant::ConstantPoolEntry;
+use classfile::descriptor::FieldType;
+use classfile::method::flags::AccessFlags;
+use classpath::Classpath;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread;
+use self::class::Initialization;
+use self::error::*;
+use self::heap::{Object, Reference};
+
+/// Maximum number of frames on the stack, beyond which `StackOverflowError` is thrown.
+const MAX_FRAMES: usize = 4096;
+
+/// What to do once an instruction has executed.
+enum Action {
+    Continue,
+    /// Call a method of a class with the given arguments.
+    Invoke(Arc<Class>, usize, Vec<Value>),
+    /// Return from the current method, with a value unless it's `void`.
+    Return(Option<Value>),
+}
+
+/// Stack-based interpreter, loading classes from a classpath as they are used and allocating objects in
+/// a heap.
+#[derive(Debug)]
+pub struct Interpreter<'a> {
+    method_area: MethodArea<'a>,
+    heap: Heap,
+    frames: Vec<Frame>,
+    /// Handles of the interned strings, by value.
+    strings: HashMap<String, usize>,
+    /// Handles of the `java.lang.Class` objects of the classes and array types, by name.
+    class_objects: HashMap<String, usize>,
+    /// Handle of the exception being thrown, once it's an object.
+    thrown: Option<usize>,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(classpath: &'a Classpath, heap: Heap) -> Interpreter<'a> {
+        Interpreter {
+            method_area: MethodArea::new(classpath),
+            heap: heap,
+            frames: Vec::new(),
+            strings: HashMap::new(),
+            class_objects: HashMap::new(),
+            thrown: None,
+        }
+    }
+
+    pub fn method_area(&mut self) -> &mut MethodArea<'a> {
+        &mut self.method_area
+    }
+
+    pub fn heap(&mut self) -> &mut Heap {
+        &mut self.heap
+    }
+
+    /// Allocate an instance of `class`, with its fields set to their default value.
+    pub fn new_instance(&mut self, class: &Arc<Class>) -> Result<Reference> {
+        try!(self.reserve(Object::instance_size(class)));
+        Ok(self.heap.insert(Object::instance(class.clone())))
+    }
+
+    /// Allocate an array of type `ty` and `length` elements, with their default value.
+    pub fn new_array(&mut self, ty: FieldType, length: i32) -> Result<Reference> {
+        if length < 0 {
+            bail!(exception("java/lang/NegativeArraySizeException", length.to_string()));
+        }
+        let component = try!(ty.component().ok_or_else(|| exception("java/lang/InternalError", "Not an array type")));
+        try!(self.reserve(Object::array_size(&component, length as usize)));
+        Ok(self.heap.insert(Object::array(ty, length as usize)))
+    }
+
+    /// `java.lang.Class` object of a type, the same for each use, named as `Class` constants name classes
+    /// and array types, e.g. `java/lang/String` or `[I`, and by their keyword for primitive types.
+    pub fn class_object(&mut self, name: &str) -> Result<Reference> {
+        if let Some(object) = self.class_objects.get(name).and_then(|&handle| self.heap.handle(handle)) {
+            return Ok(object);
+        }
+
+        let class = try!(self.method_area.load("java/lang/Class"));
+        try!(self.initialize(&class));
+        let object = try!(self.new_instance(&class));
+        let handle = self.heap.add_handle(object);
+        self.class_objects.insert(name.to_owned(), handle);
+        Ok(object)
+    }
+
+    /// Make room for an object of `size` bytes, collecting garbage if needed, or throw `OutOfMemoryError`.
+    ///
+    /// Objects may move, so that references not held by frames, static fields or handles are invalid
+    /// afterwards.
+    fn reserve(&mut self, size: usize) -> Result<()> {
+        if self.heap.has_room(size) {
+            return Ok(());
+        }
+        self.collect(false);
+        if !self.heap.has_room(size) {
+            self.collect(true);
+            if !self.heap.has_room(size) {
+                bail!(exception("java/lang/OutOfMemoryError", "Java heap space"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reclaim all the objects that can't be reached anymore, returning the number of bytes freed.
+    pub fn collect_garbage(&mut self) -> usize {
+        self.collect(true)
+    }
+
+    /// Collect garbage, objects being reachable from the locals and operand stacks of the frames, the
+    /// static fields of the loaded classes and the handles of the heap.
+    fn collect(&mut self, full: bool) -> usize {
+        let classes = self.method_area.classes();
+        let mut statics: Vec<_> = classes.iter().map(|class| class.statics()).collect();
+
+        let mut roots = Vec::new();
+        for frame in self.frames.iter_mut() {
+            roots.extend(frame.locals.iter_mut().chain(frame.stack.iter_mut()).filter_map(reference));
+        }
+        for values in statics.iter_mut() {
+            roots.extend(values.iter_mut().filter_map(reference));
+        }
+        self.heap.collect(roots, full)
+    }
+
+    /// Initialize `class`, then run its `public static void main(String[])` method.
+    pub fn run_main(&mut self, class: &Arc<Class>) -> Result<()> {
+        let main = try!(class.find_method("main", "([Ljava/lang/String;)V")
+            .filter(|&method| class.methods[method].access_flags.contains(AccessFlags::ACC_PUBLIC | AccessFlags::ACC_STATIC))
+            .ok_or_else(|| ErrorKind::MainNotFound(class.java_name())));
+
+        try!(self.initialize(class));
+        let args = try!(self.new_array(FieldType::Array(1, Box::new(FieldType::Object("java/lang/String".to_owned()))), 0));
+        try!(self.invoke(class, main, vec![Value::Reference(args)]));
+        Ok(())
+    }
+
+    /// Call a method of `class` with the given arguments and run it to completion, returning its result.
+    pub fn invoke(&mut self, class: &Arc<Class>, method: usize, args: Vec<Value>) -> Result<Option<Value>> {
+        if let Some(native) = native::lookup(class, method) {
+            return native(self, &args);
+        }
+
+        let depth = self.frames.len();
+        try!(self.push_frame(class.clone(), method, args));
+
+        let result = self.run(depth);
+        if result.is_err() {
+            self.frames.truncate(depth);
+        }
+        result
+    }
+
+    /// Initialize `class` unless it already is, following JVMS §5.5.
+    pub fn initialize(&mut self, class: &Arc<Class>) -> Result<()> {
+        match class.start_initialization(thread::current().id()) {
+            Initialization::Required => {}
+            Initialization::Done => return Ok(()),
+            Initialization::Failed => {
+                bail!(exception("java/lang/NoClassDefFoundError",
+                                format!("Could not initialize class {}", class.java_name())));
+            }
+        }
+
+        // Super classes and superinterfaces failing to initialize are reported as is
+        let result = match self.initialize_constant_strings(class).and_then(|()| self.initialize_supers(class)) {
+            Ok(()) => self.run_initializer(class),
+            Err(err) => Err(err),
+        };
+        class.finish_initialization(result.is_ok());
+        result
+    }
+
+    /// Set the static `String` fields of a class that have a `ConstantValue` attribute, which preparing the
+    /// class leaves null.
+    fn initialize_constant_strings(&mut self, class: &Arc<Class>) -> Result<()> {
+        let pool = &class.classfile.constant_pool;
+        for (field, info) in class.fields.iter().zip(class.classfile.fields.iter()) {
+            if !field.is_static() || field.desc != "Ljava/lang/String;" {
+                continue;
+            }
+            let value = info.attrs.iter().filter_map(|attr| match attr.info {
+                AttrInfo::ConstantValue(ref info) => info.value(pool),
+                _ => None,
+            }).next();
+            if let Some(ConstantPoolEntry::String(info)) = value {
+                let value = try!(info.value(pool).ok_or_else(|| exception("java/lang/ClassFormatError", "Bad string constant")));
+                let string = try!(self.intern(value));
+                class.set_static(field.slot, Value::Reference(string));
+            }
+        }
+        Ok(())
+    }
+
+    /// Initialize the super class of a class, and its superinterfaces that declare default methods.
+    fn initialize_supers(&mut self, class: &Arc<Class>) -> Result<()> {
+        if class.is_interface() {
+            return Ok(());
+        }
+        if let Some(ref super_class) = class.super_class {
+            try!(self.initialize(super_class));
+        }
+        for interface in class.interfaces.iter() {
+            try!(self.initialize_default_interfaces(interface));
+        }
+        Ok(())
+    }
+
+    fn initialize_default_interfaces(&mut self, interface: &Arc<Class>) -> Result<()> {
+        for super_interface in interface.interfaces.iter() {
+            try!(self.initialize_default_interfaces(super_interface));
+        }
+
+        let has_default = interface.methods.iter()
+            .any(|method| !method.access_flags.intersects(AccessFlags::ACC_ABSTRACT | AccessFlags::ACC_STATIC));
+        if has_default {
+            try!(self.initialize(interface));
+        }
+        Ok(())
+    }
+
+    /// Run the `<clinit>` method of `class`, wrapping the exceptions it throws other than errors in an
+    /// `ExceptionInInitializerError`.
+    fn run_initializer(&mut self, class: &Arc<Class>) -> Result<()> {
+        let method = match class.find_method("<clinit>", "()V") {
+            Some(method) if class.methods[method].is_static() => method,
+            _ => return Ok(()),
+        };
+
+        let err = match self.invoke(class, method, Vec::new()) {
+            Ok(_) => return Ok(()),
+            Err(err) => err,
+        };
+        let is_error = match err.java_exception() {
+            Some((name, _)) => self.is_error(name),
+            None => return Err(err),
+        };
+        if is_error {
+            Err(err)
+        } else {
+            Err(err).chain_err(|| ErrorKind::Exception("java/lang/ExceptionInInitializerError".to_owned(), String::new()))
+        }
+    }
+
+    /// Whether the exception class `name` is `java.lang.Error` or one of its subclasses.
+    fn is_error(&mut self, name: &str) -> bool {
+        match self.method_area.load(name) {
+            Ok(class) => class.is_subclass_of("java/lang/Error"),
+            // Without the class library, go by the naming convention
+            Err(_) => name.ends_with("Error"),
+        }
+    }
+
+    /// Execute instructions until the frame at `depth` returns.
+    ///
+    /// Errors are located in the method they occurred in.
+    fn run(&mut self, depth: usize) -> Result<Option<Value>> {
+        loop {
+            let result = match self.step() {
+                Ok(Action::Continue) => Ok(()),
+                Ok(Action::Invoke(class, method, args)) => self.call(class, method, args),
+                Ok(Action::Return(value)) => {
+                    self.frames.pop();
+                    if self.frames.len() == depth {
+                        return Ok(value);
+                    }
+                    self.resume(value)
+                }
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                try!(self.unwind(err, depth));
+            }
+        }
+    }
+
+    /// Call a method from the innermost frame, running it at once if it's native.
+    fn call(&mut self, class: Arc<Class>, method: usize, args: Vec<Value>) -> Result<()> {
+        match native::lookup(&class, method) {
+            Some(native) => {
+                let value = try!(native(self, &args));
+                self.resume(value)
+            }
+            None => self.push_frame(class, method, args),
+        }
+    }
+
+    fn step(&mut self) -> Result<Action> {
+        let (insn, next) = {
+            let frame = self.frames.last().expect("Running without a frame");
+            try!(bytecode::decode(&frame.code().code, frame.pc))
+        };
+        instruction::execute(self, &insn, next)
+    }
+
+    /// Continue the innermost frame after the call that returned `value`.
+    fn resume(&mut self, value: Option<Value>) -> Result<()> {
+        let frame = self.frames.last_mut().expect("Returning without a caller");
+        frame.pc = try!(bytecode::decode(&frame.code().code, frame.pc)).1;
+        if let Some(value) = value {
+            frame.push(value);
+        }
+        Ok(())
+    }
+
+    /// Continue at the innermost handler of the exception `err` in the frames above `depth`, popping
+    /// those without one (JVMS §2.10).
+    ///
+    /// Fails with `err`, located in the method it occurred in, if it isn't a Java exception or no handler
+    /// catches it. Exceptions whose class can't be loaded and initialized can't be caught.
+    fn unwind(&mut self, err: Error, depth: usize) -> Result<()> {
+        let err = Error::with_chain(err, self.location());
+        let (name, message) = match err.java_exception() {
+            Some((name, message)) => (name.to_owned(), message.to_owned()),
+            None => return Err(err),
+        };
+        let exception = match self.exception_object(&name, &message) {
+            Ok(Some(exception)) => exception,
+            Ok(None) | Err(_) => return Err(err),
+        };
+        let class = try!(self.class_of(exception));
+        self.set_thrown(exception);
+
+        while self.frames.len() > depth {
+            if let Some(handler_pc) = try!(self.find_handler(&class)) {
+                let exception = self.take_thrown().expect("Dropped exception");
+                let frame = self.frames.last_mut().expect("Catching without a frame");
+                frame.stack.clear();
+                frame.push(Value::Reference(exception));
+                frame.pc = handler_pc;
+                return Ok(());
+            }
+            self.frames.pop();
+        }
+        Err(err)
+    }
+
+    /// Offset of the first handler of the innermost frame covering its current instruction and catching
+    /// exceptions of `class`.
+    fn find_handler(&mut self, class: &Class) -> Result<Option<usize>> {
+        let (from, method, pc) = {
+            let frame = self.frames.last().expect("Catching without a frame");
+            (frame.class.clone(), frame.method, frame.pc)
+        };
+        let code = from.code(method).expect("Frame of a method without code");
+
+        for handler in code.exception_handlers.iter() {
+            if pc < handler.start_pc || pc >= handler.end_pc {
+                continue;
+            }
+            let catch_type = handler.catch_type_index();
+            if catch_type == 0 {
+                return Ok(Some(handler.handler_pc));
+            }
+            let caught = try!(constant_pool::resolve_class(&mut self.method_area, &from, catch_type));
+            if class.is_subclass_of(&caught.name) {
+                return Ok(Some(handler.handler_pc));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Object of the exception of class `name` with the given message: the one being thrown if it's the
+    /// same, or else a new one, allocated without calling its constructor. `None` if the class can't be
+    /// loaded.
+    fn exception_object(&mut self, name: &str, message: &str) -> Result<Option<Reference>> {
+        if let Some(exception) = self.thrown.and_then(|handle| self.heap.handle(handle)) {
+            if try!(self.class_of(exception)).name == name && self.exception_message(exception) == message {
+                return Ok(Some(exception));
+            }
+        }
+
+        let class = match self.method_area.load(name) {
+            Ok(ref class) if class.is_subclass_of("java/lang/Throwable") => class.clone(),
+            _ => return Ok(None),
+        };
+        try!(self.initialize(&class));
+        let detail_message = Class::lookup_field(&class, "detailMessage", "Ljava/lang/String;")
+            .map(|(class, field)| class.fields[field].slot);
+
+        // The message is held by a handle while the exception is allocated, which may move it
+        let message = match detail_message {
+            Some(_) if !message.is_empty() => {
+                let message = try!(self.new_string(message));
+                Some(self.heap.add_handle(message))
+            }
+            _ => None,
+        };
+        let exception = self.new_instance(&class);
+        let message = message.map(|handle| {
+            let message = self.heap.handle(handle).expect("Dropped exception message");
+            self.heap.remove_handle(handle);
+            message
+        });
+        let exception = try!(exception);
+
+        if let (Some(slot), Some(message)) = (detail_message, message) {
+            if let Some(&mut Object::Instance { ref mut fields, .. }) = self.heap.get_mut(exception) {
+                fields[slot] = Value::Reference(message);
+            }
+            self.heap.write_barrier(exception);
+        }
+        Ok(Some(exception))
+    }
+
+    /// Detail message of an exception, empty if it has none.
+    fn exception_message(&self, exception: Reference) -> String {
+        let message = match self.heap.get(exception) {
+            Some(Object::Instance { class, fields }) => {
+                Class::lookup_field(class, "detailMessage", "Ljava/lang/String;")
+                    .map(|(class, field)| fields[class.fields[field].slot])
+            }
+            _ => None,
+        };
+        match message {
+            Some(Value::Reference(message)) => self.read_string(message).unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+
+    /// Keep `exception` as the exception being thrown, until a handler catches it.
+    fn set_thrown(&mut self, exception: Reference) {
+        if self.thrown.and_then(|handle| self.heap.handle(handle)) == Some(exception) {
+            return;
+        }
+        if let Some(handle) = self.thrown.take() {
+            self.heap.remove_handle(handle);
+        }
+        self.thrown = Some(self.heap.add_handle(exception));
+    }
+
+    fn take_thrown(&mut self) -> Option<Reference> {
+        let handle = self.thrown.take()?;
+        let exception = self.heap.handle(handle);
+        self.heap.remove_handle(handle);
+        exception
+    }
+
+    /// Class of an object, `java/lang/Object` for arrays.
+    fn class_of(&mut self, reference: Reference) -> Result<Arc<Class>> {
+        match self.heap.get(reference) {
+            Some(Object::Instance { class, .. }) => return Ok(class.clone()),
+            Some(Object::Array { .. }) => {}
+            None => bail!(exception("java/lang/InternalError", format!("Dangling reference {}", reference))),
+        }
+        self.method_area.load("java/lang/Object")
+    }
+
+    fn push_frame(&mut self, class: Arc<Class>, method: usize, args: Vec<Value>) -> Result<()> {
+        if self.frames.len() >= MAX_FRAMES {
+            bail!(exception("java/lang/StackOverflowError", ""));
+        }
+
+        let frame = {
+            let code = match class.code(method) {
+                Some(code) => code,
+                None if class.methods[method].access_flags.contains(AccessFlags::ACC_NATIVE) => {
+                    bail!(exception("java/lang/UnsatisfiedLinkError", class.method_name(method)))
+                }
+                None => bail!(exception("java/lang/AbstractMethodError", class.method_name(method))),
+            };
+            try!(Frame::new(class.clone(), method, code, &args))
+        };
+        self.frames.push(frame);
+        Ok(())
+    }
+
+    /// Method being executed by the innermost frame, to locate errors.
+    fn location(&self) -> ErrorKind {
+        match self.frames.last() {
+            Some(frame) => {
+                let method = &frame.class.methods[frame.method];
+                ErrorKind::Method(frame.class.java_name(), method.name.clone(), method.desc.clone())
+            }
+            None => ErrorKind::Method("?".to_owned(), "?".to_owned(), "?".to_owned()),
+        }
+    }
+}
+
+fn reference(value: &mut Value) -> Option<&mut Reference> {
+    match *value {
+        Value::Reference(ref mut reference) => Some(reference),
+        _ => None,
+    }
+}
@@ -0,0 +1,143 @@
+//! Intrinsics standing for the native methods of the class library that programs can't do without.
+
+use classfile::descriptor::FieldType;
+use classfile::method::flags::AccessFlags;
+use super::Interpreter;
+use super::class::Class;
+use super::error::*;
+use super::heap::Object;
+use super::value::Value;
+
+/// Implementation of a native method, given its arguments, `this` first for instance methods.
+pub type NativeMethod = fn(&mut Interpreter, &[Value]) -> Result<Option<Value>>;
+
+/// Intrinsic implementing a method of `class`, if it's native and has one.
+pub fn lookup(class: &Class, method: usize) -> Option<NativeMethod> {
+    let method = &class.methods[method];
+    if !method.access_flags.contains(AccessFlags::ACC_NATIVE) {
+        return None;
+    }
+
+    let native: NativeMethod = match (&*class.name, &*method.name, &*method.desc) {
+        // Natives are linked by name rather than registered
+        (_, "registerNatives", "()V") | (_, "initIDs", "()V") => |_, _| Ok(None),
+        ("java/lang/Object", "getClass", "()Ljava/lang/Class;") => get_class,
+        // Stack traces aren't recorded
+        ("java/lang/Throwable", "fillInStackTrace", "(I)Ljava/lang/Throwable;") => |_, args| Ok(Some(args[0])),
+        ("java/lang/Class", "getPrimitiveClass", "(Ljava/lang/String;)Ljava/lang/Class;") => get_primitive_class,
+        ("java/lang/Class", "desiredAssertionStatus0", "(Ljava/lang/Class;)Z") => |_, _| Ok(Some(Value::Int(0))),
+        ("java/lang/System", "arraycopy", "(Ljava/lang/Object;ILjava/lang/Object;II)V") => arraycopy,
+        ("java/lang/StringUTF16", "isBigEndian", "()Z") => |_, _| Ok(Some(Value::Int(0))),
+        ("java/lang/Float", "floatToRawIntBits", "(F)I") => |_, args| match args[0] {
+            Value::Float(value) => Ok(Some(Value::Int(value.to_bits() as i32))),
+            _ => bail!(invalid_argument()),
+        },
+        ("java/lang/Float", "intBitsToFloat", "(I)F") => |_, args| match args[0] {
+            Value::Int(bits) => Ok(Some(Value::Float(f32::from_bits(bits as u32)))),
+            _ => bail!(invalid_argument()),
+        },
+        ("java/lang/Double", "doubleToRawLongBits", "(D)J") => |_, args| match args[0] {
+            Value::Double(value) => Ok(Some(Value::Long(value.to_bits() as i64))),
+            _ => bail!(invalid_argument()),
+        },
+        ("java/lang/Double", "longBitsToDouble", "(J)D") => |_, args| match args[0] {
+            Value::Long(bits) => Ok(Some(Value::Double(f64::from_bits(bits as u64)))),
+            _ => bail!(invalid_argument()),
+        },
+        _ => return None,
+    };
+    Some(native)
+}
+
+/// `Object.getClass()`
+fn get_class(interp: &mut Interpreter, args: &[Value]) -> Result<Option<Value>> {
+    let name = match args[0] {
+        Value::Reference(object) => match interp.heap.get(object) {
+            Some(Object::Instance { class, .. }) => class.name.clone(),
+            Some(Object::Array { ty, .. }) => ty.to_string(),
+            None => bail!(invalid_argument()),
+        },
+        _ => bail!(invalid_argument()),
+    };
+    Ok(Some(Value::Reference(try!(interp.class_object(&name)))))
+}
+
+/// `Class.getPrimitiveClass(String)`
+fn get_primitive_class(interp: &mut Interpreter, args: &[Value]) -> Result<Option<Value>> {
+    let name = match args[0] {
+        Value::Reference(name) => try!(interp.read_string(name).ok_or_else(invalid_argument)),
+        _ => bail!(exception("java/lang/NullPointerException", "")),
+    };
+    match &*name {
+        "boolean" | "byte" | "char" | "short" | "int" | "long" | "float" | "double" | "void" => {}
+        _ => bail!(exception("java/lang/IllegalArgumentException", name)),
+    }
+    Ok(Some(Value::Reference(try!(interp.class_object(&name)))))
+}
+
+/// `System.arraycopy(Object, int, Object, int, int)`
+fn arraycopy(interp: &mut Interpreter, args: &[Value]) -> Result<Option<Value>> {
+    let (src, src_pos, dest, dest_pos, length) = match *args {
+        [Value::Reference(src), Value::Int(src_pos), Value::Reference(dest), Value::Int(dest_pos), Value::Int(length)] => {
+            (src, src_pos, dest, dest_pos, length)
+        }
+        [_, Value::Int(..), _, Value::Int(..), Value::Int(..)] => bail!(exception("java/lang/NullPointerException", "")),
+        _ => bail!(invalid_argument()),
+    };
+
+    let (elements, component) = {
+        let (src_ty, src_data) = match interp.heap.get(src) {
+            Some(Object::Array { ty, data }) => (ty, data),
+            _ => bail!(exception("java/lang/ArrayStoreException", "arraycopy: source type is not an array")),
+        };
+        let (dest_ty, dest_len) = match interp.heap.get(dest) {
+            Some(Object::Array { ty, data }) => (ty.clone(), data.len()),
+            _ => bail!(exception("java/lang/ArrayStoreException", "arraycopy: destination type is not an array")),
+        };
+        let component = dest_ty.component().expect("Array of a non-array type");
+        let src_component = src_ty.component().expect("Array of a non-array type");
+        if component.is_reference() != src_component.is_reference() ||
+            !component.is_reference() && component != src_component {
+            bail!(exception("java/lang/ArrayStoreException",
+                            format!("arraycopy: type mismatch: can not copy {} into {}", src_ty, dest_ty)));
+        }
+
+        let in_bounds = |pos: i32, len: usize| pos >= 0 && length >= 0 && pos as i64 + length as i64 <= len as i64;
+        if !in_bounds(src_pos, src_data.len()) || !in_bounds(dest_pos, dest_len) {
+            bail!(exception("java/lang/ArrayIndexOutOfBoundsException",
+                            format!("arraycopy: range [{}, {}) out of bounds", src_pos, src_pos as i64 + length as i64)));
+        }
+        let elements: Vec<_> = (src_pos as usize..src_pos as usize + length as usize)
+            .map(|index| src_data.get(index).expect("Array index out of bounds"))
+            .collect();
+        (elements, component)
+    };
+
+    // Elements of reference arrays are checked one by one, those before a mismatch being copied
+    let mut count = elements.len();
+    if let FieldType::Object(ref name) = component {
+        count = elements.iter().position(|element| match *element {
+            Value::Reference(object) => match interp.heap.get(object) {
+                Some(Object::Instance { class, .. }) => !class.is_subclass_of(name),
+                _ => !matches!(&**name, "java/lang/Object" | "java/lang/Cloneable" | "java/io/Serializable"),
+            },
+            _ => false,
+        }).unwrap_or(count);
+    }
+    if let Some(Object::Array { data, .. }) = interp.heap.get_mut(dest) {
+        for (offset, &element) in elements[..count].iter().enumerate() {
+            data.set(dest_pos as usize + offset, element);
+        }
+    }
+    if component.is_reference() {
+        interp.heap.write_barrier(dest);
+    }
+    if count < elements.len() {
+        bail!(exception("java/lang/ArrayStoreException", "arraycopy: element type mismatch"));
+    }
+    Ok(None)
+}
+
+fn invalid_argument() -> Error {
+    exception("java/lang/InternalError", "Invalid argument of a native method")
+}
@@ -0,0 +1,112 @@
+//! `java.lang.String` objects, laid out as the class library in the classpath expects: their characters
+//! in a `char[] value` up to Java 8, and in a `byte[] value` along with a `coder` since Java 9.
+
+use classfile::descriptor::{BaseType, FieldType};
+use std::sync::Arc;
+use super::Interpreter;
+use super::class::Class;
+use super::error::*;
+use super::heap::{ArrayData, Object, Reference};
+use super::value::Value;
+
+/// `coder` of strings whose characters all fit in a byte.
+const LATIN1: i32 = 0;
+
+/// `coder` of strings stored as UTF-16 code units, in little-endian order.
+const UTF16: i32 = 1;
+
+impl<'a> Interpreter<'a> {
+    /// Allocate a string holding `value`.
+    pub fn new_string(&mut self, value: &str) -> Result<Reference> {
+        let class = try!(self.method_area.load("java/lang/String"));
+        try!(self.initialize(&class));
+
+        let units: Vec<u16> = value.encode_utf16().collect();
+        let (value_slot, coder) = match string_layout(&class) {
+            Some(layout) => layout,
+            None => bail!(exception("java/lang/InternalError", "Unsupported java.lang.String layout")),
+        };
+        let (data, coder) = match coder {
+            None => (ArrayData::Char(units), None),
+            Some(slot) if units.iter().all(|&unit| unit <= 0xff) => {
+                (ArrayData::Byte(units.iter().map(|&unit| unit as i8).collect()), Some((slot, LATIN1)))
+            }
+            Some(slot) => {
+                let bytes = units.iter().flat_map(|unit| unit.to_le_bytes()).map(|byte| byte as i8).collect();
+                (ArrayData::Byte(bytes), Some((slot, UTF16)))
+            }
+        };
+
+        // The array is held by a handle while the string is allocated, which may move it
+        let ty = FieldType::Array(1, Box::new(FieldType::Base(match data {
+            ArrayData::Char(..) => BaseType::Char,
+            _ => BaseType::Byte,
+        })));
+        let array = try!(self.new_array(ty, data.len() as i32));
+        if let Some(&mut Object::Array { data: ref mut elements, .. }) = self.heap.get_mut(array) {
+            *elements = data;
+        }
+        let handle = self.heap.add_handle(array);
+        let string = self.new_instance(&class);
+        let array = self.heap.handle(handle).expect("Dropped string value");
+        self.heap.remove_handle(handle);
+        let string = try!(string);
+
+        if let Some(&mut Object::Instance { ref mut fields, .. }) = self.heap.get_mut(string) {
+            fields[value_slot] = Value::Reference(array);
+            if let Some((slot, coder)) = coder {
+                fields[slot] = Value::Int(coder);
+            }
+        }
+        self.heap.write_barrier(string);
+        Ok(string)
+    }
+
+    /// Canonical string holding `value`, as string literals evaluate to (JLS §3.10.5).
+    pub fn intern(&mut self, value: &str) -> Result<Reference> {
+        if let Some(string) = self.strings.get(value).and_then(|&handle| self.heap.handle(handle)) {
+            return Ok(string);
+        }
+        let string = try!(self.new_string(value));
+        let handle = self.heap.add_handle(string);
+        self.strings.insert(value.to_owned(), handle);
+        Ok(string)
+    }
+
+    /// Characters of a string, `None` if `reference` isn't one.
+    pub fn read_string(&self, reference: Reference) -> Option<String> {
+        let (class, fields) = match self.heap.get(reference) {
+            Some(Object::Instance { class, fields }) if class.name == "java/lang/String" => (class, fields),
+            _ => return None,
+        };
+        let (value_slot, coder) = string_layout(class)?;
+        let data = match fields[value_slot] {
+            Value::Reference(array) => match self.heap.get(array) {
+                Some(Object::Array { data, .. }) => data,
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        match (data, coder.map(|slot| fields[slot])) {
+            (ArrayData::Char(units), None) => Some(String::from_utf16_lossy(units)),
+            (ArrayData::Byte(bytes), Some(Value::Int(LATIN1))) => {
+                Some(bytes.iter().map(|&byte| byte as u8 as char).collect())
+            }
+            (ArrayData::Byte(bytes), Some(Value::Int(UTF16))) => {
+                let units: Vec<_> = bytes.chunks(2).map(|pair| u16::from_le_bytes([pair[0] as u8, pair[1] as u8])).collect();
+                Some(String::from_utf16_lossy(&units))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Slots of the `value` field of strings, and of their `coder` field if they have one.
+fn string_layout(class: &Arc<Class>) -> Option<(usize, Option<usize>)> {
+    let slot = |name, desc| Class::lookup_field(class, name, desc).map(|(class, field)| class.fields[field].slot);
+    match slot("value", "[C") {
+        Some(value) => Some((value, None)),
+        None => Some((slot("value", "[B")?, Some(slot("coder", "B")?))),
+    }
+}
@@ -0,0 +1,52 @@
+use classfile::descriptor::{BaseType, FieldType};
+use std::fmt;
+use super::heap::Reference;
+
+/// Value of a local variable or operand stack entry.
+///
+/// Unlike in the class file, `long` and `double` values take a single operand stack entry. In locals,
+/// the entry following them is left unused.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    /// `boolean`, `byte`, `char`, `short` and `int` values.
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Null,
+    Reference(Reference),
+    /// Offset of the instruction following a `jsr`.
+    ReturnAddress(usize),
+}
+
+impl Value {
+    /// Initial value of fields and array elements of type `ty`.
+    pub fn default_for(ty: &FieldType) -> Value {
+        match *ty {
+            FieldType::Base(BaseType::Long) => Value::Long(0),
+            FieldType::Base(BaseType::Float) => Value::Float(0.0),
+            FieldType::Base(BaseType::Double) => Value::Double(0.0),
+            FieldType::Base(..) => Value::Int(0),
+            FieldType::Object(..) | FieldType::Array(..) => Value::Null,
+        }
+    }
+
+    /// Whether the value takes two local variables.
+    pub fn is_category2(&self) -> bool {
+        matches!(*self, Value::Long(..) | Value::Double(..))
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Long(value) => write!(f, "{}L", value),
+            Value::Float(value) => write!(f, "{}f", value),
+            Value::Double(value) => write!(f, "{}d", value),
+            Value::Null => write!(f, "null"),
+            Value::Reference(reference) => write!(f, "{}", reference),
+            Value::ReturnAddress(pc) => write!(f, "returnAddress({})", pc),
+        }
+    }
+}
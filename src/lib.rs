@@ -1,3 +1,8 @@
+#[macro_use] extern crate error_chain;
+#[macro_use] extern crate log;
 pub extern crate jvm_classfile as classfile;
+extern crate zip;
 
 pub mod classpath;
+pub mod interpreter;
+pub mod verifier;
@@ -0,0 +1,64 @@
+use classfile;
+use classpath;
+
+error_chain! {
+    links {
+        Classfile(classfile::error::Error, classfile::error::ErrorKind);
+        Classpath(classpath::error::Error, classpath::error::ErrorKind);
+    }
+
+    errors {
+        BadBranch(pc: usize, target: i64) {
+            description("Bad branch target")
+            display("Bad branch target {} at pc {}", target, pc)
+        }
+        BadConstant(pc: usize, index: usize) {
+            description("Bad constant pool reference")
+            display("Bad constant pool reference #{} at pc {}", index, pc)
+        }
+        BadExceptionHandler(index: usize) {
+            description("Bad exception handler")
+            display("Bad exception handler #{}", index)
+        }
+        BadInstruction(pc: usize, reason: &'static str) {
+            description("Illegal instruction")
+            display("{} at pc {}", reason, pc)
+        }
+        BadLocal(pc: usize, index: usize) {
+            description("Bad local variable index")
+            display("Bad local variable index {} at pc {}", index, pc)
+        }
+        BadStackMapFrame(offset: usize, reason: &'static str) {
+            description("Bad stack map frame")
+            display("Bad stack map frame at pc {}: {}", offset, reason)
+        }
+        FallsOffCode {
+            description("Execution falls off the end of the code")
+            display("Execution falls off the end of the code")
+        }
+        FrameMismatch(pc: usize, expected: String, actual: String) {
+            description("Incompatible stack map frame")
+            display("Incompatible stack map frame at pc {}: expected {}, found {}", pc, expected, actual)
+        }
+        Method(name: String, desc: String) {
+            description("Verification failed")
+            display("Verification of method {}{} failed", name, desc)
+        }
+        MissingStackMapFrame(pc: usize) {
+            description("Missing stack map frame")
+            display("Missing stack map frame at pc {}", pc)
+        }
+        StackOverflow(pc: usize) {
+            description("Operand stack overflow")
+            display("Operand stack overflow at pc {}", pc)
+        }
+        StackUnderflow(pc: usize) {
+            description("Operand stack underflow")
+            display("Operand stack underflow at pc {}", pc)
+        }
+        TypeMismatch(pc: usize, expected: String, actual: String) {
+            description("Type mismatch")
+            display("Type mismatch at pc {}: expected {}, found {}", pc, expected, actual)
+        }
+    }
+}
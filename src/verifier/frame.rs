@@ -0,0 +1,325 @@
+use classfile::attr::info::code::StackMapTableAttrInfo;
+use classfile::attr::info::code::stack_map::frame::StackMapFrameInfo;
+use classfile::attr::info::code::stack_map::verification_type::VerificationTypeInfo;
+use std::fmt;
+use super::{ClassHierarchy, Context};
+use super::error::*;
+use super::types::{self, VerificationType};
+
+/// Types of the local variables and operand stack entries at some point of a method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub locals: Vec<VerificationType>,
+    pub stack: Vec<VerificationType>,
+    /// Whether `this` may still be uninitialized, in an instance initializer.
+    pub this_uninit: bool,
+}
+
+impl Frame {
+    /// Frame at the start of the method, as derived from its descriptor.
+    pub fn initial(ctx: &Context) -> Result<Frame> {
+        let locals = initial_locals(ctx);
+        let len = expand(&locals).len();
+        if len > ctx.code.max_locals {
+            bail!(ErrorKind::BadLocal(0, len - 1));
+        }
+        Frame::from_compact(ctx, 0, &locals, &[])
+    }
+
+    /// Build a frame from locals and stack entries where `long` and `double` values take a single entry,
+    /// as found in the `StackMapTable` attribute.
+    fn from_compact(ctx: &Context, offset: usize, locals: &[VerificationType], stack: &[VerificationType])
+        -> Result<Frame>
+    {
+        let mut locals = expand(locals);
+        if locals.len() > ctx.code.max_locals {
+            bail!(ErrorKind::BadStackMapFrame(offset, "More locals than max_locals"));
+        }
+        locals.resize(ctx.code.max_locals, VerificationType::Top);
+
+        let stack = expand(stack);
+        if stack.len() > ctx.code.max_stack {
+            bail!(ErrorKind::BadStackMapFrame(offset, "Operand stack larger than max_stack"));
+        }
+
+        let this_uninit = locals.contains(&VerificationType::UninitializedThis);
+        Ok(Frame {
+            locals: locals,
+            stack: stack,
+            this_uninit: this_uninit,
+        })
+    }
+
+    /// Whether this frame can flow into the frame `to` (JVMS §4.10.1.4).
+    pub fn is_assignable<H: ClassHierarchy + ?Sized>(&self, to: &Frame, hierarchy: &H) -> Result<bool> {
+        if self.locals.len() != to.locals.len() || self.stack.len() != to.stack.len() {
+            return Ok(false);
+        }
+        if self.this_uninit && !to.this_uninit {
+            return Ok(false);
+        }
+
+        for (from, to) in self.locals.iter().chain(self.stack.iter()).zip(to.locals.iter().chain(to.stack.iter())) {
+            if !try!(types::is_assignable(from, to, hierarchy)) {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Merge the frame `other` flowing into this one at `pc` during type inference (JVMS §4.10.2.2),
+    /// returning whether this frame changed.
+    pub fn merge<H: ClassHierarchy + ?Sized>(&mut self, pc: usize, other: &Frame, hierarchy: &H) -> Result<bool> {
+        let mismatch = || ErrorKind::FrameMismatch(pc, self.to_string(), other.to_string());
+        if self.locals.len() != other.locals.len() || self.stack.len() != other.stack.len() {
+            bail!(mismatch());
+        }
+
+        // Values on the stack must stay usable, unlike locals which are merely invalidated
+        let mut stack = Vec::with_capacity(self.stack.len());
+        for (a, b) in self.stack.iter().zip(other.stack.iter()) {
+            let ty = try!(types::merge(a, b, hierarchy));
+            if ty == VerificationType::Top && (*a != ty || *b != ty) {
+                bail!(mismatch());
+            }
+            stack.push(ty);
+        }
+
+        let mut locals = Vec::with_capacity(self.locals.len());
+        for (a, b) in self.locals.iter().zip(other.locals.iter()) {
+            locals.push(try!(types::merge(a, b, hierarchy)));
+        }
+
+        let merged = Frame {
+            locals: locals,
+            stack: stack,
+            this_uninit: self.this_uninit || other.this_uninit,
+        };
+        if merged == *self {
+            return Ok(false);
+        }
+        *self = merged;
+        Ok(true)
+    }
+
+    pub fn push(&mut self, pc: usize, ty: VerificationType, max_stack: usize) -> Result<()> {
+        let size = if ty.is_category2() { 2 } else { 1 };
+        if self.stack.len() + size > max_stack {
+            bail!(ErrorKind::StackOverflow(pc));
+        }
+
+        let is_category2 = ty.is_category2();
+        self.stack.push(ty);
+        if is_category2 {
+            self.stack.push(VerificationType::Top);
+        }
+        Ok(())
+    }
+
+    /// Pop a value, `long` and `double` values being popped as a whole.
+    pub fn pop(&mut self, pc: usize) -> Result<VerificationType> {
+        let ty = try!(self.stack.pop().ok_or(ErrorKind::StackUnderflow(pc)));
+        if ty == VerificationType::Top && self.stack.last().is_some_and(VerificationType::is_category2) {
+            return Ok(self.stack.pop().unwrap());
+        }
+        Ok(ty)
+    }
+
+    /// Pop a value assignable to `expected`.
+    pub fn pop_matching<H: ClassHierarchy + ?Sized>(&mut self, pc: usize, expected: &VerificationType, hierarchy: &H)
+        -> Result<VerificationType>
+    {
+        let ty = try!(self.pop(pc));
+        if !try!(types::is_assignable(&ty, expected, hierarchy)) {
+            bail!(ErrorKind::TypeMismatch(pc, expected.to_string(), ty.to_string()));
+        }
+        Ok(ty)
+    }
+
+    /// Pop a reference, possibly uninitialized.
+    pub fn pop_reference(&mut self, pc: usize) -> Result<VerificationType> {
+        let ty = try!(self.pop(pc));
+        if !ty.is_reference() {
+            bail!(ErrorKind::TypeMismatch(pc, "reference".to_owned(), ty.to_string()));
+        }
+        Ok(ty)
+    }
+
+    /// Check that the `depth` topmost stack entries don't start in the middle of a `long` or `double` value,
+    /// so that they can be moved around as a whole.
+    pub fn check_boundary(&self, pc: usize, depth: usize) -> Result<()> {
+        if depth > self.stack.len() {
+            bail!(ErrorKind::StackUnderflow(pc));
+        }
+
+        let index = self.stack.len() - depth;
+        if index > 0 && self.stack[index] == VerificationType::Top && self.stack[index - 1].is_category2() {
+            let actual = self.stack[index - 1].to_string();
+            bail!(ErrorKind::TypeMismatch(pc, "category 1 value".to_owned(), actual));
+        }
+        Ok(())
+    }
+
+    pub fn local(&self, pc: usize, index: usize) -> Result<&VerificationType> {
+        self.locals.get(index).ok_or_else(|| ErrorKind::BadLocal(pc, index).into())
+    }
+
+    /// Check that the local variable `index` holds a value assignable to `expected`, and return its type.
+    pub fn load<H: ClassHierarchy + ?Sized>(&self, pc: usize, index: usize, expected: &VerificationType,
+                                            hierarchy: &H) -> Result<VerificationType>
+    {
+        if expected.is_category2() && index + 1 >= self.locals.len() {
+            bail!(ErrorKind::BadLocal(pc, index + 1));
+        }
+
+        let ty = try!(self.local(pc, index));
+        if !try!(types::is_assignable(ty, expected, hierarchy)) {
+            bail!(ErrorKind::TypeMismatch(pc, expected.to_string(), ty.to_string()));
+        }
+        Ok(ty.clone())
+    }
+
+    /// Store a value of type `ty` into the local variable `index`, invalidating the `long` or `double`
+    /// value it overwrites half of, if any.
+    pub fn store(&mut self, pc: usize, index: usize, ty: VerificationType) -> Result<()> {
+        let size = if ty.is_category2() { 2 } else { 1 };
+        if index + size > self.locals.len() {
+            bail!(ErrorKind::BadLocal(pc, index + size - 1));
+        }
+
+        if index > 0 && self.locals[index - 1].is_category2() {
+            self.locals[index - 1] = VerificationType::Top;
+        }
+        if size == 2 || (index + 1 < self.locals.len() && self.locals[index].is_category2()) {
+            self.locals[index + 1] = VerificationType::Top;
+        }
+        self.locals[index] = ty;
+        Ok(())
+    }
+
+    /// Replace every occurrence of `from` in the locals and on the stack by `to`.
+    pub fn replace(&mut self, from: &VerificationType, to: &VerificationType) {
+        for ty in self.locals.iter_mut().chain(self.stack.iter_mut()) {
+            if ty == from {
+                *ty = to.clone();
+            }
+        }
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn write_types(f: &mut fmt::Formatter, types: &[VerificationType]) -> fmt::Result {
+            try!(write!(f, "["));
+            for (i, ty) in types.iter().enumerate() {
+                if i > 0 {
+                    try!(write!(f, ", "));
+                }
+                try!(write!(f, "{}", ty));
+            }
+            write!(f, "]")
+        }
+
+        try!(write!(f, "{{locals: "));
+        try!(write_types(f, &self.locals));
+        try!(write!(f, ", stack: "));
+        try!(write_types(f, &self.stack));
+        if self.this_uninit {
+            try!(write!(f, ", flags: [flagThisUninit]"));
+        }
+        write!(f, "}}")
+    }
+}
+
+/// Expand the frames of a `StackMapTable` attribute into absolute frames, along with their offsets.
+pub fn stack_map_frames(ctx: &Context, info: &StackMapTableAttrInfo) -> Result<Vec<(usize, Frame)>> {
+    let mut frames: Vec<(usize, Frame)> = Vec::with_capacity(info.entries().len());
+    let mut locals = initial_locals(ctx);
+
+    for entry in info.entries() {
+        let offset = match frames.last() {
+            Some(&(prev, _)) => prev + entry.offset_delta() + 1,
+            None => entry.offset_delta(),
+        };
+
+        let stack = match entry.info {
+            StackMapFrameInfo::SameFrame | StackMapFrameInfo::SameFrameExtended(..) => Vec::new(),
+            StackMapFrameInfo::SameLocalsOneStackItemFrame(ref info) => {
+                vec![try!(convert(ctx, offset, &info.stack_item))]
+            }
+            StackMapFrameInfo::SameLocalsOneStackItemFrameExtended(ref info) => {
+                vec![try!(convert(ctx, offset, &info.stack_item))]
+            }
+            StackMapFrameInfo::ChopFrame(ref info) => {
+                if info.chopped() > locals.len() {
+                    bail!(ErrorKind::BadStackMapFrame(offset, "Chops more locals than defined"));
+                }
+                let len = locals.len() - info.chopped();
+                locals.truncate(len);
+                Vec::new()
+            }
+            StackMapFrameInfo::AppendFrame(ref info) => {
+                for local in info.locals.iter() {
+                    locals.push(try!(convert(ctx, offset, local)));
+                }
+                Vec::new()
+            }
+            StackMapFrameInfo::FullFrame(ref info) => {
+                locals = try!(info.locals.iter().map(|local| convert(ctx, offset, local)).collect());
+                try!(info.stack.iter().map(|item| convert(ctx, offset, item)).collect())
+            }
+        };
+
+        let frame = try!(Frame::from_compact(ctx, offset, &locals, &stack));
+        frames.push((offset, frame));
+    }
+
+    Ok(frames)
+}
+
+/// Locals at the start of the method, `long` and `double` values taking a single entry.
+fn initial_locals(ctx: &Context) -> Vec<VerificationType> {
+    let mut locals = Vec::with_capacity(ctx.desc.params.len() + 1);
+    if !ctx.is_static {
+        if ctx.name == "<init>" && ctx.this_class != types::OBJECT {
+            locals.push(VerificationType::UninitializedThis);
+        } else {
+            locals.push(VerificationType::Reference(ctx.this_class.to_owned()));
+        }
+    }
+    locals.extend(ctx.desc.params.iter().map(VerificationType::from_field_type));
+    locals
+}
+
+fn expand(types: &[VerificationType]) -> Vec<VerificationType> {
+    let mut expanded = Vec::with_capacity(types.len());
+    for ty in types {
+        expanded.push(ty.clone());
+        if ty.is_category2() {
+            expanded.push(VerificationType::Top);
+        }
+    }
+    expanded
+}
+
+fn convert(ctx: &Context, offset: usize, info: &VerificationTypeInfo) -> Result<VerificationType> {
+    let ty = match *info {
+        VerificationTypeInfo::Top => VerificationType::Top,
+        VerificationTypeInfo::Integer => VerificationType::Integer,
+        VerificationTypeInfo::Float => VerificationType::Float,
+        VerificationTypeInfo::Long => VerificationType::Long,
+        VerificationTypeInfo::Double => VerificationType::Double,
+        VerificationTypeInfo::Null => VerificationType::Null,
+        VerificationTypeInfo::UninitializedThis => VerificationType::UninitializedThis,
+        VerificationTypeInfo::Object(..) => {
+            match info.class(ctx.pool).and_then(|class| class.name(ctx.pool)) {
+                Some(name) => VerificationType::Reference(name.to_owned()),
+                None => bail!(ErrorKind::BadStackMapFrame(offset, "Bad class reference")),
+            }
+        }
+        VerificationTypeInfo::Uninitialized(new_offset) => VerificationType::Uninitialized(new_offset),
+    };
+
+    Ok(ty)
+}
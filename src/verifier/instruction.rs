@@ -0,0 +1,705 @@
+use classfile::bytecode::{self, ArrayType, Instruction};
+use classfile::constant::ConstantPoolEntry;
+use classfile::descriptor::{FieldType, MethodDescriptor, ReturnType};
+use super::{ClassHierarchy, Context};
+use super::error::*;
+use super::frame::Frame;
+use super::types::{self, VerificationType as Type};
+
+/// Where execution may continue after an instruction.
+#[derive(Debug, Default)]
+pub struct Flow {
+    /// Whether execution may continue with the next instruction.
+    pub falls_through: bool,
+    /// Absolute offsets of the instructions that may be branched to, exception handlers aside.
+    pub targets: Vec<usize>,
+}
+
+/// Check the operands of the instruction `insn` at `pc` against `frame`, and update it with the effect
+/// of the instruction (JVMS §4.10.1.9).
+///
+/// `jsr` and `ret` are rejected, being left to type inference which handles subroutines itself.
+pub fn execute<H>(ctx: &Context, hierarchy: &H, pc: usize, insn: &Instruction, frame: &mut Frame) -> Result<Flow>
+    where H: ClassHierarchy + ?Sized
+{
+    let mut step = Step {
+        ctx: ctx,
+        hierarchy: hierarchy,
+        pc: pc,
+        frame: frame,
+        flow: Flow {
+            falls_through: true,
+            targets: Vec::new(),
+        },
+    };
+
+    try!(step.execute(insn));
+    Ok(step.flow)
+}
+
+struct Step<'a, 'b, H: ClassHierarchy + ?Sized + 'a> {
+    ctx: &'a Context<'a>,
+    hierarchy: &'a H,
+    pc: usize,
+    frame: &'b mut Frame,
+    flow: Flow,
+}
+
+impl<'a, 'b, H: ClassHierarchy + ?Sized> Step<'a, 'b, H> {
+    fn execute(&mut self, insn: &Instruction) -> Result<()> {
+        match *insn {
+            Instruction::Nop => {}
+            Instruction::AconstNull => try!(self.push(Type::Null)),
+            Instruction::IconstM1 | Instruction::Iconst0 | Instruction::Iconst1 | Instruction::Iconst2 |
+            Instruction::Iconst3 | Instruction::Iconst4 | Instruction::Iconst5 |
+            Instruction::Bipush(..) | Instruction::Sipush(..) => try!(self.push(Type::Integer)),
+            Instruction::Lconst0 | Instruction::Lconst1 => try!(self.push(Type::Long)),
+            Instruction::Fconst0 | Instruction::Fconst1 | Instruction::Fconst2 => try!(self.push(Type::Float)),
+            Instruction::Dconst0 | Instruction::Dconst1 => try!(self.push(Type::Double)),
+            Instruction::Ldc(index) | Instruction::LdcW(index) => try!(self.ldc(index, false)),
+            Instruction::Ldc2W(index) => try!(self.ldc(index, true)),
+
+            Instruction::Iload(index) => try!(self.load(index as usize, Type::Integer)),
+            Instruction::Lload(index) => try!(self.load(index as usize, Type::Long)),
+            Instruction::Fload(index) => try!(self.load(index as usize, Type::Float)),
+            Instruction::Dload(index) => try!(self.load(index as usize, Type::Double)),
+            Instruction::Aload(index) => try!(self.load_reference(index as usize)),
+            Instruction::Iload0 => try!(self.load(0, Type::Integer)),
+            Instruction::Iload1 => try!(self.load(1, Type::Integer)),
+            Instruction::Iload2 => try!(self.load(2, Type::Integer)),
+            Instruction::Iload3 => try!(self.load(3, Type::Integer)),
+            Instruction::Lload0 => try!(self.load(0, Type::Long)),
+            Instruction::Lload1 => try!(self.load(1, Type::Long)),
+            Instruction::Lload2 => try!(self.load(2, Type::Long)),
+            Instruction::Lload3 => try!(self.load(3, Type::Long)),
+            Instruction::Fload0 => try!(self.load(0, Type::Float)),
+            Instruction::Fload1 => try!(self.load(1, Type::Float)),
+            Instruction::Fload2 => try!(self.load(2, Type::Float)),
+            Instruction::Fload3 => try!(self.load(3, Type::Float)),
+            Instruction::Dload0 => try!(self.load(0, Type::Double)),
+            Instruction::Dload1 => try!(self.load(1, Type::Double)),
+            Instruction::Dload2 => try!(self.load(2, Type::Double)),
+            Instruction::Dload3 => try!(self.load(3, Type::Double)),
+            Instruction::Aload0 => try!(self.load_reference(0)),
+            Instruction::Aload1 => try!(self.load_reference(1)),
+            Instruction::Aload2 => try!(self.load_reference(2)),
+            Instruction::Aload3 => try!(self.load_reference(3)),
+
+            Instruction::Iaload => try!(self.array_load(&["[I"], Type::Integer)),
+            Instruction::Laload => try!(self.array_load(&["[J"], Type::Long)),
+            Instruction::Faload => try!(self.array_load(&["[F"], Type::Float)),
+            Instruction::Daload => try!(self.array_load(&["[D"], Type::Double)),
+            Instruction::Baload => try!(self.array_load(&["[B", "[Z"], Type::Integer)),
+            Instruction::Caload => try!(self.array_load(&["[C"], Type::Integer)),
+            Instruction::Saload => try!(self.array_load(&["[S"], Type::Integer)),
+            Instruction::Aaload => {
+                try!(self.pop(Type::Integer));
+                let array = try!(self.frame.pop(self.pc));
+                let component = match array {
+                    Type::Null => Type::Null,
+                    _ => match array.component() {
+                        Some(component) if component.is_reference() => component,
+                        _ => bail!(ErrorKind::TypeMismatch(self.pc, "array of references".to_owned(), array.to_string())),
+                    },
+                };
+                try!(self.push(component));
+            }
+
+            Instruction::Istore(index) => try!(self.store(index as usize, Type::Integer)),
+            Instruction::Lstore(index) => try!(self.store(index as usize, Type::Long)),
+            Instruction::Fstore(index) => try!(self.store(index as usize, Type::Float)),
+            Instruction::Dstore(index) => try!(self.store(index as usize, Type::Double)),
+            Instruction::Astore(index) => try!(self.store_reference(index as usize)),
+            Instruction::Istore0 => try!(self.store(0, Type::Integer)),
+            Instruction::Istore1 => try!(self.store(1, Type::Integer)),
+            Instruction::Istore2 => try!(self.store(2, Type::Integer)),
+            Instruction::Istore3 => try!(self.store(3, Type::Integer)),
+            Instruction::Lstore0 => try!(self.store(0, Type::Long)),
+            Instruction::Lstore1 => try!(self.store(1, Type::Long)),
+            Instruction::Lstore2 => try!(self.store(2, Type::Long)),
+            Instruction::Lstore3 => try!(self.store(3, Type::Long)),
+            Instruction::Fstore0 => try!(self.store(0, Type::Float)),
+            Instruction::Fstore1 => try!(self.store(1, Type::Float)),
+            Instruction::Fstore2 => try!(self.store(2, Type::Float)),
+            Instruction::Fstore3 => try!(self.store(3, Type::Float)),
+            Instruction::Dstore0 => try!(self.store(0, Type::Double)),
+            Instruction::Dstore1 => try!(self.store(1, Type::Double)),
+            Instruction::Dstore2 => try!(self.store(2, Type::Double)),
+            Instruction::Dstore3 => try!(self.store(3, Type::Double)),
+            Instruction::Astore0 => try!(self.store_reference(0)),
+            Instruction::Astore1 => try!(self.store_reference(1)),
+            Instruction::Astore2 => try!(self.store_reference(2)),
+            Instruction::Astore3 => try!(self.store_reference(3)),
+
+            Instruction::Iastore => try!(self.array_store(&["[I"], Type::Integer)),
+            Instruction::Lastore => try!(self.array_store(&["[J"], Type::Long)),
+            Instruction::Fastore => try!(self.array_store(&["[F"], Type::Float)),
+            Instruction::Dastore => try!(self.array_store(&["[D"], Type::Double)),
+            Instruction::Bastore => try!(self.array_store(&["[B", "[Z"], Type::Integer)),
+            Instruction::Castore => try!(self.array_store(&["[C"], Type::Integer)),
+            Instruction::Sastore => try!(self.array_store(&["[S"], Type::Integer)),
+            Instruction::Aastore => {
+                // Whether the value fits in the array is checked at run time
+                try!(self.pop(Type::object()));
+                try!(self.pop(Type::Integer));
+                let array = try!(self.frame.pop(self.pc));
+                if array != Type::Null && !array.component().is_some_and(|component| component.is_reference()) {
+                    bail!(ErrorKind::TypeMismatch(self.pc, "array of references".to_owned(), array.to_string()));
+                }
+            }
+
+            // Stack manipulation, as sequences of stack entries that mustn't split `long` or `double` values
+            Instruction::Pop => try!(self.shuffle(&[1], &[])),
+            Instruction::Pop2 => try!(self.shuffle(&[2], &[])),
+            Instruction::Dup => try!(self.shuffle(&[1], &[0, 0])),
+            Instruction::DupX1 => try!(self.shuffle(&[1, 2], &[1, 0, 1])),
+            Instruction::DupX2 => try!(self.shuffle(&[1, 3], &[2, 0, 1, 2])),
+            Instruction::Dup2 => try!(self.shuffle(&[2], &[0, 1, 0, 1])),
+            Instruction::Dup2X1 => try!(self.shuffle(&[2, 3], &[1, 2, 0, 1, 2])),
+            Instruction::Dup2X2 => try!(self.shuffle(&[2, 4], &[2, 3, 0, 1, 2, 3])),
+            Instruction::Swap => try!(self.shuffle(&[1, 2], &[1, 0])),
+
+            Instruction::Iadd | Instruction::Isub | Instruction::Imul | Instruction::Idiv | Instruction::Irem |
+            Instruction::Iand | Instruction::Ior | Instruction::Ixor |
+            Instruction::Ishl | Instruction::Ishr | Instruction::Iushr => try!(self.binary(Type::Integer, Type::Integer)),
+            Instruction::Ladd | Instruction::Lsub | Instruction::Lmul | Instruction::Ldiv | Instruction::Lrem |
+            Instruction::Land | Instruction::Lor | Instruction::Lxor => try!(self.binary(Type::Long, Type::Long)),
+            Instruction::Lshl | Instruction::Lshr | Instruction::Lushr => try!(self.binary(Type::Long, Type::Integer)),
+            Instruction::Fadd | Instruction::Fsub | Instruction::Fmul | Instruction::Fdiv |
+            Instruction::Frem => try!(self.binary(Type::Float, Type::Float)),
+            Instruction::Dadd | Instruction::Dsub | Instruction::Dmul | Instruction::Ddiv |
+            Instruction::Drem => try!(self.binary(Type::Double, Type::Double)),
+            Instruction::Ineg | Instruction::I2b | Instruction::I2c |
+            Instruction::I2s => try!(self.unary(Type::Integer, Type::Integer)),
+            Instruction::Lneg => try!(self.unary(Type::Long, Type::Long)),
+            Instruction::Fneg => try!(self.unary(Type::Float, Type::Float)),
+            Instruction::Dneg => try!(self.unary(Type::Double, Type::Double)),
+            Instruction::Iinc(index, _) => {
+                try!(self.frame.load(self.pc, index as usize, &Type::Integer, self.hierarchy));
+            }
+            Instruction::I2l => try!(self.unary(Type::Integer, Type::Long)),
+            Instruction::I2f => try!(self.unary(Type::Integer, Type::Float)),
+            Instruction::I2d => try!(self.unary(Type::Integer, Type::Double)),
+            Instruction::L2i => try!(self.unary(Type::Long, Type::Integer)),
+            Instruction::L2f => try!(self.unary(Type::Long, Type::Float)),
+            Instruction::L2d => try!(self.unary(Type::Long, Type::Double)),
+            Instruction::F2i => try!(self.unary(Type::Float, Type::Integer)),
+            Instruction::F2l => try!(self.unary(Type::Float, Type::Long)),
+            Instruction::F2d => try!(self.unary(Type::Float, Type::Double)),
+            Instruction::D2i => try!(self.unary(Type::Double, Type::Integer)),
+            Instruction::D2l => try!(self.unary(Type::Double, Type::Long)),
+            Instruction::D2f => try!(self.unary(Type::Double, Type::Float)),
+            Instruction::Lcmp => try!(self.compare(Type::Long)),
+            Instruction::Fcmpl | Instruction::Fcmpg => try!(self.compare(Type::Float)),
+            Instruction::Dcmpl | Instruction::Dcmpg => try!(self.compare(Type::Double)),
+
+            Instruction::Ifeq(offset) | Instruction::Ifne(offset) | Instruction::Iflt(offset) |
+            Instruction::Ifge(offset) | Instruction::Ifgt(offset) | Instruction::Ifle(offset) => {
+                try!(self.pop(Type::Integer));
+                try!(self.branch(offset));
+            }
+            Instruction::IfIcmpeq(offset) | Instruction::IfIcmpne(offset) | Instruction::IfIcmplt(offset) |
+            Instruction::IfIcmpge(offset) | Instruction::IfIcmpgt(offset) | Instruction::IfIcmple(offset) => {
+                try!(self.pop(Type::Integer));
+                try!(self.pop(Type::Integer));
+                try!(self.branch(offset));
+            }
+            Instruction::IfAcmpeq(offset) | Instruction::IfAcmpne(offset) => {
+                try!(self.frame.pop_reference(self.pc));
+                try!(self.frame.pop_reference(self.pc));
+                try!(self.branch(offset));
+            }
+            Instruction::Ifnull(offset) | Instruction::Ifnonnull(offset) => {
+                try!(self.frame.pop_reference(self.pc));
+                try!(self.branch(offset));
+            }
+            Instruction::Goto(offset) | Instruction::GotoW(offset) => {
+                try!(self.branch(offset));
+                self.flow.falls_through = false;
+            }
+            Instruction::Jsr(..) | Instruction::JsrW(..) | Instruction::Ret(..) => {
+                bail!(ErrorKind::BadInstruction(self.pc, "Subroutines aren't allowed with stack map frames"));
+            }
+            Instruction::Tableswitch(ref info) => {
+                try!(self.pop(Type::Integer));
+                try!(self.branch(info.default));
+                for &offset in info.offsets.iter() {
+                    try!(self.branch(offset));
+                }
+                self.flow.falls_through = false;
+            }
+            Instruction::Lookupswitch(ref info) => {
+                if info.pairs.windows(2).any(|pairs| pairs[0].0 >= pairs[1].0) {
+                    bail!(ErrorKind::BadInstruction(self.pc, "Unsorted lookupswitch keys"));
+                }
+                try!(self.pop(Type::Integer));
+                try!(self.branch(info.default));
+                for &(_, offset) in info.pairs.iter() {
+                    try!(self.branch(offset));
+                }
+                self.flow.falls_through = false;
+            }
+
+            Instruction::Ireturn => try!(self.return_value(Type::Integer)),
+            Instruction::Lreturn => try!(self.return_value(Type::Long)),
+            Instruction::Freturn => try!(self.return_value(Type::Float)),
+            Instruction::Dreturn => try!(self.return_value(Type::Double)),
+            Instruction::Areturn => try!(self.return_value(Type::object())),
+            Instruction::Return => {
+                if self.ctx.desc.ret != ReturnType::Void {
+                    bail!(ErrorKind::TypeMismatch(self.pc, self.ctx.desc.ret.to_string(), "void".to_owned()));
+                }
+                if self.frame.this_uninit {
+                    bail!(ErrorKind::BadInstruction(self.pc, "Returning before `this` is initialized"));
+                }
+                self.flow.falls_through = false;
+            }
+
+            Instruction::Getstatic(index) => {
+                let (_, _, ty) = try!(self.field_ref(index));
+                try!(self.push(Type::from_field_type(&ty)));
+            }
+            Instruction::Putstatic(index) => {
+                let (_, _, ty) = try!(self.field_ref(index));
+                try!(self.pop(Type::from_field_type(&ty)));
+            }
+            Instruction::Getfield(index) => {
+                let (class, name, ty) = try!(self.field_ref(index));
+                let object = try!(self.pop(Type::Reference(class.to_owned())));
+                try!(self.check_protected(class, name, &ty.to_string(), &object));
+                try!(self.push(Type::from_field_type(&ty)));
+            }
+            Instruction::Putfield(index) => {
+                let (class, name, ty) = try!(self.field_ref(index));
+                try!(self.pop(Type::from_field_type(&ty)));
+
+                // Fields declared by the class can be set before calling the super constructor
+                let object = try!(self.frame.pop(self.pc));
+                if object != Type::UninitializedThis || class != self.ctx.this_class || !self.declares_field(name, &ty) {
+                    let expected = Type::Reference(class.to_owned());
+                    if !try!(types::is_assignable(&object, &expected, self.hierarchy)) {
+                        bail!(ErrorKind::TypeMismatch(self.pc, expected.to_string(), object.to_string()));
+                    }
+                    try!(self.check_protected(class, name, &ty.to_string(), &object));
+                }
+            }
+
+            Instruction::Invokevirtual(index) => {
+                let (class, name, desc) = try!(self.method_ref(index, false, true));
+                try!(self.check_method_name(name));
+                try!(self.pop_args(&desc));
+                let object = try!(self.pop(Type::Reference(class.to_owned())));
+                try!(self.check_protected(class, name, &desc.to_string(), &object));
+                try!(self.push_return(&desc));
+            }
+            Instruction::Invokespecial(index) => {
+                let interface = self.ctx.class.version.allows_interface_method_refs();
+                let (class, name, desc) = try!(self.method_ref(index, interface, true));
+                try!(self.pop_args(&desc));
+                if name == "<init>" {
+                    try!(self.initialize(class));
+                } else {
+                    try!(self.check_method_name(name));
+                    try!(self.pop(Type::Reference(self.ctx.this_class.to_owned())));
+                }
+                try!(self.push_return(&desc));
+            }
+            Instruction::Invokestatic(index) => {
+                let interface = self.ctx.class.version.allows_interface_method_refs();
+                let (_, name, desc) = try!(self.method_ref(index, interface, true));
+                try!(self.check_method_name(name));
+                try!(self.pop_args(&desc));
+                try!(self.push_return(&desc));
+            }
+            Instruction::Invokeinterface(index, count) => {
+                let (class, name, desc) = try!(self.method_ref(index, true, false));
+                try!(self.check_method_name(name));
+                if count as usize != desc.arg_slots() + 1 {
+                    bail!(ErrorKind::BadInstruction(self.pc, "Bad invokeinterface argument count"));
+                }
+                try!(self.pop_args(&desc));
+                try!(self.pop(Type::Reference(class.to_owned())));
+                try!(self.push_return(&desc));
+            }
+            Instruction::Invokedynamic(index) => {
+                let (name, desc) = match self.ctx.pool.get(index) {
+                    Some(ConstantPoolEntry::InvokedDynamic(info)) => {
+                        let info = info.name_and_type(self.ctx.pool);
+                        let name = info.and_then(|info| info.name(self.ctx.pool));
+                        let desc = info.and_then(|info| info.method_descriptor(self.ctx.pool).ok());
+                        match (name, desc) {
+                            (Some(name), Some(desc)) => (name, desc),
+                            _ => bail!(ErrorKind::BadConstant(self.pc, index)),
+                        }
+                    }
+                    _ => bail!(ErrorKind::BadConstant(self.pc, index)),
+                };
+                try!(self.check_method_name(name));
+                try!(self.pop_args(&desc));
+                try!(self.push_return(&desc));
+            }
+
+            Instruction::New(index) => {
+                let class = try!(self.class_ref(index));
+                if class.starts_with('[') {
+                    bail!(ErrorKind::BadInstruction(self.pc, "`new` of an array type"));
+                }
+
+                let ty = Type::Uninitialized(self.pc);
+                if self.frame.stack.contains(&ty) {
+                    bail!(ErrorKind::BadInstruction(self.pc, "Uninitialized object already on the stack"));
+                }
+                self.frame.replace(&ty, &Type::Top);
+                try!(self.push(ty));
+            }
+            Instruction::Newarray(ty) => {
+                try!(self.pop(Type::Integer));
+                try!(self.push(Type::Reference(format!("[{}", array_type_char(ty)))));
+            }
+            Instruction::Anewarray(index) => {
+                let class = try!(self.class_ref(index));
+                try!(self.pop(Type::Integer));
+                try!(self.push(Type::array_of(class)));
+            }
+            Instruction::Multianewarray(index, dimensions) => {
+                let class = try!(self.class_ref(index));
+                let array_dimensions = class.bytes().take_while(|&b| b == b'[').count();
+                if dimensions == 0 || array_dimensions < dimensions as usize {
+                    bail!(ErrorKind::BadInstruction(self.pc, "Bad multianewarray dimensions"));
+                }
+                for _ in 0..dimensions {
+                    try!(self.pop(Type::Integer));
+                }
+                try!(self.push(Type::Reference(class.to_owned())));
+            }
+            Instruction::Arraylength => {
+                let array = try!(self.frame.pop(self.pc));
+                if array != Type::Null && array.array_name().is_none() {
+                    bail!(ErrorKind::TypeMismatch(self.pc, "array".to_owned(), array.to_string()));
+                }
+                try!(self.push(Type::Integer));
+            }
+            Instruction::Athrow => {
+                try!(self.pop(Type::Reference(types::THROWABLE.to_owned())));
+                self.flow.falls_through = false;
+            }
+            Instruction::Checkcast(index) => {
+                let class = try!(self.class_ref(index));
+                try!(self.pop(Type::object()));
+                try!(self.push(Type::Reference(class.to_owned())));
+            }
+            Instruction::Instanceof(index) => {
+                try!(self.class_ref(index));
+                try!(self.pop(Type::object()));
+                try!(self.push(Type::Integer));
+            }
+            Instruction::Monitorenter | Instruction::Monitorexit => {
+                try!(self.pop(Type::object()));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn push(&mut self, ty: Type) -> Result<()> {
+        self.frame.push(self.pc, ty, self.ctx.code.max_stack)
+    }
+
+    fn pop(&mut self, expected: Type) -> Result<Type> {
+        self.frame.pop_matching(self.pc, &expected, self.hierarchy)
+    }
+
+    fn branch(&mut self, offset: i32) -> Result<()> {
+        let target = self.pc as i64 + offset as i64;
+        if target < 0 || target >= self.ctx.code.code.len() as i64 {
+            bail!(ErrorKind::BadBranch(self.pc, target));
+        }
+
+        self.flow.targets.push(target as usize);
+        Ok(())
+    }
+
+    fn load(&mut self, index: usize, ty: Type) -> Result<()> {
+        try!(self.frame.load(self.pc, index, &ty, self.hierarchy));
+        self.push(ty)
+    }
+
+    /// Load a reference, which may be uninitialized.
+    fn load_reference(&mut self, index: usize) -> Result<()> {
+        let ty = try!(self.frame.local(self.pc, index)).clone();
+        if !ty.is_reference() {
+            bail!(ErrorKind::TypeMismatch(self.pc, "reference".to_owned(), ty.to_string()));
+        }
+        self.push(ty)
+    }
+
+    fn store(&mut self, index: usize, ty: Type) -> Result<()> {
+        try!(self.pop(ty.clone()));
+        self.frame.store(self.pc, index, ty)
+    }
+
+    /// Store a reference, which may be uninitialized, or the return address of a subroutine.
+    fn store_reference(&mut self, index: usize) -> Result<()> {
+        let ty = try!(self.frame.pop(self.pc));
+        if !ty.is_reference() && !matches!(ty, Type::ReturnAddress(..)) {
+            bail!(ErrorKind::TypeMismatch(self.pc, "reference".to_owned(), ty.to_string()));
+        }
+        self.frame.store(self.pc, index, ty)
+    }
+
+    /// Pop an array reference whose type is one of `arrays`, or `null`.
+    fn pop_array(&mut self, arrays: &[&str]) -> Result<()> {
+        let array = try!(self.frame.pop(self.pc));
+        match array {
+            Type::Null => Ok(()),
+            Type::Reference(ref name) if arrays.contains(&name.as_str()) => Ok(()),
+            _ => bail!(ErrorKind::TypeMismatch(self.pc, arrays.join(" or "), array.to_string())),
+        }
+    }
+
+    fn array_load(&mut self, arrays: &[&str], ty: Type) -> Result<()> {
+        try!(self.pop(Type::Integer));
+        try!(self.pop_array(arrays));
+        self.push(ty)
+    }
+
+    fn array_store(&mut self, arrays: &[&str], ty: Type) -> Result<()> {
+        try!(self.pop(ty));
+        try!(self.pop(Type::Integer));
+        self.pop_array(arrays)
+    }
+
+    /// Replace the topmost stack entries by some of them, as stack manipulation instructions do.
+    ///
+    /// The entries are taken as a whole, down to the greatest depth of `boundaries`, none of which may fall
+    /// in the middle of a `long` or `double` value. They are then pushed back in the order given by
+    /// `result`, as indexes from the deepest entry taken.
+    fn shuffle(&mut self, boundaries: &[usize], result: &[usize]) -> Result<()> {
+        for &depth in boundaries {
+            try!(self.frame.check_boundary(self.pc, depth));
+        }
+
+        let depth = boundaries.iter().cloned().max().unwrap_or(0);
+        let len = self.frame.stack.len();
+        if len - depth + result.len() > self.ctx.code.max_stack {
+            bail!(ErrorKind::StackOverflow(self.pc));
+        }
+
+        let entries = self.frame.stack.split_off(len - depth);
+        self.frame.stack.extend(result.iter().map(|&index| entries[index].clone()));
+        Ok(())
+    }
+
+    fn unary(&mut self, operand: Type, result: Type) -> Result<()> {
+        try!(self.pop(operand));
+        self.push(result)
+    }
+
+    /// Pop an operand of type `right` then one of type `left`, pushing a value of type `left`.
+    fn binary(&mut self, left: Type, right: Type) -> Result<()> {
+        try!(self.pop(right));
+        try!(self.pop(left.clone()));
+        self.push(left)
+    }
+
+    fn compare(&mut self, ty: Type) -> Result<()> {
+        try!(self.pop(ty.clone()));
+        try!(self.pop(ty));
+        self.push(Type::Integer)
+    }
+
+    /// Return a value of the kind of `ty`, checking it against the return type of the method.
+    fn return_value(&mut self, ty: Type) -> Result<()> {
+        let expected = match self.ctx.desc.ret {
+            ReturnType::Type(ref ret) => Some(Type::from_field_type(ret)),
+            ReturnType::Void => None,
+        };
+
+        match expected {
+            Some(ref expected) if expected == &ty || (expected.is_reference() && ty.is_reference()) => {
+                try!(self.pop(expected.clone()));
+            }
+            _ => {
+                let expected = expected.map_or_else(|| "void".to_owned(), |expected| expected.to_string());
+                bail!(ErrorKind::TypeMismatch(self.pc, expected, ty.to_string()));
+            }
+        }
+
+        self.flow.falls_through = false;
+        Ok(())
+    }
+
+    fn ldc(&mut self, index: usize, wide: bool) -> Result<()> {
+        let pool = self.ctx.pool;
+        let ty = match pool.get(index) {
+            Some(&ConstantPoolEntry::Integer(..)) => Type::Integer,
+            Some(&ConstantPoolEntry::Float(..)) => Type::Float,
+            Some(&ConstantPoolEntry::Long(..)) => Type::Long,
+            Some(&ConstantPoolEntry::Double(..)) => Type::Double,
+            Some(&ConstantPoolEntry::String(..)) => Type::Reference("java/lang/String".to_owned()),
+            Some(&ConstantPoolEntry::Class(..)) => Type::Reference("java/lang/Class".to_owned()),
+            Some(&ConstantPoolEntry::MethodType(..)) => Type::Reference("java/lang/invoke/MethodType".to_owned()),
+            Some(&ConstantPoolEntry::MethodHandle(..)) => Type::Reference("java/lang/invoke/MethodHandle".to_owned()),
+            Some(ConstantPoolEntry::Dynamic(info)) => {
+                match info.name_and_type(pool).and_then(|info| info.field_type(pool).ok()) {
+                    Some(ty) => Type::from_field_type(&ty),
+                    None => bail!(ErrorKind::BadConstant(self.pc, index)),
+                }
+            }
+            _ => bail!(ErrorKind::BadConstant(self.pc, index)),
+        };
+
+        if ty.is_category2() != wide {
+            bail!(ErrorKind::BadConstant(self.pc, index));
+        }
+        self.push(ty)
+    }
+
+    fn class_ref(&self, index: usize) -> Result<&'a str> {
+        let pool = self.ctx.pool;
+        pool.get_class_info(index)
+            .and_then(|info| info.name(pool))
+            .ok_or_else(|| ErrorKind::BadConstant(self.pc, index).into())
+    }
+
+    /// Class, name and type of a `Fieldref` constant.
+    fn field_ref(&self, index: usize) -> Result<(&'a str, &'a str, FieldType)> {
+        let pool = self.ctx.pool;
+        let resolved = match pool.get(index) {
+            Some(ConstantPoolEntry::FieldRef(info)) => {
+                let class = info.class(pool).and_then(|class| class.name(pool));
+                let info = info.name_and_type(pool);
+                let name = info.and_then(|info| info.name(pool));
+                let ty = info.and_then(|info| info.field_type(pool).ok());
+                match (class, name, ty) {
+                    (Some(class), Some(name), Some(ty)) => Some((class, name, ty)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        resolved.ok_or_else(|| ErrorKind::BadConstant(self.pc, index).into())
+    }
+
+    /// Class, name and descriptor of a `Methodref` or `InterfaceMethodref` constant, as allowed.
+    fn method_ref(&self, index: usize, interface: bool, class: bool) -> Result<(&'a str, &'a str, MethodDescriptor)> {
+        let pool = self.ctx.pool;
+        let (class_info, info) = match pool.get(index) {
+            Some(ConstantPoolEntry::MethodRef(info)) if class => (info.class(pool), info.name_and_type(pool)),
+            Some(ConstantPoolEntry::InterfaceMethodRef(info)) if interface => {
+                (info.class(pool), info.name_and_type(pool))
+            }
+            _ => bail!(ErrorKind::BadConstant(self.pc, index)),
+        };
+
+        let class = class_info.and_then(|class| class.name(pool));
+        let name = info.and_then(|info| info.name(pool));
+        let desc = info.and_then(|info| info.method_descriptor(pool).ok());
+        match (class, name, desc) {
+            (Some(class), Some(name), Some(desc)) => Ok((class, name, desc)),
+            _ => bail!(ErrorKind::BadConstant(self.pc, index)),
+        }
+    }
+
+    /// Check that a protected member of a superclass in another run-time package is only accessed
+    /// through an `object` of the current class or of a subclass (JVMS §4.10.1.8).
+    fn check_protected(&self, class: &str, name: &str, desc: &str, object: &Type) -> Result<()> {
+        let this_class = self.ctx.this_class;
+        if class == this_class || package(class) == package(this_class) {
+            return Ok(());
+        }
+        if !try!(types::is_subclass(this_class, class, self.hierarchy)) ||
+            !try!(self.hierarchy.is_protected(class, name, desc)) {
+            return Ok(());
+        }
+        // Arrays override `clone` publicly, yet javac 1.4 and older call it as `Object.clone`
+        if class == types::OBJECT && name == "clone" && object.array_name().is_some() {
+            return Ok(());
+        }
+
+        let expected = Type::Reference(this_class.to_owned());
+        if !try!(types::is_assignable(object, &expected, self.hierarchy)) {
+            bail!(ErrorKind::TypeMismatch(self.pc, expected.to_string(), object.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Check that a method other than `<init>` called through `invokespecial` isn't an initializer.
+    fn check_method_name(&self, name: &str) -> Result<()> {
+        if name.starts_with('<') {
+            bail!(ErrorKind::BadInstruction(self.pc, "Illegal call to an initialization method"));
+        }
+        Ok(())
+    }
+
+    fn declares_field(&self, name: &str, ty: &FieldType) -> bool {
+        let pool = self.ctx.pool;
+        self.ctx.class.fields.iter().any(|field| {
+            field.name(pool) == Some(name) && field.field_type(pool).ok().as_ref() == Some(ty)
+        })
+    }
+
+    fn pop_args(&mut self, desc: &MethodDescriptor) -> Result<()> {
+        for param in desc.params.iter().rev() {
+            try!(self.pop(Type::from_field_type(param)));
+        }
+        Ok(())
+    }
+
+    fn push_return(&mut self, desc: &MethodDescriptor) -> Result<()> {
+        match desc.ret {
+            ReturnType::Type(ref ty) => self.push(Type::from_field_type(ty)),
+            ReturnType::Void => Ok(()),
+        }
+    }
+
+    /// Pop the object initialized by a call to an `<init>` method of `class`, and mark it initialized.
+    fn initialize(&mut self, class: &str) -> Result<()> {
+        let object = try!(self.frame.pop(self.pc));
+        let initialized = match object {
+            Type::UninitializedThis => {
+                // Either another constructor of this class, or one of the super class
+                if class != self.ctx.this_class && Some(class) != self.ctx.super_class {
+                    let expected = self.ctx.super_class.unwrap_or(self.ctx.this_class);
+                    bail!(ErrorKind::TypeMismatch(self.pc, expected.to_owned(), class.to_owned()));
+                }
+                self.frame.this_uninit = false;
+                Type::Reference(self.ctx.this_class.to_owned())
+            }
+            Type::Uninitialized(offset) => {
+                let created = match bytecode::decode(&self.ctx.code.code, offset) {
+                    Ok((Instruction::New(index), _)) => try!(self.class_ref(index)),
+                    _ => bail!(ErrorKind::BadInstruction(self.pc, "Uninitialized object not created by `new`")),
+                };
+                if created != class {
+                    bail!(ErrorKind::TypeMismatch(self.pc, created.to_owned(), class.to_owned()));
+                }
+                Type::Reference(class.to_owned())
+            }
+            _ => bail!(ErrorKind::TypeMismatch(self.pc, "uninitialized".to_owned(), object.to_string())),
+        };
+
+        self.frame.replace(&object, &initialized);
+        Ok(())
+    }
+}
+
+/// Descriptor character of the elements of arrays created by `newarray`.
+fn array_type_char(ty: ArrayType) -> char {
+    match ty {
+        ArrayType::Boolean => 'Z',
+        ArrayType::Char => 'C',
+        ArrayType::Float => 'F',
+        ArrayType::Double => 'D',
+        ArrayType::Byte => 'B',
+        ArrayType::Short => 'S',
+        ArrayType::Int => 'I',
+        ArrayType::Long => 'J',
+    }
+}
+
+/// Package of the class named `class`, empty for the unnamed package.
+fn package(class: &str) -> &str {
+    class.rfind('/').map_or("", |index| &class[..index])
+}
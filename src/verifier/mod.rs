@@ -0,0 +1,571 @@
+//! Bytecode verification (JVMS §4.10).
+
+pub mod error;
+pub mod frame;
+pub mod type_checker;
+pub mod type_inference;
+pub mod types;
+mod instruction;
+
+pub use self::frame::Frame;
+pub use self::types::VerificationType;
+
+use classfile::{field, flags, method, Classfile};
+use classfile::attr::info::AttrInfo;
+use classfile::attr::info::method::CodeAttrInfo;
+use classfile::bytecode::Instruction;
+use classfile::constant::ConstantPool;
+use classfile::descriptor::MethodDescriptor;
+use classfile::method::MethodInfo;
+use classpath::Classpath;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use std::rc::Rc;
+use self::error::*;
+
+/// Knowledge of the classes referenced by the verified code, needed to check assignments between
+/// reference types.
+pub trait ClassHierarchy {
+    /// Name of the super class of `class`, `None` for `java/lang/Object`.
+    fn super_class(&self, class: &str) -> Result<Option<String>>;
+    fn is_interface(&self, class: &str) -> Result<bool>;
+    /// Whether `class` itself declares a protected field or method named `name` with descriptor `desc`.
+    fn is_protected(&self, class: &str, name: &str, desc: &str) -> Result<bool>;
+}
+
+/// What the verifier needs to know about a class.
+#[derive(Debug)]
+struct ClassInfo {
+    super_class: Option<String>,
+    is_interface: bool,
+    /// Names and descriptors of the protected members declared by the class.
+    protected: HashSet<(String, String)>,
+}
+
+/// Class hierarchy loaded from a classpath, each class being read once.
+#[derive(Debug)]
+pub struct ClasspathHierarchy<'a> {
+    classpath: &'a Classpath,
+    classes: RefCell<HashMap<String, Rc<ClassInfo>>>,
+}
+
+impl<'a> ClasspathHierarchy<'a> {
+    pub fn new(classpath: &'a Classpath) -> ClasspathHierarchy<'a> {
+        ClasspathHierarchy {
+            classpath: classpath,
+            classes: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn class(&self, name: &str) -> Result<Rc<ClassInfo>> {
+        if let Some(class) = self.classes.borrow().get(name) {
+            return Ok(class.clone());
+        }
+
+        let data = try!(self.classpath.read_class(name));
+        let class = try!(Classfile::read(&mut Cursor::new(data)));
+        let pool = &class.constant_pool;
+        let super_class = class.super_class().and_then(|info| info.name(pool));
+
+        let mut protected = HashSet::new();
+        let fields = class.fields.iter()
+            .filter(|field| field.access_flags.contains(field::flags::AccessFlags::ACC_PROTECTED))
+            .map(|field| (field.name(pool), field.desc(pool)));
+        let methods = class.methods.iter()
+            .filter(|method| method.access_flags.contains(method::flags::AccessFlags::ACC_PROTECTED))
+            .map(|method| (method.name(pool), method.desc(pool)));
+        for member in fields.chain(methods) {
+            if let (Some(name), Some(desc)) = member {
+                protected.insert((name.to_owned(), desc.to_owned()));
+            }
+        }
+
+        let info = Rc::new(ClassInfo {
+            super_class: super_class.map(str::to_owned),
+            is_interface: class.access_flags.contains(flags::AccessFlags::ACC_INTERFACE),
+            protected: protected,
+        });
+        self.classes.borrow_mut().insert(name.to_owned(), info.clone());
+        Ok(info)
+    }
+}
+
+impl<'a> ClassHierarchy for ClasspathHierarchy<'a> {
+    fn super_class(&self, class: &str) -> Result<Option<String>> {
+        self.class(class).map(|info| info.super_class.clone())
+    }
+
+    fn is_interface(&self, class: &str) -> Result<bool> {
+        self.class(class).map(|info| info.is_interface)
+    }
+
+    fn is_protected(&self, class: &str, name: &str, desc: &str) -> Result<bool> {
+        self.class(class).map(|info| info.protected.contains(&(name.to_owned(), desc.to_owned())))
+    }
+}
+
+/// The method being verified, along with what's needed from its class.
+#[derive(Debug)]
+pub struct Context<'a> {
+    pub class: &'a Classfile,
+    pub pool: &'a ConstantPool,
+    pub this_class: &'a str,
+    pub super_class: Option<&'a str>,
+    pub name: &'a str,
+    pub desc: MethodDescriptor,
+    pub is_static: bool,
+    pub code: &'a CodeAttrInfo,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(class: &'a Classfile, method: &'a MethodInfo, code: &'a CodeAttrInfo) -> Result<Context<'a>> {
+        use classfile::error::{Error as ClassfileError, ErrorKind as ClassfileErrorKind};
+
+        let pool = &class.constant_pool;
+        let this_class = try!(class.this_class()
+            .and_then(|info| info.name(pool))
+            .ok_or_else(|| ClassfileError::from(ClassfileErrorKind::FormatViolation("Bad this_class"))));
+        let name = try!(method.name(pool).ok_or_else(|| {
+            ClassfileError::from(ClassfileErrorKind::BadConstantIndex(method.name_index(), "Utf8"))
+        }));
+
+        Ok(Context {
+            class: class,
+            pool: pool,
+            this_class: this_class,
+            super_class: class.super_class().and_then(|info| info.name(pool)),
+            name: name,
+            desc: try!(method.descriptor(pool)),
+            is_static: method.access_flags.contains(method::flags::AccessFlags::ACC_STATIC),
+            code: code,
+        })
+    }
+}
+
+/// Verify every method of `class`.
+pub fn verify<H: ClassHierarchy + ?Sized>(class: &Classfile, hierarchy: &H) -> Result<()> {
+    let pool = &class.constant_pool;
+    for method in class.methods.iter() {
+        let code = match code(method) {
+            Some(code) => code,
+            None => continue,
+        };
+
+        try!(verify_method(class, method, code, hierarchy).chain_err(|| {
+            let name = method.name(pool).unwrap_or("?").to_owned();
+            let desc = method.desc(pool).unwrap_or("?").to_owned();
+            ErrorKind::Method(name, desc)
+        }));
+    }
+
+    Ok(())
+}
+
+/// Verify a method by type checking against the frames of its `StackMapTable` attribute, or by type
+/// inference in class files older than version 50.
+pub fn verify_method<H: ClassHierarchy + ?Sized>(class: &Classfile, method: &MethodInfo, code: &CodeAttrInfo,
+                                                 hierarchy: &H) -> Result<()> {
+    let ctx = try!(Context::new(class, method, code));
+    if !class.version.uses_type_checking() {
+        return type_inference::infer_types(&ctx, hierarchy);
+    }
+
+    let stack_map = code.attrs.iter().filter_map(|attr| match attr.info {
+        AttrInfo::StackMapTable(ref info) => Some(info),
+        _ => None,
+    }).next();
+    let frames = match stack_map {
+        Some(info) => try!(frame::stack_map_frames(&ctx, info)),
+        None => Vec::new(),
+    };
+
+    type_checker::type_check(&ctx, &frames, hierarchy)
+}
+
+fn code(method: &MethodInfo) -> Option<&CodeAttrInfo> {
+    method.attrs.iter().filter_map(|attr| match attr.info {
+        AttrInfo::Code(ref info) => Some(info),
+        _ => None,
+    }).next()
+}
+
+/// Instructions of a method along with their offsets, and whether an instruction starts at each offset.
+type Decoded = (Vec<(usize, Instruction)>, Vec<bool>);
+
+fn decode(code: &CodeAttrInfo) -> Result<Decoded> {
+    let mut instructions = Vec::new();
+    let mut starts = vec![false; code.code.len()];
+    for insn in code.instructions() {
+        let (pc, insn) = try!(insn);
+        starts[pc] = true;
+        instructions.push((pc, insn));
+    }
+
+    if instructions.is_empty() {
+        bail!(ErrorKind::FallsOffCode);
+    }
+    Ok((instructions, starts))
+}
+
+/// Whether there is a `new` instruction at `offset`.
+fn is_new(code: &CodeAttrInfo, starts: &[bool], offset: usize) -> bool {
+    const NEW: u8 = 0xbb;
+    starts.get(offset).cloned().unwrap_or(false) && code.code[offset] == NEW
+}
+
+/// Check the ranges of the exception handlers of the method, and return the type of the exceptions
+/// caught by each.
+fn catch_types<H: ClassHierarchy + ?Sized>(ctx: &Context, starts: &[bool], hierarchy: &H)
+    -> Result<Vec<VerificationType>>
+{
+    let is_start = |offset: usize| starts.get(offset).cloned().unwrap_or(false);
+    let throwable = VerificationType::Reference(types::THROWABLE.to_owned());
+
+    let mut catch_types = Vec::with_capacity(ctx.code.exception_handlers.len());
+    for (i, handler) in ctx.code.exception_handlers.iter().enumerate() {
+        let end_ok = handler.end_pc == starts.len() || is_start(handler.end_pc);
+        if handler.start_pc >= handler.end_pc || !is_start(handler.start_pc) || !end_ok ||
+            !is_start(handler.handler_pc) {
+            bail!(ErrorKind::BadExceptionHandler(i));
+        }
+
+        let catch_type = if handler.catch_type_index() == 0 {
+            throwable.clone()
+        } else {
+            match handler.catch_type(ctx.pool).and_then(|info| info.name(ctx.pool)) {
+                Some(name) => VerificationType::Reference(name.to_owned()),
+                None => bail!(ErrorKind::BadExceptionHandler(i)),
+            }
+        };
+        if !try!(types::is_assignable(&catch_type, &throwable, hierarchy)) {
+            bail!(ErrorKind::BadExceptionHandler(i));
+        }
+        catch_types.push(catch_type);
+    }
+
+    Ok(catch_types)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use classfile::Classfile;
+    use std::collections::HashMap;
+    use std::io::Cursor;
+    use super::*;
+
+    /// Constant pool being assembled, each entry encoded with its tag.
+    #[derive(Default)]
+    pub struct Pool {
+        entries: Vec<Vec<u8>>,
+    }
+
+    impl Pool {
+        fn add(&mut self, entry: Vec<u8>) -> u16 {
+            match self.entries.iter().position(|other| *other == entry) {
+                Some(index) => index as u16 + 1,
+                None => {
+                    self.entries.push(entry);
+                    self.entries.len() as u16
+                }
+            }
+        }
+
+        pub fn utf8(&mut self, value: &str) -> u16 {
+            let mut entry = vec![1];
+            entry.extend(&(value.len() as u16).to_be_bytes());
+            entry.extend(value.as_bytes());
+            self.add(entry)
+        }
+
+        pub fn class(&mut self, name: &str) -> u16 {
+            let name = self.utf8(name);
+            self.add(with_indexes(7, &[name]))
+        }
+
+        fn member_ref(&mut self, tag: u8, class: &str, name: &str, desc: &str) -> u16 {
+            let class = self.class(class);
+            let (name, desc) = (self.utf8(name), self.utf8(desc));
+            let name_and_type = self.add(with_indexes(12, &[name, desc]));
+            self.add(with_indexes(tag, &[class, name_and_type]))
+        }
+
+        pub fn field_ref(&mut self, class: &str, name: &str, desc: &str) -> u16 {
+            self.member_ref(9, class, name, desc)
+        }
+
+        pub fn method_ref(&mut self, class: &str, name: &str, desc: &str) -> u16 {
+            self.member_ref(10, class, name, desc)
+        }
+    }
+
+    fn with_indexes(tag: u8, indexes: &[u16]) -> Vec<u8> {
+        let mut entry = vec![tag];
+        for index in indexes {
+            entry.extend(&index.to_be_bytes());
+        }
+        entry
+    }
+
+    /// Class `p/A` with a single method, whose code is given as is.
+    pub struct Fixture {
+        pub version: u16,
+        pub super_class: &'static str,
+        pub pool: Pool,
+        pub access_flags: u16,
+        pub name: &'static str,
+        pub desc: &'static str,
+        pub max_stack: u16,
+        pub max_locals: u16,
+        pub code: Vec<u8>,
+        /// `start_pc`, `end_pc`, `handler_pc` and `catch_type` of each exception handler.
+        pub handlers: Vec<[u16; 4]>,
+        /// Number of entries and content of the `StackMapTable` attribute, if any.
+        pub stack_map: Option<(u16, Vec<u8>)>,
+    }
+
+    impl Fixture {
+        /// Static method `m()V` of a version 52 class.
+        pub fn new(max_stack: u16, max_locals: u16, code: &[u8]) -> Fixture {
+            Fixture {
+                version: 52,
+                super_class: "java/lang/Object",
+                pool: Pool::default(),
+                access_flags: 0x0009,
+                name: "m",
+                desc: "()V",
+                max_stack: max_stack,
+                max_locals: max_locals,
+                code: code.to_vec(),
+                handlers: Vec::new(),
+                stack_map: None,
+            }
+        }
+
+        pub fn build(mut self) -> Classfile {
+            let this_class = self.pool.class("p/A");
+            let super_class = self.pool.class(self.super_class);
+            let (name, desc) = (self.pool.utf8(self.name), self.pool.utf8(self.desc));
+            let code_name = self.pool.utf8("Code");
+
+            let mut code = Vec::new();
+            code.extend(&self.max_stack.to_be_bytes());
+            code.extend(&self.max_locals.to_be_bytes());
+            code.extend(&(self.code.len() as u32).to_be_bytes());
+            code.extend(&self.code);
+            code.extend(&(self.handlers.len() as u16).to_be_bytes());
+            for handler in self.handlers.iter() {
+                for index in handler {
+                    code.extend(&index.to_be_bytes());
+                }
+            }
+            match self.stack_map {
+                Some((count, ref frames)) => {
+                    let stack_map_name = self.pool.utf8("StackMapTable");
+                    code.extend(&[0, 1]);
+                    code.extend(&stack_map_name.to_be_bytes());
+                    code.extend(&(frames.len() as u32 + 2).to_be_bytes());
+                    code.extend(&count.to_be_bytes());
+                    code.extend(frames);
+                }
+                None => code.extend(&[0, 0]),
+            }
+
+            let mut data = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0];
+            data.extend(&self.version.to_be_bytes());
+            data.extend(&(self.pool.entries.len() as u16 + 1).to_be_bytes());
+            for entry in self.pool.entries.iter() {
+                data.extend(entry);
+            }
+            data.extend(&[0x00, 0x21]);
+            data.extend(&this_class.to_be_bytes());
+            data.extend(&super_class.to_be_bytes());
+            data.extend(&[0, 0, 0, 0, 0, 1]);
+            data.extend(&self.access_flags.to_be_bytes());
+            data.extend(&name.to_be_bytes());
+            data.extend(&desc.to_be_bytes());
+            data.extend(&[0, 1]);
+            data.extend(&code_name.to_be_bytes());
+            data.extend(&(code.len() as u32).to_be_bytes());
+            data.extend(&code);
+            data.extend(&[0, 0]);
+
+            Classfile::read(&mut Cursor::new(data)).unwrap()
+        }
+
+        /// Verify the method, returning the kind of the error if it's rejected.
+        pub fn verify(self, hierarchy: &Hierarchy) -> Option<ErrorKind> {
+            let class = self.build();
+            let method = &class.methods[0];
+            match verify_method(&class, method, code(method).unwrap(), hierarchy) {
+                Ok(()) => None,
+                Err(Error(kind, _)) => Some(kind),
+            }
+        }
+    }
+
+    /// Class known to the verifier.
+    struct Known {
+        super_class: Option<&'static str>,
+        protected: Vec<(&'static str, &'static str)>,
+    }
+
+    /// Classes known to the verifier, none of them an interface.
+    pub struct Hierarchy {
+        classes: HashMap<&'static str, Known>,
+    }
+
+    impl Default for Hierarchy {
+        /// `java/lang/Object` and `p/A` extending it.
+        fn default() -> Hierarchy {
+            let hierarchy = Hierarchy {
+                classes: HashMap::new(),
+            };
+            hierarchy.class("p/A", "java/lang/Object", &[])
+        }
+    }
+
+    impl Hierarchy {
+        pub fn new() -> Hierarchy {
+            Hierarchy::default()
+        }
+
+        /// Add `name` extending `super_class`, declaring the `protected` fields and methods.
+        pub fn class(mut self, name: &'static str, super_class: &'static str,
+                     protected: &[(&'static str, &'static str)]) -> Hierarchy {
+            self.classes.entry("java/lang/Object").or_insert(Known {
+                super_class: None,
+                protected: vec![("clone", "()Ljava/lang/Object;")],
+            });
+            self.classes.insert(name, Known {
+                super_class: Some(super_class),
+                protected: protected.to_vec(),
+            });
+            self
+        }
+
+        fn get(&self, class: &str) -> Result<&Known> {
+            self.classes.get(class).ok_or_else(|| format!("Unknown class {}", class).into())
+        }
+    }
+
+    impl ClassHierarchy for Hierarchy {
+        fn super_class(&self, class: &str) -> Result<Option<String>> {
+            self.get(class).map(|known| known.super_class.map(str::to_owned))
+        }
+
+        fn is_interface(&self, class: &str) -> Result<bool> {
+            self.get(class).map(|_| false)
+        }
+
+        fn is_protected(&self, class: &str, name: &str, desc: &str) -> Result<bool> {
+            self.get(class).map(|known| known.protected.contains(&(name, desc)))
+        }
+    }
+
+    #[test]
+    fn accepts_valid_code() {
+        // iconst_0; istore_0; iload_0; pop; return
+        let fixture = Fixture::new(1, 1, &[0x03, 0x3b, 0x1a, 0x57, 0xb1]);
+        assert_eq!(fixture.verify(&Hierarchy::new()).map(|kind| kind.to_string()), None);
+    }
+
+    #[test]
+    fn rejects_stack_underflow() {
+        // pop; return
+        let fixture = Fixture::new(1, 0, &[0x57, 0xb1]);
+        assert!(matches!(fixture.verify(&Hierarchy::new()), Some(ErrorKind::StackUnderflow(0))));
+    }
+
+    #[test]
+    fn rejects_bad_branch_targets() {
+        // goto 100
+        let fixture = Fixture::new(0, 0, &[0xa7, 0x00, 0x64]);
+        assert!(matches!(fixture.verify(&Hierarchy::new()), Some(ErrorKind::BadBranch(0, 100))));
+
+        // iconst_0; ifeq 2 (into the ifeq itself); return
+        let mut fixture = Fixture::new(1, 0, &[0x03, 0x99, 0x00, 0x01, 0xb1]);
+        fixture.stack_map = Some((1, vec![4]));
+        assert!(matches!(fixture.verify(&Hierarchy::new()), Some(ErrorKind::BadBranch(1, 2))));
+    }
+
+    #[test]
+    fn rejects_frame_mismatch() {
+        // iconst_0; ifeq 5; nop; return, with an int on the stack at 5 in the stack map
+        let mut fixture = Fixture::new(1, 0, &[0x03, 0x99, 0x00, 0x04, 0x00, 0xb1]);
+        fixture.stack_map = Some((1, vec![64 + 5, 1]));
+        assert!(matches!(fixture.verify(&Hierarchy::new()), Some(ErrorKind::FrameMismatch(1, ..))));
+
+        // The same without the frame
+        let fixture = Fixture::new(1, 0, &[0x03, 0x99, 0x00, 0x04, 0x00, 0xb1]);
+        assert!(matches!(fixture.verify(&Hierarchy::new()), Some(ErrorKind::MissingStackMapFrame(5))));
+    }
+
+    #[test]
+    fn rejects_uninitialized_this_at_return() {
+        // return, without calling the super constructor
+        let mut fixture = Fixture::new(0, 1, &[0xb1]);
+        fixture.access_flags = 0x0001;
+        fixture.name = "<init>";
+        assert!(matches!(fixture.verify(&Hierarchy::new()), Some(ErrorKind::BadInstruction(0, _))));
+
+        // aload_0; invokespecial Object.<init>; return
+        let mut fixture = Fixture::new(1, 1, &[]);
+        let init = fixture.pool.method_ref("java/lang/Object", "<init>", "()V");
+        fixture.code = vec![0x2a, 0xb7, (init >> 8) as u8, init as u8, 0xb1];
+        fixture.access_flags = 0x0001;
+        fixture.name = "<init>";
+        assert!(fixture.verify(&Hierarchy::new()).is_none());
+    }
+
+    #[test]
+    fn rejects_max_stack_and_max_locals_overflow() {
+        // iconst_0; iconst_0; pop2; return
+        let fixture = Fixture::new(1, 0, &[0x03, 0x03, 0x58, 0xb1]);
+        assert!(matches!(fixture.verify(&Hierarchy::new()), Some(ErrorKind::StackOverflow(1))));
+
+        // iconst_0; istore_1; return
+        let fixture = Fixture::new(1, 1, &[0x03, 0x3c, 0xb1]);
+        assert!(matches!(fixture.verify(&Hierarchy::new()), Some(ErrorKind::BadLocal(1, 1))));
+
+        // The arguments don't fit in the locals
+        let mut fixture = Fixture::new(0, 1, &[0xb1]);
+        fixture.desc = "(J)V";
+        assert!(matches!(fixture.verify(&Hierarchy::new()), Some(ErrorKind::BadLocal(0, 1))));
+    }
+
+    #[test]
+    fn checks_protected_access() {
+        let hierarchy = Hierarchy::new()
+            .class("q/B", "java/lang/Object", &[("f", "I")])
+            .class("p/A", "q/B", &[])
+            .class("p/C", "q/B", &[]);
+        let fixture = |receiver| {
+            // aload_0; getfield q/B.f:I; pop; return
+            let mut fixture = Fixture::new(1, 1, &[]);
+            let field = fixture.pool.field_ref("q/B", "f", "I");
+            fixture.code = vec![0x2a, 0xb4, (field >> 8) as u8, field as u8, 0x57, 0xb1];
+            fixture.super_class = "q/B";
+            fixture.desc = receiver;
+            fixture
+        };
+
+        assert!(fixture("(Lp/A;)V").verify(&hierarchy).is_none());
+        // Only through the current class or its subclasses, even in the same package
+        assert!(matches!(fixture("(Lq/B;)V").verify(&hierarchy), Some(ErrorKind::TypeMismatch(1, ..))));
+        assert!(matches!(fixture("(Lp/C;)V").verify(&hierarchy), Some(ErrorKind::TypeMismatch(1, ..))));
+
+        let clone = |receiver| {
+            // aload_0; invokevirtual java/lang/Object.clone:()Ljava/lang/Object;; pop; return
+            let mut fixture = Fixture::new(1, 1, &[]);
+            let method = fixture.pool.method_ref("java/lang/Object", "clone", "()Ljava/lang/Object;");
+            fixture.code = vec![0x2a, 0xb6, (method >> 8) as u8, method as u8, 0x57, 0xb1];
+            fixture.desc = receiver;
+            fixture
+        };
+        // Arrays are cloned through `Object.clone` by older compilers
+        assert!(clone("([I)V").verify(&hierarchy).is_none());
+        assert!(clone("([Ljava/lang/String;)V").verify(&hierarchy).is_none());
+        assert!(matches!(clone("(Lp/C;)V").verify(&hierarchy), Some(ErrorKind::TypeMismatch(1, ..))));
+    }
+}
@@ -0,0 +1,84 @@
+//! Verification by type checking (JVMS §4.10.1), for class files of version 50 and later.
+
+use std::collections::HashMap;
+use super::{ClassHierarchy, Context};
+use super::error::*;
+use super::frame::Frame;
+use super::instruction;
+use super::types::VerificationType;
+
+/// Type check the code of a method, given the frames of its `StackMapTable` attribute along with their
+/// offsets, as returned by `frame::stack_map_frames`.
+pub fn type_check<H: ClassHierarchy + ?Sized>(ctx: &Context, frames: &[(usize, Frame)], hierarchy: &H)
+    -> Result<()>
+{
+    let (instructions, starts) = try!(super::decode(ctx.code));
+
+    let mut stack_map = HashMap::with_capacity(frames.len());
+    for &(offset, ref frame) in frames {
+        if !starts.get(offset).cloned().unwrap_or(false) {
+            bail!(ErrorKind::BadStackMapFrame(offset, "Not at an instruction boundary"));
+        }
+        for ty in frame.locals.iter().chain(frame.stack.iter()) {
+            if let VerificationType::Uninitialized(new) = *ty {
+                if !super::is_new(ctx.code, &starts, new) {
+                    bail!(ErrorKind::BadStackMapFrame(offset, "Uninitialized object not created by `new`"));
+                }
+            }
+        }
+        stack_map.insert(offset, frame);
+    }
+
+    let catch_types = try!(super::catch_types(ctx, &starts, hierarchy));
+    for handler in ctx.code.exception_handlers.iter() {
+        if !stack_map.contains_key(&handler.handler_pc) {
+            bail!(ErrorKind::MissingStackMapFrame(handler.handler_pc));
+        }
+    }
+
+    let mut current = Some(try!(Frame::initial(ctx)));
+    for &(pc, ref insn) in instructions.iter() {
+        if let Some(&frame) = stack_map.get(&pc) {
+            if let Some(ref current) = current {
+                try!(check_flow(pc, current, frame, hierarchy));
+            }
+            current = Some(frame.clone());
+        }
+        let mut frame = try!(current.take().ok_or(ErrorKind::MissingStackMapFrame(pc)));
+
+        // Any instruction in a handler's range may throw, with the locals it started with
+        let handlers = ctx.code.exception_handlers.iter().zip(catch_types.iter());
+        for (handler, catch_type) in handlers.filter(|&(handler, _)| handler.start_pc <= pc && pc < handler.end_pc) {
+            let exception = Frame {
+                locals: frame.locals.clone(),
+                stack: vec![catch_type.clone()],
+                this_uninit: frame.this_uninit,
+            };
+            try!(check_flow(pc, &exception, stack_map[&handler.handler_pc], hierarchy));
+        }
+
+        let flow = try!(instruction::execute(ctx, hierarchy, pc, insn, &mut frame));
+        for &target in flow.targets.iter() {
+            if !starts[target] {
+                bail!(ErrorKind::BadBranch(pc, target as i64));
+            }
+            let target_frame = try!(stack_map.get(&target).ok_or(ErrorKind::MissingStackMapFrame(target)));
+            try!(check_flow(pc, &frame, target_frame, hierarchy));
+        }
+
+        current = if flow.falls_through { Some(frame) } else { None };
+    }
+
+    if current.is_some() {
+        bail!(ErrorKind::FallsOffCode);
+    }
+    Ok(())
+}
+
+/// Check that the frame `from` at `pc` can flow into the stack map frame `to`.
+fn check_flow<H: ClassHierarchy + ?Sized>(pc: usize, from: &Frame, to: &Frame, hierarchy: &H) -> Result<()> {
+    if !try!(from.is_assignable(to, hierarchy)) {
+        bail!(ErrorKind::FrameMismatch(pc, to.to_string(), from.to_string()));
+    }
+    Ok(())
+}
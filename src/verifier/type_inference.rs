@@ -0,0 +1,429 @@
+//! Verification by type inference (JVMS §4.10.2), for class files older than version 50.
+
+use classfile::bytecode::Instruction;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use super::{ClassHierarchy, Context};
+use super::error::*;
+use super::frame::Frame;
+use super::instruction;
+use super::types::VerificationType;
+
+/// Verify the code of a method by inferring the types of its locals and operand stack at each instruction,
+/// until they reach a fixed point.
+///
+/// Subroutines are followed through their calls: on `ret`, the locals modified by the subroutine flow back
+/// to the instructions following each `jsr` to it, along with the other locals as they were before the call.
+pub fn infer_types<H: ClassHierarchy + ?Sized>(ctx: &Context, hierarchy: &H) -> Result<()> {
+    let (instructions, starts) = try!(super::decode(ctx.code));
+    let catch_types = try!(super::catch_types(ctx, &starts, hierarchy));
+
+    let indexes: HashMap<usize, usize> = instructions.iter().enumerate()
+        .map(|(index, &(pc, _))| (pc, index))
+        .collect();
+    let modified = modified_locals(ctx, &instructions, &indexes);
+
+    let mut inference = Inference {
+        ctx: ctx,
+        hierarchy: hierarchy,
+        instructions: &instructions,
+        indexes: &indexes,
+        catch_types: &catch_types,
+        modified: &modified,
+        frames: vec![None; instructions.len()],
+        pending: BTreeSet::new(),
+        callers: HashMap::new(),
+        returns: HashMap::new(),
+    };
+
+    let initial = try!(Frame::initial(ctx));
+    try!(inference.merge_into(0, 0, &initial));
+
+    // Process instructions in code order, which converges faster than a stack
+    while let Some(index) = inference.pending.iter().next().cloned() {
+        inference.pending.remove(&index);
+        try!(inference.step(index));
+    }
+
+    Ok(())
+}
+
+struct Inference<'a, H: ClassHierarchy + ?Sized + 'a> {
+    ctx: &'a Context<'a>,
+    hierarchy: &'a H,
+    instructions: &'a [(usize, Instruction)],
+    /// Index of the instruction at each offset.
+    indexes: &'a HashMap<usize, usize>,
+    catch_types: &'a [VerificationType],
+    /// Locals modified by each subroutine, by offset, including through nested subroutines.
+    modified: &'a HashMap<usize, HashSet<usize>>,
+    /// Inferred frame at the start of each instruction, once reached.
+    frames: Vec<Option<Frame>>,
+    /// Instructions whose frame changed since they were last processed.
+    pending: BTreeSet<usize>,
+    /// Indexes of the `jsr` instructions reached for each subroutine.
+    callers: HashMap<usize, BTreeSet<usize>>,
+    /// Frames merged over the `ret` instructions reached for each subroutine.
+    returns: HashMap<usize, Frame>,
+}
+
+impl<'a, H: ClassHierarchy + ?Sized> Inference<'a, H> {
+    fn step(&mut self, index: usize) -> Result<()> {
+        let (pc, ref insn) = self.instructions[index];
+        let frame = self.frames[index].clone().expect("pending instruction without a frame");
+
+        // Any instruction in a handler's range may throw, with the locals it started with
+        for (handler, catch_type) in self.ctx.code.exception_handlers.iter().zip(self.catch_types.iter()) {
+            if handler.start_pc <= pc && pc < handler.end_pc {
+                let exception = Frame {
+                    locals: frame.locals.clone(),
+                    stack: vec![catch_type.clone()],
+                    this_uninit: frame.this_uninit,
+                };
+                try!(self.merge_into(pc, handler.handler_pc, &exception));
+            }
+        }
+
+        match *insn {
+            Instruction::Jsr(offset) | Instruction::JsrW(offset) => {
+                let target = try!(self.target(pc, offset));
+                let address = VerificationType::ReturnAddress(target);
+                if frame.locals.contains(&address) || frame.stack.contains(&address) {
+                    bail!(ErrorKind::BadInstruction(pc, "Recursive call to a subroutine"));
+                }
+
+                let mut entry = frame;
+                try!(entry.push(pc, address, self.ctx.code.max_stack));
+                try!(self.merge_into(pc, target, &entry));
+
+                self.callers.entry(target).or_default().insert(index);
+                if let Some(ret) = self.returns.get(&target).cloned() {
+                    try!(self.return_to(index, target, &ret));
+                }
+            }
+            Instruction::Ret(local) => {
+                let target = match *try!(frame.local(pc, local as usize)) {
+                    VerificationType::ReturnAddress(target) => target,
+                    ref ty => bail!(ErrorKind::TypeMismatch(pc, "returnAddress".to_owned(), ty.to_string())),
+                };
+
+                let changed = match self.returns.get_mut(&target) {
+                    Some(ret) => try!(ret.merge(pc, &frame, self.hierarchy)),
+                    None => true,
+                };
+                if changed {
+                    let ret = self.returns.entry(target).or_insert(frame).clone();
+                    let callers = self.callers.get(&target).cloned().unwrap_or_default();
+                    for caller in callers {
+                        try!(self.return_to(caller, target, &ret));
+                    }
+                }
+            }
+            _ => {
+                let mut frame = frame;
+                let flow = try!(instruction::execute(self.ctx, self.hierarchy, pc, insn, &mut frame));
+                for &target in flow.targets.iter() {
+                    try!(self.merge_into(pc, target, &frame));
+                }
+                if flow.falls_through {
+                    try!(self.merge_next(index, &frame));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Absolute offset of a branch, which must be an instruction.
+    fn target(&self, pc: usize, offset: i32) -> Result<usize> {
+        let target = pc as i64 + offset as i64;
+        if target < 0 || !self.indexes.contains_key(&(target as usize)) {
+            bail!(ErrorKind::BadBranch(pc, target));
+        }
+        Ok(target as usize)
+    }
+
+    /// Merge `frame` flowing from the instruction at `pc` into the instruction at `target`.
+    fn merge_into(&mut self, pc: usize, target: usize, frame: &Frame) -> Result<()> {
+        let index = match self.indexes.get(&target) {
+            Some(&index) => index,
+            None => bail!(ErrorKind::BadBranch(pc, target as i64)),
+        };
+
+        let changed = match self.frames[index] {
+            Some(ref mut current) => try!(current.merge(target, frame, self.hierarchy)),
+            None => true,
+        };
+        if changed {
+            if self.frames[index].is_none() {
+                self.frames[index] = Some(frame.clone());
+            }
+            self.pending.insert(index);
+        }
+        Ok(())
+    }
+
+    /// Merge `frame` into the instruction following the one at `index`.
+    fn merge_next(&mut self, index: usize, frame: &Frame) -> Result<()> {
+        match self.instructions.get(index + 1) {
+            Some(&(next, _)) => self.merge_into(self.instructions[index].0, next, frame),
+            None => bail!(ErrorKind::FallsOffCode),
+        }
+    }
+
+    /// Flow the frame `ret` at the end of the subroutine at `target` back to the `jsr` at `caller`.
+    fn return_to(&mut self, caller: usize, target: usize, ret: &Frame) -> Result<()> {
+        let frame = {
+            let call = self.frames[caller].as_ref().expect("subroutine call without a frame");
+            let modified = &self.modified[&target];
+            let address = VerificationType::ReturnAddress(target);
+
+            // The return address can't be used once returned through
+            let mut locals: Vec<_> = call.locals.iter().zip(ret.locals.iter()).enumerate().map(|(i, (call, ret))| {
+                let ty = if modified.contains(&i) { ret } else { call };
+                if *ty == address { VerificationType::Top } else { ty.clone() }
+            }).collect();
+
+            // Values the subroutine overwrote half of are no longer usable
+            for i in 1..locals.len() {
+                if locals[i - 1].is_category2() && locals[i] != VerificationType::Top {
+                    locals[i - 1] = VerificationType::Top;
+                }
+            }
+
+            Frame {
+                locals: locals,
+                stack: ret.stack.clone(),
+                this_uninit: ret.this_uninit,
+            }
+        };
+
+        self.merge_next(caller, &frame)
+    }
+}
+
+/// Find the locals modified by each subroutine, by the offset it starts at.
+///
+/// Each instruction belongs to the main code or to a single subroutine: the first, in order of discovery,
+/// to reach it without going through `ret`, calls to nested subroutines returning to the following
+/// instruction. Code reached through exception handlers may thus belong to the main code.
+fn modified_locals(ctx: &Context, instructions: &[(usize, Instruction)], indexes: &HashMap<usize, usize>)
+    -> HashMap<usize, HashSet<usize>>
+{
+    let mut owned = vec![false; instructions.len()];
+    let mut modified = HashMap::new();
+    let mut nested = HashMap::new();
+
+    let mut subroutines = vec![None];
+    let mut next = 0;
+    while next < subroutines.len() {
+        let subroutine = subroutines[next];
+        next += 1;
+
+        let mut locals = HashSet::new();
+        let mut calls = HashSet::new();
+        let mut pending = match subroutine {
+            Some(target) => vec![indexes[&target]],
+            None => vec![0],
+        };
+
+        while let Some(index) = pending.pop() {
+            if owned[index] {
+                continue;
+            }
+            owned[index] = true;
+
+            let (pc, ref insn) = instructions[index];
+            if let Some((local, size)) = stored_local(insn) {
+                locals.extend(local..local + size);
+            }
+            if let Some(offset) = jsr_offset(insn) {
+                let target = (pc as i64 + offset as i64) as usize;
+                if indexes.contains_key(&target) {
+                    calls.insert(target);
+                    if !subroutines.contains(&Some(target)) {
+                        subroutines.push(Some(target));
+                    }
+                }
+            }
+
+            let next = instructions.get(index + 1).map(|&(next, _)| next);
+            let handlers = ctx.code.exception_handlers.iter()
+                .filter(|handler| handler.start_pc <= pc && pc < handler.end_pc)
+                .map(|handler| handler.handler_pc);
+            for successor in successors(pc, insn, next).into_iter().chain(handlers) {
+                if let Some(&index) = indexes.get(&successor) {
+                    pending.push(index);
+                }
+            }
+        }
+
+        if let Some(target) = subroutine {
+            modified.insert(target, locals);
+            nested.insert(target, calls);
+        }
+    }
+
+    // Nested subroutines modify the locals of their callers too
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (target, calls) in nested.iter() {
+            let mut locals = modified[target].clone();
+            for call in calls.iter() {
+                locals.extend(modified[call].iter().cloned());
+            }
+            if locals.len() != modified[target].len() {
+                modified.insert(*target, locals);
+                changed = true;
+            }
+        }
+    }
+
+    modified
+}
+
+fn jsr_offset(insn: &Instruction) -> Option<i32> {
+    match *insn {
+        Instruction::Jsr(offset) | Instruction::JsrW(offset) => Some(offset),
+        _ => None,
+    }
+}
+
+/// Offsets where execution may continue after the instruction at `pc`, `next` being the offset of the
+/// following instruction. Calls to subroutines continue after the call.
+fn successors(pc: usize, insn: &Instruction, next: Option<usize>) -> Vec<usize> {
+    let target = |offset: i32| (pc as i64 + offset as i64) as usize;
+
+    match *insn {
+        Instruction::Goto(offset) | Instruction::GotoW(offset) => vec![target(offset)],
+        Instruction::Tableswitch(ref info) => {
+            Some(info.default).into_iter().chain(info.offsets.iter().cloned()).map(target).collect()
+        }
+        Instruction::Lookupswitch(ref info) => {
+            Some(info.default).into_iter().chain(info.pairs.iter().map(|&(_, offset)| offset)).map(target).collect()
+        }
+        Instruction::Ireturn | Instruction::Lreturn | Instruction::Freturn | Instruction::Dreturn |
+        Instruction::Areturn | Instruction::Return | Instruction::Athrow | Instruction::Ret(..) => Vec::new(),
+        Instruction::Jsr(..) | Instruction::JsrW(..) => next.into_iter().collect(),
+        _ => insn.branch_offset().map(target).into_iter().chain(next).collect(),
+    }
+}
+
+/// Index of the local variable written by a store instruction, along with the number of entries it takes.
+fn stored_local(insn: &Instruction) -> Option<(usize, usize)> {
+    let local = match *insn {
+        Instruction::Istore(index) | Instruction::Fstore(index) | Instruction::Astore(index) => (index as usize, 1),
+        Instruction::Lstore(index) | Instruction::Dstore(index) => (index as usize, 2),
+        Instruction::Istore0 | Instruction::Fstore0 | Instruction::Astore0 => (0, 1),
+        Instruction::Istore1 | Instruction::Fstore1 | Instruction::Astore1 => (1, 1),
+        Instruction::Istore2 | Instruction::Fstore2 | Instruction::Astore2 => (2, 1),
+        Instruction::Istore3 | Instruction::Fstore3 | Instruction::Astore3 => (3, 1),
+        Instruction::Lstore0 | Instruction::Dstore0 => (0, 2),
+        Instruction::Lstore1 | Instruction::Dstore1 => (1, 2),
+        Instruction::Lstore2 | Instruction::Dstore2 => (2, 2),
+        Instruction::Lstore3 | Instruction::Dstore3 => (3, 2),
+        _ => return None,
+    };
+    Some(local)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use super::super::tests::{Fixture, Hierarchy};
+    use super::super::{code, decode, Context, ErrorKind};
+    use super::modified_locals;
+
+    /// Static method `m()V` of a version 49 class, verified by type inference.
+    fn fixture(max_locals: u16, code: &[u8]) -> Fixture {
+        let mut fixture = Fixture::new(1, max_locals, code);
+        fixture.version = 49;
+        fixture
+    }
+
+    #[test]
+    fn nested_subroutines() {
+        let fixture = fixture(3, &[
+            0xa8, 0x00, 0x04, // 0: jsr 4
+            0xb1,             // 3: return
+            0x4c,             // 4: astore_1
+            0xa8, 0x00, 0x05, // 5: jsr 10
+            0xa9, 0x01,       // 8: ret 1
+            0x4d,             // 10: astore_2
+            0xa9, 0x02,       // 11: ret 2
+        ]);
+        assert_eq!(fixture.verify(&Hierarchy::new()).map(|kind| kind.to_string()), None);
+    }
+
+    #[test]
+    fn rejects_recursive_calls() {
+        let fixture = fixture(2, &[
+            0xa8, 0x00, 0x04, // 0: jsr 4
+            0xb1,             // 3: return
+            0x4c,             // 4: astore_1
+            0xa8, 0xff, 0xff, // 5: jsr 4
+            0xa9, 0x01,       // 8: ret 1
+        ]);
+        assert!(matches!(fixture.verify(&Hierarchy::new()), Some(ErrorKind::BadInstruction(5, _))));
+    }
+
+    /// Subroutine at 9 storing `store` into local 2, called once local 2 holds an int, and whose caller then
+    /// loads `load` from local 2.
+    fn modifying_subroutine(store: [u8; 2], load: u8) -> Fixture {
+        fixture(3, &[
+            0x03,             // 0: iconst_0
+            0x3d,             // 1: istore_2
+            0xa8, 0x00, 0x07, // 2: jsr 9
+            load,             // 5: load local 2
+            0x57,             // 6: pop
+            0xb1,             // 7: return
+            0x00,             // 8: nop
+            0x4c,             // 9: astore_1
+            store[0],         // 10: push a value
+            store[1],         // 11: store it into local 2
+            0xa9, 0x01,       // 12: ret 1
+        ])
+    }
+
+    #[test]
+    fn modified_locals_merge_back_at_the_return_site() {
+        // fconst_0; fstore_2, then fload_2 after the call
+        let fixture = modifying_subroutine([0x0b, 0x45], 0x24);
+        assert_eq!(fixture.verify(&Hierarchy::new()).map(|kind| kind.to_string()), None);
+
+        // The int local 2 held before the call was overwritten
+        let fixture = modifying_subroutine([0x0b, 0x45], 0x1c);
+        assert!(matches!(fixture.verify(&Hierarchy::new()), Some(ErrorKind::TypeMismatch(5, ..))));
+
+        // Locals the subroutine leaves alone keep their type from the caller: nop; nop, then iload_2
+        let fixture = modifying_subroutine([0x00, 0x00], 0x1c);
+        assert_eq!(fixture.verify(&Hierarchy::new()).map(|kind| kind.to_string()), None);
+    }
+
+    #[test]
+    fn instructions_belong_to_the_first_subroutine_reaching_them() {
+        let class = fixture(4, &[
+            0xa8, 0x00, 0x07, // 0: jsr 7
+            0xa8, 0x00, 0x0c, // 3: jsr 15
+            0xb1,             // 6: return
+            0x4c,             // 7: astore_1
+            0x03,             // 8: iconst_0
+            0x3e,             // 9: istore_3
+            0xa7, 0x00, 0x06, // 10: goto 16
+            0x00, 0x00,       // 13: nop; nop
+            0x4c,             // 15: astore_1
+            0x0b,             // 16: fconst_0
+            0x45,             // 17: fstore_2
+            0xa9, 0x01,       // 18: ret 1
+        ]).build();
+        let method = &class.methods[0];
+        let ctx = Context::new(&class, method, code(method).unwrap()).unwrap();
+        let (instructions, _) = decode(ctx.code).unwrap();
+        let indexes: HashMap<_, _> = instructions.iter().enumerate().map(|(index, &(pc, _))| (pc, index)).collect();
+
+        // The subroutine at 15 shares its tail with the one at 7, which reached it first
+        let modified = modified_locals(&ctx, &instructions, &indexes);
+        assert_eq!(modified[&7], [1, 2, 3].iter().cloned().collect::<HashSet<_>>());
+        assert_eq!(modified[&15], [1].iter().cloned().collect::<HashSet<_>>());
+    }
+}
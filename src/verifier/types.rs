@@ -0,0 +1,238 @@
+use classfile::descriptor::{BaseType, FieldType};
+use std::collections::HashSet;
+use std::fmt;
+use super::ClassHierarchy;
+use super::error::*;
+
+pub const OBJECT: &str = "java/lang/Object";
+pub const THROWABLE: &str = "java/lang/Throwable";
+
+/// Type of a local variable or operand stack entry (JVMS §4.10.1.2).
+///
+/// `long` and `double` values take two entries: the type itself, then `Top`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum VerificationType {
+    Top,
+    Integer,
+    Float,
+    Long,
+    Double,
+    Null,
+    /// `this` in an instance initializer, before the call to another `<init>` method.
+    UninitializedThis,
+    /// Object created by the `new` instruction at the given offset, not yet initialized.
+    Uninitialized(usize),
+    /// Class, interface or array, by the name used in `Class` constants, e.g. `java/lang/String` or `[I`.
+    Reference(String),
+    /// Return address pushed by a `jsr` instruction to the subroutine at the given offset, in class files
+    /// verified by type inference.
+    ReturnAddress(usize),
+}
+
+impl VerificationType {
+    pub fn object() -> VerificationType {
+        VerificationType::Reference(OBJECT.to_owned())
+    }
+
+    /// Type of values of a field, parameter or array element of type `ty`.
+    pub fn from_field_type(ty: &FieldType) -> VerificationType {
+        match *ty {
+            FieldType::Base(BaseType::Long) => VerificationType::Long,
+            FieldType::Base(BaseType::Double) => VerificationType::Double,
+            FieldType::Base(BaseType::Float) => VerificationType::Float,
+            FieldType::Base(..) => VerificationType::Integer,
+            FieldType::Object(ref name) => VerificationType::Reference(name.clone()),
+            FieldType::Array(..) => VerificationType::Reference(ty.to_string()),
+        }
+    }
+
+    /// Type of arrays of `component`, e.g. `[Ljava/lang/String;` for `java/lang/String`.
+    pub fn array_of(component: &str) -> VerificationType {
+        if component.starts_with('[') {
+            VerificationType::Reference(format!("[{}", component))
+        } else {
+            VerificationType::Reference(format!("[L{};", component))
+        }
+    }
+
+    /// Whether a value of this type takes two entries.
+    pub fn is_category2(&self) -> bool {
+        matches!(*self, VerificationType::Long | VerificationType::Double)
+    }
+
+    /// Whether this is a reference type, initialized or not.
+    pub fn is_reference(&self) -> bool {
+        matches!(*self, VerificationType::Null | VerificationType::UninitializedThis |
+                 VerificationType::Uninitialized(..) | VerificationType::Reference(..))
+    }
+
+    pub fn is_uninitialized(&self) -> bool {
+        matches!(*self, VerificationType::UninitializedThis | VerificationType::Uninitialized(..))
+    }
+
+    /// Name of the array type, if this is one.
+    pub fn array_name(&self) -> Option<&str> {
+        match *self {
+            VerificationType::Reference(ref name) if name.starts_with('[') => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Type of the elements of this array type.
+    pub fn component(&self) -> Option<VerificationType> {
+        self.array_name()
+            .and_then(|name| FieldType::parse(&name[1..]).ok())
+            .map(|ty| VerificationType::from_field_type(&ty))
+    }
+}
+
+impl fmt::Display for VerificationType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VerificationType::Top => write!(f, "top"),
+            VerificationType::Integer => write!(f, "int"),
+            VerificationType::Float => write!(f, "float"),
+            VerificationType::Long => write!(f, "long"),
+            VerificationType::Double => write!(f, "double"),
+            VerificationType::Null => write!(f, "null"),
+            VerificationType::UninitializedThis => write!(f, "uninitializedThis"),
+            VerificationType::Uninitialized(offset) => write!(f, "uninitialized({})", offset),
+            VerificationType::Reference(ref name) => write!(f, "{}", name),
+            VerificationType::ReturnAddress(..) => write!(f, "returnAddress"),
+        }
+    }
+}
+
+/// Whether a value of type `from` can be used where a value of type `to` is expected (JVMS §4.10.1.2).
+///
+/// Interfaces are treated like `java/lang/Object`, any reference being assignable to them.
+pub fn is_assignable<H: ClassHierarchy + ?Sized>(from: &VerificationType, to: &VerificationType,
+                                                 hierarchy: &H) -> Result<bool> {
+    if from == to || *to == VerificationType::Top {
+        return Ok(true);
+    }
+
+    match (from, to) {
+        (VerificationType::Null, VerificationType::Reference(..)) => Ok(true),
+        (VerificationType::Reference(from), VerificationType::Reference(to)) => {
+            is_java_assignable(from, to, hierarchy)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Whether the class or array type named `from` is assignable to the one named `to`.
+fn is_java_assignable<H: ClassHierarchy + ?Sized>(from: &str, to: &str, hierarchy: &H) -> Result<bool> {
+    if from == to || to == OBJECT {
+        return Ok(true);
+    }
+
+    match (from.starts_with('['), to.starts_with('[')) {
+        (true, true) => {
+            let from = VerificationType::Reference(from.to_owned()).component();
+            let to = VerificationType::Reference(to.to_owned()).component();
+            match (from, to) {
+                (Some(VerificationType::Reference(ref from)), Some(VerificationType::Reference(ref to))) => {
+                    is_java_assignable(from, to, hierarchy)
+                }
+                _ => Ok(false),
+            }
+        }
+        // Arrays implement these two interfaces only
+        (true, false) => Ok(to == "java/lang/Cloneable" || to == "java/io/Serializable"),
+        (false, true) => Ok(false),
+        (false, false) => {
+            if try!(is_subclass(from, to, hierarchy)) {
+                Ok(true)
+            } else {
+                hierarchy.is_interface(to)
+            }
+        }
+    }
+}
+
+/// Most specific type to which both `a` and `b` are assignable, `Top` if there is none, as when merging
+/// frames during type inference (JVMS §4.10.2.2).
+pub fn merge<H: ClassHierarchy + ?Sized>(a: &VerificationType, b: &VerificationType, hierarchy: &H)
+    -> Result<VerificationType>
+{
+    if a == b {
+        return Ok(a.clone());
+    }
+
+    match (a, b) {
+        (VerificationType::Null, VerificationType::Reference(..)) => Ok(b.clone()),
+        (VerificationType::Reference(..), VerificationType::Null) => Ok(a.clone()),
+        (VerificationType::Reference(a), VerificationType::Reference(b)) => {
+            common_super_class(a, b, hierarchy).map(VerificationType::Reference)
+        }
+        _ => Ok(VerificationType::Top),
+    }
+}
+
+/// Closest common super class of the class or array types `a` and `b`. Interfaces being treated like
+/// `java/lang/Object`, so is any common super interface.
+fn common_super_class<H: ClassHierarchy + ?Sized>(a: &str, b: &str, hierarchy: &H) -> Result<String> {
+    if a == b {
+        return Ok(a.to_owned());
+    }
+
+    match (a.starts_with('['), b.starts_with('[')) {
+        (true, true) => {
+            let a = VerificationType::Reference(a.to_owned()).component();
+            let b = VerificationType::Reference(b.to_owned()).component();
+            match (a, b) {
+                (Some(VerificationType::Reference(ref a)), Some(VerificationType::Reference(ref b))) => {
+                    let component = try!(common_super_class(a, b, hierarchy));
+                    Ok(VerificationType::array_of(&component).to_string())
+                }
+                _ => Ok(OBJECT.to_owned()),
+            }
+        }
+        (false, false) => {
+            let mut ancestors = HashSet::new();
+            let mut current = Some(a.to_owned());
+            while let Some(class) = current {
+                if !ancestors.insert(class.clone()) {
+                    break;
+                }
+                current = try!(hierarchy.super_class(&class));
+            }
+
+            let mut visited = HashSet::new();
+            let mut current = Some(b.to_owned());
+            while let Some(class) = current {
+                if ancestors.contains(&class) {
+                    return Ok(class);
+                }
+                if !visited.insert(class.clone()) {
+                    break;
+                }
+                current = try!(hierarchy.super_class(&class));
+            }
+
+            Ok(OBJECT.to_owned())
+        }
+        _ => Ok(OBJECT.to_owned()),
+    }
+}
+
+/// Whether the class `class` is `ancestor` or one of its subclasses.
+pub fn is_subclass<H: ClassHierarchy + ?Sized>(class: &str, ancestor: &str, hierarchy: &H) -> Result<bool> {
+    let mut visited = HashSet::new();
+    let mut current = class.to_owned();
+
+    while current != ancestor {
+        // Guard against circular hierarchies
+        if !visited.insert(current.clone()) {
+            return Ok(false);
+        }
+
+        current = match try!(hierarchy.super_class(&current)) {
+            Some(super_class) => super_class,
+            None => return Ok(false),
+        };
+    }
+
+    Ok(true)
+}
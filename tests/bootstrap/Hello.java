@@ -0,0 +1,9 @@
+public class Hello {
+    static int sum;
+
+    public static void main(String[] args) {
+        for (int i = 1; i <= 10; i++) {
+            sum += i;
+        }
+    }
+}
@@ -0,0 +1,43 @@
+public class Initialization {
+    static int order;
+    static boolean initializerError;
+    static String noClassDef;
+
+    static class Base {
+        static {
+            order = order * 10 + 1;
+        }
+    }
+
+    static class Derived extends Base {
+        static final String NAME = "derived";
+        static int value = 1;
+
+        static {
+            order = order * 10 + 2;
+        }
+    }
+
+    static class Failing {
+        static int value = fail();
+
+        static int fail() {
+            throw new IllegalStateException("static");
+        }
+    }
+
+    public static void main(String[] args) {
+        int value = Derived.value;
+
+        try {
+            value += Failing.value;
+        } catch (ExceptionInInitializerError e) {
+            initializerError = true;
+        }
+        try {
+            value += Failing.value;
+        } catch (NoClassDefFoundError e) {
+            noClassDef = e.getMessage();
+        }
+    }
+}
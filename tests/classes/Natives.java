@@ -0,0 +1,22 @@
+public class Natives {
+    static boolean sameClass;
+    static int[] copy;
+    static Object[] objects;
+    static String storeError;
+
+    public static void main(String[] args) {
+        sameClass = new Natives().getClass() == Natives.class;
+
+        int[] from = {1, 2, 3, 4};
+        copy = new int[4];
+        System.arraycopy(from, 1, copy, 0, 3);
+        System.arraycopy(copy, 0, copy, 1, 3);
+
+        objects = new Object[] {"a", new Natives(), "c"};
+        try {
+            System.arraycopy(objects, 0, new String[3], 0, 3);
+        } catch (ArrayStoreException e) {
+            storeError = e.getMessage();
+        }
+    }
+}
@@ -0,0 +1,56 @@
+public class TryCatch {
+    static final String GREETING = "hello";
+
+    static String message;
+    static String arithmetic;
+    static int steps;
+    static boolean sameObject;
+    static Class<?> type;
+    static boolean sameType;
+
+    static void fail(String message) {
+        throw new IllegalStateException(message);
+    }
+
+    static int divide(int a, int b) {
+        return a / b;
+    }
+
+    public static void main(String[] args) {
+        try {
+            fail("boom");
+        } catch (NullPointerException e) {
+            steps = -1;
+        } catch (RuntimeException e) {
+            message = e.getMessage();
+        }
+
+        try {
+            divide(1, 0);
+        } catch (ArithmeticException e) {
+            arithmetic = e.getMessage();
+        } finally {
+            steps++;
+        }
+
+        try {
+            try {
+                throw new Error("inner");
+            } finally {
+                steps += 10;
+            }
+        } catch (Throwable e) {
+            steps += 100;
+        }
+
+        RuntimeException thrown = new RuntimeException();
+        try {
+            throw thrown;
+        } catch (RuntimeException e) {
+            sameObject = e == thrown;
+        }
+
+        type = TryCatch.class;
+        sameType = type == TryCatch.class;
+    }
+}
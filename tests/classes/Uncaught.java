@@ -0,0 +1,8 @@
+public class Uncaught {
+    public static void main(String[] args) {
+        try {
+            throw new IllegalStateException("no handler");
+        } catch (ArithmeticException e) {
+        }
+    }
+}
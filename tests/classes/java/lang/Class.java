@@ -0,0 +1,5 @@
+package java.lang;
+
+public final class Class<T> {
+    private Class() {}
+}
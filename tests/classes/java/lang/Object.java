@@ -0,0 +1,13 @@
+package java.lang;
+
+public class Object {
+    private static native void registerNatives();
+
+    static {
+        registerNatives();
+    }
+
+    public Object() {}
+
+    public final native Class<?> getClass();
+}
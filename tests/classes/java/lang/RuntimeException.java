@@ -0,0 +1,6 @@
+package java.lang;
+
+public class RuntimeException extends Exception {
+    public RuntimeException() {}
+    public RuntimeException(String message) { super(message); }
+}
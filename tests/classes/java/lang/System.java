@@ -0,0 +1,13 @@
+package java.lang;
+
+public final class System {
+    private static native void registerNatives();
+
+    static {
+        registerNatives();
+    }
+
+    private System() {}
+
+    public static native void arraycopy(Object src, int srcPos, Object dest, int destPos, int length);
+}
@@ -0,0 +1,130 @@
+//! Runs the classes of `tests/classes`, compiled along with a minimal class library by
+//! `javac --patch-module java.base=. -d . java/lang/*.java *.java` from that directory, those of
+//! `tests/bootstrap`, which run without a class library, and those of `tests/linkage`, compiled against the
+//! classes of `original` before these change by `javac -d . -sourcepath original Linkage.java` then
+//! `javac -d . Members.java Shape.java` from that directory.
+
+extern crate jvm;
+
+use jvm::classpath::Classpath;
+use jvm::interpreter::heap::{ArrayData, Object};
+use jvm::interpreter::{Heap, Interpreter, Value};
+
+fn classpath() -> Classpath {
+    Classpath::parse(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/classes")).unwrap()
+}
+
+/// Load `class` and run its `main` method.
+fn run_main(interpreter: &mut Interpreter, class: &str) -> jvm::interpreter::error::Result<()> {
+    let class = interpreter.method_area().load(class).unwrap();
+    interpreter.run_main(&class)
+}
+
+fn get_static(interpreter: &mut Interpreter, class: &str, name: &str) -> Value {
+    let class = interpreter.method_area().get(class).unwrap().clone();
+    let field = class.fields.iter().find(|field| field.name == name).unwrap();
+    class.get_static(field.slot)
+}
+
+fn get_string(interpreter: &mut Interpreter, class: &str, name: &str) -> Option<String> {
+    match get_static(interpreter, class, name) {
+        Value::Reference(string) => interpreter.read_string(string),
+        _ => None,
+    }
+}
+
+#[test]
+fn catches_exceptions() {
+    let classpath = classpath();
+    let mut interpreter = Interpreter::new(&classpath, Heap::default());
+    run_main(&mut interpreter, "TryCatch").unwrap();
+
+    assert_eq!(get_string(&mut interpreter, "TryCatch", "message"), Some("boom".to_owned()));
+    assert_eq!(get_string(&mut interpreter, "TryCatch", "arithmetic"), Some("/ by zero".to_owned()));
+    assert_eq!(get_static(&mut interpreter, "TryCatch", "steps"), Value::Int(111));
+    assert_eq!(get_static(&mut interpreter, "TryCatch", "sameObject"), Value::Int(1));
+}
+
+#[test]
+fn initializes_classes() {
+    let classpath = classpath();
+    let mut interpreter = Interpreter::new(&classpath, Heap::default());
+    run_main(&mut interpreter, "Initialization").unwrap();
+
+    // Superclasses first
+    assert_eq!(get_static(&mut interpreter, "Initialization", "order"), Value::Int(12));
+    assert_eq!(get_string(&mut interpreter, "Initialization$Derived", "NAME"), Some("derived".to_owned()));
+    assert_eq!(get_static(&mut interpreter, "Initialization", "initializerError"), Value::Int(1));
+    assert_eq!(get_string(&mut interpreter, "Initialization", "noClassDef"),
+               Some("Could not initialize class Initialization$Failing".to_owned()));
+}
+
+#[test]
+fn loads_string_and_class_constants() {
+    let classpath = classpath();
+    let mut interpreter = Interpreter::new(&classpath, Heap::default());
+    run_main(&mut interpreter, "TryCatch").unwrap();
+
+    assert_eq!(get_string(&mut interpreter, "TryCatch", "GREETING"), Some("hello".to_owned()));
+    let hello = interpreter.intern("hello").unwrap();
+    assert_eq!(get_static(&mut interpreter, "TryCatch", "GREETING"), Value::Reference(hello));
+    assert!(matches!(get_static(&mut interpreter, "TryCatch", "type"), Value::Reference(..)));
+    assert_eq!(get_static(&mut interpreter, "TryCatch", "sameType"), Value::Int(1));
+}
+
+#[test]
+fn reports_uncaught_exceptions() {
+    let classpath = classpath();
+    let mut interpreter = Interpreter::new(&classpath, Heap::default());
+    let err = run_main(&mut interpreter, "Uncaught").unwrap_err();
+
+    assert_eq!(err.java_exception(), Some(("java/lang/IllegalStateException", "no handler")));
+    assert_eq!(err.to_string(), "In method Uncaught.main([Ljava/lang/String;)V");
+}
+
+#[test]
+fn reports_incompatible_class_changes() {
+    let classpath = Classpath::parse(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/linkage:",
+                                             env!("CARGO_MANIFEST_DIR"), "/tests/classes")).unwrap();
+    let mut interpreter = Interpreter::new(&classpath, Heap::default());
+    run_main(&mut interpreter, "Linkage").unwrap();
+
+    let errors = [
+        ("field", "class Linkage tried to access field Members.value"),
+        ("method", "class Linkage tried to access method Members.twice(I)I"),
+        ("removed", "Members.removed()I"),
+        ("shape", "Found interface Shape, but class was expected"),
+    ];
+    for &(name, message) in errors.iter() {
+        assert_eq!(get_string(&mut interpreter, "Linkage", name), Some(message.to_owned()));
+        // Resolving the constant again fails the same way
+        assert_eq!(get_string(&mut interpreter, "Linkage", &format!("{}Again", name)), Some(message.to_owned()));
+    }
+}
+
+#[test]
+fn runs_without_the_class_library() {
+    let classpath = Classpath::parse(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/bootstrap")).unwrap();
+    let mut interpreter = Interpreter::new(&classpath, Heap::default());
+    run_main(&mut interpreter, "Hello").unwrap();
+
+    assert_eq!(get_static(&mut interpreter, "Hello", "sum"), Value::Int(55));
+}
+
+#[test]
+fn runs_native_methods() {
+    let classpath = classpath();
+    let mut interpreter = Interpreter::new(&classpath, Heap::default());
+    run_main(&mut interpreter, "Natives").unwrap();
+
+    assert_eq!(get_static(&mut interpreter, "Natives", "sameClass"), Value::Int(1));
+    let copy = match get_static(&mut interpreter, "Natives", "copy") {
+        Value::Reference(copy) => copy,
+        value => panic!("Unexpected copy: {}", value),
+    };
+    match interpreter.heap().get(copy) {
+        Some(&Object::Array { data: ArrayData::Int(ref data), .. }) => assert_eq!(*data, vec![2, 2, 3, 4]),
+        object => panic!("Unexpected copy: {:?}", object),
+    }
+    assert_eq!(get_string(&mut interpreter, "Natives", "storeError"), Some("arraycopy: element type mismatch".to_owned()));
+}
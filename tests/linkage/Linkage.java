@@ -0,0 +1,57 @@
+public class Linkage {
+    static String field;
+    static String fieldAgain;
+    static String method;
+    static String methodAgain;
+    static String removed;
+    static String removedAgain;
+    static String shape;
+    static String shapeAgain;
+
+    static String readField() {
+        try {
+            int value = Members.value;
+            return null;
+        } catch (IllegalAccessError e) {
+            return e.getMessage();
+        }
+    }
+
+    static String callMethod() {
+        try {
+            Members.twice(1);
+            return null;
+        } catch (IllegalAccessError e) {
+            return e.getMessage();
+        }
+    }
+
+    static String callRemoved() {
+        try {
+            Members.removed();
+            return null;
+        } catch (NoSuchMethodError e) {
+            return e.getMessage();
+        }
+    }
+
+    static String callShape() {
+        try {
+            Shape.sides();
+            return null;
+        } catch (IncompatibleClassChangeError e) {
+            return e.getMessage();
+        }
+    }
+
+    public static void main(String[] args) {
+        field = readField();
+        fieldAgain = readField();
+        method = callMethod();
+        methodAgain = callMethod();
+        removed = callRemoved();
+        removedAgain = callRemoved();
+        shape = callShape();
+        shapeAgain = callShape();
+    }
+}
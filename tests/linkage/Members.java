@@ -0,0 +1,7 @@
+public class Members {
+    private static int value = 1;
+
+    private static int twice(int value) {
+        return 2 * value;
+    }
+}
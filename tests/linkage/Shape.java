@@ -0,0 +1,5 @@
+public interface Shape {
+    static int sides() {
+        return 4;
+    }
+}
@@ -0,0 +1,11 @@
+public class Members {
+    public static int value = 1;
+
+    public static int twice(int value) {
+        return 2 * value;
+    }
+
+    public static int removed() {
+        return 0;
+    }
+}